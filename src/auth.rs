@@ -0,0 +1,207 @@
+//! 本地用户、角色与认证
+//!
+//! 数据库中没有任何用户时不做权限检查，与引入用户之前的行为一致；添加第一个用户（必须是
+//! admin）后，修改数据的命令需要以口令或令牌认证，未认证的查询按 viewer 处理。
+//!
+//! 口令以 PBKDF2-HMAC-SHA256 加盐哈希保存，令牌只保存 SHA-256 摘要，数据库中没有可还原的凭据。
+
+use std::num::NonZeroU32;
+use std::str::FromStr;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use clap::ValueEnum;
+use rand::RngCore;
+use ring::{digest, pbkdf2};
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+
+/// 口令哈希的迭代次数
+const PASSWORD_ROUNDS: NonZeroU32 = match NonZeroU32::new(100_000) {
+    Some(n) => n,
+    None => unreachable!(),
+};
+const SALT_LEN: usize = 16;
+const HASH_LEN: usize = 32;
+const TOKEN_LEN: usize = 32;
+/// 令牌前缀，便于在配置和日志中识别
+const TOKEN_PREFIX: &str = "wat_";
+/// 口令最短长度
+pub const MIN_PASSWORD_LEN: usize = 8;
+
+/// 用户角色，权限依次递减
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// 管理员：全部权限，包括用户管理和数据库维护
+    Admin,
+    /// 录入员：添加、修改、删除和导入供应商
+    Editor,
+    /// 客户：只能查询，电话和微信脱敏显示
+    Viewer,
+}
+
+impl Role {
+    pub fn name(self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::Editor => "editor",
+            Role::Viewer => "viewer",
+        }
+    }
+
+    /// 是否满足 `required` 要求的权限
+    pub fn allows(self, required: Role) -> bool {
+        self <= required
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        <Role as ValueEnum>::from_str(s, true).map_err(|_| format!("未知角色: {}，可选 admin、editor、viewer", s))
+    }
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// 一个本地用户
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct User {
+    pub name: String,
+    pub role: Role,
+    /// 是否已签发令牌
+    pub has_token: bool,
+    pub created_at: String,
+}
+
+/// 已认证的身份
+#[derive(Debug, Clone, PartialEq)]
+pub struct Identity {
+    pub name: String,
+    pub role: Role,
+}
+
+impl Identity {
+    /// 要求至少具有 `required` 角色，否则返回权限错误
+    pub fn require(&self, required: Role) -> Result<()> {
+        if self.role.allows(required) {
+            Ok(())
+        } else {
+            Err(Error::PermissionDenied(format!("用户 {}（{}）无权执行此操作，需要 {} 及以上角色", self.name, self.role, required)))
+        }
+    }
+}
+
+/// 当前命令的访问身份
+#[derive(Debug, Clone, PartialEq)]
+pub enum Session {
+    /// 数据库中没有用户，不做权限检查
+    Unrestricted,
+    /// 未提供凭据，只能查询，权限同 viewer
+    Anonymous,
+    /// 已认证的用户
+    User(Identity),
+}
+
+impl Session {
+    /// 要求至少具有 `required` 角色
+    pub fn require(&self, required: Role) -> Result<()> {
+        match self {
+            Session::Unrestricted => Ok(()),
+            Session::Anonymous if required == Role::Viewer => Ok(()),
+            Session::Anonymous => Err(Error::PermissionDenied(format!(
+                "该操作需要 {} 及以上角色，请用 --user 和口令或令牌登录",
+                required
+            ))),
+            Session::User(identity) => identity.require(required),
+        }
+    }
+
+    /// 是否只有 viewer 权限，此时查询结果中的电话和微信总是脱敏
+    pub fn is_viewer(&self) -> bool {
+        match self {
+            Session::Unrestricted => false,
+            Session::Anonymous => true,
+            Session::User(identity) => identity.role == Role::Viewer,
+        }
+    }
+
+    /// 已认证的用户名
+    pub fn user_name(&self) -> Option<&str> {
+        match self {
+            Session::User(identity) => Some(&identity.name),
+            _ => None,
+        }
+    }
+}
+
+/// 用户名只允许字母、数字、`-`、`_` 和 `.`
+pub fn validate_user_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::Validation(format!("用户名无效: {}，只能包含字母、数字、-、_ 和 .", name)))
+    }
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes
+}
+
+/// 生成口令哈希，格式为 `pbkdf2-sha256$迭代次数$盐$哈希`（Base64）
+pub fn hash_password(password: &str) -> Result<String> {
+    if password.chars().count() < MIN_PASSWORD_LEN {
+        return Err(Error::Validation(format!("口令至少需要 {} 个字符", MIN_PASSWORD_LEN)));
+    }
+    let salt = random_bytes::<SALT_LEN>();
+    let mut hash = [0u8; HASH_LEN];
+    pbkdf2::derive(pbkdf2::PBKDF2_HMAC_SHA256, PASSWORD_ROUNDS, &salt, password.as_bytes(), &mut hash);
+    Ok(format!("pbkdf2-sha256${}${}${}", PASSWORD_ROUNDS, BASE64.encode(salt), BASE64.encode(hash)))
+}
+
+/// 校验口令；哈希格式无法识别时视为不匹配
+pub fn verify_password(password: &str, stored: &str) -> bool {
+    let parts: Vec<&str> = stored.split('$').collect();
+    let [scheme, rounds, salt, hash] = parts[..] else {
+        return false;
+    };
+    let (Some(rounds), Ok(salt), Ok(hash)) =
+        (rounds.parse().ok().and_then(NonZeroU32::new), BASE64.decode(salt), BASE64.decode(hash))
+    else {
+        return false;
+    };
+    scheme == "pbkdf2-sha256"
+        && pbkdf2::verify(pbkdf2::PBKDF2_HMAC_SHA256, rounds, &salt, password.as_bytes(), &hash).is_ok()
+}
+
+/// 用户不存在时按同样的迭代次数做一次注定失败的校验，使耗时与口令错误时一致，避免据此探测用户名
+pub fn verify_unknown_user(password: &str) {
+    let salt = [0u8; SALT_LEN];
+    let hash = [0u8; HASH_LEN];
+    std::hint::black_box(pbkdf2::verify(pbkdf2::PBKDF2_HMAC_SHA256, PASSWORD_ROUNDS, &salt, password.as_bytes(), &hash)).ok();
+}
+
+/// 生成新令牌，返回 (令牌原文, 保存到数据库的摘要)
+pub fn new_token() -> (String, String) {
+    let token = format!("{}{}", TOKEN_PREFIX, hex(&random_bytes::<TOKEN_LEN>()));
+    let digest = token_digest(&token);
+    (token, digest)
+}
+
+/// 令牌的 SHA-256 摘要（十六进制）
+pub fn token_digest(token: &str) -> String {
+    hex(digest::digest(&digest::SHA256, token.trim().as_bytes()).as_ref())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
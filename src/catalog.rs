@@ -0,0 +1,263 @@
+//! 对外报价目录
+//!
+//! 转售时向客户展示的资源列表：以报价编号代替供应商ID，不含联系人、微信和电话，
+//! 价格可按加价比例上浮。报价编号在首次发布时随机分配并保存在 `offer_codes` 表中，
+//! 之后每次发布保持不变；只有内部人员可以用 `catalog lookup` 由编号查回供应商。
+
+use std::collections::HashMap;
+
+use clap::ValueEnum;
+use rand::Rng;
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::render::{escape_html, HTML_SCRIPT, HTML_STYLE};
+use crate::SupplierRow;
+
+/// 报价编号前缀
+pub const OFFER_CODE_PREFIX: &str = "OF-";
+/// 编号字符集，去掉了容易混淆的 0/O、1/I
+const CODE_ALPHABET: &[u8] = b"23456789ABCDEFGHJKLMNPQRSTUVWXYZ";
+const CODE_LEN: usize = 8;
+
+/// 目录列标题，与 [`CatalogEntry::cells`] 的顺序一致
+const HEADERS: [&str; 13] = [
+    "编号", "数量", "地点", "价格", "带宽价", "存储价", "合同期", "违约金", "付款", "服务器名", "配置", "租赁", "组网",
+];
+
+/// 生成新的随机报价编号，如 `OF-7KQ2M9XA`
+pub fn new_offer_code() -> String {
+    let mut rng = rand::thread_rng();
+    let code: String = (0..CODE_LEN).map(|_| CODE_ALPHABET[rng.gen_range(0..CODE_ALPHABET.len())] as char).collect();
+    format!("{}{}", OFFER_CODE_PREFIX, code)
+}
+
+/// 目录输出格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum CatalogFormat {
+    #[default]
+    Json,
+    Csv,
+    /// 自带样式、可点击列头排序的独立 HTML 页面
+    Html,
+}
+
+/// 目录中的一条报价，不含任何可识别供应商的信息
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CatalogEntry {
+    pub code: String,
+    pub quantity: Option<i32>,
+    pub location: Option<String>,
+    /// 加价后的价格，保留两位小数
+    pub price: Option<f64>,
+    pub bandwidth_price: Option<f64>,
+    pub storage_price: Option<f64>,
+    pub min_contract_period: Option<String>,
+    pub breach_penalties: Option<String>,
+    pub payment_terms: Option<String>,
+    pub server_name: Option<String>,
+    pub server_config: Option<String>,
+    pub rental_model: Option<String>,
+    pub networking_category: Option<String>,
+}
+
+impl CatalogEntry {
+    /// 由供应商记录生成报价，`markup` 为价格的加价百分比
+    pub fn new(row: &SupplierRow, code: String, markup: Option<f64>) -> Self {
+        Self {
+            code,
+            quantity: row.quantity,
+            location: row.location.clone(),
+            price: row.price.map(|p| apply_markup(p, markup.unwrap_or(0.0))),
+            bandwidth_price: row.bandwidth_price,
+            storage_price: row.storage_price,
+            min_contract_period: row.min_contract_period.clone(),
+            breach_penalties: row.breach_penalties.clone(),
+            payment_terms: row.payment_terms.clone(),
+            server_name: row.server_name.clone(),
+            server_config: row.server_config.clone(),
+            rental_model: row.rental_model.clone(),
+            networking_category: row.networking_category.clone(),
+        }
+    }
+
+    /// 按 `HEADERS` 顺序排列的单元格文本
+    fn cells(&self) -> [String; 13] {
+        let text = |v: &Option<String>| v.clone().unwrap_or_default();
+        let money = |v: Option<f64>| v.map(|v| format!("{:.2}", v)).unwrap_or_default();
+        [
+            self.code.clone(),
+            self.quantity.map(|v| v.to_string()).unwrap_or_default(),
+            text(&self.location),
+            money(self.price),
+            money(self.bandwidth_price),
+            money(self.storage_price),
+            text(&self.min_contract_period),
+            text(&self.breach_penalties),
+            text(&self.payment_terms),
+            text(&self.server_name),
+            text(&self.server_config),
+            text(&self.rental_model),
+            text(&self.networking_category),
+        ]
+    }
+}
+
+/// 按百分比加价并四舍五入到分
+pub fn apply_markup(price: f64, percent: f64) -> f64 {
+    (price * (100.0 + percent)).round() / 100.0
+}
+
+/// 由查询结果和报价编号生成目录；加价比例不能为负
+pub fn build(rows: &[SupplierRow], codes: &HashMap<i64, String>, markup: Option<f64>) -> Result<Vec<CatalogEntry>> {
+    if let Some(m) = markup {
+        if !m.is_finite() || m < 0.0 {
+            return Err(Error::Validation(format!("加价比例必须是不小于0的数字: {}", m)));
+        }
+    }
+    rows.iter()
+        .map(|row| {
+            let code = codes.get(&row.id).cloned().ok_or_else(|| Error::NotFound(format!("供应商 {} 没有报价编号", row.id)))?;
+            Ok(CatalogEntry::new(row, code, markup))
+        })
+        .collect()
+}
+
+/// 按格式生成目录内容；`currency` 显示在 CSV 和 HTML 的价格列标题中
+pub fn render(entries: &[CatalogEntry], format: CatalogFormat, currency: Option<&str>) -> Result<String> {
+    match format {
+        CatalogFormat::Json => Ok(serde_json::to_string_pretty(entries)?),
+        CatalogFormat::Csv => render_csv(entries, currency),
+        CatalogFormat::Html => Ok(render_html(entries, currency)),
+    }
+}
+
+/// 列标题，指定币种时价格列附带币种，如 `价格(CNY)`
+fn headers(currency: Option<&str>) -> Vec<String> {
+    HEADERS
+        .iter()
+        .enumerate()
+        .map(|(i, h)| match currency {
+            Some(cur) if is_price_column(i) => format!("{}({})", h, cur),
+            _ => h.to_string(),
+        })
+        .collect()
+}
+
+fn is_price_column(i: usize) -> bool {
+    matches!(i, 3..=5)
+}
+
+fn render_csv(entries: &[CatalogEntry], currency: Option<&str>) -> Result<String> {
+    let mut wtr = csv::Writer::from_writer(Vec::new());
+    wtr.write_record(headers(currency)).map_err(std::io::Error::from)?;
+    for entry in entries {
+        wtr.write_record(entry.cells()).map_err(std::io::Error::from)?;
+    }
+    let data = wtr.into_inner().map_err(|e| e.into_error())?;
+    String::from_utf8(data).map_err(|e| Error::Parse(e.to_string()))
+}
+
+fn render_html(entries: &[CatalogEntry], currency: Option<&str>) -> String {
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str("<title>资源报价</title>\n");
+    out.push_str(&format!("<style>\n{}\n</style>\n", HTML_STYLE));
+    out.push_str("</head>\n<body>\n");
+    out.push_str(&format!("<p>共 {} 项资源，点击列头排序</p>\n", entries.len()));
+    out.push_str("<table>\n<thead>\n<tr>");
+    for (i, h) in headers(currency).iter().enumerate() {
+        if i == 1 || is_price_column(i) {
+            out.push_str(&format!("<th data-type=\"num\">{}</th>", escape_html(h)));
+        } else {
+            out.push_str(&format!("<th>{}</th>", escape_html(h)));
+        }
+    }
+    out.push_str("</tr>\n</thead>\n<tbody>\n");
+    for entry in entries {
+        out.push_str("<tr>");
+        for (i, cell) in entry.cells().iter().enumerate() {
+            if i == 1 || is_price_column(i) {
+                out.push_str(&format!("<td class=\"num\">{}</td>", escape_html(cell)));
+            } else {
+                out.push_str(&format!("<td>{}</td>", escape_html(cell)));
+            }
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</tbody>\n</table>\n");
+    out.push_str(&format!("<script>\n{}\n</script>\n", HTML_SCRIPT));
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Supplier;
+
+    fn row(id: i64, price: f64) -> SupplierRow {
+        Supplier {
+            contact: Some("张三".to_string()),
+            phone: Some("13812348000".to_string()),
+            location: Some("北京".to_string()),
+            price: Some(price),
+            ..Default::default()
+        }
+        .to_row(id)
+    }
+
+    #[test]
+    fn test_new_offer_code_format() {
+        let code = new_offer_code();
+        assert!(code.starts_with(OFFER_CODE_PREFIX));
+        let body = &code[OFFER_CODE_PREFIX.len()..];
+        assert_eq!(body.len(), CODE_LEN);
+        assert!(body.bytes().all(|b| CODE_ALPHABET.contains(&b)));
+    }
+
+    #[test]
+    fn test_apply_markup_rounds_to_cents() {
+        assert_eq!(apply_markup(1000.0, 15.0), 1150.0);
+        assert_eq!(apply_markup(1200.5, 15.0), 1380.58);
+        assert_eq!(apply_markup(999.99, 0.0), 999.99);
+    }
+
+    #[test]
+    fn test_build_uses_codes_and_markup() {
+        let codes = HashMap::from([(1, "OF-AAAA2222".to_string())]);
+        let entries = build(&[row(1, 1000.0)], &codes, Some(20.0)).unwrap();
+        assert_eq!(entries[0].code, "OF-AAAA2222");
+        assert_eq!(entries[0].price, Some(1200.0));
+        assert_eq!(entries[0].location.as_deref(), Some("北京"));
+    }
+
+    #[test]
+    fn test_build_rejects_negative_markup() {
+        let codes = HashMap::from([(1, "OF-AAAA2222".to_string())]);
+        assert!(matches!(build(&[row(1, 1000.0)], &codes, Some(-5.0)), Err(Error::Validation(_))));
+        assert!(matches!(build(&[row(1, 1000.0)], &codes, Some(f64::NAN)), Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn test_build_requires_code() {
+        assert!(matches!(build(&[row(1, 1000.0)], &HashMap::new(), None), Err(Error::NotFound(_))));
+    }
+
+    #[test]
+    fn test_render_hides_supplier_identity() {
+        let codes = HashMap::from([(1, "OF-AAAA2222".to_string())]);
+        let entries = build(&[row(1, 1000.0)], &codes, None).unwrap();
+        for format in [CatalogFormat::Json, CatalogFormat::Csv, CatalogFormat::Html] {
+            let out = render(&entries, format, None).unwrap();
+            assert!(out.contains("OF-AAAA2222"), "{:?}", format);
+            assert!(!out.contains("张三") && !out.contains("13812348000"), "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn test_render_csv_currency_headers() {
+        let out = render(&[], CatalogFormat::Csv, Some("CNY")).unwrap();
+        assert_eq!(out.lines().next().unwrap(), "编号,数量,地点,价格(CNY),带宽价(CNY),存储价(CNY),合同期,违约金,付款,服务器名,配置,租赁,组网");
+    }
+}
@@ -0,0 +1,18 @@
+//! audit 子命令：查看审计日志
+
+use wei_assistant_gpu::{Result, SupplierStore};
+
+/// 输出最近 `limit` 条审计日志
+pub fn run_audit(store: &SupplierStore, limit: usize, json: bool) -> Result<()> {
+    let entries = store.audit_log(limit)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+    } else if entries.is_empty() {
+        println!("暂无审计日志");
+    } else {
+        for e in entries {
+            println!("{}\t{}\t{}\t{}", e.at, e.actor, e.action, e.detail);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,78 @@
+//! catalog 子命令：发布对外报价目录，由报价编号查回供应商
+
+use std::path::PathBuf;
+
+use clap::Subcommand;
+use wei_assistant_gpu::auth::Session;
+use wei_assistant_gpu::catalog::{self, CatalogFormat};
+use wei_assistant_gpu::render::{self, ColorChoice, TableLayout, TableOptions};
+use wei_assistant_gpu::{parse_filter, QueryBuilder, Result, Settings, SortKey, SupplierStore};
+
+use super::current_actor;
+
+#[derive(Subcommand, Debug)]
+pub enum CatalogAction {
+    /// 发布匿名报价目录，首次发布的供应商自动分配报价编号
+    Publish {
+        /// 筛选表达式，与 query --filter 相同，如 "quantity > 0 and location = 北京"
+        #[arg(long)]
+        filter: Option<String>,
+        /// 排序，逗号分隔，如 price,quantity:desc
+        #[arg(long, value_delimiter = ',', value_name = "列[:asc|desc]", action = clap::ArgAction::Set)]
+        sort: Vec<SortKey>,
+        /// 价格加价百分比，如 15 表示上浮 15%
+        #[arg(long, value_name = "PCT")]
+        markup: Option<f64>,
+        /// 输出格式
+        #[arg(long, value_enum, default_value_t = CatalogFormat::Json)]
+        format: CatalogFormat,
+        /// 写入文件而不是标准输出
+        #[arg(long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+    /// 由报价编号查回供应商（仅限内部使用，记入审计日志）
+    Lookup {
+        /// 报价编号，如 OF-7KQ2M9XA
+        code: String,
+        /// 以JSON格式输出
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// 审计日志中由报价编号查回供应商的操作类型
+pub const AUDIT_OFFER_LOOKUP: &str = "offer_lookup";
+
+pub fn run_catalog(settings: &Settings, store: &SupplierStore, session: &Session, action: &CatalogAction) -> Result<()> {
+    match action {
+        CatalogAction::Publish { filter, sort, markup, format, output } => {
+            let mut query = QueryBuilder::new();
+            if let Some(expr) = filter {
+                query = query.condition(parse_filter(expr)?);
+            }
+            query = sort.iter().fold(query, |qb, key| qb.order_by(*key));
+            let rows = store.query(query)?;
+            let ids: Vec<i64> = rows.iter().map(|r| r.id).collect();
+            let entries = catalog::build(&rows, &store.offer_codes(&ids)?, *markup)?;
+            let content = catalog::render(&entries, *format, settings.config.defaults.currency.as_deref())?;
+            match output {
+                Some(path) => {
+                    std::fs::write(path, content)?;
+                    println!("已发布 {} 项报价到 {}", entries.len(), path.display());
+                }
+                None => println!("{}", content.trim_end()),
+            }
+        }
+        CatalogAction::Lookup { code, json } => {
+            let row = store.lookup_offer(code)?;
+            store.record_audit(&current_actor(session), AUDIT_OFFER_LOOKUP, &format!("{} -> {}", code.trim().to_uppercase(), row.id))?;
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&row)?);
+            } else {
+                let opts = TableOptions::detect(TableLayout::Vertical, ColorChoice::Auto, None);
+                print!("{}", render::render_table(&[row], &opts));
+            }
+        }
+    }
+    Ok(())
+}
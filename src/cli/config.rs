@@ -0,0 +1,91 @@
+//! config 子命令：查看和修改配置文件
+
+use std::path::PathBuf;
+
+use clap::Subcommand;
+use wei_assistant_gpu::config::{self, Overrides};
+use wei_assistant_gpu::{Error, Result, Settings};
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigAction {
+    /// 显示合并后的配置、读取的配置文件及数据库来源
+    Show,
+    /// 读取配置项的生效值，如 config get defaults.format
+    Get {
+        #[arg(help = key_help())]
+        key: String,
+    },
+    /// 写入配置项，列表用逗号分隔，值为空字符串时删除该项
+    Set {
+        #[arg(help = key_help())]
+        key: String,
+        /// 配置值
+        value: String,
+        /// 写入当前项目的 .wei-assistant.toml，默认写入用户配置
+        #[arg(long)]
+        project: bool,
+    },
+}
+
+/// 配置项参数的帮助文字，由 [`config::CONFIG_KEYS`] 生成，新增配置项时无需修改
+fn key_help() -> String {
+    format!("配置项：{}", config::CONFIG_KEYS.join("、"))
+}
+
+/// config set、profile add/use 写入的配置文件：--config 指定的文件、项目配置或用户配置
+pub fn config_target(overrides: Overrides<'_>, project: bool) -> Result<PathBuf> {
+    match config::explicit_config_path(overrides.config) {
+        Some(path) => Ok(path),
+        None if project => {
+            let cwd = std::env::current_dir()?;
+            Ok(config::find_project_config(&cwd).unwrap_or_else(|| cwd.join(config::PROJECT_CONFIG_FILE)))
+        }
+        None => config::user_config_path()
+            .ok_or_else(|| Error::Validation("无法确定用户主目录，请用 --config 指定配置文件".to_string())),
+    }
+}
+
+/// 执行 config 子命令；set 只读写目标文件，不要求其他配置文件有效
+pub fn run_config(overrides: Overrides<'_>, action: &ConfigAction) -> Result<()> {
+    match action {
+        ConfigAction::Show => {
+            let settings = Settings::load(overrides)?;
+            if settings.files.is_empty() {
+                println!("# 未找到配置文件");
+            }
+            for path in &settings.files {
+                println!("# 配置文件: {}", path.display());
+            }
+            println!("# 数据库来源: {}", settings.db_source);
+            let mut effective = settings.config.clone();
+            effective.db = Some(settings.db.clone());
+            print!("{}", effective.to_toml());
+        }
+        ConfigAction::Get { key } => {
+            let settings = Settings::load(overrides)?;
+            let value = match key.as_str() {
+                "db" => Some(settings.db.clone()),
+                "profile" => match &settings.db_source {
+                    config::DbSource::Profile(name) => Some(name.clone()),
+                    _ => None,
+                },
+                _ => settings.config.get(key)?,
+            };
+            match value {
+                Some(value) => println!("{}", value),
+                None => return Err(Error::NotFound(format!("配置项 {} 未设置", key))),
+            }
+        }
+        ConfigAction::Set { key, value, project } => {
+            let path = config_target(overrides, *project)?;
+            let mut file = config::Config::load_or_default(&path)?;
+            file.set(key, value)?;
+            file.save(&path)?;
+            match file.get(key)? {
+                Some(value) => println!("已设置 {} = {}（{}）", key, value, path.display()),
+                None => println!("已删除 {}（{}）", key, path.display()),
+            }
+        }
+    }
+    Ok(())
+}
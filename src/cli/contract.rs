@@ -0,0 +1,224 @@
+//! contract 子命令：签约、续约、提前终止和违约金估算
+
+use clap::Subcommand;
+use wei_assistant_gpu::contract::{self, Contract, ContractStatus, NewContract, TerminationEstimate};
+use wei_assistant_gpu::dates::Date;
+use wei_assistant_gpu::render::{self, TableOptions};
+use wei_assistant_gpu::{Error, Result, SupplierStore};
+
+use super::TableArgs;
+
+#[derive(Subcommand, Debug)]
+pub enum ContractAction {
+    /// 新建合同，记录签约时的合同期、付款方式和违约条款
+    Create {
+        /// 供应商ID或报价编号，指定 --quote 时可省略
+        #[arg(long, required_unless_present = "quote")]
+        offer: Option<String>,
+        /// 对应的客户报价单，数量和月数默认取自报价单
+        #[arg(long)]
+        quote: Option<String>,
+        /// 数量（台）
+        #[arg(long)]
+        qty: Option<u32>,
+        /// 约定单价（每台每月），默认取供应商当前价格
+        #[arg(long)]
+        price: Option<f64>,
+        /// 开始日期（YYYY-MM-DD），默认今天
+        #[arg(long)]
+        start: Option<Date>,
+        /// 合同月数，默认取报价单的月数或供应商的最短合同期
+        #[arg(long, conflicts_with = "end")]
+        months: Option<u32>,
+        /// 结束日期（YYYY-MM-DD，不含）
+        #[arg(long)]
+        end: Option<Date>,
+    },
+    /// 续约，结束日期顺延
+    Renew {
+        /// 合同ID
+        id: i64,
+        /// 续约月数，默认与原合同期相同
+        #[arg(long)]
+        months: Option<u32>,
+        /// 新的约定单价
+        #[arg(long)]
+        price: Option<f64>,
+    },
+    /// 提前终止合同，并按违约条款估算违约金
+    Terminate {
+        /// 合同ID
+        id: i64,
+        /// 终止日期（YYYY-MM-DD），默认今天
+        #[arg(long)]
+        on: Option<Date>,
+        /// 只估算违约金，不终止
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// 列出合同
+    List {
+        /// 只列出指定状态的合同
+        #[arg(long, value_enum)]
+        status: Option<ContractStatus>,
+        /// 以JSON格式输出
+        #[arg(long)]
+        json: bool,
+        #[command(flatten)]
+        table: TableArgs,
+    },
+    /// 查看合同详情及今天终止的违约金估算
+    Show {
+        /// 合同ID
+        id: i64,
+        /// 以JSON格式输出
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// 输出违约金估算
+fn print_termination_estimate(estimate: &TerminationEstimate) {
+    println!(
+        "{} 终止：剩余 {} 个月，剩余租金 {:.2}",
+        estimate.terminate_on, estimate.remaining_months, estimate.remaining_rent
+    );
+    match estimate.penalty {
+        Some(penalty) => println!("预计违约金 {:.2}（{}）", penalty, estimate.basis),
+        None => println!("违约金需人工核算（{}）", estimate.basis),
+    }
+}
+
+/// 合同列表表格
+fn render_contracts(contracts: &[Contract], opts: &TableOptions) -> String {
+    const HEADERS: [&str; 8] = ["ID", "状态", "供应商", "数量", "约定单价", "月租", "开始", "结束"];
+    const NUMERIC: [bool; 8] = [true, false, true, true, true, true, false, false];
+    let data: Vec<Vec<String>> = contracts
+        .iter()
+        .map(|c| {
+            vec![
+                c.id.to_string(),
+                c.status.name().to_string(),
+                c.supplier_id.to_string(),
+                c.quantity.to_string(),
+                format!("{:.2}", c.agreed_price),
+                format!("{:.2}", c.monthly_rent()),
+                c.start_date.to_string(),
+                c.end_date.to_string(),
+            ]
+        })
+        .collect();
+    render::render_grid(&HEADERS, &data, &NUMERIC, opts)
+}
+
+fn print_contract(contract: &Contract) {
+    let text = |v: &Option<String>| v.clone().unwrap_or_else(|| "-".to_string());
+    println!("合同 {}（{}）", contract.id, contract.status.name());
+    println!("供应商\t{}", contract.supplier_id);
+    println!("报价单\t{}", text(&contract.quote_no));
+    println!("数量\t{}", contract.quantity);
+    println!("约定单价\t{:.2}", contract.agreed_price);
+    println!("月租\t{:.2}", contract.monthly_rent());
+    println!("期限\t{} 至 {}（{} 个月，续约 {} 次）", contract.start_date, contract.end_date, contract.term_months(), contract.renewals);
+    println!("最短合同期\t{}", text(&contract.min_contract_period));
+    println!("付款方式\t{}", text(&contract.payment_terms));
+    println!("违约条款\t{}", text(&contract.breach_penalties));
+    if let Some(on) = contract.terminated_on {
+        let cost = contract.termination_cost.map(|c| format!("{:.2}", c)).unwrap_or_else(|| "需人工核算".to_string());
+        println!("终止\t{}，违约金 {}", on, cost);
+    }
+}
+
+pub fn run_contract(store: &SupplierStore, action: &ContractAction) -> Result<()> {
+    match action {
+        ContractAction::Create { offer, quote, qty, price, start, months, end } => {
+            let quote = quote.as_deref().map(|no| store.get_quote(no)).transpose()?;
+            let row = match (offer, &quote) {
+                (Some(offer), _) => store.find_offer(offer)?,
+                (None, Some(q)) => store.get(q.supplier_id)?,
+                (None, None) => unreachable!("clap 保证 --offer 与 --quote 至少提供一个"),
+            };
+            let quantity = qty
+                .or(quote.as_ref().map(|q| q.quantity))
+                .ok_or_else(|| Error::Validation("请通过 --qty 指定数量".to_string()))?;
+            let agreed_price = price
+                .or(row.price)
+                .ok_or_else(|| Error::Validation(format!("供应商 {} 没有价格，请通过 --price 指定约定单价", row.id)))?;
+            let start_date = match start {
+                Some(date) => *date,
+                None => store.today()?,
+            };
+            let min_months = row.min_contract_period.as_deref().and_then(contract::parse_period_months);
+            let end_date = match end {
+                Some(date) => *date,
+                None => {
+                    let months = months.or(quote.as_ref().map(|q| q.months)).or(min_months).ok_or_else(|| {
+                        Error::Validation("无法确定合同期，请通过 --months 或 --end 指定".to_string())
+                    })?;
+                    start_date.add_months(months as i32)
+                }
+            };
+            if let Some(min) = min_months.filter(|m| end_date < start_date.add_months(*m as i32)) {
+                eprintln!("注意：合同期短于供应商的最短合同期 {} 个月", min);
+            }
+            let id = store.create_contract(&NewContract {
+                supplier_id: row.id,
+                quote_no: quote.map(|q| q.quote_no),
+                quantity,
+                agreed_price,
+                start_date,
+                end_date,
+                min_contract_period: row.min_contract_period.clone(),
+                payment_terms: row.payment_terms.clone(),
+                breach_penalties: row.breach_penalties.clone(),
+            })?;
+            println!("已新建合同 {}：供应商 {}，{} 台，{} 至 {}", id, row.id, quantity, start_date, end_date);
+        }
+        ContractAction::Renew { id, months, price } => {
+            let current = store.get_contract(*id)?;
+            let months = months.unwrap_or_else(|| current.term_months());
+            let contract = store.renew_contract(*id, months, *price)?;
+            println!("合同 {} 已续约 {} 个月，新的结束日期 {}", id, months, contract.end_date);
+        }
+        ContractAction::Terminate { id, on, dry_run } => {
+            let on = match on {
+                Some(date) => *date,
+                None => store.today()?,
+            };
+            if *dry_run {
+                print_termination_estimate(&store.get_contract(*id)?.termination_estimate(on));
+            } else {
+                let estimate = store.get_contract(*id)?.termination_estimate(on);
+                store.terminate_contract(*id, on)?;
+                println!("合同 {} 已终止", id);
+                print_termination_estimate(&estimate);
+            }
+        }
+        ContractAction::List { status, json, table } => {
+            let contracts = store.list_contracts(*status)?;
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&contracts)?);
+            } else if contracts.is_empty() {
+                println!("暂无合同");
+            } else {
+                print!("{}", render_contracts(&contracts, &table.options()));
+            }
+        }
+        ContractAction::Show { id, json } => {
+            let contract = store.get_contract(*id)?;
+            let estimate = match contract.status {
+                ContractStatus::Active => Some(contract.termination_estimate(store.today()?)),
+                ContractStatus::Terminated => None,
+            };
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "contract": contract, "termination_estimate": estimate }))?);
+            } else {
+                print_contract(&contract);
+                if let Some(estimate) = estimate {
+                    print_termination_estimate(&estimate);
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,95 @@
+//! db 子命令：备份、恢复、完整性检查、快照和加密
+
+use std::path::PathBuf;
+
+use clap::Subcommand;
+use wei_assistant_gpu::{config, store};
+use wei_assistant_gpu::{Error, Result, Settings, SupplierStore};
+
+use super::{new_secret, NEW_PASSPHRASE_ENV};
+
+#[derive(Subcommand, Debug)]
+pub enum DbAction {
+    /// 在线备份数据库到指定文件，备份期间不影响其他命令读写
+    Backup {
+        /// 备份文件路径
+        path: PathBuf,
+        /// 备份文件已存在时覆盖
+        #[arg(long)]
+        force: bool,
+    },
+    /// 从备份文件恢复，恢复前自动保存当前数据库的快照
+    Restore {
+        /// 备份文件路径，也可以是 db snapshots 列出的快照
+        path: PathBuf,
+    },
+    /// 检查数据库文件是否损坏
+    IntegrityCheck,
+    /// 列出删除、导入和恢复前自动保存的快照，从旧到新
+    Snapshots,
+    /// 加密联系人、微信和电话，口令取自 --passphrase-file 或环境变量 WEI_ASSISTANT_PASSPHRASE
+    Encrypt,
+    /// 解密全部敏感字段并关闭加密
+    Decrypt,
+    /// 更换口令，新口令取自 --new-passphrase-file 或环境变量 WEI_ASSISTANT_NEW_PASSPHRASE
+    Rekey {
+        /// 保存新口令的文件
+        #[arg(long, value_name = "PATH")]
+        new_passphrase_file: Option<PathBuf>,
+    },
+}
+
+pub fn run_db(settings: &Settings, store: &mut SupplierStore, action: &DbAction) -> Result<()> {
+    match action {
+        DbAction::Backup { path, force } => {
+            if path.exists() && !force {
+                return Err(Error::Validation(format!("{} 已存在，如需覆盖请加 --force", path.display())));
+            }
+            store.backup(path)?;
+            println!("已备份到 {}", path.display());
+        }
+        DbAction::Restore { path } => {
+            if let Some(snapshot) = settings.auto_snapshot(store, "restore")? {
+                println!("恢复前的数据已保存到 {}", snapshot.display());
+            }
+            store.restore(path)?;
+            println!("已从 {} 恢复", path.display());
+        }
+        DbAction::IntegrityCheck => {
+            let problems = store.integrity_check()?;
+            if !problems.is_empty() {
+                return Err(Error::Validation(format!("数据库完整性检查未通过: {}", problems.join("; "))));
+            }
+            println!("完整性检查通过");
+        }
+        DbAction::Encrypt => {
+            let passphrase = settings.passphrase()?.ok_or_else(|| {
+                Error::Validation(format!("请通过 --passphrase-file 或环境变量 {} 提供口令", config::PASSPHRASE_ENV))
+            })?;
+            store.encrypt(&passphrase)?;
+            println!("已加密联系人、微信和电话");
+            if !store::list_snapshots(&settings.snapshot_dir())?.is_empty() {
+                eprintln!("注意：{} 中加密前的快照仍为明文，请按需删除", settings.snapshot_dir().display());
+            }
+        }
+        DbAction::Decrypt => {
+            store.decrypt()?;
+            println!("已解密，数据库不再加密");
+        }
+        DbAction::Rekey { new_passphrase_file } => {
+            let new_passphrase = new_secret(new_passphrase_file.as_deref(), NEW_PASSPHRASE_ENV, "--new-passphrase-file")?;
+            store.rekey(&new_passphrase)?;
+            println!("口令已更换");
+        }
+        DbAction::Snapshots => {
+            let snapshots = store::list_snapshots(&settings.snapshot_dir())?;
+            if snapshots.is_empty() {
+                println!("暂无快照");
+            }
+            for path in snapshots {
+                println!("{}", path.display());
+            }
+        }
+    }
+    Ok(())
+}
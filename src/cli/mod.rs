@@ -0,0 +1,72 @@
+//! 命令行各子命令的参数定义与处理函数
+//!
+//! 每个子命令（或一组相关的子命令）一个模块，包含其 clap 参数和 `run_*` 处理函数；`main.rs` 只负责
+//! 解析参数、认证和分发。处理函数接收 `run` 中打开的同一个 [`SupplierStore`](wei_assistant_gpu::SupplierStore)，
+//! 不自行打开数据库。
+
+use clap::Args;
+use wei_assistant_gpu::auth::Session;
+use wei_assistant_gpu::config::read_passphrase_file;
+use wei_assistant_gpu::render::{ColorChoice, TableLayout, TableOptions};
+use wei_assistant_gpu::{Error, Result};
+
+pub mod audit;
+pub mod catalog;
+pub mod config;
+pub mod contract;
+pub mod db;
+pub mod notify;
+pub mod payments;
+pub mod profile;
+pub mod query;
+pub mod rating;
+pub mod supplier;
+pub mod synonym;
+pub mod user;
+pub mod pricing;
+pub mod quote;
+pub mod view;
+
+/// 指定新口令的环境变量，用于 db rekey
+pub const NEW_PASSPHRASE_ENV: &str = "WEI_ASSISTANT_NEW_PASSPHRASE";
+/// 指定新用户口令的环境变量，用于 user add/passwd
+pub const NEW_PASSWORD_ENV: &str = "WEI_ASSISTANT_NEW_PASSWORD";
+
+/// 终端表格的显示选项，`query` 以及合同、付款等列表共用
+#[derive(Args, Debug, Default)]
+pub struct TableArgs {
+    /// 表格布局：auto 在终端过窄时自动改为逐条纵向显示
+    #[arg(long, value_enum, default_value_t = TableLayout::Auto)]
+    layout: TableLayout,
+    /// 表格最大显示宽度，默认取终端宽度
+    #[arg(long)]
+    width: Option<usize>,
+    /// 表头着色
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+}
+
+impl TableArgs {
+    pub fn options(&self) -> TableOptions {
+        TableOptions::detect(self.layout, self.color, self.width)
+    }
+}
+
+/// 读取新口令：文件优先于环境变量，都没有时提示对应的参数
+pub fn new_secret(file: Option<&std::path::Path>, env: &str, flag: &str) -> Result<String> {
+    match file {
+        Some(path) => read_passphrase_file(path),
+        None => std::env::var(env)
+            .ok()
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| Error::Validation(format!("请通过 {} 或环境变量 {} 提供新口令", flag, env))),
+    }
+}
+
+/// 审计日志中的操作人：已登录的用户，否则为当前系统用户
+pub fn current_actor(session: &Session) -> String {
+    match session.user_name() {
+        Some(name) => name.to_string(),
+        None => std::env::var("USER").or_else(|_| std::env::var("USERNAME")).unwrap_or_else(|_| "unknown".to_string()),
+    }
+}
@@ -0,0 +1,72 @@
+//! notify 子命令：检查即将到期的合同、过期的报价单和库存偏低的供应商并发送提醒
+
+use clap::Subcommand;
+use wei_assistant_gpu::dates;
+use wei_assistant_gpu::notify::{self, NotifyRules, NotifySink};
+use wei_assistant_gpu::{Error, Result, Settings, SupplierStore};
+
+#[derive(Subcommand, Debug)]
+pub enum NotifyAction {
+    /// 检查并发送新的提醒，适合由 cron 定期执行
+    Check {
+        /// 通知通道，可重复：stdout、file:PATH、maildir:DIR、command:CMD、webhook:URL；默认取配置 notify.sinks，都没有时为 stdout
+        #[arg(long = "sink", value_name = "SINK")]
+        sinks: Vec<NotifySink>,
+        /// 提前提醒合同到期的时间，如 14d、2w；默认取配置 notify.contract_days，都没有时为14天
+        #[arg(long, value_parser = dates::parse_days)]
+        contract_within: Option<u32>,
+        /// 可提供数量不超过该值时提醒，默认取配置 notify.low_quantity，都没有时为2
+        #[arg(long)]
+        low_quantity: Option<i32>,
+        /// 包括已发送过的提醒
+        #[arg(long)]
+        all: bool,
+        /// 只在标准输出列出将要发送的提醒，不发送也不记录
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+pub fn run_notify(settings: &Settings, store: &SupplierStore, action: &NotifyAction) -> Result<()> {
+    match action {
+        NotifyAction::Check { sinks, contract_within, low_quantity, all, dry_run } => {
+            let config = &settings.config.notify;
+            let rules = NotifyRules {
+                contract_days: contract_within.or(config.contract_days).unwrap_or(notify::DEFAULT_CONTRACT_DAYS),
+                low_quantity: low_quantity.or(config.low_quantity).unwrap_or(notify::DEFAULT_LOW_QUANTITY),
+            };
+            let mut notifications = notify::collect(store, store.today()?, &rules)?;
+            if !*all {
+                notifications = store.unsent_notifications(notifications)?;
+            }
+            if *dry_run {
+                for n in &notifications {
+                    println!("{}", n.line());
+                }
+                return Ok(());
+            }
+            if notifications.is_empty() {
+                return Ok(());
+            }
+            let sinks = match (sinks.is_empty(), &config.sinks) {
+                (false, _) => sinks.clone(),
+                (true, Some(configured)) if !configured.is_empty() => configured.clone(),
+                _ => vec![NotifySink::Stdout],
+            };
+            // 每个通道都尝试发送；全部成功后才记为已发送，失败的下次检查时重发
+            let mut failed = 0;
+            for sink in &sinks {
+                if let Err(e) = sink.deliver(&notifications) {
+                    eprintln!("通知通道 {} 发送失败: {}", sink, e);
+                    failed += 1;
+                }
+            }
+            if failed > 0 {
+                return Err(Error::Io(std::io::Error::other(format!("{} 个通知通道发送失败，本次提醒未记为已发送", failed))));
+            }
+            store.record_notifications(&notifications)?;
+            eprintln!("已发送 {} 条提醒", notifications.len());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,176 @@
+//! payments 子命令：按合同的付款方式生成付款计划，查看到期款项和未付余额
+
+use clap::Subcommand;
+use wei_assistant_gpu::contract::ContractStatus;
+use wei_assistant_gpu::dates::{self, Date};
+use wei_assistant_gpu::payment::{SupplierBalance, Payment, PaymentCadence};
+use wei_assistant_gpu::render::{self, TableOptions};
+use wei_assistant_gpu::{Error, Result, SupplierStore};
+
+use super::TableArgs;
+
+#[derive(Subcommand, Debug)]
+pub enum PaymentsAction {
+    /// 为履行中的合同生成付款计划，续约后再次执行会补上新增的各期
+    Schedule {
+        /// 合同ID，默认全部履行中的合同
+        #[arg(long)]
+        contract: Option<i64>,
+        /// 付款周期，默认按合同的付款方式识别
+        #[arg(long, value_enum, requires = "contract")]
+        cadence: Option<PaymentCadence>,
+    },
+    /// 列出付款计划
+    List {
+        /// 只列出指定合同的付款
+        #[arg(long)]
+        contract: Option<i64>,
+        /// 只列出未付的款项
+        #[arg(long)]
+        unpaid: bool,
+        /// 以JSON格式输出
+        #[arg(long)]
+        json: bool,
+        #[command(flatten)]
+        table: TableArgs,
+    },
+    /// 列出指定时间内到期及已逾期的未付款项
+    Due {
+        /// 时间范围，如 14d、2w
+        #[arg(long, default_value = "14d", value_parser = dates::parse_days)]
+        within: u32,
+        /// 以JSON格式输出
+        #[arg(long)]
+        json: bool,
+        #[command(flatten)]
+        table: TableArgs,
+    },
+    /// 标记付款已支付
+    Pay {
+        /// 付款ID
+        id: i64,
+        /// 付款日期（YYYY-MM-DD），默认今天
+        #[arg(long)]
+        on: Option<Date>,
+    },
+    /// 各供应商的未付余额
+    Balance {
+        /// 以JSON格式输出
+        #[arg(long)]
+        json: bool,
+        #[command(flatten)]
+        table: TableArgs,
+    },
+}
+
+/// 付款列表表格，状态列显示支付日期或逾期天数
+fn render_payments(payments: &[Payment], today: Date, opts: &TableOptions) -> String {
+    const HEADERS: [&str; 8] = ["ID", "合同", "供应商", "到期", "账期开始", "账期结束", "金额", "状态"];
+    const NUMERIC: [bool; 8] = [true, true, true, false, false, false, true, false];
+    let data: Vec<Vec<String>> = payments
+        .iter()
+        .map(|p| {
+            let status = match p.paid_on {
+                Some(on) => format!("已付 {}", on),
+                None if p.due_date < today => format!("逾期 {} 天", p.due_date.days_until(today)),
+                None => "未付".to_string(),
+            };
+            vec![
+                p.id.to_string(),
+                p.contract_id.to_string(),
+                p.supplier_id.to_string(),
+                p.due_date.to_string(),
+                p.period_start.to_string(),
+                p.period_end.to_string(),
+                format!("{:.2}", p.amount),
+                status,
+            ]
+        })
+        .collect();
+    render::render_grid(&HEADERS, &data, &NUMERIC, opts)
+}
+
+/// 各供应商未付余额表格
+fn render_balances(balances: &[SupplierBalance], opts: &TableOptions) -> String {
+    const HEADERS: [&str; 5] = ["供应商", "已到期", "其中逾期", "未到期", "最早到期"];
+    const NUMERIC: [bool; 5] = [true, true, true, true, false];
+    let data: Vec<Vec<String>> = balances
+        .iter()
+        .map(|b| {
+            vec![
+                b.supplier_id.to_string(),
+                format!("{:.2}", b.due),
+                format!("{:.2}", b.overdue),
+                format!("{:.2}", b.upcoming),
+                b.next_due_date.map(|d| d.to_string()).unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect();
+    render::render_grid(&HEADERS, &data, &NUMERIC, opts)
+}
+
+pub fn run_payments(store: &SupplierStore, action: &PaymentsAction) -> Result<()> {
+    let today = store.today()?;
+    match action {
+        PaymentsAction::Schedule { contract, cadence } => {
+            let ids = match contract {
+                Some(id) => vec![*id],
+                None => store.list_contracts(Some(ContractStatus::Active))?.into_iter().map(|c| c.id).collect(),
+            };
+            let mut added = 0;
+            for id in ids {
+                match store.generate_payments(id, *cadence) {
+                    Ok(n) => added += n,
+                    // 批量生成时跳过付款方式无法识别的合同，逐个提示
+                    Err(Error::Validation(msg)) if contract.is_none() => eprintln!("跳过：{}", msg),
+                    Err(e) => return Err(e),
+                }
+            }
+            println!("已生成 {} 期付款", added);
+        }
+        PaymentsAction::List { contract, unpaid, json, table } => {
+            if let Some(id) = contract {
+                store.get_contract(*id)?;
+            }
+            let payments: Vec<Payment> = store.list_payments(*contract)?.into_iter().filter(|p| !*unpaid || p.paid_on.is_none()).collect();
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&payments)?);
+            } else if payments.is_empty() {
+                println!("暂无付款计划");
+            } else {
+                print!("{}", render_payments(&payments, today, &table.options()));
+            }
+        }
+        PaymentsAction::Due { within, json, table } => {
+            let until = today.add_days(i64::from(*within));
+            let payments = store.payments_due(until)?;
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&payments)?);
+            } else if payments.is_empty() {
+                println!("截至 {} 没有到期的未付款项", until);
+            } else {
+                print!("{}", render_payments(&payments, today, &table.options()));
+                let total: f64 = payments.iter().map(|p| p.amount).sum();
+                println!("截至 {} 到期 {} 笔，合计 {:.2}", until, payments.len(), total);
+            }
+        }
+        PaymentsAction::Pay { id, on } => {
+            let payment = store.mark_payment_paid(*id, on.unwrap_or(today))?;
+            println!("付款 {} 已标记为于 {} 支付，金额 {:.2}", id, payment.paid_on.unwrap_or(today), payment.amount);
+        }
+        PaymentsAction::Balance { json, table } => {
+            let balances = store.outstanding_balances(today)?;
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&balances)?);
+            } else if balances.is_empty() {
+                println!("没有未付款项");
+            } else {
+                print!("{}", render_balances(&balances, &table.options()));
+                let due: f64 = balances.iter().map(|b| b.due).sum();
+                let upcoming: f64 = balances.iter().map(|b| b.upcoming).sum();
+                println!("合计：已到期 {:.2}，未到期 {:.2}", due, upcoming);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,81 @@
+//! pricing 子命令：管理按地点、组网类型和租赁模式加价的定价规则
+
+use clap::Subcommand;
+use wei_assistant_gpu::pricing::{MarkupKind, PricingRule};
+use wei_assistant_gpu::{Result, SupplierStore};
+
+#[derive(Subcommand, Debug)]
+pub enum PricingAction {
+    /// 添加定价规则，未指定的条件匹配任意值；多条规则匹配时条件最多的优先
+    Add {
+        /// 地点，按同义词匹配
+        #[arg(long)]
+        location: Option<String>,
+        /// 组网类型
+        #[arg(long)]
+        networking_category: Option<String>,
+        /// 租赁模式
+        #[arg(long)]
+        rental_model: Option<String>,
+        /// 按百分比加价，作用于价格、带宽价和存储价
+        #[arg(long, value_name = "PCT", conflicts_with = "fixed", required_unless_present = "fixed")]
+        percent: Option<f64>,
+        /// 每台加固定金额，加在价格上
+        #[arg(long, value_name = "AMOUNT")]
+        fixed: Option<f64>,
+        /// 每台最低毛利，加价后不足时补足
+        #[arg(long, value_name = "AMOUNT")]
+        min_margin: Option<f64>,
+    },
+    /// 列出全部定价规则
+    List {
+        /// 以JSON格式输出
+        #[arg(long)]
+        json: bool,
+    },
+    /// 删除定价规则
+    Remove {
+        /// 规则ID
+        id: i64,
+    },
+}
+
+pub fn run_pricing(store: &SupplierStore, action: &PricingAction) -> Result<()> {
+    match action {
+        PricingAction::Add { location, networking_category, rental_model, percent, fixed, min_margin } => {
+            let (kind, value) = match (percent, fixed) {
+                (Some(v), _) => (MarkupKind::Percent, *v),
+                (None, Some(v)) => (MarkupKind::Fixed, *v),
+                (None, None) => unreachable!("clap 保证 --percent 与 --fixed 恰好提供一个"),
+            };
+            let rule = PricingRule {
+                id: 0,
+                location: location.clone(),
+                networking_category: networking_category.clone(),
+                rental_model: rental_model.clone(),
+                kind,
+                value,
+                min_margin: *min_margin,
+            };
+            let id = store.add_pricing_rule(&rule)?;
+            println!("已添加定价规则 {}：{}", id, rule.describe());
+        }
+        PricingAction::List { json } => {
+            let rules = store.pricing_rules()?;
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&rules)?);
+            } else if rules.is_empty() {
+                println!("暂无定价规则，报价按成本价计算");
+            } else {
+                for rule in rules {
+                    println!("{}\t{}", rule.id, rule.describe());
+                }
+            }
+        }
+        PricingAction::Remove { id } => {
+            store.remove_pricing_rule(*id)?;
+            println!("定价规则 {} 已删除", id);
+        }
+    }
+    Ok(())
+}
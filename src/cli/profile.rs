@@ -0,0 +1,68 @@
+//! profile 子命令：管理配置文件中的命名数据库
+
+use clap::Subcommand;
+use wei_assistant_gpu::config::{self, LoadedConfig, Overrides};
+use wei_assistant_gpu::{Error, Result};
+
+use super::config::config_target;
+
+#[derive(Subcommand, Debug)]
+pub enum ProfileAction {
+    /// 列出全部 profile，当前使用的以 * 标出
+    List,
+    /// 添加 profile，同名时覆盖，如 profile add cn-east /data/cn-east.db
+    Add {
+        /// profile 名称
+        name: String,
+        /// 数据库文件路径，相对路径相对于配置文件所在目录
+        db: String,
+        /// 写入当前项目的 .wei-assistant.toml，默认写入用户配置
+        #[arg(long)]
+        project: bool,
+    },
+    /// 设为默认 profile
+    Use {
+        /// profile 名称
+        name: String,
+        /// 写入当前项目的 .wei-assistant.toml，默认写入用户配置
+        #[arg(long)]
+        project: bool,
+    },
+}
+
+/// 执行 profile 子命令，只读取配置文件，不打开数据库
+pub fn run_profile(overrides: Overrides<'_>, action: &ProfileAction) -> Result<()> {
+    match action {
+        ProfileAction::List => {
+            let loaded = LoadedConfig::load(overrides.config)?;
+            if loaded.config.profiles.is_empty() {
+                println!("未定义任何 profile");
+            }
+            let active = loaded.active_profile(overrides.profile);
+            for (name, profile) in &loaded.config.profiles {
+                let mark = if active.as_deref() == Some(name.as_str()) { "*" } else { " " };
+                println!("{} {}\t{}", mark, name, profile.db);
+            }
+        }
+        ProfileAction::Add { name, db, project } => {
+            let path = config_target(overrides, *project)?;
+            let mut file = config::Config::load_or_default(&path)?;
+            file.add_profile(name, db)?;
+            file.save(&path)?;
+            println!("已添加 profile {} = {}（{}）", name, db, path.display());
+        }
+        ProfileAction::Use { name, project } => {
+            let path = config_target(overrides, *project)?;
+            let mut file = config::Config::load_or_default(&path)?;
+            let defined = file.profiles.contains_key(name)
+                || LoadedConfig::load(overrides.config)?.config.profiles.contains_key(name);
+            if !defined {
+                return Err(Error::NotFound(format!("未定义 profile: {}", name)));
+            }
+            file.set("profile", name)?;
+            file.save(&path)?;
+            println!("已切换到 profile {}（{}）", name, path.display());
+        }
+    }
+    Ok(())
+}
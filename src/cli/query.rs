@@ -0,0 +1,279 @@
+//! query 子命令：按字段、范围和筛选表达式查询，并按格式输出或导出
+
+use clap::Args;
+use wei_assistant_gpu::auth::{Role, Session};
+use wei_assistant_gpu::render::{self, print_suppliers_json, OutputFormat, TableOptions};
+use wei_assistant_gpu::{csv_io, privacy, xlsx};
+use wei_assistant_gpu::{parse_filter, Column, ComparisonOp, Condition, Error, FilterCriteria, MatchMode, QueryBuilder, Result, SavedView, Settings, SortKey, SupplierField, SupplierRow, SupplierStore};
+
+use super::{current_actor, TableArgs};
+use super::supplier::SupplierArgs;
+
+/// 数值字段的范围筛选参数，边界均为闭区间
+#[derive(Args, Debug, Default)]
+pub struct RangeArgs {
+    #[arg(long, name = "quantity-min")]
+    quantity_min: Option<i32>,
+    #[arg(long, name = "quantity-max")]
+    quantity_max: Option<i32>,
+    #[arg(long, name = "price-min")]
+    price_min: Option<f64>,
+    #[arg(long, name = "price-max")]
+    price_max: Option<f64>,
+    #[arg(long, name = "bandwidth-price-min")]
+    bandwidth_price_min: Option<f64>,
+    #[arg(long, name = "bandwidth-price-max")]
+    bandwidth_price_max: Option<f64>,
+    #[arg(long, name = "storage-price-min")]
+    storage_price_min: Option<f64>,
+    #[arg(long, name = "storage-price-max")]
+    storage_price_max: Option<f64>,
+}
+
+impl RangeArgs {
+    /// 每个数值字段的 (字段, 下限, 上限)
+    fn bounds(&self) -> [(SupplierField, Option<f64>, Option<f64>); 4] {
+        [
+            (SupplierField::Quantity, self.quantity_min.map(f64::from), self.quantity_max.map(f64::from)),
+            (SupplierField::Price, self.price_min, self.price_max),
+            (SupplierField::BandwidthPrice, self.bandwidth_price_min, self.bandwidth_price_max),
+            (SupplierField::StoragePrice, self.storage_price_min, self.storage_price_max),
+        ]
+    }
+}
+
+/// 筛选、排序和列选择参数，命名视图只保存这一部分
+#[derive(Args, Debug, Default)]
+pub struct SelectionArgs {
+    #[command(flatten)]
+    fields: SupplierArgs,
+    #[command(flatten)]
+    ranges: RangeArgs,
+    /// 筛选表达式，支持 and/or/not、括号和 in，如 "(location in [北京,上海]) and price < 1500"
+    #[arg(long)]
+    filter: Option<String>,
+    /// 综合评分下限（见 rating 子命令），没有评分的供应商不会匹配
+    #[arg(long)]
+    score_min: Option<f64>,
+    /// 综合评分上限
+    #[arg(long)]
+    score_max: Option<f64>,
+    /// 文本字段的匹配方式；地点和服务器名还会按同义词表匹配（如 北京/Beijing/BJ）
+    #[arg(long = "match", value_enum, default_value_t = MatchMode::Exact)]
+    match_mode: MatchMode,
+    /// 排序，逗号分隔，如 price,quantity:desc；相同时按ID排序。重复指定时以最后一次为准
+    #[arg(long, value_delimiter = ',', value_name = "列[:asc|desc]", action = clap::ArgAction::Set)]
+    sort: Vec<SortKey>,
+    /// 只输出指定列（表格、JSON、CSV、Markdown、HTML 均适用），逗号分隔，如 id,contact,price
+    #[arg(long, value_delimiter = ',', action = clap::ArgAction::Set)]
+    columns: Vec<Column>,
+}
+
+#[derive(Args, Debug, Default)]
+pub struct QueryArgs {
+    #[command(flatten)]
+    selection: SelectionArgs,
+    /// 在所有 profile 中查询并合并结果，输出增加 profile 列
+    #[arg(long)]
+    all_profiles: bool,
+    /// 导出为CSV文件（可选）
+    #[arg(long, name = "export-csv")]
+    export_csv: Option<String>,
+    #[command(flatten)]
+    csv_options: csv_io::CsvOptions,
+    /// 导出为Excel文件（可选）
+    #[arg(long, name = "export-xlsx")]
+    export_xlsx: Option<String>,
+    /// Excel导出时附加按地点汇总的统计表
+    #[arg(long, name = "xlsx-stats", requires = "export-xlsx")]
+    xlsx_stats: bool,
+    /// 输出格式，默认 table（可在配置文件中修改）
+    #[arg(long, value_enum)]
+    format: Option<OutputFormat>,
+    #[command(flatten)]
+    table: TableArgs,
+    /// 以JSON格式输出（可选，等同于 --format json）
+    #[arg(long, conflicts_with = "format")]
+    json: bool,
+    /// 电话、微信脱敏显示（如 138****8000），所有输出格式和导出均适用
+    #[arg(long)]
+    mask_pii: bool,
+    /// 显示未脱敏的电话、微信，覆盖 --mask-pii 和配置；每次使用都会记入审计日志
+    #[arg(long, conflicts_with = "mask_pii")]
+    reveal_pii: bool,
+}
+
+impl SelectionArgs {
+    /// 将字段参数转换为等值筛选条件，范围参数转换为 >= / <= 条件
+    pub fn to_query(&self) -> Result<QueryBuilder> {
+        let s = self.fields.to_supplier();
+        let text = |v: &Option<String>| v.clone();
+        let num = |v: Option<f64>| v.map(|v| v.to_string());
+        let values = [
+            (SupplierField::ContactPerson, text(&s.contact)),
+            (SupplierField::Wechat, text(&s.wechat)),
+            (SupplierField::Phone, text(&s.phone)),
+            (SupplierField::Quantity, s.quantity.map(|v| v.to_string())),
+            (SupplierField::Location, text(&s.location)),
+            (SupplierField::Price, num(s.price)),
+            (SupplierField::BandwidthPrice, num(s.bandwidth_price)),
+            (SupplierField::StoragePrice, num(s.storage_price)),
+            (SupplierField::MinContractPeriod, text(&s.min_contract_period)),
+            (SupplierField::BreachPenalties, text(&s.breach_penalties)),
+            (SupplierField::PaymentTerms, text(&s.payment_terms)),
+            (SupplierField::ServerName, text(&s.server_name)),
+            (SupplierField::ServerConfig, text(&s.server_config)),
+            (SupplierField::RentalModel, text(&s.rental_model)),
+            (SupplierField::NetworkingCategory, text(&s.networking_category)),
+        ];
+        let mut qb = values.into_iter()
+            .filter_map(|(field, value)| value.map(|v| (field, v)))
+            .fold(QueryBuilder::new(), |qb, (field, value)| {
+                qb.filter(FilterCriteria { field, op: ComparisonOp::Eq, value: Some(value), mode: self.match_mode })
+            });
+
+        for (field, min, max) in self.ranges.bounds() {
+            if let (Some(min), Some(max)) = (min, max) {
+                if min > max {
+                    return Err(Error::Validation(format!(
+                        "--{0}-min 不能大于 --{0}-max", field.column().replace('_', "-")
+                    )));
+                }
+            }
+            for (op, bound) in [(ComparisonOp::Gte, min), (ComparisonOp::Lte, max)] {
+                if let Some(bound) = bound {
+                    qb = qb.filter(FilterCriteria { field, op, value: Some(bound.to_string()), mode: MatchMode::Exact });
+                }
+            }
+        }
+        if let (Some(min), Some(max)) = (self.score_min, self.score_max) {
+            if min > max {
+                return Err(Error::Validation("--score-min 不能大于 --score-max".to_string()));
+            }
+        }
+        for (op, bound) in [(ComparisonOp::Gte, self.score_min), (ComparisonOp::Lte, self.score_max)] {
+            if let Some(bound) = bound {
+                qb = qb.condition(Condition::Score { op, value: Some(bound) });
+            }
+        }
+        if let Some(expr) = &self.filter {
+            qb = qb.condition(parse_filter(expr)?.with_match_mode(self.match_mode));
+        }
+        Ok(self.sort.iter().fold(qb, |qb, key| qb.order_by(*key)))
+    }
+
+    /// 转为视图：先构建一次查询，不合法的条件不会被保存
+    pub fn to_view(&self) -> Result<SavedView> {
+        let query = self.to_query()?;
+        query.clone().build()?;
+        Ok(query.into_view(self.columns.clone()))
+    }
+}
+
+impl QueryArgs {
+    pub fn to_query(&self) -> Result<QueryBuilder> {
+        self.selection.to_query()
+    }
+}
+
+/// 审计日志中查看未脱敏个人信息的操作类型
+pub const AUDIT_REVEAL_PII: &str = "reveal_pii";
+
+/// 执行查询并按参数输出、导出，query 与 view run 共用；未指定的格式、列和排序取配置文件中的默认值
+///
+/// 执行视图时 `view` 的筛选条件与参数中的条件同时生效，参数中指定的排序和列优先于视图保存的。
+/// viewer 和未登录用户总是看到脱敏后的电话、微信，`--reveal-pii` 需要 editor 及以上角色。
+pub fn run_query(settings: &Settings, store: &SupplierStore, session: &Session, args: &QueryArgs, view: Option<&SavedView>) -> Result<()> {
+    args.csv_options.validate()?;
+    if args.reveal_pii {
+        session.require(Role::Editor)?;
+    }
+    let defaults = &settings.config.defaults;
+    let mut query = args.to_query()?;
+    let mut selected = args.selection.columns.clone();
+    if let Some(view) = view {
+        query = view.apply(query);
+        if selected.is_empty() {
+            selected = view.columns.clone();
+        }
+    }
+    if !query.is_ordered() {
+        query = defaults.sort.iter().flatten().fold(query, |qb, key| qb.order_by(*key));
+    }
+    // 未指定列时，有供应商已打分则在末尾增加评分列
+    let show_score = selected.is_empty() && defaults.columns.is_none();
+    let mut columns = match (&selected, &defaults.columns) {
+        (selected, Some(default)) if selected.is_empty() => default.clone(),
+        (selected, _) => selected.clone(),
+    };
+    // 跨 profile 查询时 profile 列总在最前
+    if args.all_profiles && !columns.contains(&Column::Profile) {
+        columns = std::iter::once(Column::Profile).chain(Column::resolve(&columns)).collect();
+    }
+    let mut opts = TableOptions {
+        columns,
+        currency: defaults.currency.clone(),
+        ..args.table.options()
+    };
+    let format = if args.json { OutputFormat::Json } else { args.format.or(defaults.format).unwrap_or_default() };
+
+    // 显示原文时先在各数据库中记录审计日志，记录失败时不输出原文；viewer 看到的结果按各库中的身份脱敏
+    let command = std::env::args().skip(1).collect::<Vec<_>>().join(" ");
+    let mask = args.mask_pii || settings.config.privacy.mask_pii.unwrap_or(false);
+    let protect = |store: &SupplierStore, session: &Session, rows: &mut Vec<SupplierRow>| {
+        if args.reveal_pii {
+            session.require(Role::Editor)?;
+            store.record_audit(&current_actor(session), AUDIT_REVEAL_PII, &format!("{}（{} 条记录）", command, rows.len()))?;
+        } else if mask || session.is_viewer() {
+            privacy::mask_rows(rows);
+        }
+        Ok(())
+    };
+    let rows = if args.all_profiles {
+        let found = settings.query_all_profiles(store, session, query, protect)?;
+        for (name, e) in &found.skipped {
+            eprintln!("已跳过 profile {}: {}", name, e);
+        }
+        found.rows
+    } else {
+        let mut rows = store.query(query)?;
+        protect(store, session, &mut rows)?;
+        rows
+    };
+    if rows.is_empty() {
+        println!("没有找到符合条件的供应商");
+        return Ok(());
+    }
+    // 不选列的 JSON 输出是完整记录，已包含 score 字段
+    let full_json = format == OutputFormat::Json && opts.columns.is_empty();
+    if show_score && !full_json && rows.iter().any(|r| r.score.is_some()) {
+        opts.columns = Column::resolve(&opts.columns);
+        opts.columns.push(Column::Score);
+    }
+
+    // 根据需要使用不同的输出格式
+    match format {
+        OutputFormat::Table => print!("{}", render::render_table(&rows, &opts)),
+        OutputFormat::Json => print_suppliers_json(&rows, &opts.columns),
+        OutputFormat::Markdown => print!("{}", render::render_markdown(&rows, &opts)),
+        OutputFormat::Html => print!("{}", render::render_html(&rows, &opts)),
+        OutputFormat::Csv => {
+            use std::io::Write;
+            let bytes = csv_io::suppliers_to_csv_bytes(&rows, &opts.columns, &args.csv_options)?;
+            std::io::stdout().write_all(&bytes)?;
+        }
+    }
+
+    // 如果需要导出CSV
+    if let Some(csv_path) = &args.export_csv {
+        csv_io::export_suppliers_to_csv(&rows, &opts.columns, csv_path, &args.csv_options)?;
+        println!("已导出 {} 条记录到 {}", rows.len(), csv_path);
+    }
+
+    // 如果需要导出Excel
+    if let Some(xlsx_path) = &args.export_xlsx {
+        xlsx::export_suppliers_to_xlsx(&rows, &opts.columns, xlsx_path, args.xlsx_stats)?;
+        println!("已导出 {} 条记录到 {}", rows.len(), xlsx_path);
+    }
+    Ok(())
+}
@@ -0,0 +1,178 @@
+//! quote 子命令：按定价规则报价，生成、保存和重新输出客户报价单
+
+use std::path::PathBuf;
+
+use clap::{Args, Subcommand};
+use wei_assistant_gpu::config;
+use wei_assistant_gpu::pricing::{self, PriceQuote};
+use wei_assistant_gpu::quotation::{self, Quotation, QuoteFormat, QuoteRequest};
+use wei_assistant_gpu::{Error, Result, Settings, SupplierStore};
+
+#[derive(Subcommand, Debug)]
+pub enum QuoteAction {
+    /// 计算一个报价的客户价格，并列出成本、售价和毛利
+    Price {
+        /// 供应商ID或报价编号（如 OF-7KQ2M9XA）
+        #[arg(long)]
+        offer: String,
+        /// 数量（台）
+        #[arg(long, default_value_t = 1)]
+        qty: u32,
+        /// 以JSON格式输出
+        #[arg(long)]
+        json: bool,
+    },
+    /// 生成客户报价单并保存，编号形如 Q20240101-001
+    Generate {
+        /// 供应商ID或报价编号（如 OF-7KQ2M9XA）
+        #[arg(long)]
+        offer: String,
+        /// 数量（台）
+        #[arg(long, default_value_t = 1)]
+        qty: u32,
+        /// 租期月数
+        #[arg(long, default_value_t = 1)]
+        months: u32,
+        /// 客户名称
+        #[arg(long)]
+        customer: String,
+        /// 附加说明，对应模板中的 {{notes}}
+        #[arg(long)]
+        notes: Option<String>,
+        /// 有效天数，默认取配置 quotes.valid_days，未配置时为7天
+        #[arg(long)]
+        valid_days: Option<u32>,
+        #[command(flatten)]
+        output: QuoteOutputArgs,
+    },
+    /// 重新生成已保存的报价单，如修改模板之后
+    Show {
+        /// 报价单编号
+        quote_no: String,
+        #[command(flatten)]
+        output: QuoteOutputArgs,
+    },
+    /// 列出已保存的报价单，从新到旧
+    List {
+        /// 以JSON格式输出
+        #[arg(long)]
+        json: bool,
+    },
+    /// 输出内置模板，可保存后修改，再通过 --template 或配置 quotes.*_template 使用
+    Template {
+        /// 模板格式
+        #[arg(long, value_enum, default_value_t = QuoteFormat::Markdown)]
+        format: QuoteFormat,
+    },
+}
+
+/// 报价单的输出参数
+#[derive(Args, Debug)]
+pub struct QuoteOutputArgs {
+    /// 输出格式
+    #[arg(long, value_enum, default_value_t = QuoteFormat::Markdown)]
+    format: QuoteFormat,
+    /// 模板文件，优先于配置 quotes.markdown_template / quotes.html_template
+    #[arg(long, value_name = "PATH")]
+    template: Option<PathBuf>,
+    /// 写入文件而不是标准输出
+    #[arg(long, value_name = "PATH")]
+    output: Option<PathBuf>,
+}
+
+impl QuoteOutputArgs {
+    /// 读取模板：--template > 配置中的模板 > 内置模板
+    fn template(&self, settings: &Settings) -> Result<String> {
+        let configured = match self.format {
+            QuoteFormat::Markdown => &settings.config.quotes.markdown_template,
+            QuoteFormat::Html => &settings.config.quotes.html_template,
+        };
+        match self.template.clone().or_else(|| configured.as_ref().map(PathBuf::from)) {
+            Some(path) => std::fs::read_to_string(&path)
+                .map_err(|e| Error::Io(std::io::Error::new(e.kind(), format!("无法读取模板 {}: {}", path.display(), e)))),
+            None => Ok(self.format.builtin_template().to_string()),
+        }
+    }
+
+    /// 按模板生成报价单并输出
+    fn write(&self, settings: &Settings, template: &str, quotation: &Quotation) -> Result<()> {
+        let content = quotation.render(template, self.format, settings.config.defaults.currency.as_deref())?;
+        match &self.output {
+            Some(path) => {
+                std::fs::write(path, content)?;
+                println!("报价单 {} 已写入 {}", quotation.quote_no, path.display());
+            }
+            None => print!("{}", content),
+        }
+        Ok(())
+    }
+}
+
+/// 逐项输出报价的成本、售价和毛利
+fn print_price_quote(quote: &PriceQuote) {
+    match &quote.rule {
+        Some(rule) => println!("供应商 {}，数量 {}，定价规则 {}（{}）", quote.supplier_id, quote.quantity, rule.id, rule.describe()),
+        None => println!("供应商 {}，数量 {}，未匹配定价规则，按成本价报价", quote.supplier_id, quote.quantity),
+    }
+    println!("项目\t单价成本\t单价售价\t成本\t售价\t毛利");
+    for line in &quote.lines {
+        println!(
+            "{}\t{:.2}\t{:.2}\t{:.2}\t{:.2}\t{:.2}",
+            line.item, line.unit_cost, line.unit_price, line.cost, line.price, line.margin
+        );
+    }
+    println!("合计\t\t\t{:.2}\t{:.2}\t{:.2}", quote.total_cost, quote.total_price, quote.margin);
+    println!("毛利率 {:.1}%", quote.margin_rate * 100.0);
+}
+
+pub fn run_quote(settings: &Settings, store: &SupplierStore, action: &QuoteAction) -> Result<()> {
+    match action {
+        QuoteAction::Price { offer, qty, json } => {
+            let row = store.find_offer(offer)?;
+            let rule = store.pricing_rule_for(&row)?;
+            let quote = pricing::quote(&row, *qty, rule.as_ref())?;
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&quote)?);
+            } else {
+                print_price_quote(&quote);
+            }
+        }
+        QuoteAction::Generate { offer, qty, months, customer, notes, valid_days, output } => {
+            let row = store.find_offer(offer)?;
+            let rule = store.pricing_rule_for(&row)?;
+            let price = pricing::quote(&row, *qty, rule.as_ref())?;
+            let request = QuoteRequest {
+                customer,
+                months: *months,
+                valid_days: valid_days.or(settings.config.quotes.valid_days).unwrap_or(config::DEFAULT_QUOTE_VALID_DAYS),
+                notes: notes.as_deref(),
+            };
+            // 先读取并检查模板，模板有误时不保存报价单，也不占用编号
+            let template = output.template(settings)?;
+            quotation::check_template(&template)?;
+            let quotation = store.create_quote(&row, &price, &request)?;
+            output.write(settings, &template, &quotation)?;
+            if output.output.is_none() {
+                eprintln!("已保存报价单 {}", quotation.quote_no);
+            }
+        }
+        QuoteAction::Show { quote_no, output } => output.write(settings, &output.template(settings)?, &store.get_quote(quote_no)?)?,
+        QuoteAction::List { json } => {
+            let quotes = store.list_quotes()?;
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&quotes)?);
+            } else if quotes.is_empty() {
+                println!("暂无报价单");
+            } else {
+                for q in quotes {
+                    println!(
+                        "{}\t{}\t{}\t供应商 {}\t{} 台 × {} 个月\t{:.2}\t有效期至 {}",
+                        q.quote_no, q.created_on, q.customer, q.supplier_id, q.quantity, q.months, q.total, q.valid_until
+                    );
+                }
+            }
+        }
+        QuoteAction::Template { format } => print!("{}", format.builtin_template()),
+    }
+    Ok(())
+}
@@ -0,0 +1,208 @@
+//! rating 和 incident 子命令：供应商评分与事故记录
+
+use clap::Subcommand;
+use wei_assistant_gpu::dates::Date;
+use wei_assistant_gpu::rating::{self, Incident, IncidentSeverity, NewIncident, Rating, RatingDimension, RatingUpdate};
+use wei_assistant_gpu::{Result, SupplierStore};
+
+#[derive(Subcommand, Debug)]
+pub enum RatingAction {
+    /// 为供应商打分（1-5分），只修改指定的项
+    Set {
+        /// 供应商ID
+        supplier: i64,
+        /// 交付：是否按时、按约定配置交付
+        #[arg(long)]
+        delivery: Option<u8>,
+        /// 稳定性：宕机、断网等故障的频率
+        #[arg(long)]
+        stability: Option<u8>,
+        /// 支持：响应速度和处理问题的能力
+        #[arg(long)]
+        support: Option<u8>,
+        /// 价格诚信：报价是否透明、有无临时加价
+        #[arg(long)]
+        price_honesty: Option<u8>,
+        /// 备注
+        #[arg(long)]
+        note: Option<String>,
+    },
+    /// 查看供应商的评分明细和计入评分的事故
+    Show {
+        /// 供应商ID
+        supplier: i64,
+        /// 以JSON格式输出
+        #[arg(long)]
+        json: bool,
+    },
+    /// 列出已打分的供应商，按综合评分从高到低排列
+    List {
+        /// 以JSON格式输出
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum IncidentAction {
+    /// 记录一起事故
+    Add {
+        /// 供应商ID
+        supplier: i64,
+        /// 事故描述，如 "断网4小时"
+        description: String,
+        /// 严重程度
+        #[arg(long, value_enum, default_value_t = IncidentSeverity::Minor)]
+        severity: IncidentSeverity,
+        /// 发生日期（YYYY-MM-DD），默认今天
+        #[arg(long)]
+        on: Option<Date>,
+    },
+    /// 列出事故，最近发生的在前
+    List {
+        /// 只列出指定供应商的事故
+        #[arg(long)]
+        supplier: Option<i64>,
+        /// 以JSON格式输出
+        #[arg(long)]
+        json: bool,
+    },
+    /// 删除事故记录
+    Remove {
+        /// 事故ID
+        id: i64,
+    },
+}
+
+/// 评分文本，未打分时为 `-`
+fn score_text(score: Option<f64>) -> String {
+    score.map(|v| format!("{:.2}", v)).unwrap_or_else(|| "-".to_string())
+}
+
+pub fn run_rating(store: &SupplierStore, action: &RatingAction) -> Result<()> {
+    match action {
+        RatingAction::Set { supplier, delivery, stability, support, price_honesty, note } => {
+            let update = RatingUpdate {
+                delivery: *delivery,
+                stability: *stability,
+                support: *support,
+                price_honesty: *price_honesty,
+                note: note.clone(),
+            };
+            store.set_rating(*supplier, &update)?;
+            println!("已更新供应商 {} 的评分，综合评分 {}", supplier, score_text(store.get(*supplier)?.score));
+        }
+        RatingAction::Show { supplier, json } => {
+            store.get(*supplier)?;
+            let rating = store.get_rating(*supplier)?;
+            let today = store.today()?;
+            let incidents: Vec<Incident> = store.list_incidents(Some(*supplier))?.into_iter().filter(|i| rating::counts_on(i, today)).collect();
+            let penalty = rating::incident_penalty(&incidents, today);
+            let score = rating.as_ref().and_then(|r| rating::score(r, &incidents, today));
+            if *json {
+                let body = serde_json::json!({
+                    "supplier_id": supplier,
+                    "rating": rating,
+                    "incidents": incidents,
+                    "incident_penalty": penalty,
+                    "score": score,
+                });
+                println!("{}", serde_json::to_string_pretty(&body)?);
+                return Ok(());
+            }
+            match &rating {
+                Some(r) => {
+                    println!("供应商 {} 的评分（更新于 {}）", supplier, r.updated_at);
+                    for dimension in RatingDimension::ALL {
+                        println!("{}\t{}", dimension.label(), r.get(dimension).map(|v| v.to_string()).unwrap_or_else(|| "-".to_string()));
+                    }
+                    if let Some(note) = &r.note {
+                        println!("备注\t{}", note);
+                    }
+                    println!("平均分\t{}", score_text(r.average()));
+                }
+                None => println!("供应商 {} 暂无评分", supplier),
+            }
+            println!("事故扣分\t{:.2}（最近 {} 天 {} 起）", penalty, rating::INCIDENT_WINDOW_DAYS, incidents.len());
+            println!("综合评分\t{}", score_text(score));
+            for i in &incidents {
+                println!("{}\t{}\t{}\t{}", i.id, i.occurred_on, i.severity.label(), i.description);
+            }
+        }
+        RatingAction::List { json } => {
+            let today = store.today()?;
+            let incidents = store.list_incidents(None)?;
+            let mut scored: Vec<(Rating, Option<f64>)> = store
+                .list_ratings()?
+                .into_iter()
+                .map(|r| {
+                    let own: Vec<Incident> = incidents.iter().filter(|i| i.supplier_id == r.supplier_id).cloned().collect();
+                    let score = rating::score(&r, &own, today);
+                    (r, score)
+                })
+                .collect();
+            // 综合评分从高到低，没有评分的排在最后
+            scored.sort_by(|(a, sa), (b, sb)| match (sa, sb) {
+                (Some(x), Some(y)) => y.total_cmp(x),
+                (x, y) => y.is_some().cmp(&x.is_some()),
+            }.then(a.supplier_id.cmp(&b.supplier_id)));
+            if *json {
+                let items: Vec<serde_json::Value> = scored
+                    .iter()
+                    .map(|(r, score)| {
+                        let mut item = serde_json::to_value(r)?;
+                        item["score"] = serde_json::json!(score);
+                        Ok(item)
+                    })
+                    .collect::<std::result::Result<_, serde_json::Error>>()?;
+                println!("{}", serde_json::to_string_pretty(&items)?);
+            } else if scored.is_empty() {
+                println!("暂无评分");
+            } else {
+                for (r, score) in &scored {
+                    let items: Vec<String> = RatingDimension::ALL
+                        .iter()
+                        .map(|d| format!("{} {}", d.label(), r.get(*d).map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())))
+                        .collect();
+                    println!("供应商 {}\t{}\t综合 {}", r.supplier_id, items.join("\t"), score_text(*score));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn run_incident(store: &SupplierStore, action: &IncidentAction) -> Result<()> {
+    match action {
+        IncidentAction::Add { supplier, description, severity, on } => {
+            let occurred_on = match on {
+                Some(date) => *date,
+                None => store.today()?,
+            };
+            let id = store.add_incident(&NewIncident {
+                supplier_id: *supplier,
+                occurred_on,
+                severity: *severity,
+                description: description.clone(),
+            })?;
+            println!("已记录事故 {}：供应商 {}，{}（{}），综合评分 {}", id, supplier, occurred_on, severity.label(), score_text(store.get(*supplier)?.score));
+        }
+        IncidentAction::List { supplier, json } => {
+            let incidents = store.list_incidents(*supplier)?;
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&incidents)?);
+            } else if incidents.is_empty() {
+                println!("暂无事故记录");
+            } else {
+                for i in incidents {
+                    println!("{}\t{}\t供应商 {}\t{}\t{}", i.id, i.occurred_on, i.supplier_id, i.severity.label(), i.description);
+                }
+            }
+        }
+        IncidentAction::Remove { id } => {
+            store.remove_incident(*id)?;
+            println!("已删除事故 {}", id);
+        }
+    }
+    Ok(())
+}
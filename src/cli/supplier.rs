@@ -0,0 +1,148 @@
+//! 供应商的增删改和批量导入：add、update、delete、import 子命令
+
+use clap::Args;
+use wei_assistant_gpu::render::print_suppliers_table_v2;
+use wei_assistant_gpu::{csv_io, import, xlsx};
+use wei_assistant_gpu::{Error, Result, Settings, Supplier, SupplierRow, SupplierStore};
+
+/// 供应商字段参数，新增和修改共用
+#[derive(Args, Debug, Default)]
+pub struct SupplierArgs {
+    #[arg(long)]
+    contact: Option<String>,
+    #[arg(long)]
+    wechat: Option<String>,
+    #[arg(long)]
+    phone: Option<String>,
+    #[arg(long)]
+    quantity: Option<i32>,
+    #[arg(long)]
+    location: Option<String>,
+    #[arg(long)]
+    price: Option<f64>,
+    #[arg(long, name = "bandwidth-price")]
+    bandwidth_price: Option<f64>,
+    #[arg(long, name = "storage-price")]
+    storage_price: Option<f64>,
+    #[arg(long, name = "min-contract-period")]
+    min_contract_period: Option<String>,
+    #[arg(long, name = "breach-penalties")]
+    breach_penalties: Option<String>,
+    #[arg(long, name = "payment-terms")]
+    payment_terms: Option<String>,
+    #[arg(long, name = "server-name")]
+    server_name: Option<String>,
+    #[arg(long, name = "server-config")]
+    server_config: Option<String>,
+    #[arg(long, name = "rental-model")]
+    rental_model: Option<String>,
+    #[arg(long, name = "networking-category")]
+    networking_category: Option<String>,
+}
+
+impl SupplierArgs {
+    pub fn to_supplier(&self) -> Supplier {
+        Supplier {
+            contact: self.contact.clone(),
+            wechat: self.wechat.clone(),
+            phone: self.phone.clone(),
+            quantity: self.quantity,
+            location: self.location.clone(),
+            price: self.price,
+            bandwidth_price: self.bandwidth_price,
+            storage_price: self.storage_price,
+            min_contract_period: self.min_contract_period.clone(),
+            breach_penalties: self.breach_penalties.clone(),
+            payment_terms: self.payment_terms.clone(),
+            server_name: self.server_name.clone(),
+            server_config: self.server_config.clone(),
+            rental_model: self.rental_model.clone(),
+            networking_category: self.networking_category.clone(),
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+#[group(id = "source", required = true, multiple = false, args = ["xlsx", "csv"])]
+pub struct ImportArgs {
+    /// 要导入的Excel文件（.xlsx）
+    #[arg(long)]
+    xlsx: Option<String>,
+    /// 要导入的CSV文件，格式由 --csv-* 参数指定
+    #[arg(long)]
+    csv: Option<String>,
+    /// 工作表名称，默认读取第一个工作表（仅Excel）
+    // 数据来源组已要求二选一；同组的 --csv 出现时 clap 会认为 requires = "xlsx" 已满足，因此改用冲突
+    #[arg(long, conflicts_with = "csv")]
+    sheet: Option<String>,
+    /// 自定义列映射，格式为 表头=字段名，可重复指定（如 --map 单价=price）
+    #[arg(long = "map", value_name = "表头=字段名")]
+    mappings: Vec<String>,
+    /// 仅解析并预览，不写入数据库
+    #[arg(long, name = "dry-run")]
+    pub dry_run: bool,
+    #[command(flatten)]
+    csv_options: csv_io::CsvOptions,
+}
+
+/// 添加供应商：JSON 整体输入，或按字段参数输入（至少需要联系人）
+pub fn run_add(store: &SupplierStore, json: Option<&str>, fields: &SupplierArgs) -> Result<()> {
+    // 处理JSON模式
+    if let Some(json_str) = json {
+        let supplier: Supplier = serde_json::from_str(json_str)?;
+        store.add(&supplier)?;
+        println!("供应商信息添加成功！");
+    } else {
+        // 处理字段模式
+        if fields.contact.is_none() {
+            return Err(Error::Validation("至少需要提供联系人字段！".to_string()));
+        }
+        // 成功时不需要输出，保持界面简洁
+        store.add(&fields.to_supplier())?;
+    }
+    Ok(())
+}
+
+pub fn run_import(settings: &Settings, store: &SupplierStore, args: &ImportArgs) -> Result<()> {
+    let mapping = import::ColumnMapping::from_rules(&args.mappings)?;
+    let report = match (&args.xlsx, &args.csv) {
+        (Some(path), _) => xlsx::read_suppliers_from_xlsx(path, args.sheet.as_deref(), &mapping),
+        (None, Some(path)) => csv_io::read_suppliers_from_csv(path, &args.csv_options, &mapping),
+        (None, None) => unreachable!("clap 保证 --xlsx 与 --csv 至少提供一个"),
+    }?;
+    for err in &report.errors {
+        eprintln!("第{}行: {}", err.row, err.message);
+    }
+
+    if args.dry_run {
+        // 预览时 ID 列显示为表格行号，便于与错误信息对照
+        let rows: Vec<SupplierRow> = report.suppliers.iter()
+            .map(|(row_no, s)| s.to_row(*row_no as i64))
+            .collect();
+        print_suppliers_table_v2(&rows);
+        println!("预览：可导入 {} 条，失败 {} 条（未写入数据库）", rows.len(), report.errors.len());
+    } else {
+        // 没有可写入的行时不保存快照
+        if !report.suppliers.is_empty() {
+            settings.auto_snapshot(store, "import")?;
+            store.add_many(report.suppliers.iter().map(|(_, s)| s))?;
+        }
+        println!("导入完成：成功 {} 条，失败 {} 条", report.suppliers.len(), report.errors.len());
+    }
+    Ok(())
+}
+
+pub fn run_update(store: &SupplierStore, id: i64, fields: &SupplierArgs) -> Result<()> {
+    store.update(id, &fields.to_supplier())?;
+    println!("供应商 {} 已更新", id);
+    Ok(())
+}
+
+/// 删除供应商，检查通过后、删除前自动保存快照
+pub fn run_delete(settings: &Settings, store: &SupplierStore, id: i64) -> Result<()> {
+    store.check_delete(id)?;
+    settings.auto_snapshot(store, "delete")?;
+    store.delete(id)?;
+    println!("供应商 {} 已删除", id);
+    Ok(())
+}
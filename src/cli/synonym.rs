@@ -0,0 +1,37 @@
+//! synonym 子命令：地点和服务器名的同义词
+
+use clap::Subcommand;
+use wei_assistant_gpu::{Result, SupplierField, SupplierStore};
+
+#[derive(Subcommand, Debug)]
+pub enum SynonymAction {
+    /// 添加同义词，如 --field location --canonical 北京 --alias 帝都
+    Add {
+        /// 字段名：location 或 server_name
+        #[arg(long)]
+        field: SupplierField,
+        /// 标准写法
+        #[arg(long)]
+        canonical: String,
+        /// 别名
+        #[arg(long)]
+        alias: String,
+    },
+    /// 列出全部同义词（含内置）
+    List,
+}
+
+pub fn run_synonym(store: &SupplierStore, action: &SynonymAction) -> Result<()> {
+    match action {
+        SynonymAction::Add { field, canonical, alias } => {
+            store.add_synonym(*field, canonical, alias)?;
+            println!("已添加同义词：{} = {}", alias, canonical);
+        }
+        SynonymAction::List => {
+            for (field, canonical, words) in store.synonyms()?.groups() {
+                println!("{}\t{}: {}", field.column(), canonical, words[1..].join(", "));
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,129 @@
+//! user 子命令：本地用户、角色、口令和令牌
+
+use std::path::PathBuf;
+
+use clap::Subcommand;
+use wei_assistant_gpu::auth::{Role, Session};
+use wei_assistant_gpu::{config, Result, SupplierStore};
+
+use super::{current_actor, new_secret, NEW_PASSWORD_ENV};
+
+#[derive(Subcommand, Debug)]
+pub enum UserAction {
+    /// 添加用户；第一个用户必须是 admin，之后需要 admin 登录
+    Add {
+        /// 用户名
+        name: String,
+        /// 角色
+        #[arg(long, value_enum)]
+        role: Role,
+        /// 保存新用户口令的文件，也可用环境变量 WEI_ASSISTANT_NEW_PASSWORD
+        #[arg(long, value_name = "PATH")]
+        new_password_file: Option<PathBuf>,
+    },
+    /// 列出全部用户
+    List,
+    /// 删除用户
+    Remove {
+        /// 用户名
+        name: String,
+    },
+    /// 修改用户角色
+    Role {
+        /// 用户名
+        name: String,
+        /// 新角色
+        #[arg(value_enum)]
+        role: Role,
+    },
+    /// 修改口令，admin 可修改任何用户，其他用户只能修改自己的
+    Passwd {
+        /// 用户名
+        name: String,
+        /// 保存新口令的文件，也可用环境变量 WEI_ASSISTANT_NEW_PASSWORD
+        #[arg(long, value_name = "PATH")]
+        new_password_file: Option<PathBuf>,
+    },
+    /// 签发新令牌并输出（只显示一次），旧令牌失效
+    Token {
+        /// 用户名
+        name: String,
+    },
+    /// 吊销用户的令牌
+    RevokeToken {
+        /// 用户名
+        name: String,
+    },
+    /// 显示当前登录的用户和角色
+    Whoami,
+}
+
+
+pub fn run_user(store: &SupplierStore, session: &Session, action: &UserAction) -> Result<()> {
+    // admin 可管理任何用户，其他用户只能修改自己的口令和令牌
+    let require_self_or_admin = |name: &str| match session.user_name() {
+        Some(current) if current == name => Ok(()),
+        _ => session.require(Role::Admin),
+    };
+    let audit = |action: &str, detail: &str| store.record_audit(&current_actor(session), action, detail).map(|_| ());
+    match action {
+        UserAction::Add { name, role, new_password_file } => {
+            // 第一个用户无需登录
+            if store.has_users()? {
+                session.require(Role::Admin)?;
+            }
+            let password = new_secret(new_password_file.as_deref(), NEW_PASSWORD_ENV, "--new-password-file")?;
+            store.add_user(name, *role, &password)?;
+            audit("user_add", &format!("{}（{}）", name, role))?;
+            println!("已添加用户 {}（{}）", name, role);
+        }
+        UserAction::List => {
+            session.require(Role::Admin)?;
+            let users = store.list_users()?;
+            if users.is_empty() {
+                println!("暂无用户，所有命令均不做权限检查");
+            }
+            for user in users {
+                println!("{}\t{}\t{}\t{}", user.name, user.role, if user.has_token { "有令牌" } else { "-" }, user.created_at);
+            }
+        }
+        UserAction::Remove { name } => {
+            session.require(Role::Admin)?;
+            store.remove_user(name)?;
+            audit("user_remove", name)?;
+            println!("已删除用户 {}", name);
+        }
+        UserAction::Role { name, role } => {
+            session.require(Role::Admin)?;
+            store.set_user_role(name, *role)?;
+            audit("user_role", &format!("{} -> {}", name, role))?;
+            println!("用户 {} 的角色已改为 {}", name, role);
+        }
+        UserAction::Passwd { name, new_password_file } => {
+            require_self_or_admin(name)?;
+            let password = new_secret(new_password_file.as_deref(), NEW_PASSWORD_ENV, "--new-password-file")?;
+            store.set_user_password(name, &password)?;
+            audit("user_passwd", name)?;
+            println!("用户 {} 的口令已修改", name);
+        }
+        UserAction::Token { name } => {
+            require_self_or_admin(name)?;
+            let token = store.issue_token(name)?;
+            audit("user_token", name)?;
+            println!("{}", token);
+            eprintln!("令牌只显示这一次，请妥善保存；可通过 --token-file 或环境变量 {} 使用", config::TOKEN_ENV);
+        }
+        UserAction::RevokeToken { name } => {
+            require_self_or_admin(name)?;
+            store.revoke_token(name)?;
+            audit("user_revoke_token", name)?;
+            println!("已吊销用户 {} 的令牌", name);
+        }
+        UserAction::Whoami => match session {
+            Session::Unrestricted => println!("未启用用户管理，所有命令均不做权限检查"),
+            Session::Anonymous => println!("未登录（仅可查询，电话和微信脱敏显示）"),
+            Session::User(identity) => println!("{}（{}）", identity.name, identity.role),
+        },
+    }
+    Ok(())
+}
@@ -0,0 +1,92 @@
+//! view 子命令：保存和执行命名查询
+
+use clap::{Parser, Subcommand};
+use wei_assistant_gpu::auth::Session;
+use wei_assistant_gpu::{Error, Result, Settings, SupplierStore};
+
+use super::query::{run_query, QueryArgs, SelectionArgs};
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Subcommand, Debug)]
+pub enum ViewAction {
+    /// 保存视图，如 view save cheap-bj -- query --location 北京 --price-max 1500 --sort price
+    Save {
+        /// 视图名称，已存在时覆盖
+        name: String,
+        /// query 子命令的筛选、排序和列参数（写在 -- 之后，开头的 query 可省略）
+        #[arg(last = true, required = true)]
+        args: Vec<String>,
+    },
+    /// 列出全部视图
+    List,
+    /// 执行视图，可在名称后追加 query 参数：筛选条件与视图叠加，排序和列覆盖视图，输出格式、导出等在此指定
+    Run {
+        /// 视图名称
+        name: String,
+        #[command(flatten)]
+        args: QueryArgs,
+    },
+    /// 删除视图
+    Delete {
+        /// 视图名称
+        name: String,
+    },
+}
+
+/// 按 query 子命令的规则解析要保存的筛选、排序和列参数；重复出现的参数以后者为准
+///
+/// 输出格式、导出文件、显示隐私等参数不属于视图，执行视图时再指定。
+#[derive(Parser, Debug)]
+#[command(name = "query", no_binary_name = true, args_override_self = true)]
+struct ViewQuery {
+    #[command(flatten)]
+    selection: SelectionArgs,
+}
+
+impl ViewQuery {
+    fn parse_args(args: &[String]) -> Result<SelectionArgs> {
+        ViewQuery::try_parse_from(args)
+            .map(|v| v.selection)
+            .map_err(|e| {
+                // 只保留clap错误的首行，不附带用法说明
+                let msg = e.to_string();
+                let first = msg.lines().next().unwrap_or_default().trim_start_matches("error: ").to_string();
+                let hint = if e.kind() == clap::error::ErrorKind::UnknownArgument {
+                    "（视图只保存筛选条件、排序和列，输出格式、导出和 --reveal-pii 请在 view run 时指定）"
+                } else {
+                    ""
+                };
+                Error::Validation(format!("视图参数无效: {}{}", first, hint))
+            })
+    }
+}
+
+pub fn run_view(settings: &Settings, store: &SupplierStore, session: &Session, action: &ViewAction) -> Result<()> {
+    match action {
+        ViewAction::Save { name, args } => {
+            let args = match args.first() {
+                Some(first) if first == "query" => &args[1..],
+                _ => &args[..],
+            };
+            store.save_view(name, &ViewQuery::parse_args(args)?.to_view()?)?;
+            println!("已保存视图 {}", name);
+        }
+        ViewAction::List => {
+            let views = store.list_views()?;
+            if views.is_empty() {
+                println!("暂无视图");
+            }
+            for (name, view) in views {
+                println!("{}\t{}", name, view);
+            }
+        }
+        ViewAction::Run { name, args } => {
+            run_query(settings, store, session, args, Some(&store.get_view(name)?))?;
+        }
+        ViewAction::Delete { name } => {
+            store.delete_view(name)?;
+            println!("视图 {} 已删除", name);
+        }
+    }
+    Ok(())
+}
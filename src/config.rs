@@ -0,0 +1,725 @@
+//! 配置文件：数据库路径及查询的默认输出设置
+//!
+//! 未指定 `--config` 时依次读取：
+//!
+//! 1. 用户配置 `~/.config/wei-assistant/config.toml`（设置了 `XDG_CONFIG_HOME` 时为
+//!    `$XDG_CONFIG_HOME/wei-assistant/config.toml`）
+//! 2. 项目配置：从当前目录向上查找到的第一个 `.wei-assistant.toml`
+//!
+//! 后读取的文件覆盖先读取的同名配置项。指定 `--config`（或环境变量 `WEI_ASSISTANT_CONFIG`）
+//! 时只读取该文件。
+//!
+//! 同一设置的优先级：命令行参数 > 环境变量 > 配置文件 > 内置默认值。数据库路径具体为：
+//! `--db` > `--profile` > `DB_FILE` > `WEI_ASSISTANT_PROFILE` > 配置文件中的 `profile` > 配置文件中的 `db`。
+//!
+//! ```toml
+//! db = "/data/wei-assistant.db"
+//! profile = "cn-east"
+//!
+//! [profiles.cn-east]
+//! db = "/data/cn-east.db"
+//!
+//! [defaults]
+//! format = "markdown"
+//! columns = ["id", "contact", "location", "price"]
+//! sort = ["price", "quantity:desc"]
+//! currency = "CNY"
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::auth::Session;
+use crate::error::{Error, Result};
+use crate::model::{Column, SupplierRow};
+use crate::notify::NotifySink;
+use crate::query::{self, QueryBuilder, SortKey};
+use crate::render::OutputFormat;
+use crate::store::SupplierStore;
+
+/// 未配置时使用的数据库文件
+pub const DEFAULT_DB_FILE: &str = "wei-assistant.db";
+/// 项目配置文件名
+pub const PROJECT_CONFIG_FILE: &str = ".wei-assistant.toml";
+/// 指定数据库路径的环境变量
+pub const DB_ENV: &str = "DB_FILE";
+/// 指定配置文件路径的环境变量
+pub const CONFIG_ENV: &str = "WEI_ASSISTANT_CONFIG";
+/// 选择 profile 的环境变量
+pub const PROFILE_ENV: &str = "WEI_ASSISTANT_PROFILE";
+/// 加密数据库口令的环境变量
+pub const PASSPHRASE_ENV: &str = "WEI_ASSISTANT_PASSPHRASE";
+/// 登录用户名的环境变量
+pub const USER_ENV: &str = "WEI_ASSISTANT_USER";
+/// 登录口令的环境变量
+pub const PASSWORD_ENV: &str = "WEI_ASSISTANT_PASSWORD";
+/// 登录令牌的环境变量
+pub const TOKEN_ENV: &str = "WEI_ASSISTANT_TOKEN";
+
+/// `config get/set` 支持的配置项
+pub const CONFIG_KEYS: [&str; 14] = [
+    "db",
+    "profile",
+    "defaults.format",
+    "defaults.columns",
+    "defaults.sort",
+    "defaults.currency",
+    "snapshots.keep",
+    "privacy.mask_pii",
+    "quotes.markdown_template",
+    "quotes.html_template",
+    "quotes.valid_days",
+    "notify.sinks",
+    "notify.contract_days",
+    "notify.low_quantity",
+];
+
+/// 未配置 `snapshots.keep` 时保留的自动快照份数
+pub const DEFAULT_SNAPSHOT_KEEP: usize = 10;
+
+/// 未配置 `quotes.valid_days` 时报价单的有效天数
+pub const DEFAULT_QUOTE_VALID_DAYS: u32 = 7;
+
+/// 配置文件内容，所有配置项均可省略
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// 数据库文件路径
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub db: Option<String>,
+    /// 当前使用的 profile
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// 查询的默认设置
+    #[serde(skip_serializing_if = "Defaults::is_empty")]
+    pub defaults: Defaults,
+    /// 自动快照设置
+    #[serde(skip_serializing_if = "Snapshots::is_empty")]
+    pub snapshots: Snapshots,
+    /// 个人信息脱敏设置
+    #[serde(skip_serializing_if = "Privacy::is_empty")]
+    pub privacy: Privacy,
+    /// 报价单设置
+    #[serde(skip_serializing_if = "Quotes::is_empty")]
+    pub quotes: Quotes,
+    /// 到期提醒设置
+    #[serde(skip_serializing_if = "Notify::is_empty")]
+    pub notify: Notify,
+    /// 命名的数据库，如按业务线或地区划分
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// 一个命名数据库
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// 数据库文件路径
+    pub db: String,
+}
+
+/// 查询的默认设置，命令行中指定了对应参数时不生效
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Defaults {
+    /// 默认输出格式
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<OutputFormat>,
+    /// 默认输出列
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub columns: Option<Vec<Column>>,
+    /// 默认排序
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<Vec<SortKey>>,
+    /// 价格币种，显示在价格列标题中
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+}
+
+impl Defaults {
+    fn is_empty(&self) -> bool {
+        self == &Defaults::default()
+    }
+}
+
+/// 删除、导入和恢复前自动保存的快照
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Snapshots {
+    /// 保留的快照份数，0 表示不保存快照
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep: Option<usize>,
+}
+
+impl Snapshots {
+    fn is_empty(&self) -> bool {
+        self == &Snapshots::default()
+    }
+}
+
+/// 查询输出中电话、微信的脱敏设置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Privacy {
+    /// 默认脱敏，可用 `query --reveal-pii` 临时显示原文（记录审计日志）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mask_pii: Option<bool>,
+}
+
+impl Privacy {
+    fn is_empty(&self) -> bool {
+        self == &Privacy::default()
+    }
+}
+
+/// 报价单的模板和有效期
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Quotes {
+    /// Markdown 报价单模板文件，未设置时使用内置模板
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub markdown_template: Option<String>,
+    /// HTML 报价单模板文件，未设置时使用内置模板
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub html_template: Option<String>,
+    /// 报价单有效天数
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_days: Option<u32>,
+}
+
+impl Quotes {
+    fn is_empty(&self) -> bool {
+        self == &Quotes::default()
+    }
+}
+
+/// `notify check` 的通知通道和提醒阈值
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Notify {
+    /// 通知通道，如 `stdout`、`file:notify.log`、`webhook:https://...`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sinks: Option<Vec<NotifySink>>,
+    /// 提前提醒合同到期的天数
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contract_days: Option<u32>,
+    /// 可提供数量不超过该值时提醒
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub low_quantity: Option<i32>,
+}
+
+impl Notify {
+    fn is_empty(&self) -> bool {
+        self == &Notify::default()
+    }
+}
+
+fn join<T: Copy + Into<String>>(items: &[T]) -> String {
+    items.iter().map(|v| (*v).into()).collect::<Vec<String>>().join(",")
+}
+
+fn parse_list<T: std::str::FromStr<Err = String>>(value: &str) -> std::result::Result<Vec<T>, String> {
+    value.split(',').map(|v| v.trim().parse()).collect()
+}
+
+impl Config {
+    /// 读取配置文件
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)?;
+        toml::from_str(&text)
+            .map_err(|e| Error::Parse(format!("配置文件 {} 解析失败: {}", path.display(), e.message())))
+    }
+
+    /// 读取配置文件，文件不存在时返回空配置
+    pub fn load_or_default(path: &Path) -> Result<Self> {
+        if path.exists() { Self::load(path) } else { Ok(Self::default()) }
+    }
+
+    /// 写入配置文件，必要时创建所在目录
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_toml())?;
+        Ok(())
+    }
+
+    /// 序列化为 TOML 文本
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// 用 `other` 中设置了的配置项覆盖当前配置，同名 profile 以 `other` 为准
+    pub fn merge(&mut self, other: Config) {
+        let d = other.defaults;
+        self.db = other.db.or(self.db.take());
+        self.profile = other.profile.or(self.profile.take());
+        self.profiles.extend(other.profiles);
+        self.defaults.format = d.format.or(self.defaults.format);
+        self.defaults.columns = d.columns.or(self.defaults.columns.take());
+        self.defaults.sort = d.sort.or(self.defaults.sort.take());
+        self.defaults.currency = d.currency.or(self.defaults.currency.take());
+        self.snapshots.keep = other.snapshots.keep.or(self.snapshots.keep);
+        self.privacy.mask_pii = other.privacy.mask_pii.or(self.privacy.mask_pii);
+        let q = other.quotes;
+        self.quotes.markdown_template = q.markdown_template.or(self.quotes.markdown_template.take());
+        self.quotes.html_template = q.html_template.or(self.quotes.html_template.take());
+        self.quotes.valid_days = q.valid_days.or(self.quotes.valid_days);
+        let n = other.notify;
+        self.notify.sinks = n.sinks.or(self.notify.sinks.take());
+        self.notify.contract_days = n.contract_days.or(self.notify.contract_days);
+        self.notify.low_quantity = n.low_quantity.or(self.notify.low_quantity);
+    }
+
+    /// 按 `section.key` 形式读取配置项，未设置时返回 `None`；列表以逗号连接
+    pub fn get(&self, key: &str) -> Result<Option<String>> {
+        let d = &self.defaults;
+        Ok(match key {
+            "db" => self.db.clone(),
+            "profile" => self.profile.clone(),
+            "defaults.format" => d.format.map(|f| format_name(f).to_string()),
+            "defaults.columns" => d.columns.as_deref().map(join),
+            "defaults.sort" => d.sort.as_deref().map(join),
+            "defaults.currency" => d.currency.clone(),
+            "snapshots.keep" => self.snapshots.keep.map(|n| n.to_string()),
+            "privacy.mask_pii" => self.privacy.mask_pii.map(|b| b.to_string()),
+            "quotes.markdown_template" => self.quotes.markdown_template.clone(),
+            "quotes.html_template" => self.quotes.html_template.clone(),
+            "quotes.valid_days" => self.quotes.valid_days.map(|n| n.to_string()),
+            "notify.sinks" => self.notify.sinks.as_ref().map(|s| s.iter().map(ToString::to_string).collect::<Vec<_>>().join(",")),
+            "notify.contract_days" => self.notify.contract_days.map(|n| n.to_string()),
+            "notify.low_quantity" => self.notify.low_quantity.map(|n| n.to_string()),
+            _ => return Err(unknown_key(key)),
+        })
+    }
+
+    /// 设置配置项，列表用逗号分隔；值为空字符串时删除该项
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let value = value.trim();
+        let invalid = |e: String| Error::Validation(format!("配置项 {} 的值无效: {}", key, e));
+        let d = &mut self.defaults;
+        let text = (!value.is_empty()).then(|| value.to_string());
+        match key {
+            "db" => self.db = text,
+            "profile" => {
+                if let Some(name) = &text {
+                    validate_profile_name(name)?;
+                }
+                self.profile = text;
+            }
+            "defaults.format" => {
+                d.format = text
+                    .map(|v| {
+                        <OutputFormat as clap::ValueEnum>::from_str(&v, true)
+                            .map_err(|_| format!("未知输出格式 {}，可选 table、json、csv、markdown、html", v))
+                    })
+                    .transpose()
+                    .map_err(invalid)?
+            }
+            "defaults.columns" => d.columns = text.map(|v| parse_list(&v)).transpose().map_err(invalid)?,
+            "defaults.sort" => d.sort = text.map(|v| parse_list(&v)).transpose().map_err(invalid)?,
+            "defaults.currency" => d.currency = text,
+            "snapshots.keep" => {
+                self.snapshots.keep = text
+                    .map(|v| v.parse().map_err(|_| format!("{} 不是非负整数", v)))
+                    .transpose()
+                    .map_err(invalid)?
+            }
+            "privacy.mask_pii" => {
+                self.privacy.mask_pii = text
+                    .map(|v| v.parse().map_err(|_| format!("{} 不是 true 或 false", v)))
+                    .transpose()
+                    .map_err(invalid)?
+            }
+            "quotes.markdown_template" => self.quotes.markdown_template = text,
+            "quotes.html_template" => self.quotes.html_template = text,
+            "quotes.valid_days" => {
+                self.quotes.valid_days = text
+                    .map(|v| v.parse().map_err(|_| format!("{} 不是非负整数", v)))
+                    .transpose()
+                    .map_err(invalid)?
+            }
+            "notify.sinks" => self.notify.sinks = text.map(|v| parse_list(&v)).transpose().map_err(invalid)?,
+            "notify.contract_days" => {
+                self.notify.contract_days = text
+                    .map(|v| v.parse().map_err(|_| format!("{} 不是非负整数", v)))
+                    .transpose()
+                    .map_err(invalid)?
+            }
+            "notify.low_quantity" => {
+                self.notify.low_quantity = text
+                    .map(|v| v.parse().map_err(|_| format!("{} 不是整数", v)))
+                    .transpose()
+                    .map_err(invalid)?
+            }
+            _ => return Err(unknown_key(key)),
+        }
+        Ok(())
+    }
+
+    /// 添加或覆盖 profile
+    pub fn add_profile(&mut self, name: &str, db: &str) -> Result<()> {
+        validate_profile_name(name)?;
+        if db.trim().is_empty() {
+            return Err(Error::Validation("profile 的数据库路径不能为空".to_string()));
+        }
+        self.profiles.insert(name.to_string(), Profile { db: db.trim().to_string() });
+        Ok(())
+    }
+
+    /// 配置文件中的相对路径改为相对于 `dir`
+    fn resolve_paths(&mut self, dir: &Path) {
+        let resolve = |db: &mut String| {
+            if Path::new(db.as_str()).is_relative() {
+                *db = dir.join(db.as_str()).to_string_lossy().into_owned();
+            }
+        };
+        self.db.iter_mut().for_each(resolve);
+        self.profiles.values_mut().for_each(|p| resolve(&mut p.db));
+        self.quotes.markdown_template.iter_mut().chain(self.quotes.html_template.iter_mut()).for_each(resolve);
+        self.notify.sinks.iter_mut().flatten().for_each(|sink| sink.resolve_path(dir));
+    }
+}
+
+/// profile 名称只允许字母、数字、`-`、`_` 和 `.`，便于在命令行和 TOML 中使用
+fn validate_profile_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::Validation(format!("profile 名称无效: {}，只能包含字母、数字、-、_ 和 .", name)))
+    }
+}
+
+fn format_name(format: OutputFormat) -> &'static str {
+    match format {
+        OutputFormat::Table => "table",
+        OutputFormat::Json => "json",
+        OutputFormat::Csv => "csv",
+        OutputFormat::Markdown => "markdown",
+        OutputFormat::Html => "html",
+    }
+}
+
+fn unknown_key(key: &str) -> Error {
+    Error::Validation(format!("未知配置项: {}，可用配置项: {}", key, CONFIG_KEYS.join(", ")))
+}
+
+/// 用户配置文件路径，无法确定主目录时为 `None`
+pub fn user_config_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|h| h.join(".config")))?;
+    Some(base.join("wei-assistant").join("config.toml"))
+}
+
+/// 显式指定的配置文件：`--config` 优先，其次为环境变量 `WEI_ASSISTANT_CONFIG`
+pub fn explicit_config_path(config_flag: Option<&Path>) -> Option<PathBuf> {
+    config_flag
+        .map(Path::to_path_buf)
+        .or_else(|| std::env::var_os(CONFIG_ENV).filter(|v| !v.is_empty()).map(PathBuf::from))
+}
+
+/// 从 `start` 向上查找项目配置文件
+pub fn find_project_config(start: &Path) -> Option<PathBuf> {
+    start.ancestors().map(|dir| dir.join(PROJECT_CONFIG_FILE)).find(|p| p.is_file())
+}
+
+/// 数据库路径的来源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DbSource {
+    Flag,
+    Env,
+    Profile(String),
+    File(PathBuf),
+    Default,
+}
+
+impl fmt::Display for DbSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DbSource::Flag => write!(f, "命令行参数 --db"),
+            DbSource::Env => write!(f, "环境变量 {}", DB_ENV),
+            DbSource::Profile(name) => write!(f, "profile {}", name),
+            DbSource::File(path) => write!(f, "配置文件 {}", path.display()),
+            DbSource::Default => write!(f, "默认值"),
+        }
+    }
+}
+
+/// 读取口令文件，去掉末尾换行
+pub fn read_passphrase_file(path: &Path) -> Result<String> {
+    Ok(fs::read_to_string(path)?.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// 命令行中可覆盖配置文件的全局参数
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Overrides<'a> {
+    /// `--config`
+    pub config: Option<&'a Path>,
+    /// `--db`
+    pub db: Option<&'a str>,
+    /// `--profile`
+    pub profile: Option<&'a str>,
+    /// `--passphrase-file`
+    pub passphrase_file: Option<&'a Path>,
+    /// `--user`
+    pub user: Option<&'a str>,
+    /// `--password-file`
+    pub password_file: Option<&'a Path>,
+    /// `--token-file`
+    pub token_file: Option<&'a Path>,
+}
+
+/// 按查找规则读取并合并后的配置文件
+#[derive(Debug, Clone, Default)]
+pub struct LoadedConfig {
+    pub config: Config,
+    /// 实际读取的配置文件，按读取顺序
+    pub files: Vec<PathBuf>,
+    /// 最后一个设置了 `db` 的配置文件
+    db_file: Option<PathBuf>,
+}
+
+impl LoadedConfig {
+    /// 读取配置文件；显式指定的配置文件不存在时报错，自动查找的配置文件不存在时忽略
+    pub fn load(config_flag: Option<&Path>) -> Result<Self> {
+        let files = match explicit_config_path(config_flag) {
+            Some(path) => {
+                if !path.is_file() {
+                    return Err(Error::NotFound(format!("配置文件不存在: {}", path.display())));
+                }
+                vec![path]
+            }
+            None => {
+                let cwd = std::env::current_dir()?;
+                user_config_path()
+                    .filter(|p| p.is_file())
+                    .into_iter()
+                    .chain(find_project_config(&cwd))
+                    .collect()
+            }
+        };
+
+        let mut loaded = LoadedConfig::default();
+        for path in &files {
+            let mut file = Config::load(path)?;
+            // 配置文件中的相对路径相对于该文件所在目录
+            if let Some(dir) = path.parent() {
+                file.resolve_paths(dir);
+            }
+            if file.db.is_some() {
+                loaded.db_file = Some(path.clone());
+            }
+            loaded.config.merge(file);
+        }
+        loaded.files = files;
+        Ok(loaded)
+    }
+
+    /// 当前选择的 profile 名称（不检查是否已定义）
+    pub fn active_profile(&self, profile_flag: Option<&str>) -> Option<String> {
+        profile_flag
+            .map(str::to_string)
+            .or_else(|| std::env::var(PROFILE_ENV).ok().filter(|v| !v.is_empty()))
+            .or_else(|| self.config.profile.clone())
+    }
+
+    fn profile_db(&self, name: &str) -> Result<(String, DbSource)> {
+        self.config.profiles.get(name)
+            .map(|p| (p.db.clone(), DbSource::Profile(name.to_string())))
+            .ok_or_else(|| Error::NotFound(format!("未定义 profile: {}", name)))
+    }
+}
+
+/// 合并命令行参数、环境变量和配置文件后的最终设置
+#[derive(Debug, Clone)]
+pub struct Settings {
+    /// 合并后的配置文件内容
+    pub config: Config,
+    /// 实际读取的配置文件，按读取顺序
+    pub files: Vec<PathBuf>,
+    /// 最终使用的数据库路径
+    pub db: String,
+    pub db_source: DbSource,
+    /// 保存口令的文件，未指定时使用环境变量 [`PASSPHRASE_ENV`]
+    pub passphrase_file: Option<PathBuf>,
+    /// 登录凭据
+    pub credentials: Credentials,
+}
+
+/// 跨 profile 查询的结果
+#[derive(Debug, Default)]
+pub struct ProfileRows {
+    /// 合并排序后的记录，`profile` 字段为来源 profile
+    pub rows: Vec<SupplierRow>,
+    /// 凭据认证失败而跳过的 profile 及原因
+    pub skipped: Vec<(String, Error)>,
+}
+
+/// 登录凭据：用户名加口令，或令牌；文件优先于环境变量
+#[derive(Debug, Clone, Default)]
+pub struct Credentials {
+    pub user: Option<String>,
+    pub password_file: Option<PathBuf>,
+    pub token_file: Option<PathBuf>,
+}
+
+fn env_value(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+impl Credentials {
+    /// 按凭据认证；数据库中没有用户时不检查，未提供凭据时为匿名（只能查询）
+    pub fn authenticate(&self, store: &SupplierStore) -> Result<Session> {
+        if !store.has_users()? {
+            return Ok(Session::Unrestricted);
+        }
+        let token = match &self.token_file {
+            Some(path) => Some(read_passphrase_file(path)?),
+            None => env_value(TOKEN_ENV),
+        };
+        if let Some(token) = token {
+            return store.authenticate_token(&token).map(Session::User);
+        }
+        let Some(user) = self.user.clone().or_else(|| env_value(USER_ENV)) else {
+            return Ok(Session::Anonymous);
+        };
+        let password = match &self.password_file {
+            Some(path) => read_passphrase_file(path)?,
+            None => env_value(PASSWORD_ENV).ok_or_else(|| {
+                Error::PermissionDenied(format!("请通过 --password-file 或环境变量 {} 提供用户 {} 的口令", PASSWORD_ENV, user))
+            })?,
+        };
+        store.authenticate_password(&user, &password).map(Session::User)
+    }
+}
+
+impl Settings {
+    /// 按优先级合并设置，选择了未定义的 profile 时报错
+    pub fn load(overrides: Overrides) -> Result<Self> {
+        let loaded = LoadedConfig::load(overrides.config)?;
+        let env_db = env_value(DB_ENV);
+        let env_profile = env_value(PROFILE_ENV);
+
+        let (db, db_source) = if let Some(db) = overrides.db {
+            (db.to_string(), DbSource::Flag)
+        } else if let Some(name) = overrides.profile {
+            loaded.profile_db(name)?
+        } else if let Some(db) = env_db {
+            (db, DbSource::Env)
+        } else if let Some(name) = env_profile.or_else(|| loaded.config.profile.clone()) {
+            loaded.profile_db(&name)?
+        } else if let (Some(db), Some(path)) = (&loaded.config.db, &loaded.db_file) {
+            (db.clone(), DbSource::File(path.clone()))
+        } else {
+            (DEFAULT_DB_FILE.to_string(), DbSource::Default)
+        };
+        let passphrase_file = overrides.passphrase_file.map(Path::to_path_buf);
+        let credentials = Credentials {
+            user: overrides.user.map(str::to_string),
+            password_file: overrides.password_file.map(Path::to_path_buf),
+            token_file: overrides.token_file.map(Path::to_path_buf),
+        };
+        Ok(Self { config: loaded.config, files: loaded.files, db, db_source, passphrase_file, credentials })
+    }
+
+    /// 加密数据库的口令：`--passphrase-file` 指定的文件内容（去掉末尾换行）或环境变量
+    pub fn passphrase(&self) -> Result<Option<String>> {
+        if let Some(path) = &self.passphrase_file {
+            return read_passphrase_file(path).map(Some);
+        }
+        Ok(env_value(PASSPHRASE_ENV))
+    }
+
+    /// 打开最终确定的数据库
+    pub fn open_store(&self) -> Result<SupplierStore> {
+        self.open_db(&self.db)
+    }
+
+    /// 打开数据库，已加密且提供了口令时解锁；未提供口令时读写联系人等字段会报错
+    fn open_db(&self, path: &str) -> Result<SupplierStore> {
+        let mut store = SupplierStore::open(path)?;
+        if store.is_encrypted() {
+            if let Some(passphrase) = self.passphrase()? {
+                store.unlock(&passphrase)?;
+            }
+        }
+        Ok(store)
+    }
+
+    /// 自动快照目录：数据库文件旁的 `<数据库文件名>.snapshots`
+    pub fn snapshot_dir(&self) -> PathBuf {
+        PathBuf::from(format!("{}.snapshots", self.db))
+    }
+
+    /// 保留的自动快照份数
+    pub fn snapshot_keep(&self) -> usize {
+        self.config.snapshots.keep.unwrap_or(DEFAULT_SNAPSHOT_KEEP)
+    }
+
+    /// 修改数据前保存自动快照，`snapshots.keep` 为 0 或内存数据库时跳过
+    pub fn auto_snapshot(&self, store: &SupplierStore, label: &str) -> Result<Option<PathBuf>> {
+        let keep = self.snapshot_keep();
+        if keep == 0 || store.path().is_none() {
+            return Ok(None);
+        }
+        store.snapshot(&self.snapshot_dir(), label, keep).map(Some)
+    }
+
+    /// 在所有 profile 中执行同一查询，结果标注来源 profile
+    ///
+    /// `current` 是已打开的当前数据库，`session` 是在其中认证得到的身份，与某个 profile 相同时直接复用，不再重复打开；
+    /// 其他 profile 用同一份凭据在各自的数据库中分别认证，认证失败的 profile 不查询，连同原因记入 [`ProfileRows::skipped`]。
+    /// 每个 profile 查询后以该库的连接、身份和结果调用 `each`（如记录审计日志、按身份脱敏）。
+    /// 排序键在合并后统一应用；未指定排序时按 profile 名称、ID 排序。
+    pub fn query_all_profiles<F>(
+        &self,
+        current: &SupplierStore,
+        session: &Session,
+        builder: QueryBuilder,
+        mut each: F,
+    ) -> Result<ProfileRows>
+    where
+        F: FnMut(&SupplierStore, &Session, &mut Vec<SupplierRow>) -> Result<()>,
+    {
+        if self.config.profiles.is_empty() {
+            return Err(Error::Validation("未定义任何 profile，请先使用 profile add 添加".to_string()));
+        }
+        let (builder, order) = builder.take_order();
+        let mut rows = Vec::new();
+        let mut skipped = Vec::new();
+        for (name, profile) in &self.config.profiles {
+            let opened;
+            let authenticated;
+            let (store, session) = match profile.db == self.db {
+                true => (current, session),
+                false => {
+                    opened = self.open_db(&profile.db)?;
+                    authenticated = match self.credentials.authenticate(&opened) {
+                        Ok(session) => session,
+                        Err(e @ Error::PermissionDenied(_)) => {
+                            skipped.push((name.clone(), e));
+                            continue;
+                        }
+                        Err(e) => return Err(e),
+                    };
+                    (&opened, &authenticated)
+                }
+            };
+            let mut found = store.query(builder.clone())?;
+            each(store, session, &mut found)?;
+            rows.extend(found.into_iter().map(|mut row| {
+                row.profile = Some(name.clone());
+                row
+            }));
+        }
+        query::sort_rows(&mut rows, &order);
+        Ok(ProfileRows { rows, skipped })
+    }
+}
@@ -0,0 +1,345 @@
+//! 供应商合同
+//!
+//! 与供应商签约后，报价中的最短合同期、付款方式和违约条款成为我们的义务。合同记录签约时的
+//! 这些条款、数量、约定单价（每台每月）和起止日期，支持续约和提前终止。
+//!
+//! 合同期和违约条款是自由文本，能识别以下写法时自动计算：
+//!
+//! - 合同期：`6个月`、`6月`、`半年`、`1年`、`两年`、`3 months`、`1 year`
+//! - 违约条款：`无`；`扣2个月租金`、`2个月`（按月租计）；`剩余租金的30%`、`30%`（按剩余租期的租金计）；
+//!   `赔付剩余租金`（剩余租金全额）；`5000元`（固定金额）
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::dates::Date;
+use crate::error::{Error, Result};
+
+/// 合同状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContractStatus {
+    /// 履行中（含已过结束日期、尚未续约或终止的合同）
+    Active,
+    /// 已终止
+    Terminated,
+}
+
+impl ContractStatus {
+    pub fn name(self) -> &'static str {
+        match self {
+            ContractStatus::Active => "active",
+            ContractStatus::Terminated => "terminated",
+        }
+    }
+}
+
+impl std::str::FromStr for ContractStatus {
+    type Err = String;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        <ContractStatus as ValueEnum>::from_str(s, true).map_err(|_| format!("未知合同状态: {}", s))
+    }
+}
+
+/// 一份合同
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Contract {
+    pub id: i64,
+    pub supplier_id: i64,
+    /// 对应的客户报价单
+    pub quote_no: Option<String>,
+    pub quantity: u32,
+    /// 约定单价（每台每月）
+    pub agreed_price: f64,
+    pub start_date: Date,
+    /// 结束日期（不含），即下一个租期的开始
+    pub end_date: Date,
+    pub status: ContractStatus,
+    /// 签约时的最短合同期
+    pub min_contract_period: Option<String>,
+    /// 签约时的付款方式
+    pub payment_terms: Option<String>,
+    /// 签约时的违约条款
+    pub breach_penalties: Option<String>,
+    pub renewals: u32,
+    pub terminated_on: Option<Date>,
+    /// 终止时估算的违约金
+    pub termination_cost: Option<f64>,
+}
+
+/// 新建合同的参数
+#[derive(Debug, Clone)]
+pub struct NewContract {
+    pub supplier_id: i64,
+    pub quote_no: Option<String>,
+    pub quantity: u32,
+    pub agreed_price: f64,
+    pub start_date: Date,
+    pub end_date: Date,
+    pub min_contract_period: Option<String>,
+    pub payment_terms: Option<String>,
+    pub breach_penalties: Option<String>,
+}
+
+impl NewContract {
+    /// 数量、单价和起止日期必须有效
+    pub fn validate(&self) -> Result<()> {
+        if self.quantity == 0 {
+            return Err(Error::Validation("合同数量必须大于0".to_string()));
+        }
+        if !self.agreed_price.is_finite() || self.agreed_price < 0.0 {
+            return Err(Error::Validation(format!("约定单价必须是不小于0的数字: {}", self.agreed_price)));
+        }
+        if self.end_date <= self.start_date {
+            return Err(Error::Validation(format!("结束日期 {} 必须晚于开始日期 {}", self.end_date, self.start_date)));
+        }
+        Ok(())
+    }
+}
+
+/// 违约条款的结构化形式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Penalty {
+    /// 无违约金
+    Waived,
+    /// 按月租计，如扣2个月租金
+    Months(f64),
+    /// 剩余租期租金的百分比
+    RemainingPercent(f64),
+    /// 固定金额
+    Fixed(f64),
+}
+
+/// 提前终止费用的估算
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TerminationEstimate {
+    pub terminate_on: Date,
+    /// 剩余月数，不足一个月按一个月计
+    pub remaining_months: u32,
+    /// 剩余租期的租金
+    pub remaining_rent: f64,
+    /// 违约金，条款无法识别时为 `None`
+    pub penalty: Option<f64>,
+    /// 计算依据
+    pub basis: String,
+}
+
+/// 中文数字或阿拉伯数字，只支持合同中常见的较小数值
+fn parse_number(text: &str) -> Option<f64> {
+    if let Ok(v) = text.parse::<f64>() {
+        return Some(v);
+    }
+    let digit = |c: char| "零一二三四五六七八九".chars().position(|d| d == c).map(|d| d as f64).or((c == '两').then_some(2.0));
+    let chars: Vec<char> = text.chars().collect();
+    match chars.as_slice() {
+        [c] if *c == '十' => Some(10.0),
+        [c] => digit(*c),
+        ['十', c] => Some(10.0 + digit(*c)?),
+        [c, '十'] => Some(digit(*c)? * 10.0),
+        [a, '十', b] => Some(digit(*a)? * 10.0 + digit(*b)?),
+        _ => None,
+    }
+}
+
+/// 把文本拆成 (数字, 数字之后的文本)，取第一个数字
+fn split_number(text: &str) -> Option<(f64, &str)> {
+    let is_num = |c: char| c.is_ascii_digit() || c == '.' || "零一二三四五六七八九十两".contains(c);
+    let start = text.find(is_num)?;
+    let rest = &text[start..];
+    let end = rest.find(|c: char| !is_num(c)).unwrap_or(rest.len());
+    Some((parse_number(&rest[..end])?, rest[end..].trim_start()))
+}
+
+/// 识别合同期的月数，如 `6个月`、`半年`、`1年`、`3 months`；无法识别时为 `None`
+pub fn parse_period_months(text: &str) -> Option<u32> {
+    let text = text.trim().to_lowercase();
+    if text.contains("半年") {
+        return Some(6);
+    }
+    let (n, unit) = split_number(&text)?;
+    let months = if unit.starts_with("个月") || unit.starts_with('月') || unit.starts_with("month") || unit == "m" {
+        n
+    } else if unit.starts_with('年') || unit.starts_with("year") || unit == "y" {
+        n * 12.0
+    } else {
+        return None;
+    };
+    (months >= 1.0 && months.fract() == 0.0).then_some(months as u32)
+}
+
+/// 识别违约条款，无法识别时为 `None`
+pub fn parse_penalty(text: &str) -> Option<Penalty> {
+    let text = text.trim();
+    if matches!(text, "无" | "无违约金" | "0" | "不收取") {
+        return Some(Penalty::Waived);
+    }
+    if let Some((n, unit)) = split_number(text) {
+        if unit.starts_with('%') {
+            return Some(Penalty::RemainingPercent(n));
+        }
+        if unit.starts_with("个月") || unit.starts_with('月') {
+            return Some(Penalty::Months(n));
+        }
+        if unit.starts_with('元') || unit.starts_with("块") || (unit.is_empty() && !text.contains("剩余")) {
+            return Some(Penalty::Fixed(n));
+        }
+    }
+    text.contains("剩余").then_some(Penalty::RemainingPercent(100.0))
+}
+
+fn round_cents(v: f64) -> f64 {
+    (v * 100.0).round() / 100.0
+}
+
+impl Contract {
+    /// 每月租金
+    pub fn monthly_rent(&self) -> f64 {
+        round_cents(self.agreed_price * f64::from(self.quantity))
+    }
+
+    /// 合同期的月数，不足一个月按一个月计
+    pub fn term_months(&self) -> u32 {
+        self.start_date.months_until_ceil(self.end_date)
+    }
+
+    /// 估算在 `on` 终止合同的费用；`on` 不早于结束日期时不是提前终止，违约金为0
+    pub fn termination_estimate(&self, on: Date) -> TerminationEstimate {
+        let from = on.max(self.start_date);
+        let remaining_months = from.months_until_ceil(self.end_date);
+        let monthly = self.monthly_rent();
+        let remaining_rent = round_cents(monthly * f64::from(remaining_months));
+        let (penalty, basis) = if remaining_months == 0 {
+            (Some(0.0), "合同已到期，无需支付违约金".to_string())
+        } else {
+            match self.breach_penalties.as_deref().and_then(parse_penalty) {
+                Some(Penalty::Waived) => (Some(0.0), "违约条款：无违约金".to_string()),
+                Some(Penalty::Months(n)) => (Some(round_cents(monthly * n)), format!("{} 个月租金 × 月租 {:.2}", n, monthly)),
+                Some(Penalty::RemainingPercent(p)) => {
+                    (Some(round_cents(remaining_rent * p / 100.0)), format!("剩余 {} 个月租金 {:.2} × {}%", remaining_months, remaining_rent, p))
+                }
+                Some(Penalty::Fixed(v)) => (Some(round_cents(v)), format!("固定金额 {:.2}", v)),
+                None => (None, format!("无法识别违约条款：{}", self.breach_penalties.as_deref().unwrap_or("未填写"))),
+            }
+        };
+        TerminationEstimate { terminate_on: on, remaining_months, remaining_rent, penalty, basis }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> Date {
+        s.parse().unwrap()
+    }
+
+    fn contract(breach_penalties: Option<&str>) -> Contract {
+        Contract {
+            id: 1,
+            supplier_id: 1,
+            quote_no: None,
+            quantity: 4,
+            agreed_price: 1000.0,
+            start_date: date("2024-01-01"),
+            end_date: date("2024-07-01"),
+            status: ContractStatus::Active,
+            min_contract_period: None,
+            payment_terms: None,
+            breach_penalties: breach_penalties.map(str::to_string),
+            renewals: 0,
+            terminated_on: None,
+            termination_cost: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_period_months() {
+        assert_eq!(parse_period_months("6个月"), Some(6));
+        assert_eq!(parse_period_months("6月"), Some(6));
+        assert_eq!(parse_period_months("半年"), Some(6));
+        assert_eq!(parse_period_months("1年"), Some(12));
+        assert_eq!(parse_period_months("两年"), Some(24));
+        assert_eq!(parse_period_months("十二个月"), Some(12));
+        assert_eq!(parse_period_months("3 months"), Some(3));
+        assert_eq!(parse_period_months("1 Year"), Some(12));
+    }
+
+    #[test]
+    fn test_parse_period_months_unrecognized() {
+        assert_eq!(parse_period_months("面议"), None);
+        assert_eq!(parse_period_months("6"), None);
+        assert_eq!(parse_period_months("0个月"), None);
+        assert_eq!(parse_period_months("1.5个月"), None);
+    }
+
+    #[test]
+    fn test_parse_penalty() {
+        assert_eq!(parse_penalty("扣2个月租金"), Some(Penalty::Months(2.0)));
+        assert_eq!(parse_penalty("2个月"), Some(Penalty::Months(2.0)));
+        assert_eq!(parse_penalty("剩余租金的30%"), Some(Penalty::RemainingPercent(30.0)));
+        assert_eq!(parse_penalty("30%"), Some(Penalty::RemainingPercent(30.0)));
+        assert_eq!(parse_penalty("赔付剩余租金"), Some(Penalty::RemainingPercent(100.0)));
+        assert_eq!(parse_penalty("5000元"), Some(Penalty::Fixed(5000.0)));
+        assert_eq!(parse_penalty("5000"), Some(Penalty::Fixed(5000.0)));
+        assert_eq!(parse_penalty("无"), Some(Penalty::Waived));
+        assert_eq!(parse_penalty("不收取"), Some(Penalty::Waived));
+        assert_eq!(parse_penalty("协商解决"), None);
+    }
+
+    #[test]
+    fn test_validate_new_contract() {
+        let new = NewContract {
+            supplier_id: 1,
+            quote_no: None,
+            quantity: 1,
+            agreed_price: 1000.0,
+            start_date: date("2024-01-01"),
+            end_date: date("2024-02-01"),
+            min_contract_period: None,
+            payment_terms: None,
+            breach_penalties: None,
+        };
+        assert!(new.validate().is_ok());
+        assert!(matches!(NewContract { quantity: 0, ..new.clone() }.validate(), Err(Error::Validation(_))));
+        assert!(matches!(NewContract { agreed_price: -1.0, ..new.clone() }.validate(), Err(Error::Validation(_))));
+        assert!(matches!(NewContract { agreed_price: f64::NAN, ..new.clone() }.validate(), Err(Error::Validation(_))));
+        assert!(matches!(NewContract { end_date: date("2024-01-01"), ..new }.validate(), Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn test_status_from_str() {
+        assert_eq!("Active".parse::<ContractStatus>(), Ok(ContractStatus::Active));
+        assert_eq!("terminated".parse::<ContractStatus>().map(ContractStatus::name), Ok("terminated"));
+        assert!("closed".parse::<ContractStatus>().unwrap_err().contains("closed"));
+    }
+
+    #[test]
+    fn test_monthly_rent_and_term() {
+        let contract = contract(None);
+        assert_eq!(contract.monthly_rent(), 4000.0);
+        assert_eq!(contract.term_months(), 6);
+        assert_eq!(Contract { end_date: date("2024-07-02"), ..contract }.term_months(), 7);
+    }
+
+    #[test]
+    fn test_termination_estimate() {
+        // 3月10日终止：剩余不足4个月按4个月计
+        let on = date("2024-03-10");
+        let estimate = contract(Some("剩余租金的50%")).termination_estimate(on);
+        assert_eq!((estimate.remaining_months, estimate.remaining_rent, estimate.penalty), (4, 16000.0, Some(8000.0)));
+        assert_eq!(contract(Some("扣2个月租金")).termination_estimate(on).penalty, Some(8000.0));
+        assert_eq!(contract(Some("5000元")).termination_estimate(on).penalty, Some(5000.0));
+        assert_eq!(contract(Some("无")).termination_estimate(on).penalty, Some(0.0));
+    }
+
+    #[test]
+    fn test_termination_estimate_edges() {
+        let unknown = contract(Some("协商解决")).termination_estimate(date("2024-03-10"));
+        assert_eq!(unknown.penalty, None);
+        assert!(unknown.basis.contains("协商解决"));
+        // 开始前终止按整个合同期计，到期后终止无违约金
+        assert_eq!(contract(Some("剩余租金的50%")).termination_estimate(date("2023-12-01")).remaining_months, 6);
+        let expired = contract(Some("扣2个月租金")).termination_estimate(date("2024-07-01"));
+        assert_eq!((expired.remaining_months, expired.penalty), (0, Some(0.0)));
+    }
+}
@@ -0,0 +1,100 @@
+//! 敏感字段加密
+//!
+//! 联系人、微信和电话使用 AES-256-GCM 加密后存入数据库，密钥由口令经
+//! PBKDF2-HMAC-SHA256 派生。密文以 `enc:` 开头，其余为 Base64 编码的
+//! `随机数(12字节) || 密文`；不带该前缀的值视为明文，便于加密前后的数据共存。
+//! 数据库是否加密以其中保存的加密参数为准，未加密的数据库中以 `enc:` 开头的值同样是明文。
+
+use std::num::NonZeroU32;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use rand::RngCore;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::pbkdf2;
+
+use crate::error::{Error, Result};
+
+/// 密文前缀
+pub const CIPHERTEXT_PREFIX: &str = "enc:";
+
+/// PBKDF2 迭代次数
+const PBKDF2_ROUNDS: NonZeroU32 = match NonZeroU32::new(100_000) {
+    Some(n) => n,
+    None => unreachable!(),
+};
+const SALT_LEN: usize = 16;
+
+/// 用于校验口令的固定明文，加密后保存在数据库中
+const VERIFIER_PLAINTEXT: &str = "wei-assistant";
+
+/// 由口令派生的字段加密器
+#[derive(Debug, Clone)]
+pub struct FieldCipher {
+    key: LessSafeKey,
+}
+
+/// 生成新的随机盐，Base64 编码
+pub fn new_salt() -> String {
+    let mut salt = [0u8; SALT_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    BASE64.encode(salt)
+}
+
+/// 判断值是否为密文
+pub fn is_ciphertext(value: &str) -> bool {
+    value.starts_with(CIPHERTEXT_PREFIX)
+}
+
+impl FieldCipher {
+    /// 由口令和 Base64 编码的盐派生密钥，口令不能为空
+    pub fn derive(passphrase: &str, salt: &str) -> Result<Self> {
+        if passphrase.is_empty() {
+            return Err(Error::Validation("口令不能为空".to_string()));
+        }
+        let salt = BASE64.decode(salt).map_err(|e| Error::Parse(format!("加密参数损坏: {}", e)))?;
+        let mut key = [0u8; 32];
+        pbkdf2::derive(pbkdf2::PBKDF2_HMAC_SHA256, PBKDF2_ROUNDS, &salt, passphrase.as_bytes(), &mut key);
+        let key = UnboundKey::new(&AES_256_GCM, &key).expect("AES-256 密钥长度固定为32字节");
+        Ok(Self { key: LessSafeKey::new(key) })
+    }
+
+    /// 加密文本，每次使用新的随机数，相同明文的密文也不同
+    pub fn encrypt(&self, plaintext: &str) -> String {
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let mut ciphertext = plaintext.as_bytes().to_vec();
+        self.key
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut ciphertext)
+            .expect("AES-GCM 加密内存中的数据不会失败");
+        let mut data = nonce.to_vec();
+        data.extend(ciphertext);
+        format!("{}{}", CIPHERTEXT_PREFIX, BASE64.encode(data))
+    }
+
+    /// 解密文本；不是密文时原样返回
+    pub fn decrypt(&self, value: &str) -> Result<String> {
+        let Some(encoded) = value.strip_prefix(CIPHERTEXT_PREFIX) else {
+            return Ok(value.to_string());
+        };
+        let invalid = || Error::Validation("解密失败：口令错误或数据已损坏".to_string());
+        let mut data = BASE64.decode(encoded).map_err(|_| invalid())?;
+        if data.len() < NONCE_LEN {
+            return Err(invalid());
+        }
+        let mut ciphertext = data.split_off(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(&data).map_err(|_| invalid())?;
+        let plaintext = self.key.open_in_place(nonce, Aad::empty(), &mut ciphertext).map_err(|_| invalid())?;
+        String::from_utf8(plaintext.to_vec()).map_err(|_| invalid())
+    }
+
+    /// 生成口令校验值
+    pub fn verifier(&self) -> String {
+        self.encrypt(VERIFIER_PLAINTEXT)
+    }
+
+    /// 检查口令校验值是否由同一密钥生成
+    pub fn verify(&self, verifier: &str) -> bool {
+        self.decrypt(verifier).is_ok_and(|v| v == VERIFIER_PLAINTEXT)
+    }
+}
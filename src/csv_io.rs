@@ -0,0 +1,268 @@
+use std::fs;
+use std::io;
+
+use clap::{Args, ValueEnum};
+use encoding_rs::GBK;
+
+use crate::error::{Error, Result};
+use crate::import::{CellValue, ColumnMapping, ImportReport, RowError};
+use crate::model::EXPORT_HEADERS;
+use crate::{Column, SupplierRow};
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// 英文表头，与数据库字段名一致
+const EN_HEADERS: [&str; 18] = [
+    "id", "contact", "wechat", "phone", "quantity", "location", "price",
+    "bandwidth_price", "storage_price", "min_contract_period", "breach_penalties", "payment_terms", "server_name",
+    "server_config", "rental_model", "networking_category", "profile", "score",
+];
+
+/// CSV分隔符
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum CsvDelimiter {
+    #[default]
+    #[value(alias = ",")]
+    Comma,
+    #[value(alias = ";")]
+    Semicolon,
+    #[value(alias = "\\t")]
+    Tab,
+}
+
+impl CsvDelimiter {
+    fn as_byte(self) -> u8 {
+        match self {
+            CsvDelimiter::Comma => b',',
+            CsvDelimiter::Semicolon => b';',
+            CsvDelimiter::Tab => b'\t',
+        }
+    }
+}
+
+/// CSV文件编码
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum CsvEncoding {
+    #[default]
+    Utf8,
+    /// 旧版Windows Excel默认使用的简体中文编码
+    Gbk,
+}
+
+/// 表头语言
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum HeaderLang {
+    #[default]
+    Zh,
+    En,
+}
+
+/// CSV格式选项，导出和导入共用同一套参数
+#[derive(Args, Debug, Clone, Default)]
+pub struct CsvOptions {
+    /// CSV分隔符
+    #[arg(long = "csv-delimiter", value_enum, default_value_t = CsvDelimiter::Comma)]
+    pub delimiter: CsvDelimiter,
+    /// 在UTF-8文件开头写入BOM，便于Windows版Excel识别中文
+    #[arg(long = "csv-bom")]
+    pub bom: bool,
+    /// CSV文件编码
+    #[arg(long = "csv-encoding", value_enum, default_value_t = CsvEncoding::Utf8)]
+    pub encoding: CsvEncoding,
+    /// 导出表头语言
+    #[arg(long = "csv-headers", value_enum, default_value_t = HeaderLang::Zh)]
+    pub headers: HeaderLang,
+    /// 价格保留的小数位数，默认原样输出
+    #[arg(long = "csv-decimals", value_name = "N")]
+    pub decimals: Option<usize>,
+    /// 使用逗号作为小数点（如 1200,50），通常与分号分隔符搭配
+    #[arg(long = "csv-decimal-comma")]
+    pub decimal_comma: bool,
+}
+
+impl CsvOptions {
+    /// 检查选项组合是否合法，命令行应在查询或输出任何内容之前调用
+    pub fn validate(&self) -> Result<()> {
+        if self.bom && self.encoding == CsvEncoding::Gbk {
+            return Err(Error::Validation("BOM仅适用于UTF-8编码".to_string()));
+        }
+        if self.decimal_comma && self.delimiter == CsvDelimiter::Comma {
+            return Err(Error::Validation("逗号小数点不能与逗号分隔符同时使用".to_string()));
+        }
+        Ok(())
+    }
+
+    fn format_number(&self, v: f64) -> String {
+        let text = match self.decimals {
+            Some(n) => format!("{:.*}", n, v),
+            None => v.to_string(),
+        };
+        if self.decimal_comma { text.replace('.', ",") } else { text }
+    }
+}
+
+/// 按指定格式导出CSV文件，`columns` 为空时导出全部列
+pub fn export_suppliers_to_csv(rows: &[SupplierRow], columns: &[Column], path: &str, opts: &CsvOptions) -> Result<()> {
+    fs::write(path, suppliers_to_csv_bytes(rows, columns, opts)?)?;
+    Ok(())
+}
+
+/// 按指定格式生成CSV内容（已完成编码），供导出文件和标准输出共用
+pub fn suppliers_to_csv_bytes(rows: &[SupplierRow], columns: &[Column], opts: &CsvOptions) -> Result<Vec<u8>> {
+    let columns = Column::resolve(columns);
+    opts.validate()?;
+    let mut wtr = csv::WriterBuilder::new()
+        .delimiter(opts.delimiter.as_byte())
+        .has_headers(true)
+        .from_writer(Vec::new());
+
+    // 写入表头
+    let headers = match opts.headers {
+        HeaderLang::Zh => EXPORT_HEADERS,
+        HeaderLang::En => EN_HEADERS,
+    };
+    wtr.write_record(columns.iter().map(|c| headers[c.index()]))?;
+
+    // 写入数据行
+    for s in rows {
+        let record = [
+            &s.id.to_string(),
+            s.contact.as_deref().unwrap_or(""),
+            s.wechat.as_deref().unwrap_or(""),
+            s.phone.as_deref().unwrap_or(""),
+            &s.quantity.map(|v| v.to_string()).unwrap_or_default(),
+            s.location.as_deref().unwrap_or(""),
+            &s.price.map(|v| opts.format_number(v)).unwrap_or_default(),
+            &s.bandwidth_price.map(|v| opts.format_number(v)).unwrap_or_default(),
+            &s.storage_price.map(|v| opts.format_number(v)).unwrap_or_default(),
+            s.min_contract_period.as_deref().unwrap_or(""),
+            s.breach_penalties.as_deref().unwrap_or(""),
+            s.payment_terms.as_deref().unwrap_or(""),
+            s.server_name.as_deref().unwrap_or(""),
+            s.server_config.as_deref().unwrap_or(""),
+            s.rental_model.as_deref().unwrap_or(""),
+            s.networking_category.as_deref().unwrap_or(""),
+            s.profile.as_deref().unwrap_or(""),
+            &s.score.map(|v| format!("{:.2}", v)).unwrap_or_default(),
+        ];
+        wtr.write_record(columns.iter().map(|c| record[c.index()]))?;
+    }
+
+    let data = wtr.into_inner().map_err(|e| e.into_error())?;
+    match opts.encoding {
+        CsvEncoding::Utf8 if opts.bom => Ok([UTF8_BOM, &data].concat()),
+        CsvEncoding::Utf8 => Ok(data),
+        CsvEncoding::Gbk => {
+            let text = String::from_utf8(data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            let (encoded, _, had_errors) = GBK.encode(&text);
+            if had_errors {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "存在无法用GBK编码表示的字符").into());
+            }
+            Ok(encoded.into_owned())
+        }
+    }
+}
+
+/// 按指定格式读取CSV文件，首行为表头；UTF-8 BOM 会被自动忽略
+pub fn read_suppliers_from_csv(path: &str, opts: &CsvOptions, mapping: &ColumnMapping) -> Result<ImportReport> {
+    opts.validate()?;
+    let bytes = fs::read(path).map_err(|e| io::Error::new(e.kind(), format!("无法读取CSV文件 {}: {}", path, e)))?;
+    let text = match opts.encoding {
+        CsvEncoding::Utf8 => {
+            let body = bytes.strip_prefix(UTF8_BOM).unwrap_or(&bytes);
+            String::from_utf8(body.to_vec())
+                .map_err(|_| Error::Parse("CSV文件不是有效的UTF-8编码，可尝试 --csv-encoding gbk".to_string()))?
+        }
+        CsvEncoding::Gbk => {
+            let (decoded, had_errors) = GBK.decode_without_bom_handling(&bytes);
+            if had_errors {
+                return Err(Error::Parse("CSV文件不是有效的GBK编码".to_string()));
+            }
+            decoded.into_owned()
+        }
+    };
+
+    let mut rdr = csv::ReaderBuilder::new()
+        .delimiter(opts.delimiter.as_byte())
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let headers = rdr.headers().map_err(|e| Error::Parse(format!("无法读取表头: {}", e)))?;
+    let columns = mapping.resolve_columns(headers.iter().map(str::to_string))?;
+
+    let mut report = ImportReport::default();
+    for (i, record) in rdr.records().enumerate() {
+        match record {
+            Ok(record) => {
+                // 含换行的引号字段会占用多行，优先使用记录起始行号
+                let row_no = record.position().map(|p| p.line() as usize).unwrap_or(i + 2);
+                let values = record.iter().map(|v| CellValue::Text(v.to_string())).collect();
+                report.push_row(row_no, &columns, values, opts.decimal_comma);
+            }
+            Err(e) => {
+                let row_no = e.position().map(|p| p.line() as usize).unwrap_or(i + 2);
+                report.errors.push(RowError { row: row_no, message: e.to_string() });
+            }
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Supplier;
+
+    // 测试CSV格式选项：BOM、分号分隔、GBK编码、英文表头和逗号小数点，并能按相同选项读回
+    #[test]
+    fn test_csv_dialect_round_trip() {
+        let rows = vec![
+            Supplier {
+                contact: Some("张三".to_string()),
+                location: Some("北京".to_string()),
+                quantity: Some(8),
+                price: Some(1200.5),
+                ..Default::default()
+            }.to_row(1),
+        ];
+        let csv_file = tempfile::NamedTempFile::new().unwrap();
+        let csv_path = csv_file.path().to_str().unwrap();
+
+        // UTF-8 BOM
+        let opts = CsvOptions { bom: true, ..Default::default() };
+        export_suppliers_to_csv(&rows, &[], csv_path, &opts).unwrap();
+        let bytes = std::fs::read(csv_path).unwrap();
+        assert!(bytes.starts_with(b"\xEF\xBB\xBF"));
+        let report = read_suppliers_from_csv(csv_path, &opts, &ColumnMapping::default()).unwrap();
+        assert_eq!(report.suppliers.len(), 1);
+        assert_eq!(report.suppliers[0].1.contact.as_deref(), Some("张三"));
+
+        // GBK + 分号 + 英文表头 + 逗号小数点
+        let opts = CsvOptions {
+            delimiter: CsvDelimiter::Semicolon,
+            encoding: CsvEncoding::Gbk,
+            headers: HeaderLang::En,
+            decimals: Some(2),
+            decimal_comma: true,
+            ..Default::default()
+        };
+        export_suppliers_to_csv(&rows, &[], csv_path, &opts).unwrap();
+        let bytes = std::fs::read(csv_path).unwrap();
+        assert!(std::str::from_utf8(&bytes).is_err(), "GBK编码的中文不应是有效UTF-8");
+        let (text, _, _) = encoding_rs::GBK.decode(&bytes);
+        assert!(text.starts_with("id;contact;wechat"));
+        assert!(text.contains("张三;"));
+        assert!(text.contains(";1200,50;"));
+
+        let report = read_suppliers_from_csv(csv_path, &opts, &ColumnMapping::default()).unwrap();
+        assert!(report.errors.is_empty());
+        let s = &report.suppliers[0].1;
+        assert_eq!(s.contact.as_deref(), Some("张三"));
+        assert_eq!(s.location.as_deref(), Some("北京"));
+        assert_eq!(s.quantity, Some(8));
+        assert_eq!(s.price, Some(1200.5));
+
+        // 不兼容的组合
+        let opts = CsvOptions { decimal_comma: true, ..Default::default() };
+        assert!(matches!(export_suppliers_to_csv(&rows, &[], csv_path, &opts), Err(Error::Validation(_))));
+    }
+}
@@ -0,0 +1,238 @@
+//! 日期计算
+//!
+//! 合同、付款计划只需要按日和按月推算日期，这里提供一个不含时区的公历日期类型，
+//! 以 `YYYY-MM-DD` 文本存入数据库。当天日期由 [`SupplierStore::today`](crate::SupplierStore::today)
+//! 取 SQLite 的本地时间，与其他表中的时间戳一致。
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::{Error, Result};
+
+/// 公历日期
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: i32,
+    month: u32,
+    day: u32,
+}
+
+fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl Date {
+    /// 日期不存在时返回 `None`
+    pub fn new(year: i32, month: u32, day: u32) -> Option<Self> {
+        ((1..=12).contains(&month) && (1..=days_in_month(year, month)).contains(&day)).then_some(Self { year, month, day })
+    }
+
+    pub fn year(self) -> i32 {
+        self.year
+    }
+
+    pub fn month(self) -> u32 {
+        self.month
+    }
+
+    pub fn day(self) -> u32 {
+        self.day
+    }
+
+    /// 自 1970-01-01 起的天数
+    fn to_days(self) -> i64 {
+        // Howard Hinnant 的 days_from_civil 算法
+        let y = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = y.div_euclid(400);
+        let yoe = y - era * 400;
+        let m = i64::from(self.month);
+        let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + i64::from(self.day) - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146097 + doe - 719468
+    }
+
+    /// 自 1970-01-01 起第 `days` 天的日期
+    pub(crate) fn from_days(days: i64) -> Self {
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z - era * 146097;
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = (yoe + era * 400 + i64::from(month <= 2)) as i32;
+        Self { year, month, day }
+    }
+
+    /// 加减天数
+    pub fn add_days(self, days: i64) -> Self {
+        Self::from_days(self.to_days() + days)
+    }
+
+    /// 加减月数，目标月份没有该日时取月末，如 1月31日加1个月为2月28日（或29日）
+    pub fn add_months(self, months: i32) -> Self {
+        let index = self.year * 12 + self.month as i32 - 1 + months;
+        let (year, month) = (index.div_euclid(12), index.rem_euclid(12) as u32 + 1);
+        Self { year, month, day: self.day.min(days_in_month(year, month)) }
+    }
+
+    /// 从 `self` 到 `other` 的天数，`other` 更早时为负
+    pub fn days_until(self, other: Date) -> i64 {
+        other.to_days() - self.to_days()
+    }
+
+    /// 从 `self` 到 `other` 的月数，不足一个月的部分按一个月计；`other` 不晚于 `self` 时为0
+    pub fn months_until_ceil(self, other: Date) -> u32 {
+        let mut months = 0;
+        while self.add_months(months as i32) < other {
+            months += 1;
+        }
+        months
+    }
+}
+
+/// 解析以天为单位的时长，如 `14d`、`14天`、`2w`、`2周`，不带单位时按天计
+pub fn parse_days(text: &str) -> Result<u32> {
+    let text = text.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let invalid = || Error::Validation(format!("时长格式无效: {}，应为天数（如 14d）或周数（如 2w）", text));
+    let n: u32 = number.parse().map_err(|_| invalid())?;
+    match unit.trim() {
+        "" | "d" | "天" | "日" => Ok(n),
+        "w" | "周" => n.checked_mul(7).ok_or_else(invalid),
+        _ => Err(invalid()),
+    }
+}
+
+impl FromStr for Date {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::Validation(format!("日期格式无效: {}，应为 YYYY-MM-DD", s));
+        let parts: Vec<&str> = s.trim().split('-').collect();
+        let [y, m, d] = parts[..] else {
+            return Err(invalid());
+        };
+        let (Ok(y), Ok(m), Ok(d)) = (y.parse(), m.parse(), d.parse()) else {
+            return Err(invalid());
+        };
+        Date::new(y, m, d).ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl Serialize for Date {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl rusqlite::types::FromSql for Date {
+    fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        value.as_str()?.parse().map_err(|e: Error| rusqlite::types::FromSqlError::Other(e.to_string().into()))
+    }
+}
+
+impl rusqlite::ToSql for Date {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        Ok(rusqlite::types::ToSqlOutput::from(self.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> Date {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_new_rejects_invalid_dates() {
+        assert!(Date::new(2024, 2, 29).is_some());
+        assert!(Date::new(2023, 2, 29).is_none());
+        assert!(Date::new(1900, 2, 29).is_none());
+        assert!(Date::new(2000, 2, 29).is_some());
+        assert!(Date::new(2024, 4, 31).is_none());
+        assert!(Date::new(2024, 13, 1).is_none());
+        assert!(Date::new(2024, 1, 0).is_none());
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        let d = date("2024-3-5");
+        assert_eq!((d.year(), d.month(), d.day()), (2024, 3, 5));
+        assert_eq!(d.to_string(), "2024-03-05");
+        assert!(" 2024-03-05 ".parse::<Date>().is_ok());
+        for bad in ["2024-02-30", "2024/03/05", "2024-03", "20240305", ""] {
+            assert!(matches!(bad.parse::<Date>(), Err(Error::Validation(_))), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_add_days() {
+        assert_eq!(date("2024-12-30").add_days(3), date("2025-01-02"));
+        assert_eq!(date("2024-02-28").add_days(1), date("2024-02-29"));
+        assert_eq!(date("2024-03-01").add_days(-1), date("2024-02-29"));
+        assert_eq!(date("1970-01-01").add_days(0), Date::from_days(0));
+    }
+
+    #[test]
+    fn test_add_months_clamps_to_month_end() {
+        assert_eq!(date("2024-01-31").add_months(1), date("2024-02-29"));
+        assert_eq!(date("2023-01-31").add_months(1), date("2023-02-28"));
+        assert_eq!(date("2024-03-31").add_months(-1), date("2024-02-29"));
+        assert_eq!(date("2024-11-15").add_months(3), date("2025-02-15"));
+        assert_eq!(date("2024-01-15").add_months(-13), date("2022-12-15"));
+    }
+
+    #[test]
+    fn test_days_until() {
+        assert_eq!(date("2024-01-01").days_until(date("2025-01-01")), 366);
+        assert_eq!(date("2024-03-10").days_until(date("2024-03-01")), -9);
+    }
+
+    #[test]
+    fn test_months_until_ceil() {
+        assert_eq!(date("2024-01-01").months_until_ceil(date("2024-07-01")), 6);
+        assert_eq!(date("2024-01-01").months_until_ceil(date("2024-07-02")), 7);
+        assert_eq!(date("2024-03-10").months_until_ceil(date("2024-07-01")), 4);
+        assert_eq!(date("2024-07-01").months_until_ceil(date("2024-01-01")), 0);
+    }
+
+    #[test]
+    fn test_parse_days() {
+        assert_eq!(parse_days("14").unwrap(), 14);
+        assert_eq!(parse_days("14d").unwrap(), 14);
+        assert_eq!(parse_days("14天").unwrap(), 14);
+        assert_eq!(parse_days("2w").unwrap(), 14);
+        assert_eq!(parse_days(" 2 周 ").unwrap(), 14);
+        for bad in ["fortnight", "2m", "-1d", "d", ""] {
+            assert!(matches!(parse_days(bad), Err(Error::Validation(_))), "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::fmt;
+use std::io;
+
+/// 库层统一错误类型
+#[derive(Debug)]
+pub enum Error {
+    /// 输入或筛选条件不合法
+    Validation(String),
+    /// JSON、CSV或Excel内容无法解析
+    Parse(String),
+    /// 要查找的记录（供应商、视图等）不存在
+    NotFound(String),
+    /// 文件读写错误
+    Io(io::Error),
+    /// 数据库错误
+    Database(rusqlite::Error),
+    /// 未登录、认证失败或角色权限不足
+    PermissionDenied(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// 错误类别名称，用于机器可读的错误输出
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Validation(_) => "validation",
+            Error::Parse(_) => "parse",
+            Error::NotFound(_) => "not_found",
+            Error::Io(_) => "io",
+            Error::Database(_) => "database",
+            Error::PermissionDenied(_) => "permission_denied",
+        }
+    }
+
+    /// 进程退出码，每类错误各不相同（2 保留给命令行参数错误）
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::Validation(_) => 3,
+            Error::Parse(_) => 4,
+            Error::NotFound(_) => 5,
+            Error::Io(_) => 6,
+            Error::Database(_) => 7,
+            Error::PermissionDenied(_) => 8,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Validation(msg) => write!(f, "{}", msg),
+            Error::Parse(msg) => write!(f, "{}", msg),
+            Error::NotFound(msg) => write!(f, "{}", msg),
+            Error::PermissionDenied(msg) => write!(f, "{}", msg),
+            Error::Io(e) => write!(f, "文件读写错误: {}", e),
+            Error::Database(e) => write!(f, "数据库错误: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Database(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(e: rusqlite::Error) -> Self {
+        Error::Database(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Parse(format!("JSON解析失败: {}", e))
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<csv::Error> for Error {
+    fn from(e: csv::Error) -> Self {
+        Error::Io(io::Error::from(e))
+    }
+}
+
+impl From<rust_xlsxwriter::XlsxError> for Error {
+    fn from(e: rust_xlsxwriter::XlsxError) -> Self {
+        match e {
+            rust_xlsxwriter::XlsxError::IoError(e) => Error::Io(e),
+            other => Error::Io(io::Error::other(other)),
+        }
+    }
+}
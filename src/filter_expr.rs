@@ -0,0 +1,362 @@
+//! 命令行 `--filter` 使用的筛选表达式
+//!
+//! 语法（关键字不区分大小写）：
+//!
+//! ```text
+//! expr       := and ("or" and)*
+//! and        := unary ("and" unary)*
+//! unary      := "not" unary | "(" expr ")" | comparison
+//! comparison := 字段 操作符 值
+//!             | 字段 ["not"] "in" "[" 值 ("," 值)* "]"
+//!             | 字段 "is" ["not"] "null"
+//! 操作符     := = | == | != | <> | > | < | >= | <= | like
+//! ```
+//!
+//! 字段可以是英文字段名（`bandwidth_price` 或 `bandwidth-price`）或常见中文表头，也可以是综合评分
+//! `score`（或 `评分`，只支持比较和 `is [not] null`）；
+//! 值可以用单引号或双引号括起，不含空白和 `()[],=<>!` 的值可以不加引号。
+
+use crate::error::{Error, Result};
+use crate::matching::MatchMode;
+use crate::model::{Column, SupplierField};
+use crate::query::{ComparisonOp, Condition, FilterCriteria};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+    Op(String),
+    Word(String),
+    Quoted(String),
+}
+
+/// 解析筛选表达式
+pub fn parse_filter(input: &str) -> Result<Condition> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let cond = parser.parse_or()?;
+    match parser.peek() {
+        None => Ok(cond),
+        Some(tok) => Err(syntax_error(format!("多余的内容: {}", describe(tok)))),
+    }
+}
+
+fn syntax_error(msg: String) -> Error {
+    Error::Parse(format!("筛选表达式错误: {}", msg))
+}
+
+fn describe(tok: &Token) -> String {
+    match tok {
+        Token::LParen => "(".to_string(),
+        Token::RParen => ")".to_string(),
+        Token::LBracket => "[".to_string(),
+        Token::RBracket => "]".to_string(),
+        Token::Comma => ",".to_string(),
+        Token::Op(s) | Token::Word(s) => s.clone(),
+        Token::Quoted(s) => format!("\"{}\"", s),
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    !c.is_whitespace() && !"()[],=<>!\"'".contains(c)
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' | '[' | ']' | ',' => {
+                chars.next();
+                tokens.push(match c {
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    '[' => Token::LBracket,
+                    ']' => Token::RBracket,
+                    _ => Token::Comma,
+                });
+            }
+            '=' | '!' | '<' | '>' => {
+                chars.next();
+                let mut op = c.to_string();
+                if let Some(&next) = chars.peek() {
+                    if matches!((c, next), ('=', '=') | ('!', '=') | ('<', '=') | ('>', '=') | ('<', '>')) {
+                        op.push(next);
+                        chars.next();
+                    }
+                }
+                if op == "!" {
+                    return Err(syntax_error("无效的操作符 !".to_string()));
+                }
+                tokens.push(Token::Op(op));
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some(ch) if ch == c => break,
+                        Some(ch) => text.push(ch),
+                        None => return Err(syntax_error("引号未闭合".to_string())),
+                    }
+                }
+                tokens.push(Token::Quoted(text));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&ch) = chars.peek() {
+                    if !is_word_char(ch) {
+                        break;
+                    }
+                    word.push(ch);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let tok = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        tok
+    }
+
+    /// 下一个词是否为指定关键字（不区分大小写），是则消费
+    fn eat_keyword(&mut self, kw: &str) -> bool {
+        match self.peek() {
+            Some(Token::Word(w)) if w.eq_ignore_ascii_case(kw) => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<()> {
+        match self.next() {
+            Some(tok) if tok == expected => Ok(()),
+            Some(tok) => Err(syntax_error(format!("应为 {}，实际为 {}", describe(&expected), describe(&tok)))),
+            None => Err(syntax_error(format!("缺少 {}", describe(&expected)))),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Condition> {
+        let mut items = vec![self.parse_and()?];
+        while self.eat_keyword("or") {
+            items.push(self.parse_and()?);
+        }
+        Ok(if items.len() == 1 { items.remove(0) } else { Condition::Any(items) })
+    }
+
+    fn parse_and(&mut self) -> Result<Condition> {
+        let mut items = vec![self.parse_unary()?];
+        while self.eat_keyword("and") {
+            items.push(self.parse_unary()?);
+        }
+        Ok(if items.len() == 1 { items.remove(0) } else { Condition::All(items) })
+    }
+
+    fn parse_unary(&mut self) -> Result<Condition> {
+        if self.eat_keyword("not") {
+            return Ok(Condition::Not(Box::new(self.parse_unary()?)));
+        }
+        if self.peek() == Some(&Token::LParen) {
+            self.pos += 1;
+            let cond = self.parse_or()?;
+            self.expect(Token::RParen)?;
+            return Ok(cond);
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_value(&mut self) -> Result<String> {
+        match self.next() {
+            Some(Token::Word(w)) | Some(Token::Quoted(w)) => Ok(w),
+            Some(tok) => Err(syntax_error(format!("应为值，实际为 {}", describe(&tok)))),
+            None => Err(syntax_error("缺少值".to_string())),
+        }
+    }
+
+    fn parse_comparison(&mut self) -> Result<Condition> {
+        let name = match self.next() {
+            Some(Token::Word(w)) => w,
+            Some(tok) => return Err(syntax_error(format!("应为字段名，实际为 {}", describe(&tok)))),
+            None => return Err(syntax_error("表达式不完整".to_string())),
+        };
+        if matches!(name.parse(), Ok(Column::Score)) {
+            return self.parse_score_comparison(&name);
+        }
+        let field = SupplierField::from_header(&name.replace('-', "_"))
+            .ok_or_else(|| Error::Validation(format!("未知字段名: {}", name)))?;
+
+        if self.eat_keyword("is") {
+            let op = if self.eat_keyword("not") { ComparisonOp::IsNotNull } else { ComparisonOp::IsNull };
+            if !self.eat_keyword("null") {
+                return Err(syntax_error(format!("{} is 之后应为 null", name)));
+            }
+            return Ok(Condition::Filter(FilterCriteria { field, op, value: None, mode: MatchMode::Exact }));
+        }
+
+        let negated = self.eat_keyword("not");
+        if self.eat_keyword("in") {
+            self.expect(Token::LBracket)?;
+            let mut values = vec![self.parse_value()?];
+            while self.peek() == Some(&Token::Comma) {
+                self.pos += 1;
+                values.push(self.parse_value()?);
+            }
+            self.expect(Token::RBracket)?;
+            let cond = Condition::In { field, values };
+            return Ok(if negated { Condition::Not(Box::new(cond)) } else { cond });
+        }
+        if negated {
+            return Err(syntax_error(format!("{} not 之后应为 in", name)));
+        }
+
+        let op = match self.next() {
+            Some(Token::Op(op)) => op,
+            Some(Token::Word(w)) if w.eq_ignore_ascii_case("like") => w,
+            Some(tok) => return Err(syntax_error(format!("应为操作符，实际为 {}", describe(&tok)))),
+            None => return Err(syntax_error(format!("{} 之后缺少操作符", name))),
+        };
+        let op: ComparisonOp = op.parse().map_err(syntax_error)?;
+        let value = self.parse_value()?;
+        Ok(Condition::Filter(FilterCriteria { field, op, value: Some(value), mode: MatchMode::Exact }))
+    }
+
+    /// 综合评分只支持比较和 is [not] null
+    fn parse_score_comparison(&mut self, name: &str) -> Result<Condition> {
+        if self.eat_keyword("is") {
+            let op = if self.eat_keyword("not") { ComparisonOp::IsNotNull } else { ComparisonOp::IsNull };
+            if !self.eat_keyword("null") {
+                return Err(syntax_error(format!("{} is 之后应为 null", name)));
+            }
+            return Ok(Condition::Score { op, value: None });
+        }
+        let op = match self.next() {
+            Some(Token::Op(op)) => op,
+            Some(tok) => return Err(syntax_error(format!("{} 只支持比较操作符，实际为 {}", name, describe(&tok)))),
+            None => return Err(syntax_error(format!("{} 之后缺少操作符", name))),
+        };
+        let op: ComparisonOp = op.parse().map_err(syntax_error)?;
+        let value = self.parse_value()?;
+        let value = value.parse::<f64>().map_err(|_| Error::Validation(format!("评分必须是数字: {}", value)))?;
+        Ok(Condition::Score { op, value: Some(value) })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field_of(cond: &Condition) -> Option<SupplierField> {
+        match cond {
+            Condition::Filter(f) => Some(f.field),
+            Condition::In { field, .. } => Some(*field),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_tokenize_operators_and_values() {
+        let tokens = tokenize("price>=1000 and contact <> '张 三' and x==\"a,b\"").unwrap();
+        assert_eq!(
+            tokens,
+            [
+                Token::Word("price".to_string()),
+                Token::Op(">=".to_string()),
+                Token::Word("1000".to_string()),
+                Token::Word("and".to_string()),
+                Token::Word("contact".to_string()),
+                Token::Op("<>".to_string()),
+                Token::Quoted("张 三".to_string()),
+                Token::Word("and".to_string()),
+                Token::Word("x".to_string()),
+                Token::Op("==".to_string()),
+                Token::Quoted("a,b".to_string()),
+            ]
+        );
+        assert_eq!(tokenize("[a, b]").unwrap(), [Token::LBracket, Token::Word("a".to_string()), Token::Comma, Token::Word("b".to_string()), Token::RBracket]);
+    }
+
+    #[test]
+    fn test_tokenize_errors() {
+        assert!(matches!(tokenize("contact = 'x"), Err(Error::Parse(_))));
+        assert!(matches!(tokenize("price ! 1"), Err(Error::Parse(_))));
+    }
+
+    #[test]
+    fn test_and_binds_tighter_than_or() {
+        let cond = parse_filter("contact = a or location = b and quantity = 1").unwrap();
+        let Condition::Any(items) = cond else { panic!("应为 OR: {:?}", cond) };
+        assert_eq!(field_of(&items[0]), Some(SupplierField::ContactPerson));
+        assert!(matches!(&items[1], Condition::All(inner) if inner.len() == 2));
+    }
+
+    #[test]
+    fn test_parentheses_override_precedence() {
+        let cond = parse_filter("(contact = a or location = b) and quantity = 1").unwrap();
+        let Condition::All(items) = cond else { panic!("应为 AND: {:?}", cond) };
+        assert!(matches!(&items[0], Condition::Any(inner) if inner.len() == 2));
+        assert_eq!(field_of(&items[1]), Some(SupplierField::Quantity));
+    }
+
+    #[test]
+    fn test_keywords_are_case_insensitive() {
+        let cond = parse_filter("NOT location NOT IN [北京] AND wechat IS NOT NULL").unwrap();
+        let Condition::All(items) = cond else { panic!("应为 AND: {:?}", cond) };
+        let Condition::Not(inner) = &items[0] else { panic!("应为 NOT: {:?}", items[0]) };
+        assert!(matches!(inner.as_ref(), Condition::Not(c) if matches!(c.as_ref(), Condition::In { values, .. } if values == &["北京"])));
+        assert!(matches!(&items[1], Condition::Filter(FilterCriteria { field: SupplierField::Wechat, op: ComparisonOp::IsNotNull, value: None, .. })));
+    }
+
+    #[test]
+    fn test_field_aliases() {
+        for expr in ["bandwidth_price > 1", "bandwidth-price > 1", "带宽价 > 1"] {
+            assert_eq!(field_of(&parse_filter(expr).unwrap()), Some(SupplierField::BandwidthPrice), "{}", expr);
+        }
+        assert!(matches!(parse_filter("unknown = 1"), Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn test_like_operator() {
+        let cond = parse_filter("server-name LIKE 'A100%'").unwrap();
+        assert!(matches!(cond, Condition::Filter(FilterCriteria { op: ComparisonOp::Like, value: Some(v), .. }) if v == "A100%"));
+    }
+
+    #[test]
+    fn test_score_comparison() {
+        assert!(matches!(parse_filter("评分 >= 4").unwrap(), Condition::Score { op: ComparisonOp::Gte, value: Some(v) } if v == 4.0));
+        assert!(matches!(parse_filter("score is null").unwrap(), Condition::Score { op: ComparisonOp::IsNull, value: None }));
+        assert!(matches!(parse_filter("score like 4"), Err(Error::Parse(_))));
+        assert!(matches!(parse_filter("score in [4]"), Err(Error::Parse(_))));
+        assert!(matches!(parse_filter("score > high"), Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        for bad in ["", "price <", "(location = 北京", "location in 北京", "location in [北京", "price = 1 extra", "contact not = 1", "wechat is empty", "= 1"] {
+            assert!(matches!(parse_filter(bad), Err(Error::Parse(_))), "应解析失败: {}", bad);
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+
+use crate::error::{Error, Result};
+use crate::{Supplier, SupplierField};
+
+/// 导入时的表头 → 字段映射，自定义映射优先于内置别名
+#[derive(Debug, Default)]
+pub struct ColumnMapping {
+    custom: HashMap<String, SupplierField>,
+}
+
+impl ColumnMapping {
+    /// 解析 `表头=字段名` 形式的映射规则
+    pub fn from_rules(rules: &[String]) -> Result<Self> {
+        let mut custom = HashMap::new();
+        for rule in rules {
+            let (header, field) = rule
+                .split_once('=')
+                .ok_or_else(|| Error::Validation(format!("列映射错误: 规则格式应为 表头=字段名: {}", rule)))?;
+            let field = SupplierField::from_header(field)
+                .ok_or_else(|| Error::Validation(format!("列映射错误: 未知字段名: {}", field)))?;
+            custom.insert(header.trim().to_string(), field);
+        }
+        Ok(Self { custom })
+    }
+
+    pub fn resolve(&self, header: &str) -> Option<SupplierField> {
+        match self.custom.get(header.trim()) {
+            Some(field) => Some(*field),
+            None => SupplierField::from_header(header),
+        }
+    }
+
+    /// 将表头行解析为列定义，无法识别的列为 `None`（导入时忽略）
+    pub fn resolve_columns<I>(&self, headers: I) -> Result<Vec<Option<(String, SupplierField)>>>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let columns: Vec<Option<(String, SupplierField)>> = headers
+            .into_iter()
+            .map(|h| {
+                let h = h.trim().to_string();
+                self.resolve(&h).map(|f| (h, f))
+            })
+            .collect();
+        if !columns.iter().any(|c| matches!(c, Some((_, SupplierField::ContactPerson)))) {
+            return Err(Error::Validation("表头中缺少联系人列，请检查列映射".to_string()));
+        }
+        Ok(columns)
+    }
+}
+
+/// 行级错误，`row` 为文件中的行号（从1开始，含表头）
+#[derive(Debug)]
+pub struct RowError {
+    pub row: usize,
+    pub message: String,
+}
+
+/// 导入解析结果：成功解析的记录（附行号）和失败的行
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub suppliers: Vec<(usize, Supplier)>,
+    pub errors: Vec<RowError>,
+}
+
+/// 来源文件中的单元格值，Excel可直接提供数值，CSV均为文本
+#[derive(Debug)]
+pub enum CellValue {
+    Empty,
+    Text(String),
+    Number(f64),
+}
+
+impl CellValue {
+    pub fn into_text(self) -> Option<String> {
+        match self {
+            CellValue::Empty => None,
+            // 整数值的数值单元格（如手机号）去掉多余的 ".0"
+            CellValue::Number(f) if f.fract() == 0.0 && f.abs() < 1e15 => Some(format!("{}", f as i64)),
+            CellValue::Number(f) => Some(f.to_string()),
+            CellValue::Text(s) => {
+                let s = s.trim();
+                if s.is_empty() { None } else { Some(s.to_string()) }
+            }
+        }
+    }
+
+    fn into_f64(self, decimal_comma: bool) -> std::result::Result<Option<f64>, String> {
+        match self {
+            CellValue::Empty => Ok(None),
+            CellValue::Number(f) => Ok(Some(f)),
+            CellValue::Text(s) => parse_number(&s, decimal_comma),
+        }
+    }
+
+    fn into_i32(self, decimal_comma: bool) -> std::result::Result<Option<i32>, String> {
+        match self.into_f64(decimal_comma)? {
+            None => Ok(None),
+            Some(v) if v.fract() == 0.0 && v >= i32::MIN as f64 && v <= i32::MAX as f64 => Ok(Some(v as i32)),
+            Some(v) => Err(format!("数量必须为整数: {}", v)),
+        }
+    }
+}
+
+/// 解析手工录入的数字，兼容 "1,200"、"1200元" 等写法；
+/// `decimal_comma` 为真时按 "1.200,50" 的欧式写法解析
+pub fn parse_number(text: &str, decimal_comma: bool) -> std::result::Result<Option<f64>, String> {
+    let trimmed = text.trim().trim_end_matches('元').trim();
+    if trimmed.is_empty() {
+        return Ok(None);
+    }
+    let (group_sep, decimal_sep) = if decimal_comma { ('.', ',') } else { (',', '.') };
+    let cleaned: String = trimmed
+        .chars()
+        .filter(|c| *c != group_sep && !c.is_whitespace())
+        .map(|c| if c == decimal_sep { '.' } else { c })
+        .collect();
+    cleaned.parse::<f64>().map(Some).map_err(|_| format!("无法解析为数字: {}", text))
+}
+
+fn apply_value(s: &mut Supplier, field: &SupplierField, value: CellValue, decimal_comma: bool) -> std::result::Result<(), String> {
+    match field {
+        SupplierField::ContactPerson => s.contact = value.into_text(),
+        SupplierField::Wechat => s.wechat = value.into_text(),
+        SupplierField::Phone => s.phone = value.into_text(),
+        SupplierField::Quantity => s.quantity = value.into_i32(decimal_comma)?,
+        SupplierField::Location => s.location = value.into_text(),
+        SupplierField::Price => s.price = value.into_f64(decimal_comma)?,
+        SupplierField::BandwidthPrice => s.bandwidth_price = value.into_f64(decimal_comma)?,
+        SupplierField::StoragePrice => s.storage_price = value.into_f64(decimal_comma)?,
+        SupplierField::MinContractPeriod => s.min_contract_period = value.into_text(),
+        SupplierField::BreachPenalties => s.breach_penalties = value.into_text(),
+        SupplierField::PaymentTerms => s.payment_terms = value.into_text(),
+        SupplierField::ServerName => s.server_name = value.into_text(),
+        SupplierField::ServerConfig => s.server_config = value.into_text(),
+        SupplierField::RentalModel => s.rental_model = value.into_text(),
+        SupplierField::NetworkingCategory => s.networking_category = value.into_text(),
+    }
+    Ok(())
+}
+
+impl ImportReport {
+    /// 解析一行数据并记录结果，空行直接跳过
+    pub fn push_row(&mut self, row_no: usize, columns: &[Option<(String, SupplierField)>], values: Vec<CellValue>, decimal_comma: bool) {
+        let is_blank = |v: &CellValue| match v {
+            CellValue::Empty => true,
+            CellValue::Text(t) => t.trim().is_empty(),
+            CellValue::Number(_) => false,
+        };
+        if values.iter().all(is_blank) {
+            return;
+        }
+        let mut supplier = Supplier::default();
+        let mut failed = None;
+        for (column, value) in columns.iter().zip(values) {
+            if let Some((header, field)) = column {
+                if let Err(e) = apply_value(&mut supplier, field, value, decimal_comma) {
+                    failed = Some(format!("{}列: {}", header, e));
+                    break;
+                }
+            }
+        }
+        if failed.is_none() && supplier.contact.is_none() {
+            failed = Some("缺少联系人".to_string());
+        }
+        match failed {
+            Some(message) => self.errors.push(RowError { row: row_no, message }),
+            None => self.suppliers.push((row_no, supplier)),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+//! 供应商信息管理库：数据模型、查询构建、存储以及导入导出
+//!
+//! 命令行工具 `wei-assistant-gpu` 只是本库的一层薄封装，其他程序可直接使用
+//! [`SupplierStore`] 读写供应商数据。
+
+pub mod auth;
+pub mod catalog;
+pub mod config;
+pub mod contract;
+pub mod crypto;
+pub mod csv_io;
+pub mod dates;
+pub mod error;
+pub mod filter_expr;
+pub mod import;
+pub mod matching;
+pub mod model;
+pub mod notify;
+pub mod payment;
+pub mod pricing;
+pub mod privacy;
+pub mod query;
+pub mod quotation;
+pub mod rating;
+pub mod render;
+pub mod store;
+pub mod xlsx;
+
+pub use config::{Config, Overrides, Settings};
+pub use error::{Error, Result};
+pub use model::{Column, Supplier, SupplierField, SupplierRow};
+pub use filter_expr::parse_filter;
+pub use matching::{MatchMode, Synonyms};
+pub use query::{ComparisonOp, Condition, FilterCriteria, QueryBuilder, SavedView, SortKey};
+pub use store::{SupplierStore, SCHEMA_VERSION};
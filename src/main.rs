@@ -1,26 +1,29 @@
-use std::fs::File;
-use clap::{Parser, Subcommand, Args};
-use rusqlite::{Connection, Result};
-use serde::{Deserialize, Serialize};
-use std::str::FromStr;
-use serde_json;
-use std::sync::OnceLock;
-use csv;
-
-static DB_FILE: OnceLock<String> = OnceLock::new();
-
-fn get_db_file() -> &'static str {
-    DB_FILE.get_or_init(|| {
-        std::env::var("DB_FILE").unwrap_or_else(|_| "wei-assistant.db".to_string())
-    })
-}
-
-// 已不再需要此函数，测试中直接使用环境变量更改数据库路径
-#[allow(dead_code)]
-fn set_db_file(_path: &str) {
-    // 此函数不再使用
-    // 保留函数签名以避免修改测试代码
-}
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+mod cli;
+
+use wei_assistant_gpu::auth::Role;
+use wei_assistant_gpu::config::Overrides;
+use wei_assistant_gpu::{Error, Result, Settings};
+
+use cli::audit::run_audit;
+use cli::catalog::{run_catalog, CatalogAction};
+use cli::config::{run_config, ConfigAction};
+use cli::contract::{run_contract, ContractAction};
+use cli::db::{run_db, DbAction};
+use cli::notify::{run_notify, NotifyAction};
+use cli::payments::{run_payments, PaymentsAction};
+use cli::profile::{run_profile, ProfileAction};
+use cli::pricing::{run_pricing, PricingAction};
+use cli::query::{run_query, QueryArgs};
+use cli::quote::{run_quote, QuoteAction};
+use cli::rating::{run_incident, run_rating, IncidentAction, RatingAction};
+use cli::supplier::{run_add, run_delete, run_import, run_update, ImportArgs, SupplierArgs};
+use cli::synonym::{run_synonym, SynonymAction};
+use cli::user::{run_user, UserAction};
+use cli::view::{run_view, ViewAction};
 
 #[derive(Parser, Debug)]
 #[command(name = "wei-assistant")]
@@ -28,8 +31,56 @@ fn set_db_file(_path: &str) {
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// 错误输出格式，json 便于脚本按错误类别处理
+    #[arg(long, value_enum, global = true, default_value_t = ErrorFormat::Text)]
+    error_format: ErrorFormat,
+    /// 配置文件路径，指定后不再读取用户配置和项目配置（也可用环境变量 WEI_ASSISTANT_CONFIG）
+    #[arg(long, global = true, value_name = "PATH")]
+    config: Option<PathBuf>,
+    /// 数据库文件路径，优先于 --profile、环境变量 DB_FILE 和配置文件
+    #[arg(long, global = true, value_name = "PATH")]
+    db: Option<String>,
+    /// 使用配置文件中定义的 profile（命名数据库），也可用环境变量 WEI_ASSISTANT_PROFILE
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
+    /// 从文件读取加密数据库的口令，也可用环境变量 WEI_ASSISTANT_PASSPHRASE
+    #[arg(long, global = true, value_name = "PATH")]
+    passphrase_file: Option<PathBuf>,
+    /// 登录用户名，也可用环境变量 WEI_ASSISTANT_USER
+    #[arg(long, global = true, value_name = "NAME")]
+    user: Option<String>,
+    /// 从文件读取登录口令，也可用环境变量 WEI_ASSISTANT_PASSWORD
+    #[arg(long, global = true, value_name = "PATH")]
+    password_file: Option<PathBuf>,
+    /// 从文件读取登录令牌，也可用环境变量 WEI_ASSISTANT_TOKEN
+    #[arg(long, global = true, value_name = "PATH")]
+    token_file: Option<PathBuf>,
 }
 
+impl Cli {
+    fn overrides(&self) -> Overrides<'_> {
+        Overrides {
+            config: self.config.as_deref(),
+            db: self.db.as_deref(),
+            profile: self.profile.as_deref(),
+            passphrase_file: self.passphrase_file.as_deref(),
+            user: self.user.as_deref(),
+            password_file: self.password_file.as_deref(),
+            token_file: self.token_file.as_deref(),
+        }
+    }
+}
+
+/// 错误输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ErrorFormat {
+    Text,
+    /// 单行JSON：{"error": {"kind", "code", "message"}}
+    Json,
+}
+
+// 只在启动时解析一次，变体大小差异无关紧要
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, Debug)]
 enum Commands {
     /// 添加供应商信息（支持JSON整体或参数模式）
@@ -38,743 +89,196 @@ enum Commands {
         #[arg(long)]
         json: Option<String>,
         /// 传统参数输入
+        #[command(flatten)]
+        fields: SupplierArgs,
+    },
+    /// 查询所有供应商信息，可按字段筛选
+    Query(QueryArgs),
+    /// 从Excel或CSV文件批量导入供应商信息
+    Import(ImportArgs),
+    /// 修改供应商信息，仅更新提供的字段
+    Update {
+        /// 供应商ID
         #[arg(long)]
-        contact: Option<String>,
-        #[arg(long)]
-        wechat: Option<String>,
-        #[arg(long)]
-        phone: Option<String>,
-        #[arg(long)]
-        quantity: Option<i32>,
+        id: i64,
+        #[command(flatten)]
+        fields: SupplierArgs,
+    },
+    /// 删除供应商
+    Delete {
+        /// 供应商ID
         #[arg(long)]
-        location: Option<String>,
+        id: i64,
+    },
+    /// 管理地点和服务器名的同义词
+    Synonym {
+        #[command(subcommand)]
+        action: SynonymAction,
+    },
+    /// 保存常用查询为命名视图，并按名称重复执行
+    View {
+        #[command(subcommand)]
+        action: ViewAction,
+    },
+    /// 查看和修改配置文件
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// 管理 profile（命名数据库），如按业务线或地区分开的供应商库
+    Profile {
+        #[command(subcommand)]
+        action: ProfileAction,
+    },
+    /// 管理本地用户和角色（admin、editor、viewer）
+    User {
+        #[command(subcommand)]
+        action: UserAction,
+    },
+    /// 查看审计日志，如查看未脱敏个人信息的记录
+    Audit {
+        /// 显示最近的条数
+        #[arg(long, default_value_t = 50)]
+        limit: usize,
+        /// 以JSON格式输出
         #[arg(long)]
-        price: Option<f64>,
-        #[arg(long, name = "bandwidth-price")]
-        bandwidth_price: Option<f64>,
-        #[arg(long, name = "storage-price")]
-        storage_price: Option<f64>,
-        #[arg(long, name = "min-contract-period")]
-        min_contract_period: Option<String>,
-        #[arg(long, name = "breach-penalties")]
-        breach_penalties: Option<String>,
-        #[arg(long, name = "payment-terms")]
-        payment_terms: Option<String>,
-        #[arg(long, name = "server-name")]
-        server_name: Option<String>,
-        #[arg(long, name = "server-config")]
-        server_config: Option<String>,
-        #[arg(long, name = "rental-model")]
-        rental_model: Option<String>,
-        #[arg(long, name = "networking-category")]
-        networking_category: Option<String>,
+        json: bool,
+    },
+    /// 对外报价目录：以报价编号代替供应商，不含联系方式，可加价
+    Catalog {
+        #[command(subcommand)]
+        action: CatalogAction,
+    },
+    /// 管理定价规则：按地点、组网类型和租赁模式加价
+    Pricing {
+        #[command(subcommand)]
+        action: PricingAction,
+    },
+    /// 按定价规则为客户报价，对比成本、售价和毛利
+    Quote {
+        #[command(subcommand)]
+        action: QuoteAction,
+    },
+    /// 管理与供应商签订的合同：签约、续约、提前终止
+    Contract {
+        #[command(subcommand)]
+        action: ContractAction,
+    },
+    /// 付款计划：按合同的付款方式生成应付款，查看到期款项和未付余额
+    Payments {
+        #[command(subcommand)]
+        action: PaymentsAction,
+    },
+    /// 到期提醒：检查即将到期的合同、过期的报价单和库存偏低的供应商并发送通知
+    Notify {
+        #[command(subcommand)]
+        action: NotifyAction,
+    },
+    /// 供应商评分：按交付、稳定性、支持和价格诚信打分，综合评分显示在 query 结果中
+    Rating {
+        #[command(subcommand)]
+        action: RatingAction,
+    },
+    /// 供应商事故记录：最近一年内的事故从综合评分中扣分
+    Incident {
+        #[command(subcommand)]
+        action: IncidentAction,
+    },
+    /// 数据库维护：备份、恢复、完整性检查和自动快照
+    Db {
+        #[command(subcommand)]
+        action: DbAction,
     },
-    /// 查询所有供应商信息，可按字段筛选
-    Query(QueryArgs),
-}
-
-#[derive(Args, Debug, Default)]
-struct QueryArgs {
-    #[arg(long)]
-    contact: Option<String>,
-    #[arg(long)]
-    wechat: Option<String>,
-    #[arg(long)]
-    phone: Option<String>,
-    #[arg(long)]
-    quantity: Option<i32>,
-    #[arg(long)]
-    location: Option<String>,
-    #[arg(long)]
-    price: Option<f64>,
-    #[arg(long, name = "bandwidth-price")]
-    bandwidth_price: Option<f64>,
-    #[arg(long, name = "storage-price")]
-    storage_price: Option<f64>,
-    #[arg(long, name = "min-contract-period")]
-    min_contract_period: Option<String>,
-    #[arg(long, name = "breach-penalties")]
-    breach_penalties: Option<String>,
-    #[arg(long, name = "payment-terms")]
-    payment_terms: Option<String>,
-    #[arg(long, name = "server-name")]
-    server_name: Option<String>,
-    #[arg(long, name = "server-config")]
-    server_config: Option<String>,
-    #[arg(long, name = "rental-model")]
-    rental_model: Option<String>,
-    #[arg(long, name = "networking-category")]
-    networking_category: Option<String>,
-    /// 导出为CSV文件（可选）
-    #[arg(long, name = "export-csv")]
-    export_csv: Option<String>,
-    /// 以JSON格式输出（可选）
-    #[arg(long)]
-    json: bool,
-}
-
-#[derive(Debug, Deserialize)]
-struct Supplier {
-    contact: Option<String>,
-    wechat: Option<String>,
-    phone: Option<String>,
-    quantity: Option<i32>,
-    location: Option<String>,
-    price: Option<f64>,
-    bandwidth_price: Option<f64>,
-    storage_price: Option<f64>,
-    min_contract_period: Option<String>,
-    breach_penalties: Option<String>,
-    payment_terms: Option<String>,
-    server_name: Option<String>,
-    server_config: Option<String>,
-    rental_model: Option<String>,
-    networking_category: Option<String>,
-}
-
-#[derive(Debug, Serialize)]
-pub struct SupplierRow {
-    id: i32,
-    contact: Option<String>,
-    wechat: Option<String>,
-    phone: Option<String>,
-    quantity: Option<i32>,
-    location: Option<String>,
-    price: Option<f64>,
-    bandwidth_price: Option<f64>,
-    storage_price: Option<f64>,
-    min_contract_period: Option<String>,
-    breach_penalties: Option<String>,
-    payment_terms: Option<String>,
-    server_name: Option<String>,
-    server_config: Option<String>,
-    rental_model: Option<String>,
-    networking_category: Option<String>,
-}
-
-/// 字段名枚举，防止拼写错误
-#[derive(Debug, Clone)]
-pub enum SupplierField {
-    ContactPerson,
-    Wechat,
-    Phone,
-    Quantity,
-    Location,
-    Price,
-    BandwidthPrice,
-    StoragePrice,
-    MinContractPeriod,
-    BreachPenalties,
-    PaymentTerms,
-    ServerName,
-    ServerConfig,
-    RentalModel,
-    NetworkingCategory,
-}
-
-/// 比较操作符
-#[derive(Debug, Clone)]
-pub enum ComparisonOp {
-    Eq,         // =
-    Neq,        // !=
-    Gt,         // >
-    Lt,         // <
-    Gte,        // >=
-    Lte,        // <=
-    Like,       // LIKE
-    IsNull,     // IS NULL
-    IsNotNull,  // IS NOT NULL
-}
-
-/// 单个筛选条件
-#[derive(Debug, Clone)]
-pub struct FilterCriteria {
-    pub field: SupplierField,
-    pub op: ComparisonOp,
-    pub value: Option<String>, // IS NULL/IS NOT NULL 时为 None
-}
-
-/// 查询构建器主结构
-pub struct QueryBuilder {
-    filters: Vec<FilterCriteria>,
-}
-
-impl QueryBuilder {
-    pub fn new() -> Self {
-        Self { filters: vec![] }
-    }
-
-    pub fn filter(mut self, criteria: FilterCriteria) -> Self {
-        self.filters.push(criteria);
-        self
-    }
-
-    /// 校验筛选条件组合的有效性和安全性
-    pub fn validate(&self) -> Result<(), String> {
-        use std::collections::HashSet;
-        let mut fields = HashSet::new();
-        if self.filters.len() > 20 {
-            return Err("筛选条件过多，最多支持20个条件".to_string());
-        }
-        for f in &self.filters {
-            // 验证字段和操作符的兼容性
-            let valid = match f.op {
-                ComparisonOp::Like => f.field.is_string(),
-                ComparisonOp::Gt | ComparisonOp::Lt | ComparisonOp::Gte | ComparisonOp::Lte => f.field.is_numeric(),
-                _ => true, // =、!=、IS NULL、IS NOT NULL 适用于所有类型
-            };
-            if !valid {
-                return Err(format!("字段 '{:?}' 不支持操作符 '{:?}'.", f.field, f.op));
-            }
-        
-            // 检查重复字段
-            let key = format!("{:?}-{:?}", f.field, f.op);
-            if !fields.insert(key) {
-                return Err("存在重复字段和操作符的筛选条件".to_string());
-            }
-            // 检查参数长度
-            if let Some(val) = &f.value {
-                if val.len() > 256 {
-                    return Err(format!("参数过长: {}...", &val[..32.min(val.len())]));
-                }
-            }
-        }
-        Ok(())
-    }
-
-    /// 构建 WHERE 子句和参数列表
-    pub fn build(self) -> (String, Vec<String>) {
-        self.validate().expect("筛选条件组合不合法");
-        let mut clauses = Vec::new();
-        let mut params = Vec::new();
-        for f in self.filters {
-            let field = match f.field {
-                SupplierField::ContactPerson => "contact",
-                SupplierField::Wechat => "wechat",
-                SupplierField::Phone => "phone",
-                SupplierField::Quantity => "quantity",
-                SupplierField::Location => "location",
-                SupplierField::Price => "price",
-                SupplierField::BandwidthPrice => "bandwidth_price",
-                SupplierField::StoragePrice => "storage_price",
-                SupplierField::MinContractPeriod => "min_contract_period",
-                SupplierField::BreachPenalties => "breach_penalties",
-                SupplierField::PaymentTerms => "payment_terms",
-                SupplierField::ServerName => "server_name",
-                SupplierField::ServerConfig => "server_config",
-                SupplierField::RentalModel => "rental_model",
-                SupplierField::NetworkingCategory => "networking_category",
-            };
-            match f.op {
-                ComparisonOp::Eq => {
-                    clauses.push(format!("{} = ?", field));
-                    if let Some(val) = f.value { params.push(val); }
-                }
-                ComparisonOp::Neq => {
-                    clauses.push(format!("{} != ?", field));
-                    if let Some(val) = f.value { params.push(val); }
-                }
-                ComparisonOp::Gt => {
-                    clauses.push(format!("{} > ?", field));
-                    if let Some(val) = f.value { params.push(val); }
-                }
-                ComparisonOp::Lt => {
-                    clauses.push(format!("{} < ?", field));
-                    if let Some(val) = f.value { params.push(val); }
-                }
-                ComparisonOp::Gte => {
-                    clauses.push(format!("{} >= ?", field));
-                    if let Some(val) = f.value { params.push(val); }
-                }
-                ComparisonOp::Lte => {
-                    clauses.push(format!("{} <= ?", field));
-                    if let Some(val) = f.value { params.push(val); }
-                }
-                ComparisonOp::Like => {
-                    clauses.push(format!("{} LIKE ?", field));
-                    if let Some(val) = f.value { params.push(val); }
-                }
-                ComparisonOp::IsNull => {
-                    clauses.push(format!("{} IS NULL", field));
-                }
-                ComparisonOp::IsNotNull => {
-                    clauses.push(format!("{} IS NOT NULL", field));
-                }
-            };
-        }
-        let where_sql = if clauses.is_empty() {
-            String::new()
-        } else {
-            format!("WHERE {}", clauses.join(" AND "))
-        };
-        (where_sql, params)
-    }
-}
-
-impl FromStr for SupplierField {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "contact" => Ok(SupplierField::ContactPerson),
-            "wechat" => Ok(SupplierField::Wechat),
-            "phone" => Ok(SupplierField::Phone),
-            "quantity" => Ok(SupplierField::Quantity),
-            "location" => Ok(SupplierField::Location),
-            "price" => Ok(SupplierField::Price),
-            "bandwidth_price" => Ok(SupplierField::BandwidthPrice),
-            "storage_price" => Ok(SupplierField::StoragePrice),
-            "min_contract_period" => Ok(SupplierField::MinContractPeriod),
-            "breach_penalties" => Ok(SupplierField::BreachPenalties),
-            "payment_terms" => Ok(SupplierField::PaymentTerms),
-            "server_name" => Ok(SupplierField::ServerName),
-            "server_config" => Ok(SupplierField::ServerConfig),
-            "rental_model" => Ok(SupplierField::RentalModel),
-            "networking_category" => Ok(SupplierField::NetworkingCategory),
-            _ => Err(format!("未知字段名: {}", s)),
-        }
-    }
-}
-
-impl FromStr for ComparisonOp {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "=" | "eq" => Ok(ComparisonOp::Eq),
-            "!=" | "<>" | "neq" => Ok(ComparisonOp::Neq),
-            ">" | "gt" => Ok(ComparisonOp::Gt),
-            "<" | "lt" => Ok(ComparisonOp::Lt),
-            ">=" | "gte" => Ok(ComparisonOp::Gte),
-            "<=" | "lte" => Ok(ComparisonOp::Lte),
-            "like" => Ok(ComparisonOp::Like),
-            "is null" | "isnull" => Ok(ComparisonOp::IsNull),
-            "is not null" | "isnotnull" => Ok(ComparisonOp::IsNotNull),
-            _ => Err(format!("未知操作符: {}", s)),
-        }
-    }
-}
-
-impl SupplierField {
-    pub fn is_string(&self) -> bool {
-        matches!(self,
-            SupplierField::ContactPerson |
-            SupplierField::Wechat |
-            SupplierField::Phone |
-            SupplierField::Location |
-            SupplierField::MinContractPeriod |
-            SupplierField::BreachPenalties |
-            SupplierField::PaymentTerms |
-            SupplierField::ServerName |
-            SupplierField::ServerConfig |
-            SupplierField::RentalModel |
-            SupplierField::NetworkingCategory
-        )
-    }
-    pub fn is_numeric(&self) -> bool {
-        matches!(self,
-            SupplierField::Quantity |
-            SupplierField::Price |
-            SupplierField::BandwidthPrice |
-            SupplierField::StoragePrice
-        )
-    }
 }
 
-impl FilterCriteria {
-    /// 从 (字段名, 操作符, 值) 解析为 FilterCriteria，并校验类型兼容性
-    pub fn from_str_tuple(field: &str, op: &str, value: Option<&str>) -> Result<Self, String> {
-        let field_enum = SupplierField::from_str(field)?;
-        let op_enum = ComparisonOp::from_str(op)?;
-        // 类型与操作符兼容性校验
-        let valid = match op_enum {
-            ComparisonOp::Like => field_enum.is_string(),
-            ComparisonOp::Gt | ComparisonOp::Lt | ComparisonOp::Gte | ComparisonOp::Lte => field_enum.is_numeric(),
-            _ => true, // =、!=、IS NULL、IS NOT NULL 适用于所有类型
-        };
-        if !valid {
-            return Err(format!("字段 '{}' 不支持操作符 '{}'.", field, op));
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(&cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            report_error(&e, cli.error_format);
+            ExitCode::from(e.exit_code())
         }
-        let val = match op_enum {
-            ComparisonOp::IsNull | ComparisonOp::IsNotNull => None,
-            _ => value.map(|v| v.to_string()),
-        };
-        Ok(FilterCriteria {
-            field: field_enum,
-            op: op_enum,
-            value: val,
-        })
     }
 }
 
-/// 表格格式化输出
-pub fn print_suppliers_table_v2(rows: &[SupplierRow]) {
-    use std::cmp::max;
-    let headers = [
-        "ID", "联系人", "微信", "手机", "数量", "地点", "价格", "带宽价", "存储价", "合同期", "违约金", "付款", "服务器名", "配置", "租赁", "组网"
-    ];
-    let mut col_widths = vec![2; headers.len()];
-    let mut data: Vec<Vec<String>> = Vec::new();
-    for s in rows {
-        let row = vec![
-            s.id.to_string(),
-            s.contact.as_deref().unwrap_or("").to_string(),
-            s.wechat.as_deref().unwrap_or("").to_string(),
-            s.phone.as_deref().unwrap_or("").to_string(),
-            s.quantity.map(|v| v.to_string()).unwrap_or_default(),
-            s.location.as_deref().unwrap_or("").to_string(),
-            s.price.map(|v| format!("{:.2}", v)).unwrap_or_default(),
-            s.bandwidth_price.map(|v| format!("{:.2}", v)).unwrap_or_default(),
-            s.storage_price.map(|v| format!("{:.2}", v)).unwrap_or_default(),
-            s.min_contract_period.as_deref().unwrap_or("").to_string(),
-            s.breach_penalties.as_deref().unwrap_or("").to_string(),
-            s.payment_terms.as_deref().unwrap_or("").to_string(),
-            s.server_name.as_deref().unwrap_or("").to_string(),
-            s.server_config.as_deref().unwrap_or("").to_string(),
-            s.rental_model.as_deref().unwrap_or("").to_string(),
-            s.networking_category.as_deref().unwrap_or("").to_string(),
-        ];
-        for (i, cell) in row.iter().enumerate() {
-            col_widths[i] = max(col_widths[i], cell.chars().count());
-        }
-        data.push(row);
-    }
-    // 打印表头
-    for (i, h) in headers.iter().enumerate() {
-        print!("{:<width$} ", h, width = col_widths[i]);
-    }
-    println!();
-    // 打印分隔线
-    for w in &col_widths {
-        print!("{:-<width$}-", "", width = *w);
-    }
-    println!();
-    // 打印数据
-    for row in &data {
-        for (i, cell) in row.iter().enumerate() {
-            print!("{:<width$} ", cell, width = col_widths[i]);
+/// 按指定格式将错误输出到标准错误
+fn report_error(e: &Error, format: ErrorFormat) {
+    match format {
+        ErrorFormat::Text => eprintln!("错误: {}", e),
+        ErrorFormat::Json => {
+            let body = serde_json::json!({
+                "error": {
+                    "kind": e.kind(),
+                    "code": e.exit_code(),
+                    "message": e.to_string(),
+                }
+            });
+            eprintln!("{}", body);
         }
-        println!();
-    }
-    if data.is_empty() {
-        println!("无供应商信息。");
     }
 }
 
-/// JSON格式化输出
-pub fn print_suppliers_json(rows: &[SupplierRow]) {
-    match serde_json::to_string_pretty(rows) {
-        Ok(json) => println!("{}", json),
-        Err(e) => eprintln!("JSON序列化错误: {}", e),
+/// 执行命令需要的最低角色；用户管理在 run_user 中单独检查
+fn required_role(command: &Commands) -> Role {
+    match command {
+        Commands::Add { .. } | Commands::Update { .. } | Commands::Delete { .. } => Role::Editor,
+        Commands::Import(args) if !args.dry_run => Role::Editor,
+        Commands::Synonym { action: SynonymAction::Add { .. } } => Role::Editor,
+        Commands::View { action: ViewAction::Save { .. } | ViewAction::Delete { .. } } => Role::Editor,
+        Commands::Db { action: DbAction::Snapshots } => Role::Editor,
+        // 首次发布会为供应商分配并保存报价编号，同样是写操作
+        Commands::Catalog { .. } => Role::Editor,
+        Commands::Quote { action: QuoteAction::Template { .. } } => Role::Viewer,
+        Commands::Rating { action: RatingAction::Set { .. } } | Commands::Incident { action: IncidentAction::Add { .. } | IncidentAction::Remove { .. } } => Role::Editor,
+        Commands::Pricing { action: PricingAction::List { .. } } | Commands::Quote { .. } | Commands::Contract { .. } | Commands::Payments { .. } | Commands::Notify { .. } => Role::Editor,
+        Commands::Pricing { .. } => Role::Admin,
+        Commands::Db { action: DbAction::IntegrityCheck } => Role::Viewer,
+        Commands::Db { .. } | Commands::Audit { .. } => Role::Admin,
+        _ => Role::Viewer,
     }
 }
-
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+fn run(cli: &Cli) -> Result<()> {
     match &cli.command {
-        Commands::Add { json, contact, wechat, phone, quantity, location, price, bandwidth_price, storage_price, min_contract_period, breach_penalties, payment_terms, server_name, server_config, rental_model, networking_category } => {
-            // 初始化数据库
-            init_db()?;
-            
-            // 处理JSON模式
-            if let Some(json_str) = json {
-                match serde_json::from_str::<Supplier>(json_str) {
-                    Ok(supplier) => {
-                        match insert_supplier(&supplier) {
-                            Ok(_) => println!("供应商信息添加成功！"),
-                            Err(e) => {
-                                eprintln!("添加失败: {}", e);
-                                return Err(e);
-                            }
-                        }
-                    },
-                    Err(e) => {
-                        eprintln!("JSON解析失败: {}", e);
-                        return Err(rusqlite::Error::InvalidParameterName(e.to_string()));
-                    }
-                }
-            } else {
-                // 处理字段模式
-                if contact.is_none() {
-                    eprintln!("错误：至少需要提供联系人字段！");
-                    return Err(rusqlite::Error::InvalidParameterName("缺少必填字段".to_string()));
-                }
-                
-                let supplier = Supplier {
-                    contact: contact.clone(),
-                    wechat: wechat.clone(),
-                    phone: phone.clone(),
-                    quantity: *quantity,
-                    location: location.clone(),
-                    price: *price,
-                    bandwidth_price: *bandwidth_price,
-                    storage_price: *storage_price,
-                    min_contract_period: min_contract_period.clone(),
-                    breach_penalties: breach_penalties.clone(),
-                    payment_terms: payment_terms.clone(),
-                    server_name: server_name.clone(),
-                    server_config: server_config.clone(),
-                    rental_model: rental_model.clone(),
-                    networking_category: networking_category.clone(),
-                };
-                
-                match insert_supplier(&supplier) {
-                    Ok(_) => {
-                        // 成功时不需要输出，保持界面简洁
-                    },
-                    Err(e) => {
-                        eprintln!("添加失败: {}", e);
-                        return Err(e);
-                    }
-                }
-            }
-        },
-        Commands::Query(args) => {
-            // 初始化数据库
-            init_db()?;
-            
-            match query_suppliers_with_filter(args) {
-                Ok(rows) => {
-                    if rows.is_empty() {
-                        println!("没有找到符合条件的供应商");
-                    } else {
-                        // 根据需要使用不同的输出格式
-                        if args.json {
-                            print_suppliers_json(&rows);
-                        } else {
-                            print_suppliers_table_v2(&rows);
-                        }
-                        
-                        // 如果需要导出CSV
-                        if let Some(csv_path) = &args.export_csv {
-                            match export_suppliers_to_csv(&rows, csv_path) {
-                                Ok(_) => println!("已导出 {} 条记录到 {}", rows.len(), csv_path),
-                                Err(e) => eprintln!("导出CSV失败: {}", e),
-                            }
-                        }
-                    }
-                },
-                Err(e) => {
-                    eprintln!("查询错误: {}", e);
-                    return Err(e);
-                }
-            }
-        },
-    }
-    Ok(())
-}
-
-fn insert_supplier(s: &Supplier) -> Result<()> {
-    let conn = Connection::open(get_db_file())?;
-    let sql = r#"
-        INSERT INTO suppliers (
-            contact, wechat, phone, quantity, location, price, bandwidth_price, storage_price, min_contract_period, breach_penalties, payment_terms, server_name, server_config, rental_model, networking_category
-        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
-    "#;
-    conn.execute(sql, [
-        s.contact.as_deref(),
-        s.wechat.as_deref(),
-        s.phone.as_deref(),
-        s.quantity.map(|v| v.to_string()).as_deref(),
-        s.location.as_deref(),
-        s.price.map(|v| v.to_string()).as_deref(),
-        s.bandwidth_price.map(|v| v.to_string()).as_deref(),
-        s.storage_price.map(|v| v.to_string()).as_deref(),
-        s.min_contract_period.as_deref(),
-        s.breach_penalties.as_deref(),
-        s.payment_terms.as_deref(),
-        s.server_name.as_deref(),
-        s.server_config.as_deref(),
-        s.rental_model.as_deref(),
-        s.networking_category.as_deref(),
-    ])?;
-    Ok(())
-}
-
-fn query_suppliers_with_filter(args: &QueryArgs) -> Result<Vec<SupplierRow>> {
-    let conn = Connection::open(get_db_file())?;
-    
-    // 确保数据库表存在
-    init_db()?;
-    
-    let mut conditions = Vec::new();
-    let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
-
-    // 注意：参数名可能使用的是破折号格式 (bandwidth-price)，但数据库中是下划线格式 (bandwidth_price)
-    // 确保两者匹配
-    if let Some(val) = &args.contact {
-        conditions.push("contact = ?");
-        params.push(Box::new(val.clone()));
-    }
-    if let Some(val) = &args.wechat {
-        conditions.push("wechat = ?");
-        params.push(Box::new(val.clone()));
-    }
-    if let Some(val) = &args.phone {
-        conditions.push("phone = ?");
-        params.push(Box::new(val.clone()));
-    }
-    if let Some(val) = &args.quantity {
-        conditions.push("quantity = ?");
-        params.push(Box::new(*val));
-    }
-    if let Some(val) = &args.location {
-        conditions.push("location = ?");
-        params.push(Box::new(val.clone()));
-    }
-    if let Some(val) = &args.price {
-        conditions.push("price = ?");
-        params.push(Box::new(*val));
-    }
-    if let Some(val) = &args.bandwidth_price {
-        conditions.push("bandwidth_price = ?");
-        params.push(Box::new(*val));
-    }
-    if let Some(val) = &args.storage_price {
-        conditions.push("storage_price = ?");
-        params.push(Box::new(*val));
-    }
-    if let Some(val) = &args.min_contract_period {
-        conditions.push("min_contract_period = ?");
-        params.push(Box::new(val.clone()));
-    }
-    if let Some(val) = &args.breach_penalties {
-        conditions.push("breach_penalties = ?");
-        params.push(Box::new(val.clone()));
-    }
-    if let Some(val) = &args.payment_terms {
-        conditions.push("payment_terms = ?");
-        params.push(Box::new(val.clone()));
-    }
-    if let Some(val) = &args.server_name {
-        conditions.push("server_name = ?");
-        params.push(Box::new(val.clone()));
-    }
-    if let Some(val) = &args.server_config {
-        conditions.push("server_config = ?");
-        params.push(Box::new(val.clone()));
-    }
-    if let Some(val) = &args.rental_model {
-        conditions.push("rental_model = ?");
-        params.push(Box::new(val.clone()));
-    }
-    if let Some(val) = &args.networking_category {
-        conditions.push("networking_category = ?");
-        params.push(Box::new(val.clone()));
-    }
-
-    let where_clause = if conditions.is_empty() {
-        String::new()
-    } else {
-        format!("WHERE {}", conditions.join(" AND "))
-    };
-
-    let sql = format!(
-        r#"
-        SELECT id, contact, wechat, phone, quantity, location, price, bandwidth_price, storage_price, min_contract_period, breach_penalties, payment_terms, server_name, server_config, rental_model, networking_category
-        FROM suppliers
-        {}
-        "#,
-        where_clause
-    );
-
-    let mut stmt = conn.prepare(&sql)?;
-    
-    let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
-    let rows = stmt.query(param_refs.as_slice())?;
-
-    let supplier_rows = rows.mapped(|row| {
-        Ok(SupplierRow {
-            id: row.get(0)?,
-            contact: row.get(1)?,
-            wechat: row.get(2)?,
-            phone: row.get(3)?,
-            quantity: row.get(4)?,
-            location: row.get(5)?,
-            price: row.get(6)?,
-            bandwidth_price: row.get(7)?,
-            storage_price: row.get(8)?,
-            min_contract_period: row.get(9)?,
-            breach_penalties: row.get(10)?,
-            payment_terms: row.get(11)?,
-            server_name: row.get(12)?,
-            server_config: row.get(13)?,
-            rental_model: row.get(14)?,
-            networking_category: row.get(15)?,
-        })
-    }).collect::<Result<Vec<_>, rusqlite::Error>>()?;
-    
-    Ok(supplier_rows)
-}
-
-fn init_db() -> Result<()> {
-    let conn = Connection::open(get_db_file())?;
-    // 建表SQL，字段注释以SQL注释形式写在建表语句中
-    let sql = r#"
--- 供应商表结构定义
-CREATE TABLE IF NOT EXISTS suppliers (
-    id INTEGER PRIMARY KEY AUTOINCREMENT, -- 主键，自增
-    contact TEXT NOT NULL,               -- 联系人
-    wechat TEXT,                         -- 微信
-    phone TEXT,                          -- 电话
-    quantity INTEGER,                    -- 数量
-    location TEXT,                       -- 地点
-    price REAL,                          -- 价格
-    bandwidth_price REAL,                -- 带宽价格
-    storage_price REAL,                  -- 存储价格
-    min_contract_period TEXT,            -- 最短合同期
-    breach_penalties TEXT,               -- 违约金
-    payment_terms TEXT,                  -- 付款方式
-    server_name TEXT,                    -- 服务器名称
-    server_config TEXT,                  -- 服务器配置
-    rental_model TEXT,                   -- 租赁模式
-    networking_category TEXT             -- 网络类型
-);
-"#;
-    conn.execute_batch(sql)?;
-    Ok(())
-}
-
-fn export_suppliers_to_csv(rows: &[SupplierRow], path: &str) -> std::io::Result<()> {
-    let file = File::create(path)?;
-    let mut wtr = csv::WriterBuilder::new()
-        .has_headers(true)
-        .from_writer(file);
-    
-    // 写入表头
-    wtr.write_record(&[
-        "ID", "联系人", "微信", "手机", "数量", "位置", "价格",
-        "带宽价", "存储价", "签约周期", "违约", "付款", "服务器名",
-        "配置", "租赁", "组网"
-    ])?;
-    
-    // 写入数据行
-    for s in rows {
-        wtr.write_record(&[
-            &s.id.to_string(),
-            s.contact.as_deref().unwrap_or(""),
-            s.wechat.as_deref().unwrap_or(""),
-            s.phone.as_deref().unwrap_or(""),
-            &s.quantity.map(|v| v.to_string()).unwrap_or_default(),
-            s.location.as_deref().unwrap_or(""),
-            &s.price.map(|v| v.to_string()).unwrap_or_default(),
-            &s.bandwidth_price.map(|v| v.to_string()).unwrap_or_default(),
-            &s.storage_price.map(|v| v.to_string()).unwrap_or_default(),
-            s.min_contract_period.as_deref().unwrap_or(""),
-            s.breach_penalties.as_deref().unwrap_or(""),
-            s.payment_terms.as_deref().unwrap_or(""),
-            s.server_name.as_deref().unwrap_or(""),
-            s.server_config.as_deref().unwrap_or(""),
-            s.rental_model.as_deref().unwrap_or(""),
-            s.networking_category.as_deref().unwrap_or("")
-        ])?;
+        Commands::Config { action } => return run_config(cli.overrides(), action),
+        Commands::Profile { action } => return run_profile(cli.overrides(), action),
+        _ => {}
+    }
+    let settings = Settings::load(cli.overrides())?;
+    // 整个命令只打开一次数据库，认证和各子命令共用同一个连接
+    let mut store = settings.open_store()?;
+    let session = settings.credentials.authenticate(&store)?;
+    session.require(required_role(&cli.command))?;
+    match &cli.command {
+        Commands::Add { json, fields } => run_add(&store, json.as_deref(), fields)?,
+        Commands::Query(args) => run_query(&settings, &store, &session, args, None)?,
+        Commands::Import(args) => run_import(&settings, &store, args)?,
+        Commands::Update { id, fields } => run_update(&store, *id, fields)?,
+        Commands::Delete { id } => run_delete(&settings, &store, *id)?,
+        Commands::Synonym { action } => run_synonym(&store, action)?,
+        Commands::View { action } => run_view(&settings, &store, &session, action)?,
+        Commands::Catalog { action } => run_catalog(&settings, &store, &session, action)?,
+        Commands::Contract { action } => run_contract(&store, action)?,
+        Commands::Payments { action } => run_payments(&store, action)?,
+        Commands::Notify { action } => run_notify(&settings, &store, action)?,
+        Commands::Rating { action } => run_rating(&store, action)?,
+        Commands::Incident { action } => run_incident(&store, action)?,
+        Commands::Pricing { action } => run_pricing(&store, action)?,
+        Commands::Quote { action } => run_quote(&settings, &store, action)?,
+        Commands::Db { action } => run_db(&settings, &mut store, action)?,
+        Commands::User { action } => run_user(&store, &session, action)?,
+        Commands::Audit { limit, json } => run_audit(&store, *limit, *json)?,
+        Commands::Config { .. } | Commands::Profile { .. } => unreachable!("config 和 profile 子命令已在前面处理"),
     }
-    
-    wtr.flush()?;
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use wei_assistant_gpu::csv_io;
+    use wei_assistant_gpu::{ComparisonOp, FilterCriteria, MatchMode, QueryBuilder, Supplier, SupplierField, SupplierRow, SupplierStore};
+    use rusqlite::Connection;
     use tempfile::NamedTempFile;
     use std::sync::Mutex;
     use once_cell::sync::Lazy;
@@ -913,6 +417,8 @@ mod tests {
                 server_config: row.get(13)?,
                 rental_model: row.get(14)?,
                 networking_category: row.get(15)?,
+                profile: None,
+                score: None,
             })
         }).unwrap();
         
@@ -1047,7 +553,7 @@ mod tests {
         let query_sql = "SELECT contact FROM suppliers WHERE contact = ?";
         let mut stmt = conn.prepare(query_sql).unwrap();
         let result = stmt.query_map(["张三"], |row| {
-            Ok(row.get::<_, String>(0)?)
+            row.get::<_, String>(0)
         }).unwrap().map(|r| r.unwrap()).collect::<Vec<_>>();
 
         assert_eq!(result.len(), 1);
@@ -1157,7 +663,7 @@ mod tests {
             
         assert!(builder.validate().is_ok());
         
-        let (query, params) = builder.build();
+        let (query, params) = builder.build().unwrap();
         assert!(query.contains("WHERE"));
         assert!(query.contains("contact = ?"));
         assert!(query.contains("quantity > ?"));
@@ -1175,6 +681,7 @@ mod tests {
             field: SupplierField::Quantity,
             op: ComparisonOp::Like,
             value: Some("10".to_string()),
+            mode: MatchMode::Exact,
         };
         
         let builder = QueryBuilder::new().filter(invalid_criteria);
@@ -1183,13 +690,24 @@ mod tests {
         assert!(validation_result.is_err());
         let err_msg = validation_result.unwrap_err();
         assert!(err_msg.contains("Quantity") && err_msg.contains("Like"));
+
+        // build 对不合法条件返回校验错误而不是 panic
+        let builder = QueryBuilder::new().filter(FilterCriteria {
+            field: SupplierField::Price,
+            op: ComparisonOp::Like,
+            value: Some("1%".to_string()),
+            mode: MatchMode::Exact,
+        });
+        let err = builder.build().unwrap_err();
+        assert!(matches!(err, Error::Validation(_)));
+        assert_eq!(err.exit_code(), 3);
     }
     
     // 测试JSON输入解析
     #[test]
     fn test_json_input_parsing() {
         let json_str = r#"{"contact":"张三","wechat":"wxid1","phone":"12345678901","quantity":10}"#;
-        let supplier: Result<Supplier, _> = serde_json::from_str(json_str);
+        let supplier: serde_json::Result<Supplier> = serde_json::from_str(json_str);
         
         assert!(supplier.is_ok());
         let s = supplier.unwrap();
@@ -1202,7 +720,7 @@ mod tests {
     #[test]
     fn test_export_csv() {
         // 重置测试环境
-        let _db_path = setup_test_db();
+        let db_path = setup_test_db();
         
         // 插入一条测试数据
         let supplier = Supplier {
@@ -1223,16 +741,16 @@ mod tests {
             networking_category: Some("BGP".to_string()),
         };
         
-        insert_supplier(&supplier).unwrap();
+        SupplierStore::open(&db_path).unwrap().add(&supplier).unwrap();
         
         // 查询并导出
         let args = QueryArgs::default();
-        let rows = query_suppliers_with_filter(&args).unwrap();
+        let rows = SupplierStore::open(&db_path).unwrap().query(args.to_query().unwrap()).unwrap();
         
         let csv_file = NamedTempFile::new().unwrap();
         let csv_path = csv_file.path().to_str().unwrap();
         
-        let export_result = export_suppliers_to_csv(&rows, csv_path);
+        let export_result = csv_io::export_suppliers_to_csv(&rows, &[], csv_path, &csv_io::CsvOptions::default());
         assert!(export_result.is_ok());
         
         // 检查CSV文件内容
@@ -1241,7 +759,7 @@ mod tests {
         assert!(content.contains("wxid"));
         assert!(content.contains("12345678901"));
     }
-    
+
     // 集成测试：命令行接口测试
     #[test]
     fn test_cli_help() {
@@ -1259,10 +777,10 @@ mod tests {
         // 使用临时文件作为数据库
         let db_file = NamedTempFile::new().unwrap();
         let db_path = db_file.path().to_str().unwrap();
-        std::env::set_var("DB_FILE", &db_path);
+        std::env::set_var("DB_FILE", db_path);
         
         // 初始化数据库
-        init_db().unwrap();
+        SupplierStore::open(db_path).unwrap();
         
         // 添加一个供应商
         let mut cmd = Command::cargo_bin("wei-assistant-gpu").unwrap();
@@ -1296,10 +814,10 @@ mod tests {
         // 使用临时文件作为数据库
         let db_file = NamedTempFile::new().unwrap();
         let db_path = db_file.path().to_str().unwrap();
-        std::env::set_var("DB_FILE", &db_path);
+        std::env::set_var("DB_FILE", db_path);
         
         // 初始化数据库
-        init_db().unwrap();
+        SupplierStore::open(db_path).unwrap();
         
         // JSON格式添加供应商
         let json = r#"{"contact":"JSON测试","wechat":"json-wxid","phone":"98765432101","quantity":30}"#;
@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::model::SupplierField;
+
+/// 文本字段的匹配方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchMode {
+    /// 完全相同（默认）
+    #[default]
+    Exact,
+    /// 忽略大小写、空格和连字符
+    #[value(alias = "ignore-case")]
+    Icase,
+    /// 包含（子串）
+    Contains,
+    /// 前缀
+    Prefix,
+    /// 允许少量拼写差异（编辑距离）
+    Fuzzy,
+}
+
+impl MatchMode {
+    /// 在SQL函数 `text_match` 中使用的名称
+    pub fn name(self) -> &'static str {
+        match self {
+            MatchMode::Exact => "exact",
+            MatchMode::Icase => "icase",
+            MatchMode::Contains => "contains",
+            MatchMode::Prefix => "prefix",
+            MatchMode::Fuzzy => "fuzzy",
+        }
+    }
+}
+
+impl FromStr for MatchMode {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        <MatchMode as ValueEnum>::from_str(s, true).map_err(|_| format!("未知匹配方式: {}", s))
+    }
+}
+
+/// 归一化文本：转小写并去掉空白、`-` 和 `_`，使 "RTX 4090" 与 "rtx4090" 相同
+pub fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-' && *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// 按字符计算编辑距离（Levenshtein）
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+/// 模糊匹配允许的最大编辑距离，随关键字长度增加
+fn fuzzy_threshold(len: usize) -> usize {
+    match len {
+        0..=2 => 0,
+        3..=6 => 1,
+        _ => 2,
+    }
+}
+
+/// 判断数据库中的值是否按指定方式匹配关键字
+pub fn text_matches(mode: MatchMode, value: &str, pattern: &str) -> bool {
+    if mode == MatchMode::Exact {
+        return value == pattern;
+    }
+    let value = normalize(value);
+    let pattern = normalize(pattern);
+    match mode {
+        MatchMode::Exact | MatchMode::Icase => value == pattern,
+        MatchMode::Contains => value.contains(&pattern),
+        MatchMode::Prefix => value.starts_with(&pattern),
+        MatchMode::Fuzzy => edit_distance(&value, &pattern) <= fuzzy_threshold(pattern.chars().count()),
+    }
+}
+
+/// 内置同义词，每组第一个为标准写法
+const BUILTIN_SYNONYMS: &[(SupplierField, &[&str])] = &[
+    (SupplierField::Location, &["北京", "北京市", "Beijing", "BJ", "京"]),
+    (SupplierField::Location, &["上海", "上海市", "Shanghai", "SH", "沪"]),
+    (SupplierField::Location, &["广州", "广州市", "Guangzhou", "GZ"]),
+    (SupplierField::Location, &["深圳", "深圳市", "Shenzhen", "SZ"]),
+    (SupplierField::Location, &["杭州", "杭州市", "Hangzhou", "HZ"]),
+    (SupplierField::Location, &["成都", "成都市", "Chengdu", "CD"]),
+    (SupplierField::Location, &["香港", "Hong Kong", "HK"]),
+    (SupplierField::ServerName, &["RTX 4090", "4090", "GeForce RTX 4090"]),
+    (SupplierField::ServerName, &["A100", "NVIDIA A100", "Tesla A100"]),
+    (SupplierField::ServerName, &["A800", "NVIDIA A800"]),
+    (SupplierField::ServerName, &["H100", "NVIDIA H100"]),
+    (SupplierField::ServerName, &["H800", "NVIDIA H800"]),
+];
+
+/// 同义词表：按地点和服务器名筛选时，关键字会扩展为同组的所有写法，其他写法只按整值（忽略大小写）匹配
+#[derive(Debug, Clone, Default)]
+pub struct Synonyms {
+    /// 字段 → 归一化写法 → 标准写法
+    canonical: HashMap<SupplierField, HashMap<String, String>>,
+    /// 字段 → 标准写法 → 全部写法
+    groups: HashMap<SupplierField, HashMap<String, Vec<String>>>,
+}
+
+impl Synonyms {
+    /// 仅含内置同义词
+    pub fn builtin() -> Self {
+        let mut synonyms = Self::default();
+        for (field, words) in BUILTIN_SYNONYMS {
+            for word in &words[1..] {
+                synonyms.insert(*field, words[0], word);
+            }
+        }
+        synonyms
+    }
+
+    /// 是否对该字段应用同义词
+    pub fn applies_to(field: SupplierField) -> bool {
+        matches!(field, SupplierField::Location | SupplierField::ServerName)
+    }
+
+    /// 添加一条同义词，`alias` 与 `canonical` 视为同一取值
+    pub fn insert(&mut self, field: SupplierField, canonical: &str, alias: &str) {
+        let canon_map = self.canonical.entry(field).or_default();
+        // 若标准写法本身是其他组的别名，并入那一组
+        let canonical = canon_map.get(&normalize(canonical)).cloned().unwrap_or_else(|| canonical.to_string());
+        let group = self.groups.entry(field).or_default().entry(canonical.clone()).or_default();
+        for word in [canonical.as_str(), alias] {
+            if !group.iter().any(|w| w == word) {
+                group.push(word.to_string());
+            }
+            canon_map.insert(normalize(word), canonical.clone());
+        }
+    }
+
+    /// 关键字的全部写法（包括自身）；不在同义词表中时只返回自身
+    pub fn expand(&self, field: SupplierField, value: &str) -> Vec<String> {
+        let group = self.canonical.get(&field)
+            .and_then(|m| m.get(&normalize(value)))
+            .and_then(|canon| self.groups.get(&field)?.get(canon));
+        let mut words = vec![value.to_string()];
+        for word in group.into_iter().flatten() {
+            if !words.contains(word) {
+                words.push(word.clone());
+            }
+        }
+        words
+    }
+
+    /// 按 (字段, 标准写法, 全部写法) 列出所有同义词组
+    pub fn groups(&self) -> Vec<(SupplierField, &str, &[String])> {
+        let mut out: Vec<_> = self.groups.iter()
+            .flat_map(|(field, groups)| groups.iter().map(move |(canon, words)| (*field, canon.as_str(), words.as_slice())))
+            .collect();
+        out.sort_by(|a, b| a.0.column().cmp(b.0.column()).then(a.1.cmp(b.1)));
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize(" RTX-4090_Ti "), "rtx4090ti");
+        assert_eq!(normalize("北京 市"), "北京市");
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("rtx4090", "rtx4080"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("北京", "北京市"), 1);
+        assert_eq!(edit_distance("a100", "a100"), 0);
+    }
+
+    #[test]
+    fn test_exact_is_case_sensitive() {
+        assert!(text_matches(MatchMode::Exact, "A100", "A100"));
+        assert!(!text_matches(MatchMode::Exact, "RTX 4090", "rtx4090"));
+    }
+
+    #[test]
+    fn test_icase_ignores_spacing_and_case() {
+        assert!(text_matches(MatchMode::Icase, "RTX 4090", "rtx4090"));
+        assert!(text_matches(MatchMode::Icase, "A100-80G", "a100 80g"));
+        assert!(!text_matches(MatchMode::Icase, "A100", "A800"));
+    }
+
+    #[test]
+    fn test_contains_and_prefix() {
+        assert!(text_matches(MatchMode::Contains, "北京市朝阳区", "北京"));
+        assert!(text_matches(MatchMode::Prefix, "A100-80G", "a100"));
+        assert!(!text_matches(MatchMode::Prefix, "NVIDIA A100", "a100"));
+    }
+
+    #[test]
+    fn test_fuzzy_threshold_grows_with_length() {
+        assert!(text_matches(MatchMode::Fuzzy, "RTX 4090", "rtx 4009"));
+        assert!(text_matches(MatchMode::Fuzzy, "A800", "A801"));
+        assert!(!text_matches(MatchMode::Fuzzy, "A800", "H100"));
+        // 两个字符以内必须相同
+        assert!(!text_matches(MatchMode::Fuzzy, "SH", "SZ"));
+    }
+
+    #[test]
+    fn test_match_mode_from_str() {
+        assert_eq!("ignore-case".parse::<MatchMode>(), Ok(MatchMode::Icase));
+        assert_eq!("FUZZY".parse::<MatchMode>(), Ok(MatchMode::Fuzzy));
+        assert!("regex".parse::<MatchMode>().is_err());
+    }
+
+    #[test]
+    fn test_expand_builtin_synonyms() {
+        let synonyms = Synonyms::builtin();
+        let words = synonyms.expand(SupplierField::Location, "bj");
+        assert_eq!(words[0], "bj");
+        assert!(words.contains(&"北京".to_string()) && words.contains(&"Beijing".to_string()));
+        assert_eq!(synonyms.expand(SupplierField::Location, "西安"), ["西安"]);
+        assert_eq!(synonyms.expand(SupplierField::Phone, "bj"), ["bj"]);
+    }
+
+    #[test]
+    fn test_insert_alias_of_alias_joins_group() {
+        let mut synonyms = Synonyms::builtin();
+        // “帝都”以别名“京”为标准写法时并入北京一组
+        synonyms.insert(SupplierField::Location, "京", "帝都");
+        let words = synonyms.expand(SupplierField::Location, "帝都");
+        assert!(words.contains(&"北京".to_string()));
+        assert!(synonyms.expand(SupplierField::Location, "北京").contains(&"帝都".to_string()));
+    }
+
+    #[test]
+    fn test_groups_sorted_by_field_and_canonical() {
+        let synonyms = Synonyms::builtin();
+        let groups = synonyms.groups();
+        assert_eq!(groups.len(), BUILTIN_SYNONYMS.len());
+        assert!(groups.windows(2).all(|w| (w[0].0.column(), w[0].1) <= (w[1].0.column(), w[1].1)));
+    }
+}
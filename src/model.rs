@@ -0,0 +1,373 @@
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// 供应商信息，新增时使用；更新时仅覆盖为 `Some` 的字段
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Supplier {
+    pub contact: Option<String>,
+    pub wechat: Option<String>,
+    pub phone: Option<String>,
+    pub quantity: Option<i32>,
+    pub location: Option<String>,
+    pub price: Option<f64>,
+    pub bandwidth_price: Option<f64>,
+    pub storage_price: Option<f64>,
+    pub min_contract_period: Option<String>,
+    pub breach_penalties: Option<String>,
+    pub payment_terms: Option<String>,
+    pub server_name: Option<String>,
+    pub server_config: Option<String>,
+    pub rental_model: Option<String>,
+    pub networking_category: Option<String>,
+}
+
+/// 数据库中的一条供应商记录
+#[derive(Debug, Clone, Serialize)]
+pub struct SupplierRow {
+    pub id: i64,
+    pub contact: Option<String>,
+    pub wechat: Option<String>,
+    pub phone: Option<String>,
+    pub quantity: Option<i32>,
+    pub location: Option<String>,
+    pub price: Option<f64>,
+    pub bandwidth_price: Option<f64>,
+    pub storage_price: Option<f64>,
+    pub min_contract_period: Option<String>,
+    pub breach_penalties: Option<String>,
+    pub payment_terms: Option<String>,
+    pub server_name: Option<String>,
+    pub server_config: Option<String>,
+    pub rental_model: Option<String>,
+    pub networking_category: Option<String>,
+    /// 来源 profile，仅跨 profile 查询的结果中有值
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// 综合评分（见 [`crate::rating`]），没有打分时为 `None`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+}
+
+impl SupplierRow {
+    /// 按指定列比较两条记录，空值排在最前，与 SQLite 升序排序一致
+    pub fn compare(&self, other: &Self, column: Column) -> Ordering {
+        fn text(v: &Option<String>) -> Option<&str> {
+            v.as_deref()
+        }
+        fn float(a: Option<f64>, b: Option<f64>) -> Ordering {
+            match (a, b) {
+                (Some(a), Some(b)) => a.total_cmp(&b),
+                (a, b) => a.is_some().cmp(&b.is_some()),
+            }
+        }
+        let field = match column {
+            Column::Id => return self.id.cmp(&other.id),
+            Column::Profile => return text(&self.profile).cmp(&text(&other.profile)),
+            Column::Score => return float(self.score, other.score),
+            Column::Field(field) => field,
+        };
+        match field {
+            SupplierField::ContactPerson => text(&self.contact).cmp(&text(&other.contact)),
+            SupplierField::Wechat => text(&self.wechat).cmp(&text(&other.wechat)),
+            SupplierField::Phone => text(&self.phone).cmp(&text(&other.phone)),
+            SupplierField::Quantity => self.quantity.cmp(&other.quantity),
+            SupplierField::Location => text(&self.location).cmp(&text(&other.location)),
+            SupplierField::Price => float(self.price, other.price),
+            SupplierField::BandwidthPrice => float(self.bandwidth_price, other.bandwidth_price),
+            SupplierField::StoragePrice => float(self.storage_price, other.storage_price),
+            SupplierField::MinContractPeriod => text(&self.min_contract_period).cmp(&text(&other.min_contract_period)),
+            SupplierField::BreachPenalties => text(&self.breach_penalties).cmp(&text(&other.breach_penalties)),
+            SupplierField::PaymentTerms => text(&self.payment_terms).cmp(&text(&other.payment_terms)),
+            SupplierField::ServerName => text(&self.server_name).cmp(&text(&other.server_name)),
+            SupplierField::ServerConfig => text(&self.server_config).cmp(&text(&other.server_config)),
+            SupplierField::RentalModel => text(&self.rental_model).cmp(&text(&other.rental_model)),
+            SupplierField::NetworkingCategory => text(&self.networking_category).cmp(&text(&other.networking_category)),
+        }
+    }
+}
+
+/// 字段名枚举，防止拼写错误；序列化为数据库列名
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum SupplierField {
+    ContactPerson,
+    Wechat,
+    Phone,
+    Quantity,
+    Location,
+    Price,
+    BandwidthPrice,
+    StoragePrice,
+    MinContractPeriod,
+    BreachPenalties,
+    PaymentTerms,
+    ServerName,
+    ServerConfig,
+    RentalModel,
+    NetworkingCategory,
+}
+
+impl FromStr for SupplierField {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "contact" => Ok(SupplierField::ContactPerson),
+            "wechat" => Ok(SupplierField::Wechat),
+            "phone" => Ok(SupplierField::Phone),
+            "quantity" => Ok(SupplierField::Quantity),
+            "location" => Ok(SupplierField::Location),
+            "price" => Ok(SupplierField::Price),
+            "bandwidth_price" => Ok(SupplierField::BandwidthPrice),
+            "storage_price" => Ok(SupplierField::StoragePrice),
+            "min_contract_period" => Ok(SupplierField::MinContractPeriod),
+            "breach_penalties" => Ok(SupplierField::BreachPenalties),
+            "payment_terms" => Ok(SupplierField::PaymentTerms),
+            "server_name" => Ok(SupplierField::ServerName),
+            "server_config" => Ok(SupplierField::ServerConfig),
+            "rental_model" => Ok(SupplierField::RentalModel),
+            "networking_category" => Ok(SupplierField::NetworkingCategory),
+            _ => Err(format!("未知字段名: {}", s)),
+        }
+    }
+}
+
+impl TryFrom<String> for SupplierField {
+    type Error = String;
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<SupplierField> for String {
+    fn from(f: SupplierField) -> Self {
+        f.column().to_string()
+    }
+}
+
+impl SupplierField {
+    /// 全部字段，顺序与数据库列一致
+    pub const ALL: [SupplierField; 15] = [
+        SupplierField::ContactPerson,
+        SupplierField::Wechat,
+        SupplierField::Phone,
+        SupplierField::Quantity,
+        SupplierField::Location,
+        SupplierField::Price,
+        SupplierField::BandwidthPrice,
+        SupplierField::StoragePrice,
+        SupplierField::MinContractPeriod,
+        SupplierField::BreachPenalties,
+        SupplierField::PaymentTerms,
+        SupplierField::ServerName,
+        SupplierField::ServerConfig,
+        SupplierField::RentalModel,
+        SupplierField::NetworkingCategory,
+    ];
+
+    /// 对应的数据库列名
+    pub fn column(&self) -> &'static str {
+        match self {
+            SupplierField::ContactPerson => "contact",
+            SupplierField::Wechat => "wechat",
+            SupplierField::Phone => "phone",
+            SupplierField::Quantity => "quantity",
+            SupplierField::Location => "location",
+            SupplierField::Price => "price",
+            SupplierField::BandwidthPrice => "bandwidth_price",
+            SupplierField::StoragePrice => "storage_price",
+            SupplierField::MinContractPeriod => "min_contract_period",
+            SupplierField::BreachPenalties => "breach_penalties",
+            SupplierField::PaymentTerms => "payment_terms",
+            SupplierField::ServerName => "server_name",
+            SupplierField::ServerConfig => "server_config",
+            SupplierField::RentalModel => "rental_model",
+            SupplierField::NetworkingCategory => "networking_category",
+        }
+    }
+
+    pub fn is_string(&self) -> bool {
+        matches!(self,
+            SupplierField::ContactPerson |
+            SupplierField::Wechat |
+            SupplierField::Phone |
+            SupplierField::Location |
+            SupplierField::MinContractPeriod |
+            SupplierField::BreachPenalties |
+            SupplierField::PaymentTerms |
+            SupplierField::ServerName |
+            SupplierField::ServerConfig |
+            SupplierField::RentalModel |
+            SupplierField::NetworkingCategory
+        )
+    }
+    pub fn is_numeric(&self) -> bool {
+        matches!(self,
+            SupplierField::Quantity |
+            SupplierField::Price |
+            SupplierField::BandwidthPrice |
+            SupplierField::StoragePrice
+        )
+    }
+
+    /// 浮点数字段，比较时需要容差
+    pub fn is_float(&self) -> bool {
+        matches!(self,
+            SupplierField::Price |
+            SupplierField::BandwidthPrice |
+            SupplierField::StoragePrice
+        )
+    }
+
+    /// 根据导入文件的表头识别字段，支持英文字段名和常见中文表头
+    pub fn from_header(header: &str) -> Option<Self> {
+        let h = header.trim();
+        if let Ok(field) = SupplierField::from_str(h) {
+            return Some(field);
+        }
+        match h {
+            "联系人" => Some(SupplierField::ContactPerson),
+            "微信" | "微信号" => Some(SupplierField::Wechat),
+            "手机" | "电话" | "手机号" => Some(SupplierField::Phone),
+            "数量" => Some(SupplierField::Quantity),
+            "地点" | "位置" => Some(SupplierField::Location),
+            "价格" => Some(SupplierField::Price),
+            "带宽价" | "带宽价格" => Some(SupplierField::BandwidthPrice),
+            "存储价" | "存储价格" => Some(SupplierField::StoragePrice),
+            "合同期" | "签约周期" | "最短合同期" => Some(SupplierField::MinContractPeriod),
+            "违约" | "违约金" => Some(SupplierField::BreachPenalties),
+            "付款" | "付款方式" => Some(SupplierField::PaymentTerms),
+            "服务器名" | "服务器名称" => Some(SupplierField::ServerName),
+            "配置" | "服务器配置" => Some(SupplierField::ServerConfig),
+            "租赁" | "租赁模式" => Some(SupplierField::RentalModel),
+            "组网" | "网络类型" => Some(SupplierField::NetworkingCategory),
+            _ => None,
+        }
+    }
+}
+
+impl Supplier {
+    /// 转为展示用的行结构，`id` 由调用方指定（如预览时使用表格行号）
+    pub fn to_row(&self, id: i64) -> SupplierRow {
+        SupplierRow {
+            id,
+            contact: self.contact.clone(),
+            wechat: self.wechat.clone(),
+            phone: self.phone.clone(),
+            quantity: self.quantity,
+            location: self.location.clone(),
+            price: self.price,
+            bandwidth_price: self.bandwidth_price,
+            storage_price: self.storage_price,
+            min_contract_period: self.min_contract_period.clone(),
+            breach_penalties: self.breach_penalties.clone(),
+            payment_terms: self.payment_terms.clone(),
+            server_name: self.server_name.clone(),
+            server_config: self.server_config.clone(),
+            rental_model: self.rental_model.clone(),
+            networking_category: self.networking_category.clone(),
+            profile: None,
+            score: None,
+        }
+    }
+}
+
+/// CSV 和 Excel 导出的中文表头，按 [`Column::index`] 排列；导入时 [`SupplierField::from_header`] 能识别其中的字段列
+pub const EXPORT_HEADERS: [&str; 18] = [
+    "ID", "联系人", "微信", "手机", "数量", "位置", "价格",
+    "带宽价", "存储价", "签约周期", "违约", "付款", "服务器名",
+    "配置", "租赁", "组网", "Profile", "评分",
+];
+
+/// 输出列：ID 或某个字段，用于列选择和排序；序列化为列名
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Column {
+    Id,
+    Field(SupplierField),
+    /// 来源 profile，仅用于跨 profile 查询
+    Profile,
+    /// 综合评分，由评分和事故记录计算，不能导入或修改
+    Score,
+}
+
+impl Column {
+    /// 全部数据列（不含 profile 和评分列），顺序与表格、CSV 输出一致
+    pub fn all() -> Vec<Column> {
+        std::iter::once(Column::Id)
+            .chain(SupplierField::ALL.iter().map(|f| Column::Field(*f)))
+            .collect()
+    }
+
+    /// 在完整列顺序中的位置
+    pub fn index(&self) -> usize {
+        match self {
+            Column::Id => 0,
+            Column::Field(f) => 1 + SupplierField::ALL.iter().position(|x| x == f).unwrap_or(0),
+            Column::Profile => 1 + SupplierField::ALL.len(),
+            Column::Score => 2 + SupplierField::ALL.len(),
+        }
+    }
+
+    /// 对应的数据库列名
+    pub fn name(&self) -> &'static str {
+        match self {
+            Column::Id => "id",
+            Column::Field(f) => f.column(),
+            Column::Profile => "profile",
+            Column::Score => "score",
+        }
+    }
+
+    /// 数值列（ID、数量、价格和评分），输出时右对齐且不截断
+    pub fn is_numeric(&self) -> bool {
+        match self {
+            Column::Id | Column::Score => true,
+            Column::Field(f) => f.is_numeric(),
+            Column::Profile => false,
+        }
+    }
+
+    /// 价格列，指定币种时标题附带币种
+    pub fn is_price(&self) -> bool {
+        matches!(self, Column::Field(f) if f.is_float())
+    }
+
+    /// 空列表表示全部列
+    pub fn resolve(columns: &[Column]) -> Vec<Column> {
+        if columns.is_empty() { Column::all() } else { columns.to_vec() }
+    }
+}
+
+impl FromStr for Column {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("id") {
+            return Ok(Column::Id);
+        }
+        if s.eq_ignore_ascii_case("profile") {
+            return Ok(Column::Profile);
+        }
+        if s.eq_ignore_ascii_case("score") || s == "评分" {
+            return Ok(Column::Score);
+        }
+        SupplierField::from_header(&s.replace('-', "_"))
+            .map(Column::Field)
+            .ok_or_else(|| format!("未知列名: {}", s))
+    }
+}
+
+impl TryFrom<String> for Column {
+    type Error = String;
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Column> for String {
+    fn from(c: Column) -> Self {
+        c.name().to_string()
+    }
+}
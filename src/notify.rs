@@ -0,0 +1,513 @@
+//! 到期提醒
+//!
+//! `notify check` 供 cron 定期执行：找出即将到期的合同、已过期且未签约的报价单和可提供数量偏低的
+//! 供应商，把每条提醒发送到一个或多个通知通道。已发送的提醒记录在 `notifications` 表中，
+//! 之后的检查不再重复发送；合同续约后到期日变化、供应商数量变化时会重新提醒。
+//!
+//! 通知通道写作 `类型:参数`：
+//!
+//! - `stdout`：每条提醒输出一行
+//! - `file:PATH`：追加到文件，每条提醒一行JSON
+//! - `maildir:DIR`：每条提醒写成一封邮件，放入 Maildir 的 `new` 目录
+//! - `command:CMD`：每条提醒执行一次 `sh -c CMD`，提醒的JSON写入标准输入，类别、标题和内容另以
+//!   环境变量 `WEI_NOTIFY_KIND`、`WEI_NOTIFY_TITLE`、`WEI_NOTIFY_MESSAGE` 提供
+//! - `webhook:URL`：每条提醒以JSON POST 到 URL
+
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::contract::{Contract, ContractStatus};
+use crate::dates::Date;
+use crate::error::{Error, Result};
+use crate::quotation::Quotation;
+use crate::{QueryBuilder, SupplierRow, SupplierStore};
+
+/// 未配置 `notify.contract_days` 时提前提醒合同到期的天数
+pub const DEFAULT_CONTRACT_DAYS: u32 = 14;
+/// 未配置 `notify.low_quantity` 时提醒的可提供数量上限
+pub const DEFAULT_LOW_QUANTITY: i32 = 2;
+
+/// webhook 请求的超时时间
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 提醒类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    /// 合同即将到期或已过结束日期
+    ContractExpiring,
+    /// 报价单已过期且没有签约
+    QuoteStale,
+    /// 供应商可提供数量偏低
+    LowAvailability,
+}
+
+impl NotificationKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            NotificationKind::ContractExpiring => "contract_expiring",
+            NotificationKind::QuoteStale => "quote_stale",
+            NotificationKind::LowAvailability => "low_availability",
+        }
+    }
+
+    /// 中文名称，用于标准输出和邮件标题
+    pub fn label(self) -> &'static str {
+        match self {
+            NotificationKind::ContractExpiring => "合同到期",
+            NotificationKind::QuoteStale => "报价过期",
+            NotificationKind::LowAvailability => "库存偏低",
+        }
+    }
+}
+
+/// 一条提醒
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Notification {
+    pub kind: NotificationKind,
+    /// 去重用的键，同一个键只提醒一次
+    pub key: String,
+    pub title: String,
+    pub message: String,
+    pub supplier_id: Option<i64>,
+}
+
+impl Notification {
+    /// 一行文本，如 `[合同到期] 合同 3 将于 2024-03-01 到期：...`
+    pub fn line(&self) -> String {
+        format!("[{}] {}：{}", self.kind.label(), self.title, self.message)
+    }
+}
+
+/// 检查的阈值
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NotifyRules {
+    /// 结束日期在今天之后这么多天内的合同需要提醒
+    pub contract_days: u32,
+    /// 可提供数量不超过该值的供应商需要提醒
+    pub low_quantity: i32,
+}
+
+impl Default for NotifyRules {
+    fn default() -> Self {
+        Self { contract_days: DEFAULT_CONTRACT_DAYS, low_quantity: DEFAULT_LOW_QUANTITY }
+    }
+}
+
+/// 履行中且在 `today` 之后 `days` 天内到期（含已过结束日期）的合同
+pub fn expiring_contracts(contracts: &[Contract], today: Date, days: u32) -> Vec<Notification> {
+    let until = today.add_days(i64::from(days));
+    contracts
+        .iter()
+        .filter(|c| c.status == ContractStatus::Active && c.end_date <= until)
+        .map(|c| {
+            let left = today.days_until(c.end_date);
+            let title = if left >= 0 {
+                format!("合同 {} 将于 {} 到期（剩余 {} 天）", c.id, c.end_date, left)
+            } else {
+                format!("合同 {} 已于 {} 到期，尚未续约或终止", c.id, c.end_date)
+            };
+            Notification {
+                kind: NotificationKind::ContractExpiring,
+                key: format!("{}:{}:{}", NotificationKind::ContractExpiring.name(), c.id, c.end_date),
+                title,
+                message: format!("供应商 {}，{} 台，月租 {:.2}", c.supplier_id, c.quantity, c.monthly_rent()),
+                supplier_id: Some(c.supplier_id),
+            }
+        })
+        .collect()
+}
+
+/// 有效期已过、且没有对应合同的报价单
+pub fn stale_quotes(quotes: &[Quotation], contracts: &[Contract], today: Date) -> Vec<Notification> {
+    let signed: HashSet<&str> = contracts.iter().filter_map(|c| c.quote_no.as_deref()).collect();
+    quotes
+        .iter()
+        .filter(|q| !signed.contains(q.quote_no.as_str()))
+        .filter(|q| q.valid_until.parse::<Date>().is_ok_and(|d| d < today))
+        .map(|q| Notification {
+            kind: NotificationKind::QuoteStale,
+            key: format!("{}:{}", NotificationKind::QuoteStale.name(), q.quote_no),
+            title: format!("报价单 {} 已于 {} 过期", q.quote_no, q.valid_until),
+            message: format!("客户 {}，{} 台 × {} 个月，总价 {:.2}，尚未签约", q.customer, q.quantity, q.months, q.total),
+            supplier_id: Some(q.supplier_id),
+        })
+        .collect()
+}
+
+/// 可提供数量不超过 `threshold` 的供应商；未填写数量的不提醒
+pub fn low_availability(rows: &[SupplierRow], threshold: i32) -> Vec<Notification> {
+    rows.iter()
+        .filter_map(|row| row.quantity.filter(|q| *q <= threshold).map(|q| (row, q)))
+        .map(|(row, quantity)| {
+            let what = [row.location.as_deref(), row.server_name.as_deref()].into_iter().flatten().collect::<Vec<_>>().join(" ");
+            Notification {
+                kind: NotificationKind::LowAvailability,
+                key: format!("{}:{}:{}", NotificationKind::LowAvailability.name(), row.id, quantity),
+                title: format!("供应商 {} 仅剩 {} 台可提供", row.id, quantity),
+                message: if what.is_empty() { "请确认库存或寻找替代资源".to_string() } else { format!("{}，请确认库存或寻找替代资源", what) },
+                supplier_id: Some(row.id),
+            }
+        })
+        .collect()
+}
+
+/// 按阈值检查全部提醒，按类别排列
+pub fn collect(store: &SupplierStore, today: Date, rules: &NotifyRules) -> Result<Vec<Notification>> {
+    let contracts = store.list_contracts(None)?;
+    let mut notifications = expiring_contracts(&contracts, today, rules.contract_days);
+    notifications.extend(stale_quotes(&store.list_quotes()?, &contracts, today));
+    notifications.extend(low_availability(&store.query(QueryBuilder::new())?, rules.low_quantity));
+    Ok(notifications)
+}
+
+/// 通知通道
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotifySink {
+    Stdout,
+    /// 追加JSON行的文件
+    File(PathBuf),
+    /// Maildir 目录
+    Maildir(PathBuf),
+    /// 通过 shell 执行的命令
+    Command(String),
+    /// 接收 POST 的 URL
+    Webhook(String),
+}
+
+impl FromStr for NotifySink {
+    type Err = String;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.trim();
+        if s == "stdout" {
+            return Ok(NotifySink::Stdout);
+        }
+        let invalid = || format!("通知通道无效: {}，可选 stdout、file:PATH、maildir:DIR、command:CMD、webhook:URL", s);
+        let (kind, arg) = s.split_once(':').ok_or_else(invalid)?;
+        let arg = arg.trim();
+        if arg.is_empty() {
+            return Err(invalid());
+        }
+        match kind {
+            "file" => Ok(NotifySink::File(PathBuf::from(arg))),
+            "maildir" => Ok(NotifySink::Maildir(PathBuf::from(arg))),
+            "command" => Ok(NotifySink::Command(arg.to_string())),
+            "webhook" if arg.starts_with("http://") || arg.starts_with("https://") => Ok(NotifySink::Webhook(arg.to_string())),
+            "webhook" => Err(format!("webhook 地址必须以 http:// 或 https:// 开头: {}", arg)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for NotifySink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotifySink::Stdout => write!(f, "stdout"),
+            NotifySink::File(path) => write!(f, "file:{}", path.display()),
+            NotifySink::Maildir(path) => write!(f, "maildir:{}", path.display()),
+            NotifySink::Command(cmd) => write!(f, "command:{}", cmd),
+            NotifySink::Webhook(url) => write!(f, "webhook:{}", url),
+        }
+    }
+}
+
+impl Serialize for NotifySink {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for NotifySink {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl NotifySink {
+    /// 文件和 Maildir 的相对路径改为相对于 `dir`
+    pub fn resolve_path(&mut self, dir: &Path) {
+        if let NotifySink::File(path) | NotifySink::Maildir(path) = self {
+            if path.is_relative() {
+                *path = dir.join(&*path);
+            }
+        }
+    }
+
+    /// 发送全部提醒，遇到第一个错误即停止
+    pub fn deliver(&self, notifications: &[Notification]) -> Result<()> {
+        match self {
+            NotifySink::Stdout => {
+                for n in notifications {
+                    println!("{}", n.line());
+                }
+                Ok(())
+            }
+            NotifySink::File(path) => {
+                let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+                for n in notifications {
+                    writeln!(file, "{}", serde_json::to_string(n)?)?;
+                }
+                Ok(())
+            }
+            NotifySink::Maildir(dir) => notifications.iter().enumerate().try_for_each(|(i, n)| write_maildir(dir, i, n)),
+            NotifySink::Command(cmd) => notifications.iter().try_for_each(|n| run_command(cmd, n)),
+            NotifySink::Webhook(url) => notifications.iter().try_for_each(|n| post_webhook(url, n)),
+        }
+    }
+}
+
+/// 邮件头中的非ASCII文本按 RFC 2047 编码
+fn encode_header(text: &str) -> String {
+    if text.is_ascii() {
+        text.to_string()
+    } else {
+        format!("=?UTF-8?B?{}?=", BASE64.encode(text))
+    }
+}
+
+/// RFC 5322 格式的当前时间（UTC），如 `Mon, 01 Jan 2024 09:30:00 +0000`
+fn mail_date(now: SystemTime) -> String {
+    let secs = now.duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0);
+    let days = secs.div_euclid(86400);
+    let date = Date::from_days(days);
+    let weekday = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"][days.rem_euclid(7) as usize];
+    let month = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"][date.month() as usize - 1];
+    let t = secs.rem_euclid(86400);
+    format!("{}, {:02} {} {} {:02}:{:02}:{:02} +0000", weekday, date.day(), month, date.year(), t / 3600, t % 3600 / 60, t % 60)
+}
+
+/// 按 Maildir 约定先写入 `tmp`，再移动到 `new`
+fn write_maildir(dir: &Path, index: usize, n: &Notification) -> Result<()> {
+    for sub in ["tmp", "new", "cur"] {
+        fs::create_dir_all(dir.join(sub))?;
+    }
+    let now = SystemTime::now();
+    let stamp = now.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let name = format!("{}.M{}P{}Q{}.wei-assistant", stamp.as_secs(), stamp.subsec_micros(), std::process::id(), index);
+    let message = format!(
+        "From: wei-assistant <wei-assistant@localhost>\r\nSubject: {}\r\nDate: {}\r\nX-Wei-Notify-Kind: {}\r\nMIME-Version: 1.0\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Transfer-Encoding: 8bit\r\n\r\n{}\r\n",
+        encode_header(&format!("[{}] {}", n.kind.label(), n.title)),
+        mail_date(now),
+        n.kind.name(),
+        n.message
+    );
+    let tmp = dir.join("tmp").join(&name);
+    fs::write(&tmp, message)?;
+    fs::rename(&tmp, dir.join("new").join(&name))?;
+    Ok(())
+}
+
+fn run_command(cmd: &str, n: &Notification) -> Result<()> {
+    let (shell, flag) = if cfg!(windows) { ("cmd", "/C") } else { ("sh", "-c") };
+    let mut child = Command::new(shell)
+        .args([flag, cmd])
+        .env("WEI_NOTIFY_KIND", n.kind.name())
+        .env("WEI_NOTIFY_TITLE", &n.title)
+        .env("WEI_NOTIFY_MESSAGE", &n.message)
+        .stdin(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // 命令可能不读标准输入，写入失败不算错误
+        let _ = writeln!(stdin, "{}", serde_json::to_string(n)?);
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(Error::Io(io::Error::other(format!("通知命令 {} 执行失败（{}）", cmd, status))));
+    }
+    Ok(())
+}
+
+/// POST 提醒的JSON，另附 `text` 字段便于直接接入聊天机器人
+fn post_webhook(url: &str, n: &Notification) -> Result<()> {
+    let mut body = serde_json::to_value(n)?;
+    body["text"] = serde_json::Value::String(n.line());
+    ureq::post(url)
+        .timeout(WEBHOOK_TIMEOUT)
+        .set("Content-Type", "application/json; charset=utf-8")
+        .send_string(&body.to_string())
+        .map_err(|e| Error::Io(io::Error::other(format!("webhook {} 请求失败: {}", url, e))))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quotation::QuotationLine;
+
+    fn date(s: &str) -> Date {
+        s.parse().unwrap()
+    }
+
+    fn contract(id: i64, end: &str) -> Contract {
+        Contract {
+            id,
+            supplier_id: 2,
+            quote_no: None,
+            quantity: 2,
+            agreed_price: 900.0,
+            start_date: date("2024-01-01"),
+            end_date: date(end),
+            status: ContractStatus::Active,
+            min_contract_period: None,
+            payment_terms: None,
+            breach_penalties: None,
+            renewals: 0,
+            terminated_on: None,
+            termination_cost: None,
+        }
+    }
+
+    fn quotation(quote_no: &str, valid_until: &str) -> Quotation {
+        Quotation {
+            quote_no: quote_no.to_string(),
+            created_on: "2024-02-01".to_string(),
+            valid_until: valid_until.to_string(),
+            customer: "某客户".to_string(),
+            supplier_id: 2,
+            offer_code: "OF-7KQ2M9XA".to_string(),
+            quantity: 2,
+            months: 3,
+            location: None,
+            server_name: None,
+            server_config: None,
+            networking_category: None,
+            rental_model: None,
+            min_contract_period: None,
+            payment_terms: None,
+            breach_penalties: None,
+            lines: vec![QuotationLine { item: "服务器".to_string(), unit_price: 1000.0, monthly: 2000.0, amount: 6000.0 }],
+            monthly_total: 2000.0,
+            total: 6000.0,
+            total_cost: 5400.0,
+            notes: None,
+        }
+    }
+
+    fn notification() -> Notification {
+        Notification {
+            kind: NotificationKind::LowAvailability,
+            key: "low_availability:1:1".to_string(),
+            title: "供应商 1 仅剩 1 台可提供".to_string(),
+            message: "北京，请确认库存或寻找替代资源".to_string(),
+            supplier_id: Some(1),
+        }
+    }
+
+    #[test]
+    fn test_sink_from_str() {
+        assert_eq!("stdout".parse::<NotifySink>(), Ok(NotifySink::Stdout));
+        assert_eq!("file:notify.log".parse::<NotifySink>(), Ok(NotifySink::File(PathBuf::from("notify.log"))));
+        assert_eq!("maildir: mail ".parse::<NotifySink>(), Ok(NotifySink::Maildir(PathBuf::from("mail"))));
+        assert_eq!("command:cat >> out".parse::<NotifySink>(), Ok(NotifySink::Command("cat >> out".to_string())));
+        assert_eq!(
+            "webhook:http://127.0.0.1:8080/hook".parse::<NotifySink>(),
+            Ok(NotifySink::Webhook("http://127.0.0.1:8080/hook".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_sink_from_str_rejects_invalid() {
+        assert!("webhook:ftp://example.com".parse::<NotifySink>().unwrap_err().contains("http://"));
+        for bad in ["email:a@b.c", "file:", "file", "stdout:x", ""] {
+            assert!(bad.parse::<NotifySink>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_sink_display_round_trips() {
+        for text in ["stdout", "file:notify.log", "maildir:mail", "command:echo hi", "webhook:https://example.com/hook"] {
+            assert_eq!(text.parse::<NotifySink>().unwrap().to_string(), text);
+        }
+    }
+
+    #[test]
+    fn test_sink_resolve_path() {
+        let dir = Path::new("/etc/wei");
+        let mut file: NotifySink = "file:notify.log".parse().unwrap();
+        file.resolve_path(dir);
+        assert_eq!(file, NotifySink::File(dir.join("notify.log")));
+        let mut absolute: NotifySink = "maildir:/var/mail/ops".parse().unwrap();
+        absolute.resolve_path(dir);
+        assert_eq!(absolute, NotifySink::Maildir(PathBuf::from("/var/mail/ops")));
+        let mut command: NotifySink = "command:notify.sh".parse().unwrap();
+        command.resolve_path(dir);
+        assert_eq!(command.to_string(), "command:notify.sh");
+    }
+
+    #[test]
+    fn test_expiring_contracts() {
+        let today = date("2024-03-01");
+        let contracts = [
+            contract(1, "2024-03-10"),
+            contract(2, "2024-02-20"),
+            contract(3, "2024-06-01"),
+            Contract { status: ContractStatus::Terminated, ..contract(4, "2024-03-05") },
+        ];
+        let expiring = expiring_contracts(&contracts, today, 14);
+        assert_eq!(expiring.iter().map(|n| n.key.as_str()).collect::<Vec<_>>(), ["contract_expiring:1:2024-03-10", "contract_expiring:2:2024-02-20"]);
+        assert!(expiring[0].title.contains("剩余 9 天"));
+        assert!(expiring[1].title.contains("尚未续约或终止"));
+        assert_eq!(expiring[0].message, "供应商 2，2 台，月租 1800.00");
+    }
+
+    #[test]
+    fn test_stale_quotes() {
+        let today = date("2024-03-01");
+        let expired = quotation("Q20240201-001", "2024-02-28");
+        let signed = Contract { quote_no: Some(expired.quote_no.clone()), ..contract(5, "2024-06-01") };
+        let stale = stale_quotes(std::slice::from_ref(&expired), &[], today);
+        assert_eq!(stale.iter().map(|n| n.key.as_str()).collect::<Vec<_>>(), ["quote_stale:Q20240201-001"]);
+        assert!(stale_quotes(std::slice::from_ref(&expired), &[signed], today).is_empty());
+        // 有效期当天仍有效
+        assert!(stale_quotes(&[quotation("Q20240201-002", "2024-03-01")], &[], today).is_empty());
+    }
+
+    #[test]
+    fn test_low_availability() {
+        let row = |id: i64, quantity: Option<i32>| crate::Supplier { quantity, location: Some("北京".to_string()), ..Default::default() }.to_row(id);
+        let found = low_availability(&[row(1, Some(1)), row(2, Some(20)), row(3, None), row(4, Some(2))], 2);
+        assert_eq!(found.iter().map(|n| n.key.as_str()).collect::<Vec<_>>(), ["low_availability:1:1", "low_availability:4:2"]);
+        assert_eq!(found[0].message, "北京，请确认库存或寻找替代资源");
+    }
+
+    #[test]
+    fn test_notification_line_and_rules() {
+        assert_eq!(notification().line(), "[库存偏低] 供应商 1 仅剩 1 台可提供：北京，请确认库存或寻找替代资源");
+        let rules = NotifyRules::default();
+        assert_eq!((rules.contract_days, rules.low_quantity), (DEFAULT_CONTRACT_DAYS, DEFAULT_LOW_QUANTITY));
+    }
+
+    #[test]
+    fn test_mail_headers() {
+        assert_eq!(encode_header("ok"), "ok");
+        assert_eq!(encode_header("提醒"), format!("=?UTF-8?B?{}?=", BASE64.encode("提醒")));
+        // 2024-01-01 09:30:00 UTC 是星期一
+        assert_eq!(mail_date(UNIX_EPOCH + Duration::from_secs(1_704_101_400)), "Mon, 01 Jan 2024 09:30:00 +0000");
+    }
+
+    #[test]
+    fn test_deliver_file_and_maildir() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = NotifySink::File(dir.path().join("notify.jsonl"));
+        file.deliver(&[notification()]).unwrap();
+        file.deliver(&[notification()]).unwrap();
+        let log = fs::read_to_string(dir.path().join("notify.jsonl")).unwrap();
+        assert_eq!(log.lines().count(), 2);
+        assert!(log.contains("\"kind\":\"low_availability\""));
+
+        NotifySink::Maildir(dir.path().join("mail")).deliver(&[notification(), notification()]).unwrap();
+        assert_eq!(fs::read_dir(dir.path().join("mail/new")).unwrap().count(), 2);
+        assert_eq!(fs::read_dir(dir.path().join("mail/tmp")).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,280 @@
+//! 付款计划
+//!
+//! 合同的付款方式（如 `月付`、`季付`）决定我们何时向供应商付款。付款按期预付：每期开始当天到期，
+//! 金额为该期的月数 × 月租；最后一期不足一个付款周期时按实际月数计。付款计划按合同生成并保存在
+//! `payments` 表中，续约后再次生成会接着已有的最后一期继续。
+//!
+//! 付款方式是自由文本，能识别以下写法：
+//!
+//! - 每月：`月付`、`按月付款`、`monthly`
+//! - 每季度：`季付`、`押一付三`、`每3个月`、`quarterly`
+//! - 每半年：`半年付`、`付六`
+//! - 每年：`年付`、`annually`
+//! - 一次付清：`一次性付清`、`全款预付`、`upfront`
+//!
+//! 无法识别时可以在生成付款计划时指定周期。
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::contract::Contract;
+use crate::dates::Date;
+
+/// 付款周期
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PaymentCadence {
+    /// 每月
+    Monthly,
+    /// 每季度
+    Quarterly,
+    /// 每半年
+    SemiAnnual,
+    /// 每年
+    Annual,
+    /// 一次付清整个合同期
+    Upfront,
+}
+
+impl PaymentCadence {
+    pub fn name(self) -> &'static str {
+        match self {
+            PaymentCadence::Monthly => "monthly",
+            PaymentCadence::Quarterly => "quarterly",
+            PaymentCadence::SemiAnnual => "semi-annual",
+            PaymentCadence::Annual => "annual",
+            PaymentCadence::Upfront => "upfront",
+        }
+    }
+
+    /// 每期的月数，一次付清时为 `None`
+    pub fn months(self) -> Option<u32> {
+        match self {
+            PaymentCadence::Monthly => Some(1),
+            PaymentCadence::Quarterly => Some(3),
+            PaymentCadence::SemiAnnual => Some(6),
+            PaymentCadence::Annual => Some(12),
+            PaymentCadence::Upfront => None,
+        }
+    }
+
+    fn from_months(months: u32) -> Option<Self> {
+        match months {
+            1 => Some(PaymentCadence::Monthly),
+            3 => Some(PaymentCadence::Quarterly),
+            6 => Some(PaymentCadence::SemiAnnual),
+            12 => Some(PaymentCadence::Annual),
+            _ => None,
+        }
+    }
+}
+
+impl std::str::FromStr for PaymentCadence {
+    type Err = String;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        <PaymentCadence as ValueEnum>::from_str(s, true).map_err(|_| format!("未知付款周期: {}", s))
+    }
+}
+
+/// 识别付款方式对应的付款周期，无法识别时为 `None`
+pub fn parse_cadence(text: &str) -> Option<PaymentCadence> {
+    let text = text.trim().to_lowercase();
+    if ["一次性", "一次付清", "全款", "upfront"].iter().any(|k| text.contains(k)) {
+        return Some(PaymentCadence::Upfront);
+    }
+    if text.contains("半年") || text.contains("semi") {
+        return Some(PaymentCadence::SemiAnnual);
+    }
+    if text.contains("季") || text.contains("quarter") {
+        return Some(PaymentCadence::Quarterly);
+    }
+    if text.contains("年付") || text.contains("按年") || text.contains("annual") || text.contains("yearly") {
+        return Some(PaymentCadence::Annual);
+    }
+    if text.contains("月付") || text.contains("按月") || text.contains("monthly") {
+        return Some(PaymentCadence::Monthly);
+    }
+    // 押一付三、付3、每3个月
+    let months = text
+        .split_once('付')
+        .and_then(|(_, rest)| leading_number(rest))
+        .or_else(|| text.strip_prefix('每').and_then(leading_number));
+    months.and_then(PaymentCadence::from_months)
+}
+
+/// 文本开头的数字，支持阿拉伯数字和一到十二的中文数字
+fn leading_number(text: &str) -> Option<u32> {
+    let digits: String = text.chars().take_while(char::is_ascii_digit).collect();
+    if !digits.is_empty() {
+        return digits.parse().ok();
+    }
+    ["一", "二", "三", "四", "五", "六", "七", "八", "九", "十", "十一", "十二"]
+        .iter()
+        .rposition(|n| text.starts_with(n))
+        .map(|i| i as u32 + 1)
+}
+
+/// 付款计划中的一期
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Installment {
+    pub period_start: Date,
+    /// 本期结束日期（不含）
+    pub period_end: Date,
+    pub due_date: Date,
+    pub amount: f64,
+}
+
+/// 保存的一笔付款
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Payment {
+    pub id: i64,
+    pub contract_id: i64,
+    pub supplier_id: i64,
+    pub cadence: PaymentCadence,
+    pub period_start: Date,
+    pub period_end: Date,
+    pub due_date: Date,
+    pub amount: f64,
+    /// 付款日期，未付时为 `None`
+    pub paid_on: Option<Date>,
+}
+
+/// 一个供应商的应付余额
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SupplierBalance {
+    pub supplier_id: i64,
+    /// 已到期未付的金额
+    pub due: f64,
+    /// 其中已逾期（到期日早于今天）的金额
+    pub overdue: f64,
+    /// 尚未到期的金额
+    pub upcoming: f64,
+    /// 最早一笔未付款的到期日
+    pub next_due_date: Option<Date>,
+}
+
+fn round_cents(v: f64) -> f64 {
+    (v * 100.0).round() / 100.0
+}
+
+/// 计算合同从 `from`（默认开始日期）到结束日期的各期付款
+///
+/// 各期的分界按开始日期加整月数计算，避免月末日期逐期漂移；`from` 应为某一期的分界。
+pub fn schedule(contract: &Contract, cadence: PaymentCadence, from: Option<Date>) -> Vec<Installment> {
+    let mut cursor = from.unwrap_or(contract.start_date).max(contract.start_date);
+    let monthly = contract.monthly_rent();
+    let mut installments = Vec::new();
+    while cursor < contract.end_date {
+        let elapsed = contract.start_date.months_until_ceil(cursor);
+        let period_end = match cadence.months() {
+            Some(n) => contract.start_date.add_months((elapsed + n) as i32).min(contract.end_date),
+            None => contract.end_date,
+        };
+        // 月数同样从开始日期起算，4月30日至7月31日（1月31日开始的合同）是3个月
+        let months = contract.start_date.months_until_ceil(period_end) - elapsed;
+        installments.push(Installment { period_start: cursor, period_end, due_date: cursor, amount: round_cents(monthly * f64::from(months)) });
+        cursor = period_end;
+    }
+    installments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::ContractStatus;
+
+    fn date(s: &str) -> Date {
+        s.parse().unwrap()
+    }
+
+    /// 2024-01-31 至 2024-08-31，月租 2000
+    fn contract() -> Contract {
+        Contract {
+            id: 1,
+            supplier_id: 1,
+            quote_no: None,
+            quantity: 2,
+            agreed_price: 1000.0,
+            start_date: date("2024-01-31"),
+            end_date: date("2024-08-31"),
+            status: ContractStatus::Active,
+            min_contract_period: None,
+            payment_terms: None,
+            breach_penalties: None,
+            renewals: 0,
+            terminated_on: None,
+            termination_cost: None,
+        }
+    }
+
+    fn periods(installments: &[Installment]) -> Vec<(String, String, f64)> {
+        installments.iter().map(|i| (i.period_start.to_string(), i.period_end.to_string(), i.amount)).collect()
+    }
+
+    #[test]
+    fn test_parse_cadence_keywords() {
+        assert_eq!(parse_cadence("月付"), Some(PaymentCadence::Monthly));
+        assert_eq!(parse_cadence("按月付款"), Some(PaymentCadence::Monthly));
+        assert_eq!(parse_cadence("季付"), Some(PaymentCadence::Quarterly));
+        assert_eq!(parse_cadence("Quarterly"), Some(PaymentCadence::Quarterly));
+        assert_eq!(parse_cadence("半年付"), Some(PaymentCadence::SemiAnnual));
+        assert_eq!(parse_cadence("年付"), Some(PaymentCadence::Annual));
+        assert_eq!(parse_cadence("annually"), Some(PaymentCadence::Annual));
+        assert_eq!(parse_cadence("一次性付清"), Some(PaymentCadence::Upfront));
+        assert_eq!(parse_cadence("全款预付"), Some(PaymentCadence::Upfront));
+    }
+
+    #[test]
+    fn test_parse_cadence_month_counts() {
+        assert_eq!(parse_cadence("押一付三"), Some(PaymentCadence::Quarterly));
+        assert_eq!(parse_cadence("付六"), Some(PaymentCadence::SemiAnnual));
+        assert_eq!(parse_cadence("付12"), Some(PaymentCadence::Annual));
+        assert_eq!(parse_cadence("每3个月"), Some(PaymentCadence::Quarterly));
+        assert_eq!(parse_cadence("付一"), Some(PaymentCadence::Monthly));
+        // 只支持 1、3、6、12 个月的周期
+        assert_eq!(parse_cadence("付二"), None);
+        assert_eq!(parse_cadence("面议"), None);
+    }
+
+    #[test]
+    fn test_cadence_from_str() {
+        assert_eq!("semi-annual".parse::<PaymentCadence>(), Ok(PaymentCadence::SemiAnnual));
+        assert_eq!("Upfront".parse::<PaymentCadence>().map(PaymentCadence::months), Ok(None));
+        assert!("weekly".parse::<PaymentCadence>().unwrap_err().contains("weekly"));
+        for cadence in [PaymentCadence::Monthly, PaymentCadence::Quarterly, PaymentCadence::SemiAnnual, PaymentCadence::Annual] {
+            assert_eq!(cadence.name().parse::<PaymentCadence>(), Ok(cadence));
+        }
+    }
+
+    #[test]
+    fn test_schedule_quarterly() {
+        // 分界按开始日期加整月计算，最后一期只有1个月
+        assert_eq!(
+            periods(&schedule(&contract(), PaymentCadence::Quarterly, None)),
+            [
+                ("2024-01-31".to_string(), "2024-04-30".to_string(), 6000.0),
+                ("2024-04-30".to_string(), "2024-07-31".to_string(), 6000.0),
+                ("2024-07-31".to_string(), "2024-08-31".to_string(), 2000.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_schedule_due_on_period_start() {
+        let installments = schedule(&contract(), PaymentCadence::Monthly, None);
+        assert_eq!(installments.len(), 7);
+        assert!(installments.iter().all(|i| i.due_date == i.period_start && i.amount == 2000.0));
+        // 月末日期不逐期漂移
+        assert_eq!(installments[2].period_start, date("2024-03-31"));
+    }
+
+    #[test]
+    fn test_schedule_upfront_and_from() {
+        assert_eq!(periods(&schedule(&contract(), PaymentCadence::Upfront, None)), [("2024-01-31".to_string(), "2024-08-31".to_string(), 14000.0)]);
+        let rest = schedule(&contract(), PaymentCadence::Quarterly, Some(date("2024-04-30")));
+        assert_eq!(rest.iter().map(|i| i.amount).collect::<Vec<_>>(), [6000.0, 2000.0]);
+        assert!(schedule(&contract(), PaymentCadence::Annual, Some(date("2024-08-31"))).is_empty());
+        // 早于开始日期的 `from` 从开始日期算起
+        assert_eq!(schedule(&contract(), PaymentCadence::Annual, Some(date("2023-01-01")))[0].period_start, date("2024-01-31"));
+    }
+}
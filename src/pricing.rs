@@ -0,0 +1,317 @@
+//! 定价规则与报价计算
+//!
+//! 规则按地点、组网类型和租赁模式匹配供应商，未填写的条件匹配任意值；多条规则都匹配时
+//! 条件最多的优先，条件数相同时先添加的优先。加价可以是百分比（作用于价格、带宽价和存储价）
+//! 或每台固定金额（加在价格上），并可要求每台的最低毛利。
+//!
+//! 带宽价和存储价按每台计算，报价金额均四舍五入到分。
+
+use std::str::FromStr;
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::catalog::apply_markup;
+use crate::error::{Error, Result};
+use crate::matching::{text_matches, MatchMode, Synonyms};
+use crate::model::{SupplierField, SupplierRow};
+
+/// 加价方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarkupKind {
+    /// 按百分比上浮
+    Percent,
+    /// 每台加固定金额
+    Fixed,
+}
+
+impl MarkupKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            MarkupKind::Percent => "percent",
+            MarkupKind::Fixed => "fixed",
+        }
+    }
+}
+
+impl FromStr for MarkupKind {
+    type Err = String;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        <MarkupKind as ValueEnum>::from_str(s, true).map_err(|_| format!("未知加价方式: {}，可选 percent、fixed", s))
+    }
+}
+
+/// 一条定价规则
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PricingRule {
+    /// 规则ID，新增时忽略
+    pub id: i64,
+    pub location: Option<String>,
+    pub networking_category: Option<String>,
+    pub rental_model: Option<String>,
+    pub kind: MarkupKind,
+    /// 百分比或每台金额
+    pub value: f64,
+    /// 每台最低毛利
+    pub min_margin: Option<f64>,
+}
+
+impl PricingRule {
+    /// 加价和最低毛利都不能为负
+    pub fn validate(&self) -> Result<()> {
+        if !self.value.is_finite() || self.value < 0.0 {
+            return Err(Error::Validation(format!("加价必须是不小于0的数字: {}", self.value)));
+        }
+        if let Some(m) = self.min_margin {
+            if !m.is_finite() || m < 0.0 {
+                return Err(Error::Validation(format!("最低毛利必须是不小于0的数字: {}", m)));
+            }
+        }
+        Ok(())
+    }
+
+    /// 规则中填写的条件数
+    fn specificity(&self) -> usize {
+        [&self.location, &self.networking_category, &self.rental_model].iter().filter(|v| v.is_some()).count()
+    }
+
+    /// 是否适用于该供应商；文本忽略大小写和空格，地点还按同义词匹配
+    pub fn matches(&self, row: &SupplierRow, synonyms: &Synonyms) -> bool {
+        let field_matches = |field: SupplierField, pattern: &Option<String>, value: &Option<String>| match (pattern, value) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(pattern), Some(value)) => {
+                synonyms.expand(field, pattern).iter().any(|p| text_matches(MatchMode::Icase, value, p))
+            }
+        };
+        field_matches(SupplierField::Location, &self.location, &row.location)
+            && field_matches(SupplierField::NetworkingCategory, &self.networking_category, &row.networking_category)
+            && field_matches(SupplierField::RentalModel, &self.rental_model, &row.rental_model)
+    }
+
+    /// 规则的简要说明，如 `北京/IB/*: +15%，最低毛利 100`
+    pub fn describe(&self) -> String {
+        let scope = [&self.location, &self.networking_category, &self.rental_model]
+            .iter()
+            .map(|v| v.as_deref().unwrap_or("*"))
+            .collect::<Vec<_>>()
+            .join("/");
+        let markup = match self.kind {
+            MarkupKind::Percent => format!("+{}%", self.value),
+            MarkupKind::Fixed => format!("+{}/台", self.value),
+        };
+        match self.min_margin {
+            Some(m) => format!("{}: {}，最低毛利 {}/台", scope, markup, m),
+            None => format!("{}: {}", scope, markup),
+        }
+    }
+}
+
+/// 从规则中选出适用于该供应商的一条：条件最多的优先，条件数相同时ID小的优先
+pub fn select_rule<'a>(rules: &'a [PricingRule], row: &SupplierRow, synonyms: &Synonyms) -> Option<&'a PricingRule> {
+    let mut best: Option<&PricingRule> = None;
+    for rule in rules.iter().filter(|r| r.matches(row, synonyms)) {
+        let better = match best {
+            None => true,
+            Some(b) => rule.specificity() > b.specificity() || (rule.specificity() == b.specificity() && rule.id < b.id),
+        };
+        if better {
+            best = Some(rule);
+        }
+    }
+    best
+}
+
+/// 报价中的一项费用
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuoteLine {
+    /// 项目名称：服务器、带宽或存储
+    pub item: String,
+    pub unit_cost: f64,
+    pub unit_price: f64,
+    pub cost: f64,
+    pub price: f64,
+    pub margin: f64,
+}
+
+/// 一个供应商按数量计算的报价
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PriceQuote {
+    pub supplier_id: i64,
+    pub quantity: u32,
+    /// 使用的定价规则，没有匹配的规则时按成本价报价
+    pub rule: Option<PricingRule>,
+    pub lines: Vec<QuoteLine>,
+    pub total_cost: f64,
+    pub total_price: f64,
+    pub margin: f64,
+    /// 毛利率（毛利 / 售价），售价为0时为0
+    pub margin_rate: f64,
+}
+
+fn round_cents(v: f64) -> f64 {
+    (v * 100.0).round() / 100.0
+}
+
+/// 计算供应商 `row` 数量为 `quantity` 时的报价
+///
+/// 供应商必须有价格；填写了可提供数量时，报价数量不能超过它。
+pub fn quote(row: &SupplierRow, quantity: u32, rule: Option<&PricingRule>) -> Result<PriceQuote> {
+    if quantity == 0 {
+        return Err(Error::Validation("报价数量必须大于0".to_string()));
+    }
+    if let Some(available) = row.quantity {
+        if i64::from(quantity) > i64::from(available) {
+            return Err(Error::Validation(format!("供应商 {} 只能提供 {} 台，少于报价数量 {}", row.id, available, quantity)));
+        }
+    }
+    let price = row.price.ok_or_else(|| Error::Validation(format!("供应商 {} 没有价格，无法报价", row.id)))?;
+    let components: Vec<(&str, f64)> = [("服务器", Some(price)), ("带宽", row.bandwidth_price), ("存储", row.storage_price)]
+        .into_iter()
+        .filter_map(|(item, cost)| cost.map(|c| (item, c)))
+        .collect();
+
+    // 先计算每台售价，服务器一项承担固定加价和最低毛利的补差
+    let mut unit_prices: Vec<f64> = components
+        .iter()
+        .enumerate()
+        .map(|(i, (_, cost))| match rule.map(|r| (r.kind, r.value)) {
+            Some((MarkupKind::Percent, v)) => apply_markup(*cost, v),
+            Some((MarkupKind::Fixed, v)) if i == 0 => round_cents(cost + v),
+            _ => *cost,
+        })
+        .collect();
+    if let Some(min) = rule.and_then(|r| r.min_margin) {
+        let unit_margin: f64 = unit_prices.iter().zip(&components).map(|(p, (_, c))| p - c).sum();
+        if unit_margin < min {
+            unit_prices[0] = round_cents(unit_prices[0] + min - unit_margin);
+        }
+    }
+
+    let qty = f64::from(quantity);
+    let lines: Vec<QuoteLine> = components
+        .iter()
+        .zip(unit_prices)
+        .map(|((item, unit_cost), unit_price)| {
+            let cost = round_cents(unit_cost * qty);
+            let price = round_cents(unit_price * qty);
+            QuoteLine { item: item.to_string(), unit_cost: *unit_cost, unit_price, cost, price, margin: round_cents(price - cost) }
+        })
+        .collect();
+    let total_cost = round_cents(lines.iter().map(|l| l.cost).sum());
+    let total_price = round_cents(lines.iter().map(|l| l.price).sum());
+    let margin = round_cents(total_price - total_cost);
+    let margin_rate = if total_price > 0.0 { margin / total_price } else { 0.0 };
+    Ok(PriceQuote { supplier_id: row.id, quantity, rule: rule.cloned(), lines, total_cost, total_price, margin, margin_rate })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Supplier;
+
+    fn row(id: i64) -> SupplierRow {
+        Supplier {
+            contact: Some("张三".to_string()),
+            location: Some("北京市".to_string()),
+            networking_category: Some("IB".to_string()),
+            rental_model: Some("包月".to_string()),
+            quantity: Some(8),
+            price: Some(1000.0),
+            bandwidth_price: Some(50.0),
+            ..Default::default()
+        }
+        .to_row(id)
+    }
+
+    fn rule(id: i64, location: Option<&str>, networking: Option<&str>, kind: MarkupKind, value: f64, min_margin: Option<f64>) -> PricingRule {
+        PricingRule {
+            id,
+            location: location.map(str::to_string),
+            networking_category: networking.map(str::to_string),
+            rental_model: None,
+            kind,
+            value,
+            min_margin,
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_negative_values() {
+        assert!(rule(0, None, None, MarkupKind::Percent, 10.0, Some(0.0)).validate().is_ok());
+        assert!(matches!(rule(0, None, None, MarkupKind::Percent, -1.0, None).validate(), Err(Error::Validation(_))));
+        assert!(matches!(rule(0, None, None, MarkupKind::Fixed, f64::INFINITY, None).validate(), Err(Error::Validation(_))));
+        assert!(matches!(rule(0, None, None, MarkupKind::Fixed, 1.0, Some(-1.0)).validate(), Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn test_matches_uses_synonyms_and_ignores_case() {
+        let synonyms = Synonyms::builtin();
+        assert!(rule(1, Some("Beijing"), Some("ib"), MarkupKind::Percent, 1.0, None).matches(&row(1), &synonyms));
+        assert!(!rule(1, Some("上海"), None, MarkupKind::Percent, 1.0, None).matches(&row(1), &synonyms));
+        // 供应商未填写的字段不匹配有条件的规则
+        let bare = Supplier { contact: Some("李四".to_string()), ..Default::default() }.to_row(2);
+        assert!(!rule(1, Some("北京"), None, MarkupKind::Percent, 1.0, None).matches(&bare, &synonyms));
+        assert!(rule(1, None, None, MarkupKind::Percent, 1.0, None).matches(&bare, &synonyms));
+    }
+
+    #[test]
+    fn test_select_rule_prefers_specific_then_earlier() {
+        let synonyms = Synonyms::builtin();
+        let rules = [
+            rule(1, None, None, MarkupKind::Percent, 10.0, None),
+            rule(2, Some("北京"), None, MarkupKind::Percent, 12.0, None),
+            rule(3, Some("北京"), Some("IB"), MarkupKind::Percent, 15.0, None),
+            rule(4, None, Some("IB"), MarkupKind::Percent, 20.0, None),
+            rule(5, Some("北京"), Some("IB"), MarkupKind::Percent, 18.0, None),
+        ];
+        assert_eq!(select_rule(&rules, &row(1), &synonyms).map(|r| r.id), Some(3));
+        assert_eq!(select_rule(&rules[..2], &row(1), &synonyms).map(|r| r.id), Some(2));
+        assert_eq!(select_rule(&rules[2..], &Supplier::default().to_row(2), &synonyms), None);
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(rule(1, Some("北京"), Some("IB"), MarkupKind::Percent, 15.0, None).describe(), "北京/IB/*: +15%");
+        assert_eq!(rule(1, None, None, MarkupKind::Fixed, 200.0, Some(300.0)).describe(), "*/*/*: +200/台，最低毛利 300/台");
+    }
+
+    #[test]
+    fn test_quote_without_rule_is_at_cost() {
+        let quote = quote(&row(1), 2, None).unwrap();
+        assert_eq!(quote.lines.len(), 2);
+        assert_eq!((quote.total_cost, quote.total_price, quote.margin, quote.margin_rate), (2100.0, 2100.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_quote_percent_applies_to_every_line() {
+        let quote = quote(&row(1), 2, Some(&rule(1, None, None, MarkupKind::Percent, 10.0, None))).unwrap();
+        assert_eq!(quote.lines[0].unit_price, 1100.0);
+        assert_eq!(quote.lines[1].unit_price, 55.0);
+        assert_eq!((quote.total_price, quote.margin), (2310.0, 210.0));
+    }
+
+    #[test]
+    fn test_quote_fixed_markup_tops_up_min_margin() {
+        // 固定加价 200 不足最低毛利 300，服务器一项补足差额
+        let quote = quote(&row(1), 4, Some(&rule(1, None, None, MarkupKind::Fixed, 200.0, Some(300.0)))).unwrap();
+        assert_eq!(quote.lines[0].unit_price, 1300.0);
+        assert_eq!(quote.lines[1].unit_price, 50.0);
+        assert_eq!((quote.total_cost, quote.total_price, quote.margin), (4200.0, 5400.0, 1200.0));
+    }
+
+    #[test]
+    fn test_quote_validation() {
+        assert!(matches!(quote(&row(1), 0, None), Err(Error::Validation(_))));
+        assert!(matches!(quote(&row(1), 9, None), Err(Error::Validation(_))), "超过可提供数量");
+        let no_price = Supplier { contact: Some("李四".to_string()), ..Default::default() }.to_row(2);
+        assert!(matches!(quote(&no_price, 1, None), Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn test_markup_kind_from_str() {
+        assert_eq!("Percent".parse::<MarkupKind>(), Ok(MarkupKind::Percent));
+        assert!("ratio".parse::<MarkupKind>().is_err());
+    }
+}
@@ -0,0 +1,57 @@
+//! 个人信息脱敏
+//!
+//! 面向客户输出时隐藏供应商的电话和微信，如 `13812348000` 显示为 `138****8000`。
+//! 脱敏作用于查询结果本身，因此表格、JSON、CSV、Markdown、HTML 和 Excel 输出一致。
+
+use crate::model::SupplierRow;
+
+/// 替换被隐藏部分的标记
+const MASK: &str = "****";
+
+/// 隐藏文本中间部分：8个字符及以上保留前3后4，3个字符及以上保留首尾各1，更短时全部隐藏
+pub fn mask_text(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    let (head, tail) = match chars.len() {
+        n if n >= 8 => (3, 4),
+        n if n >= 3 => (1, 1),
+        _ => (0, 0),
+    };
+    let mut masked: String = chars[..head].iter().collect();
+    masked.push_str(MASK);
+    masked.extend(&chars[chars.len() - tail..]);
+    masked
+}
+
+/// 对查询结果中的电话和微信脱敏，空值保持为空
+pub fn mask_rows(rows: &mut [SupplierRow]) {
+    for row in rows {
+        for value in [&mut row.phone, &mut row.wechat].into_iter().flatten() {
+            *value = mask_text(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Supplier;
+
+    // 测试电话、微信脱敏规则
+    #[test]
+    fn test_mask_pii() {
+        assert_eq!(mask_text("13812348000"), "138****8000");
+        assert_eq!(mask_text("wxid_abc123"), "wxi****c123");
+        assert_eq!(mask_text("张三丰"), "张****丰");
+        assert_eq!(mask_text("ab"), "****");
+
+        let mut rows = vec![Supplier {
+            contact: Some("张三".to_string()),
+            phone: Some("13812348000".to_string()),
+            ..Default::default()
+        }.to_row(1)];
+        mask_rows(&mut rows);
+        assert_eq!(rows[0].phone.as_deref(), Some("138****8000"));
+        assert_eq!(rows[0].wechat, None);
+        assert_eq!(rows[0].contact.as_deref(), Some("张三"), "联系人不脱敏");
+    }
+}
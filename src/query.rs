@@ -0,0 +1,686 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::matching::{MatchMode, Synonyms};
+use crate::model::{Column, SupplierField, SupplierRow};
+
+/// 浮点数字段比较时的绝对容差，避免 1000 与 1000.0000001 因舍入误差不相等
+pub const FLOAT_TOLERANCE: f64 = 1e-6;
+
+/// 单个 IN 列表最多包含的值个数
+const MAX_IN_VALUES: usize = 50;
+
+/// 比较操作符
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComparisonOp {
+    Eq,         // =
+    Neq,        // !=
+    Gt,         // >
+    Lt,         // <
+    Gte,        // >=
+    Lte,        // <=
+    Like,       // LIKE
+    IsNull,     // IS NULL
+    IsNotNull,  // IS NOT NULL
+}
+
+/// 单个筛选条件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilterCriteria {
+    pub field: SupplierField,
+    pub op: ComparisonOp,
+    pub value: Option<String>, // IS NULL/IS NOT NULL 时为 None
+    /// 文本匹配方式，仅对文本字段的 = 和 != 生效
+    #[serde(default)]
+    pub mode: MatchMode,
+}
+
+/// 可嵌套的筛选条件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    /// 单个比较条件
+    Filter(FilterCriteria),
+    /// 字段取值属于列表之一（IN）
+    In { field: SupplierField, values: Vec<String> },
+    /// 所有子条件均成立（AND）
+    All(Vec<Condition>),
+    /// 任一子条件成立（OR）
+    Any(Vec<Condition>),
+    /// 子条件不成立（NOT）
+    Not(Box<Condition>),
+    /// 综合评分比较（见 [`crate::rating`]），IS NULL / IS NOT NULL 时值为 `None`
+    Score { op: ComparisonOp, value: Option<f64> },
+}
+
+/// 排序键，如 `price` 或 `price:desc`；序列化为同样的文本形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct SortKey {
+    pub column: Column,
+    pub descending: bool,
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (name, dir) = s.split_once(':').unwrap_or((s, "asc"));
+        let descending = match dir.trim().to_lowercase().as_str() {
+            "asc" => false,
+            "desc" => true,
+            other => return Err(format!("未知排序方向: {}（应为 asc 或 desc）", other)),
+        };
+        Ok(SortKey { column: name.parse()?, descending })
+    }
+}
+
+impl TryFrom<String> for SortKey {
+    type Error = String;
+    fn try_from(s: String) -> std::result::Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<SortKey> for String {
+    fn from(key: SortKey) -> Self {
+        key.to_string()
+    }
+}
+
+impl std::fmt::Display for SortKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.column.name(), if self.descending { "desc" } else { "asc" })
+    }
+}
+
+/// 查询构建器主结构，顶层条件之间以 AND 连接
+#[derive(Debug, Clone, Default)]
+pub struct QueryBuilder {
+    conditions: Vec<Condition>,
+    synonyms: Option<Synonyms>,
+    order: Vec<SortKey>,
+}
+
+impl QueryBuilder {
+    pub fn new() -> Self {
+        Self { conditions: vec![], synonyms: None, order: vec![] }
+    }
+
+    /// 追加排序键，按添加顺序依次排序
+    pub fn order_by(mut self, key: SortKey) -> Self {
+        self.order.push(key);
+        self
+    }
+
+    /// 按地点和服务器名筛选时使用的同义词表
+    pub fn with_synonyms(mut self, synonyms: Synonyms) -> Self {
+        self.synonyms = Some(synonyms);
+        self
+    }
+
+    pub fn filter(mut self, criteria: FilterCriteria) -> Self {
+        self.conditions.push(Condition::Filter(criteria));
+        self
+    }
+
+    /// 添加任意条件（包括 IN 和嵌套的 Any/All/Not 分组）
+    pub fn condition(mut self, condition: Condition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+
+    /// 校验筛选条件组合的有效性和安全性
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        let mut fields = HashSet::new();
+        let count: usize = self.conditions.iter().map(Condition::leaf_count).sum();
+        if count > 20 {
+            return Err("筛选条件过多，最多支持20个条件".to_string());
+        }
+        for c in &self.conditions {
+            // 检查重复字段（OR 分组内同一字段出现多次是正常的，只检查顶层）
+            if let Condition::Filter(f) = c {
+                let key = format!("{:?}-{:?}", f.field, f.op);
+                if !fields.insert(key) {
+                    return Err("存在重复字段和操作符的筛选条件".to_string());
+                }
+            }
+            c.validate()?;
+        }
+        Ok(())
+    }
+
+    /// 是否已指定排序键
+    pub fn is_ordered(&self) -> bool {
+        !self.order.is_empty()
+    }
+
+    /// 取出筛选条件和排序键，与输出列一起保存为视图
+    pub fn into_view(self, columns: Vec<Column>) -> SavedView {
+        SavedView { conditions: self.conditions, sort: self.order, columns }
+    }
+
+    /// 取出排序键，返回不含排序的构建器；用于在内存中对合并后的结果排序
+    pub(crate) fn take_order(mut self) -> (Self, Vec<SortKey>) {
+        let order = std::mem::take(&mut self.order);
+        (self, order)
+    }
+
+    /// 构建 WHERE 子句（含 ORDER BY）和参数列表，条件不合法时返回校验错误
+    pub fn build(self) -> Result<(String, Vec<String>)> {
+        self.validate().map_err(Error::Validation)?;
+        let mut params = Vec::new();
+        let synonyms = self.synonyms.as_ref();
+        let clauses: Vec<String> = self.conditions.iter().map(|c| c.to_sql(synonyms, &mut params)).collect();
+        let mut where_sql = if clauses.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", clauses.join(" AND "))
+        };
+        if self.order.iter().any(|k| k.column == Column::Profile) {
+            return Err(Error::Validation("profile 列只能在跨 profile 查询中排序".to_string()));
+        }
+        if !self.order.is_empty() {
+            let keys: Vec<String> = self.order.iter()
+                .map(|k| format!("{} {}", k.column.name(), if k.descending { "DESC" } else { "ASC" }))
+                .collect();
+            // 追加 id 保证相同取值时顺序稳定
+            where_sql = format!("{} ORDER BY {}, id", where_sql, keys.join(", ")).trim_start().to_string();
+        }
+        Ok((where_sql, params))
+    }
+}
+
+/// 命名视图保存的查询：筛选条件、排序键和输出列
+///
+/// 只包含决定“查哪些记录、按什么顺序、显示哪些列”的部分；输出格式、导出文件和脱敏等选项
+/// 在每次执行时单独指定，不随视图保存。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SavedView {
+    pub conditions: Vec<Condition>,
+    #[serde(default)]
+    pub sort: Vec<SortKey>,
+    #[serde(default)]
+    pub columns: Vec<Column>,
+}
+
+impl SavedView {
+    /// 在 `builder` 上追加视图的筛选条件；`builder` 未指定排序时使用视图保存的排序
+    pub fn apply(&self, builder: QueryBuilder) -> QueryBuilder {
+        let mut builder = self.conditions.iter().cloned().fold(builder, QueryBuilder::condition);
+        if builder.order.is_empty() {
+            builder.order = self.sort.clone();
+        }
+        builder
+    }
+}
+
+/// 以 query 参数的形式显示视图，如 `--filter "location = 北京" --sort price:desc --columns contact,price`
+impl std::fmt::Display for SavedView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+        if !self.conditions.is_empty() {
+            let filter: Vec<String> = self.conditions.iter().map(Condition::to_string).collect();
+            parts.push(format!("--filter \"{}\"", filter.join(" and ")));
+        }
+        if !self.sort.is_empty() {
+            let sort: Vec<String> = self.sort.iter().map(SortKey::to_string).collect();
+            parts.push(format!("--sort {}", sort.join(",")));
+        }
+        if !self.columns.is_empty() {
+            let columns: Vec<&str> = self.columns.iter().map(Column::name).collect();
+            parts.push(format!("--columns {}", columns.join(",")));
+        }
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+impl std::fmt::Display for ComparisonOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let symbol = match self {
+            ComparisonOp::Eq => "=",
+            ComparisonOp::Neq => "!=",
+            ComparisonOp::Gt => ">",
+            ComparisonOp::Lt => "<",
+            ComparisonOp::Gte => ">=",
+            ComparisonOp::Lte => "<=",
+            ComparisonOp::Like => "like",
+            ComparisonOp::IsNull => "is null",
+            ComparisonOp::IsNotNull => "is not null",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+/// 含空白或筛选表达式中的特殊字符时加引号
+fn quote_value(value: &str) -> String {
+    let plain = !value.is_empty() && !value.chars().any(|c| c.is_whitespace() || "()[],'\"=<>!".contains(c));
+    match (plain, value.contains('\'')) {
+        (true, _) => value.to_string(),
+        (false, false) => format!("'{}'", value),
+        (false, true) => format!("\"{}\"", value),
+    }
+}
+
+/// 按筛选表达式的语法显示条件，非精确匹配在条件后注明匹配方式，如 `location = 北京 (contains)`
+impl std::fmt::Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let group = |items: &[Condition], sep: &str| {
+            let items: Vec<String> = items.iter().map(Condition::to_string).collect();
+            format!("({})", items.join(sep))
+        };
+        match self {
+            Condition::Filter(c) => {
+                write!(f, "{} {}", c.field.column(), c.op)?;
+                if let Some(value) = &c.value {
+                    write!(f, " {}", quote_value(value))?;
+                }
+                if c.mode != MatchMode::Exact {
+                    write!(f, " ({})", c.mode.name())?;
+                }
+                Ok(())
+            }
+            Condition::In { field, values } => {
+                let values: Vec<String> = values.iter().map(|v| quote_value(v)).collect();
+                write!(f, "{} in [{}]", field.column(), values.join(", "))
+            }
+            Condition::All(items) => write!(f, "{}", group(items, " and ")),
+            Condition::Any(items) => write!(f, "{}", group(items, " or ")),
+            Condition::Not(inner) => write!(f, "not {}", inner),
+            Condition::Score { op, value: Some(value) } => write!(f, "score {} {}", op, value),
+            Condition::Score { op, value: None } => write!(f, "score {}", op),
+        }
+    }
+}
+
+impl Condition {
+    /// 叶子条件个数，用于限制条件总数
+    fn leaf_count(&self) -> usize {
+        match self {
+            Condition::Filter(_) | Condition::In { .. } | Condition::Score { .. } => 1,
+            Condition::All(items) | Condition::Any(items) => items.iter().map(Condition::leaf_count).sum(),
+            Condition::Not(inner) => inner.leaf_count(),
+        }
+    }
+
+    fn validate(&self) -> std::result::Result<(), String> {
+        match self {
+            Condition::Filter(f) => {
+                // 验证字段和操作符的兼容性
+                if !op_supported(f.field, &f.op) {
+                    return Err(format!("字段 '{:?}' 不支持操作符 '{:?}'.", f.field, f.op));
+                }
+                let text_eq = f.field.is_string() && matches!(f.op, ComparisonOp::Eq | ComparisonOp::Neq);
+                if f.mode != MatchMode::Exact && !text_eq {
+                    return Err(format!("匹配方式 {} 仅适用于文本字段的 = 和 != 条件", f.mode.name()));
+                }
+                if let Some(val) = &f.value {
+                    check_param_length(val)?;
+                    check_numeric_value(f.field, val)?;
+                }
+            }
+            Condition::In { field, values } => {
+                if values.is_empty() {
+                    return Err("IN 列表不能为空".to_string());
+                }
+                if values.len() > MAX_IN_VALUES {
+                    return Err(format!("IN 列表过长，最多支持{}个值", MAX_IN_VALUES));
+                }
+                for val in values {
+                    check_param_length(val)?;
+                    check_numeric_value(*field, val)?;
+                }
+            }
+            Condition::All(items) | Condition::Any(items) => {
+                for item in items {
+                    item.validate()?;
+                }
+            }
+            Condition::Not(inner) => inner.validate()?,
+            Condition::Score { op, value } => {
+                if matches!(op, ComparisonOp::Like) {
+                    return Err("评分不支持 like".to_string());
+                }
+                if value.is_none() && !matches!(op, ComparisonOp::IsNull | ComparisonOp::IsNotNull) {
+                    return Err("评分比较缺少数值".to_string());
+                }
+                if value.is_some_and(|v| !v.is_finite()) {
+                    return Err("评分必须是有效的数字".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 为其中所有文本等值条件设置匹配方式；非精确匹配时 IN 列表改写为 OR 分组
+    pub fn with_match_mode(self, mode: MatchMode) -> Self {
+        match self {
+            Condition::Filter(mut f) => {
+                if f.field.is_string() && matches!(f.op, ComparisonOp::Eq | ComparisonOp::Neq) {
+                    f.mode = mode;
+                }
+                Condition::Filter(f)
+            }
+            Condition::In { field, values } if field.is_string() && mode != MatchMode::Exact => {
+                Condition::Any(values.into_iter().map(|v| Condition::Filter(FilterCriteria {
+                    field,
+                    op: ComparisonOp::Eq,
+                    value: Some(v),
+                    mode,
+                })).collect())
+            }
+            Condition::In { .. } | Condition::Score { .. } => self,
+            Condition::All(items) => Condition::All(items.into_iter().map(|c| c.with_match_mode(mode)).collect()),
+            Condition::Any(items) => Condition::Any(items.into_iter().map(|c| c.with_match_mode(mode)).collect()),
+            Condition::Not(inner) => Condition::Not(Box::new(inner.with_match_mode(mode))),
+        }
+    }
+
+    /// 生成SQL片段，参数按出现顺序追加到 `params`
+    fn to_sql(&self, synonyms: Option<&Synonyms>, params: &mut Vec<String>) -> String {
+        match self {
+            Condition::Filter(f) => f.to_sql(synonyms, params),
+            Condition::In { field, values } => {
+                let column = field.column();
+                let mut expanded: Vec<String> = Vec::new();
+                for v in values {
+                    for word in expand(synonyms, *field, v) {
+                        if !expanded.contains(&word) {
+                            expanded.push(word);
+                        }
+                    }
+                }
+                let values = expanded;
+                params.extend(values.iter().cloned());
+                if field.is_float() {
+                    let eq = format!("ABS({} - ?) <= {}", column, FLOAT_TOLERANCE);
+                    format!("({})", vec![eq; values.len()].join(" OR "))
+                } else {
+                    format!("{} IN ({})", column, vec!["?"; values.len()].join(", "))
+                }
+            }
+            // 空分组：All 恒为真，Any 恒为假
+            Condition::All(items) if items.is_empty() => "1".to_string(),
+            Condition::Any(items) if items.is_empty() => "0".to_string(),
+            Condition::All(items) => group_sql(items, " AND ", synonyms, params),
+            Condition::Any(items) => group_sql(items, " OR ", synonyms, params),
+            Condition::Not(inner) => format!("NOT ({})", inner.to_sql(synonyms, params)),
+            Condition::Score { op: ComparisonOp::IsNull, .. } => format!("{} IS NULL", Column::Score.name()),
+            Condition::Score { op: ComparisonOp::IsNotNull, .. } => format!("{} IS NOT NULL", Column::Score.name()),
+            Condition::Score { op, value } => {
+                params.push(value.unwrap_or_default().to_string());
+                float_clause(Column::Score.name(), op)
+            }
+        }
+    }
+}
+
+fn group_sql(items: &[Condition], sep: &str, synonyms: Option<&Synonyms>, params: &mut Vec<String>) -> String {
+    let parts: Vec<String> = items.iter().map(|c| c.to_sql(synonyms, params)).collect();
+    format!("({})", parts.join(sep))
+}
+
+/// 按同义词表扩展关键字，字段不适用同义词时只返回自身
+fn expand(synonyms: Option<&Synonyms>, field: SupplierField, value: &str) -> Vec<String> {
+    match synonyms {
+        Some(s) if Synonyms::applies_to(field) => s.expand(field, value),
+        _ => vec![value.to_string()],
+    }
+}
+
+fn check_param_length(val: &str) -> std::result::Result<(), String> {
+    // 检查参数长度
+    if val.len() > 256 {
+        let end = (0..=32.min(val.len())).rev().find(|&i| val.is_char_boundary(i)).unwrap_or(0);
+        return Err(format!("参数过长: {}...", &val[..end]));
+    }
+    Ok(())
+}
+
+/// 数值字段的比较值必须是数字（数量为整数），否则 SQLite 会按文本比较，结果不符合预期
+fn check_numeric_value(field: SupplierField, val: &str) -> std::result::Result<(), String> {
+    if field.is_float() && !val.trim().parse::<f64>().is_ok_and(f64::is_finite) {
+        return Err(format!("字段 {} 的值必须是数字: {}", field.column(), val));
+    }
+    if field == SupplierField::Quantity && val.trim().parse::<i64>().is_err() {
+        return Err(format!("字段 {} 的值必须是整数: {}", field.column(), val));
+    }
+    Ok(())
+}
+
+/// 字段类型与操作符是否兼容
+fn op_supported(field: SupplierField, op: &ComparisonOp) -> bool {
+    match op {
+        ComparisonOp::Like => field.is_string(),
+        ComparisonOp::Gt | ComparisonOp::Lt | ComparisonOp::Gte | ComparisonOp::Lte => field.is_numeric(),
+        _ => true, // =、!=、IS NULL、IS NOT NULL 适用于所有类型
+    }
+}
+
+/// 浮点数字段的容差比较子句
+fn float_clause(field: &str, op: &ComparisonOp) -> String {
+    let eps = FLOAT_TOLERANCE;
+    match op {
+        ComparisonOp::Eq => format!("ABS({} - ?) <= {}", field, eps),
+        ComparisonOp::Neq => format!("ABS({} - ?) > {}", field, eps),
+        ComparisonOp::Gt => format!("{} > ? + {}", field, eps),
+        ComparisonOp::Lt => format!("{} < ? - {}", field, eps),
+        ComparisonOp::Gte => format!("{} >= ? - {}", field, eps),
+        ComparisonOp::Lte => format!("{} <= ? + {}", field, eps),
+        // 浮点字段不支持 LIKE（已在校验阶段拦截），IS NULL 系列不会走到这里
+        _ => format!("{} = ?", field),
+    }
+}
+
+impl FromStr for ComparisonOp {
+    type Err = String;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "=" | "==" | "eq" => Ok(ComparisonOp::Eq),
+            "!=" | "<>" | "neq" => Ok(ComparisonOp::Neq),
+            ">" | "gt" => Ok(ComparisonOp::Gt),
+            "<" | "lt" => Ok(ComparisonOp::Lt),
+            ">=" | "gte" => Ok(ComparisonOp::Gte),
+            "<=" | "lte" => Ok(ComparisonOp::Lte),
+            "like" => Ok(ComparisonOp::Like),
+            "is null" | "isnull" => Ok(ComparisonOp::IsNull),
+            "is not null" | "isnotnull" => Ok(ComparisonOp::IsNotNull),
+            _ => Err(format!("未知操作符: {}", s)),
+        }
+    }
+}
+
+impl FilterCriteria {
+    /// 从 (字段名, 操作符, 值) 解析为 FilterCriteria，并校验类型兼容性
+    pub fn from_str_tuple(field: &str, op: &str, value: Option<&str>) -> std::result::Result<Self, String> {
+        let field_enum = SupplierField::from_str(field)?;
+        let op_enum = ComparisonOp::from_str(op)?;
+        // 类型与操作符兼容性校验
+        if !op_supported(field_enum, &op_enum) {
+            return Err(format!("字段 '{}' 不支持操作符 '{}'.", field, op));
+        }
+        let val = match op_enum {
+            ComparisonOp::IsNull | ComparisonOp::IsNotNull => None,
+            _ => value.map(|v| v.to_string()),
+        };
+        if let Some(v) = &val {
+            check_numeric_value(field_enum, v)?;
+        }
+        Ok(FilterCriteria {
+            field: field_enum,
+            op: op_enum,
+            value: val,
+            mode: MatchMode::Exact,
+        })
+    }
+
+    /// 设置文本匹配方式
+    pub fn with_mode(mut self, mode: MatchMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// 生成单个条件的SQL片段，浮点数字段使用容差比较，文本等值条件按匹配方式和同义词展开；
+    /// 关键字本身按匹配方式比较，同组的其他写法只按整值比较
+    fn to_sql(&self, synonyms: Option<&Synonyms>, params: &mut Vec<String>) -> String {
+        let field = self.field.column();
+        if let (true, Some(value), ComparisonOp::Eq | ComparisonOp::Neq) = (self.field.is_string(), &self.value, &self.op) {
+            let words = expand(synonyms, self.field, value);
+            if self.mode != MatchMode::Exact || words.len() > 1 {
+                let negate = matches!(self.op, ComparisonOp::Neq);
+                let clause = if self.mode == MatchMode::Exact {
+                    format!("{} {}IN ({})", field, if negate { "NOT " } else { "" }, vec!["?"; words.len()].join(", "))
+                } else {
+                    // 同义词只按整值（忽略大小写）比较：包含、前缀、模糊只用于关键字本身，
+                    // 否则“京”“SH”这类短别名会匹配到南京、Shenzhen
+                    let alias = format!("text_match('{}', {}, ?)", MatchMode::Icase.name(), field);
+                    let own = format!("text_match('{}', {}, ?)", self.mode.name(), field);
+                    let mut parts = vec![own];
+                    parts.extend(vec![alias; words.len() - 1]);
+                    let any = parts.join(" OR ");
+                    if negate { format!("NOT ({})", any) } else { format!("({})", any) }
+                };
+                params.extend(words);
+                return clause;
+            }
+        }
+        let clause = match (self.field.is_float(), &self.op) {
+            (_, ComparisonOp::IsNull) => return format!("{} IS NULL", field),
+            (_, ComparisonOp::IsNotNull) => return format!("{} IS NOT NULL", field),
+            (true, op) => float_clause(field, op),
+            (false, ComparisonOp::Eq) => format!("{} = ?", field),
+            (false, ComparisonOp::Neq) => format!("{} != ?", field),
+            (false, ComparisonOp::Gt) => format!("{} > ?", field),
+            (false, ComparisonOp::Lt) => format!("{} < ?", field),
+            (false, ComparisonOp::Gte) => format!("{} >= ?", field),
+            (false, ComparisonOp::Lte) => format!("{} <= ?", field),
+            (false, ComparisonOp::Like) => format!("{} LIKE ?", field),
+        };
+        if let Some(val) = &self.value {
+            params.push(val.clone());
+        }
+        clause
+    }
+}
+
+/// 在内存中按排序键排序（稳定排序），取值相同时依次按 profile、ID 排序
+pub fn sort_rows(rows: &mut [SupplierRow], keys: &[SortKey]) {
+    rows.sort_by(|a, b| {
+        keys.iter()
+            .map(|k| {
+                let ord = a.compare(b, k.column);
+                if k.descending { ord.reverse() } else { ord }
+            })
+            .chain([a.compare(b, Column::Profile), a.compare(b, Column::Id)])
+            .find(|o| o.is_ne())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_filter;
+
+    fn build(expr: &str) -> (String, Vec<String>) {
+        QueryBuilder::new().condition(parse_filter(expr).unwrap()).build().unwrap()
+    }
+
+    #[test]
+    fn test_filter_expression_in_and_comparison() {
+        let (sql, params) = build("(location in [北京, \"上海\"]) and price < 1500");
+        assert_eq!(sql, "WHERE (location IN (?, ?) AND price < ? - 0.000001)");
+        assert_eq!(params, ["北京", "上海", "1500"]);
+    }
+
+    #[test]
+    fn test_filter_expression_or_not_and_null() {
+        let (sql, params) = build("contact = 张三 or NOT (quantity >= 8 and wechat is not null)");
+        assert_eq!(sql, "WHERE (contact = ? OR NOT ((quantity >= ? AND wechat IS NOT NULL)))");
+        assert_eq!(params, ["张三", "8"]);
+    }
+
+    #[test]
+    fn test_filter_expression_not_in_and_like() {
+        let (sql, _) = build("地点 not in [广州] and server-name like 'A100%'");
+        assert_eq!(sql, "WHERE (NOT (location IN (?)) AND server_name LIKE ?)");
+    }
+
+    #[test]
+    fn test_filter_expression_rejects_like_on_numeric_field() {
+        // 字段类型与操作符不兼容在构建时报错
+        let cond = parse_filter("price like 1%").unwrap();
+        assert!(matches!(QueryBuilder::new().condition(cond).build(), Err(Error::Validation(_))));
+    }
+
+    fn build_filter(criteria: FilterCriteria) -> Result<(String, Vec<String>)> {
+        QueryBuilder::new().filter(criteria).with_synonyms(Synonyms::builtin()).build()
+    }
+
+    #[test]
+    fn test_exact_match_expands_synonyms() {
+        let (sql, params) = build_filter(FilterCriteria::from_str_tuple("location", "=", Some("Beijing")).unwrap()).unwrap();
+        assert!(sql.starts_with("WHERE location IN (?"));
+        assert!(params.contains(&"北京市".to_string()));
+    }
+
+    #[test]
+    fn test_negated_icase_match_with_synonyms() {
+        let criteria = FilterCriteria::from_str_tuple("server_name", "!=", Some("4090")).unwrap().with_mode(MatchMode::Icase);
+        let (sql, _) = build_filter(criteria).unwrap();
+        assert!(sql.starts_with("WHERE NOT (text_match('icase', server_name, ?) OR"));
+    }
+
+    #[test]
+    fn test_contains_match_compares_aliases_whole() {
+        // 只有用户输入按包含匹配，别名按整个取值比较
+        let criteria = FilterCriteria::from_str_tuple("location", "=", Some("北京")).unwrap().with_mode(MatchMode::Contains);
+        let (sql, params) = build_filter(criteria).unwrap();
+        assert!(sql.starts_with("WHERE (text_match('contains', location, ?) OR text_match('icase', location, ?)"));
+        assert!(!sql.contains("text_match('contains', location, ?) OR text_match('contains'"));
+        assert_eq!(params[0], "北京");
+    }
+
+    #[test]
+    fn test_numeric_field_rejects_non_exact_mode() {
+        let criteria = FilterCriteria::from_str_tuple("price", "=", Some("1")).unwrap().with_mode(MatchMode::Fuzzy);
+        assert!(build_filter(criteria).is_err());
+    }
+
+    #[test]
+    fn test_sort_key_parse_and_display() {
+        let key: SortKey = "price:desc".parse().unwrap();
+        assert_eq!(key.to_string(), "price:desc");
+        assert_eq!("带宽价".parse::<SortKey>().unwrap().to_string(), "bandwidth_price:asc");
+        assert!("price:up".parse::<SortKey>().is_err());
+        assert!("unknown".parse::<Column>().is_err());
+    }
+
+    #[test]
+    fn test_order_by_sql() {
+        let (sql, _) = QueryBuilder::new()
+            .order_by("quantity:desc".parse().unwrap())
+            .order_by("location".parse().unwrap())
+            .build()
+            .unwrap();
+        assert_eq!(sql, "ORDER BY quantity DESC, location ASC, id");
+    }
+
+    #[test]
+    fn test_sort_rows_nulls_first() {
+        // 合并结果在内存中排序，空值在前，与SQLite一致
+        let mut rows = vec![
+            crate::Supplier { contact: Some("甲".to_string()), price: Some(20.0), ..Default::default() }.to_row(1),
+            crate::Supplier { contact: Some("乙".to_string()), ..Default::default() }.to_row(1),
+            crate::Supplier { contact: Some("丙".to_string()), price: Some(10.0), ..Default::default() }.to_row(2),
+        ];
+        rows[0].profile = Some("b".to_string());
+        rows[1].profile = Some("a".to_string());
+        sort_rows(&mut rows, &["price".parse().unwrap()]);
+        let order: Vec<&str> = rows.iter().map(|r| r.contact.as_deref().unwrap()).collect();
+        assert_eq!(order, ["乙", "丙", "甲"]);
+    }
+}
@@ -0,0 +1,451 @@
+//! 客户报价单
+//!
+//! 由供应商记录和按定价规则计算的 [`PriceQuote`] 生成报价单，按月数计算总价，编号形如
+//! `Q20240101-001`（按日期递增），保存在 `quotes` 表中。报价单面向客户，只包含报价编号和
+//! 资源条款，不含供应商ID、联系方式和成本。
+//!
+//! 报价单由模板生成，模板中的 `{{名称}}` 替换为对应字段，可用的名称见 [`PLACEHOLDERS`]；
+//! 其中 `{{lines}}` 为各项费用的表格行，按输出格式生成 Markdown 或 HTML。
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::pricing::PriceQuote;
+use crate::render::{escape_html, escape_markdown};
+use crate::SupplierRow;
+
+/// 报价单编号前缀
+pub const QUOTE_NO_PREFIX: &str = "Q";
+
+/// 模板中可用的占位符
+pub const PLACEHOLDERS: [&str; 20] = [
+    "quote_no", "created_on", "valid_until", "customer", "offer", "quantity", "months",
+    "location", "server_name", "server_config", "networking_category", "rental_model",
+    "min_contract_period", "payment_terms", "breach_penalties", "currency",
+    "lines", "monthly_total", "total", "notes",
+];
+
+/// 内置 Markdown 模板
+pub const MARKDOWN_TEMPLATE: &str = r#"# 报价单 {{quote_no}}
+
+- 客户：{{customer}}
+- 报价日期：{{created_on}}
+- 有效期至：{{valid_until}}
+- 资源编号：{{offer}}
+
+## 资源配置
+
+| 项目 | 内容 |
+|---|---|
+| 地点 | {{location}} |
+| 服务器 | {{server_name}} |
+| 配置 | {{server_config}} |
+| 组网 | {{networking_category}} |
+| 租赁模式 | {{rental_model}} |
+
+## 费用（{{currency}}）
+
+| 项目 | 单价/台/月 | 数量 | 月费 | {{months}}个月合计 |
+|---|---:|---:|---:|---:|
+{{lines}}
+
+月费合计 **{{monthly_total}}**，{{months}} 个月总价 **{{total}}**。
+
+## 条款
+
+- 最短合同期：{{min_contract_period}}
+- 付款方式：{{payment_terms}}
+- 违约条款：{{breach_penalties}}
+{{notes}}
+"#;
+
+/// 内置 HTML 模板
+pub const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="zh-CN">
+<head>
+<meta charset="utf-8">
+<title>报价单 {{quote_no}}</title>
+<style>
+body { font-family: -apple-system, "Segoe UI", "PingFang SC", "Microsoft YaHei", sans-serif; margin: 32px; color: #222; }
+table { border-collapse: collapse; margin: 12px 0; }
+th, td { border: 1px solid #ddd; padding: 6px 12px; }
+th { background: #f4f6f8; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+</style>
+</head>
+<body>
+<h1>报价单 {{quote_no}}</h1>
+<p>客户：{{customer}}<br>报价日期：{{created_on}}<br>有效期至：{{valid_until}}<br>资源编号：{{offer}}</p>
+<h2>资源配置</h2>
+<table>
+<tr><th>地点</th><td>{{location}}</td></tr>
+<tr><th>服务器</th><td>{{server_name}}</td></tr>
+<tr><th>配置</th><td>{{server_config}}</td></tr>
+<tr><th>组网</th><td>{{networking_category}}</td></tr>
+<tr><th>租赁模式</th><td>{{rental_model}}</td></tr>
+</table>
+<h2>费用（{{currency}}）</h2>
+<table>
+<tr><th>项目</th><th>单价/台/月</th><th>数量</th><th>月费</th><th>{{months}}个月合计</th></tr>
+{{lines}}
+</table>
+<p>月费合计 <strong>{{monthly_total}}</strong>，{{months}} 个月总价 <strong>{{total}}</strong>。</p>
+<h2>条款</h2>
+<ul>
+<li>最短合同期：{{min_contract_period}}</li>
+<li>付款方式：{{payment_terms}}</li>
+<li>违约条款：{{breach_penalties}}</li>
+</ul>
+<p>{{notes}}</p>
+</body>
+</html>
+"#;
+
+/// 报价单输出格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum QuoteFormat {
+    #[default]
+    Markdown,
+    Html,
+}
+
+impl QuoteFormat {
+    /// 内置模板
+    pub fn builtin_template(self) -> &'static str {
+        match self {
+            QuoteFormat::Markdown => MARKDOWN_TEMPLATE,
+            QuoteFormat::Html => HTML_TEMPLATE,
+        }
+    }
+}
+
+/// 报价单中的一项费用
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuotationLine {
+    pub item: String,
+    /// 每台每月单价
+    pub unit_price: f64,
+    /// 每月金额
+    pub monthly: f64,
+    /// 全部月数的金额
+    pub amount: f64,
+}
+
+/// 一份报价单
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Quotation {
+    pub quote_no: String,
+    pub created_on: String,
+    pub valid_until: String,
+    pub customer: String,
+    /// 供应商ID，仅内部使用，不出现在报价单中
+    pub supplier_id: i64,
+    /// 报价编号（见 `catalog`），报价单中以此代替供应商
+    pub offer_code: String,
+    pub quantity: u32,
+    pub months: u32,
+    pub location: Option<String>,
+    pub server_name: Option<String>,
+    pub server_config: Option<String>,
+    pub networking_category: Option<String>,
+    pub rental_model: Option<String>,
+    pub min_contract_period: Option<String>,
+    pub payment_terms: Option<String>,
+    pub breach_penalties: Option<String>,
+    pub lines: Vec<QuotationLine>,
+    pub monthly_total: f64,
+    pub total: f64,
+    /// 总成本，仅内部使用
+    pub total_cost: f64,
+    pub notes: Option<String>,
+}
+
+/// 生成报价单需要的参数，编号和日期由存储层分配
+#[derive(Debug, Clone)]
+pub struct QuoteRequest<'a> {
+    pub customer: &'a str,
+    pub months: u32,
+    pub valid_days: u32,
+    pub notes: Option<&'a str>,
+}
+
+fn round_cents(v: f64) -> f64 {
+    (v * 100.0).round() / 100.0
+}
+
+impl Quotation {
+    /// 由供应商记录和按月计算的报价生成报价单
+    pub fn new(
+        quote_no: String,
+        created_on: String,
+        valid_until: String,
+        offer_code: String,
+        row: &SupplierRow,
+        price: &PriceQuote,
+        request: &QuoteRequest,
+    ) -> Result<Self> {
+        if request.customer.trim().is_empty() {
+            return Err(Error::Validation("客户名称不能为空".to_string()));
+        }
+        if request.months == 0 {
+            return Err(Error::Validation("租期月数必须大于0".to_string()));
+        }
+        let months = f64::from(request.months);
+        let lines = price
+            .lines
+            .iter()
+            .map(|l| QuotationLine { item: l.item.clone(), unit_price: l.unit_price, monthly: l.price, amount: round_cents(l.price * months) })
+            .collect::<Vec<_>>();
+        Ok(Self {
+            quote_no,
+            created_on,
+            valid_until,
+            customer: request.customer.trim().to_string(),
+            supplier_id: row.id,
+            offer_code,
+            quantity: price.quantity,
+            months: request.months,
+            location: row.location.clone(),
+            server_name: row.server_name.clone(),
+            server_config: row.server_config.clone(),
+            networking_category: row.networking_category.clone(),
+            rental_model: row.rental_model.clone(),
+            min_contract_period: row.min_contract_period.clone(),
+            payment_terms: row.payment_terms.clone(),
+            breach_penalties: row.breach_penalties.clone(),
+            total: round_cents(lines.iter().map(|l| l.amount).sum()),
+            monthly_total: price.total_price,
+            total_cost: round_cents(price.total_cost * months),
+            lines,
+            notes: request.notes.map(str::to_string),
+        })
+    }
+
+    /// 占位符对应的文本，未填写的字段显示为 `-`
+    fn value(&self, name: &str, currency: &str) -> Option<String> {
+        let text = |v: &Option<String>| v.clone().filter(|v| !v.is_empty()).unwrap_or_else(|| "-".to_string());
+        Some(match name {
+            "quote_no" => self.quote_no.clone(),
+            "created_on" => self.created_on.clone(),
+            "valid_until" => self.valid_until.clone(),
+            "customer" => self.customer.clone(),
+            "offer" => self.offer_code.clone(),
+            "quantity" => self.quantity.to_string(),
+            "months" => self.months.to_string(),
+            "location" => text(&self.location),
+            "server_name" => text(&self.server_name),
+            "server_config" => text(&self.server_config),
+            "networking_category" => text(&self.networking_category),
+            "rental_model" => text(&self.rental_model),
+            "min_contract_period" => text(&self.min_contract_period),
+            "payment_terms" => text(&self.payment_terms),
+            "breach_penalties" => text(&self.breach_penalties),
+            "currency" => currency.to_string(),
+            "monthly_total" => format!("{:.2}", self.monthly_total),
+            "total" => format!("{:.2}", self.total),
+            "notes" => self.notes.clone().unwrap_or_default(),
+            _ => return None,
+        })
+    }
+
+    /// 费用表格行
+    fn lines(&self, format: QuoteFormat) -> String {
+        self.lines
+            .iter()
+            .map(|l| {
+                let cells = [
+                    l.item.clone(),
+                    format!("{:.2}", l.unit_price),
+                    self.quantity.to_string(),
+                    format!("{:.2}", l.monthly),
+                    format!("{:.2}", l.amount),
+                ];
+                match format {
+                    QuoteFormat::Markdown => {
+                        let cells: Vec<String> = cells.iter().map(|c| escape_markdown(c)).collect();
+                        format!("| {} |", cells.join(" | "))
+                    }
+                    QuoteFormat::Html => {
+                        let tds: Vec<String> = cells
+                            .iter()
+                            .enumerate()
+                            .map(|(i, c)| match i {
+                                0 => format!("<td>{}</td>", escape_html(c)),
+                                _ => format!("<td class=\"num\">{}</td>", escape_html(c)),
+                            })
+                            .collect();
+                        format!("<tr>{}</tr>", tds.concat())
+                    }
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// 用模板生成报价单；字段值按格式转义（Markdown 转义 `|` 和换行，HTML 转义特殊字符）。
+    /// 模板中出现未知占位符时报错，避免拼写错误被忽略
+    pub fn render(&self, template: &str, format: QuoteFormat, currency: Option<&str>) -> Result<String> {
+        let currency = currency.unwrap_or("CNY");
+        substitute(template, |name| match name {
+            "lines" => Ok(self.lines(format)),
+            _ => {
+                let value = self.value(name, currency).ok_or_else(|| unknown_placeholder(name))?;
+                Ok(match format {
+                    QuoteFormat::Markdown => escape_markdown(&value),
+                    QuoteFormat::Html => escape_html(&value),
+                })
+            }
+        })
+    }
+}
+
+/// 检查模板能否生成报价单（占位符均已定义、`{{` 都有对应的 `}}`），用于保存报价单之前
+pub fn check_template(template: &str) -> Result<()> {
+    substitute(template, |name| match PLACEHOLDERS.contains(&name) {
+        true => Ok(String::new()),
+        false => Err(unknown_placeholder(name)),
+    })
+    .map(|_| ())
+}
+
+fn unknown_placeholder(name: &str) -> Error {
+    Error::Validation(format!("模板中的占位符 {{{{{}}}}} 未定义，可用: {}", name, PLACEHOLDERS.join(", ")))
+}
+
+/// 把模板中的每个 `{{名称}}` 替换为 `value(名称)` 的结果
+fn substitute(template: &str, mut value: impl FnMut(&str) -> Result<String>) -> Result<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| Error::Validation(format!("模板中的 {{{{ 没有对应的 }}}}: {}", after.lines().next().unwrap_or(""))))?;
+        out.push_str(&value(after[..end].trim())?);
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quotation() -> Quotation {
+        Quotation {
+            quote_no: "Q20240101-001".to_string(),
+            created_on: "2024-01-01".to_string(),
+            valid_until: "2024-01-08".to_string(),
+            customer: "某客户".to_string(),
+            supplier_id: 1,
+            offer_code: "OF-7KQ2M9XA".to_string(),
+            quantity: 2,
+            months: 3,
+            location: Some("北京".to_string()),
+            server_name: None,
+            server_config: Some("8×A100 | NVLink".to_string()),
+            networking_category: None,
+            rental_model: None,
+            min_contract_period: None,
+            payment_terms: Some("季付".to_string()),
+            breach_penalties: None,
+            lines: vec![QuotationLine { item: "服务器".to_string(), unit_price: 1100.0, monthly: 2200.0, amount: 6600.0 }],
+            monthly_total: 2200.0,
+            total: 6600.0,
+            total_cost: 6000.0,
+            notes: Some("含税\n不含运费".to_string()),
+        }
+    }
+
+    fn row() -> SupplierRow {
+        crate::Supplier {
+            contact: Some("张三".to_string()),
+            phone: Some("13812348000".to_string()),
+            location: Some("北京".to_string()),
+            price: Some(1000.0),
+            storage_price: Some(20.0),
+            payment_terms: Some("月付".to_string()),
+            ..Default::default()
+        }
+        .to_row(1)
+    }
+
+    fn new_quotation(request: &QuoteRequest) -> Result<Quotation> {
+        let row = row();
+        let price = crate::pricing::quote(&row, 2, None)?;
+        Quotation::new("Q20240101-001".to_string(), "2024-01-01".to_string(), "2024-01-08".to_string(), "OF-7KQ2M9XA".to_string(), &row, &price, request)
+    }
+
+    #[test]
+    fn test_new_multiplies_by_months() {
+        let q = new_quotation(&QuoteRequest { customer: " 某客户 ", months: 3, valid_days: 7, notes: None }).unwrap();
+        assert_eq!(q.customer, "某客户");
+        assert_eq!(q.lines.len(), 2);
+        assert_eq!((q.lines[0].monthly, q.lines[0].amount), (2000.0, 6000.0));
+        assert_eq!((q.monthly_total, q.total, q.total_cost), (2040.0, 6120.0, 6120.0));
+    }
+
+    #[test]
+    fn test_new_validation() {
+        let request = QuoteRequest { customer: "某客户", months: 3, valid_days: 7, notes: None };
+        assert!(matches!(new_quotation(&QuoteRequest { customer: " ", ..request.clone() }), Err(Error::Validation(_))));
+        assert!(matches!(new_quotation(&QuoteRequest { months: 0, ..request }), Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn test_builtin_templates_hide_supplier_identity() {
+        let q = new_quotation(&QuoteRequest { customer: "某客户", months: 3, valid_days: 7, notes: None }).unwrap();
+        for format in [QuoteFormat::Markdown, QuoteFormat::Html] {
+            let out = q.render(format.builtin_template(), format, Some("CNY")).unwrap();
+            assert!(out.contains("OF-7KQ2M9XA") && out.contains("月付"), "{:?}", format);
+            assert!(!out.contains("张三") && !out.contains("13812348000"), "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn test_render_placeholder_whitespace_and_default_currency() {
+        let out = quotation().render("{{ customer }}/{{currency}}", QuoteFormat::Markdown, None).unwrap();
+        assert_eq!(out, "某客户/CNY");
+    }
+
+    #[test]
+    fn test_render_unknown_placeholder() {
+        assert!(matches!(quotation().render("{{unknown}}", QuoteFormat::Markdown, None), Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn test_every_placeholder_is_known() {
+        let template: String = PLACEHOLDERS.iter().map(|p| format!("{{{{{}}}}}", p)).collect();
+        assert!(check_template(&template).is_ok());
+        assert!(quotation().render(&template, QuoteFormat::Markdown, None).is_ok());
+    }
+
+    #[test]
+    fn test_check_template() {
+        assert!(check_template(MARKDOWN_TEMPLATE).is_ok());
+        assert!(check_template(HTML_TEMPLATE).is_ok());
+        assert!(matches!(check_template("{{quote_no}} {{custmer}}"), Err(Error::Validation(m)) if m.contains("{{custmer}}")));
+        assert!(matches!(check_template("{{quote_no"), Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn test_render_markdown_escapes_values() {
+        let q = quotation();
+        let out = q.render("{{server_config}}|{{notes}}|{{location}}|{{server_name}}", QuoteFormat::Markdown, None).unwrap();
+        assert_eq!(out, "8×A100 \\| NVLink|含税<br>不含运费|北京|-");
+        let out = q.render("{{lines}}", QuoteFormat::Markdown, None).unwrap();
+        assert_eq!(out, "| 服务器 | 1100.00 | 2 | 2200.00 | 6600.00 |");
+    }
+
+    #[test]
+    fn test_render_html_escapes_values() {
+        let mut q = quotation();
+        q.customer = "<某客户>".to_string();
+        let out = q.render("{{customer}} {{currency}}", QuoteFormat::Html, Some("USD")).unwrap();
+        assert_eq!(out, "&lt;某客户&gt; USD");
+        assert!(q.render("{{lines}}", QuoteFormat::Html, None).unwrap().starts_with("<tr><td>服务器</td><td class=\"num\">1100.00</td>"));
+    }
+}
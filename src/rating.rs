@@ -0,0 +1,318 @@
+//! 供应商评分和事故记录
+//!
+//! 每个供应商按交付、稳定性、支持、价格诚信四项打分（1–5分），可以只打其中几项。综合评分为已打分
+//! 各项的平均分，减去最近 [`INCIDENT_WINDOW_DAYS`] 天内事故的扣分（按严重程度，见
+//! [`IncidentSeverity::penalty`]），最低为0，保留两位小数；没有任何打分的供应商没有综合评分。
+//!
+//! 综合评分在查询时由数据库计算（`supplier_plain` 视图的 `score` 列），因此可以按评分筛选和排序；
+//! [`score`] 给出相同的计算，用于显示评分明细。
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::dates::Date;
+use crate::error::{Error, Result};
+
+/// 计入综合评分的事故时间范围（天）
+pub const INCIDENT_WINDOW_DAYS: u32 = 365;
+
+/// 单项评分的取值范围
+pub const MIN_RATING: u8 = 1;
+pub const MAX_RATING: u8 = 5;
+
+/// 评分项
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RatingDimension {
+    /// 交付：是否按时、按约定配置交付
+    Delivery,
+    /// 稳定性：宕机、断网等故障的频率
+    Stability,
+    /// 支持：响应速度和处理问题的能力
+    Support,
+    /// 价格诚信：报价是否透明、有无临时加价
+    PriceHonesty,
+}
+
+impl RatingDimension {
+    pub const ALL: [RatingDimension; 4] =
+        [RatingDimension::Delivery, RatingDimension::Stability, RatingDimension::Support, RatingDimension::PriceHonesty];
+
+    /// 数据库列名
+    pub fn column(self) -> &'static str {
+        match self {
+            RatingDimension::Delivery => "delivery",
+            RatingDimension::Stability => "stability",
+            RatingDimension::Support => "support",
+            RatingDimension::PriceHonesty => "price_honesty",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RatingDimension::Delivery => "交付",
+            RatingDimension::Stability => "稳定性",
+            RatingDimension::Support => "支持",
+            RatingDimension::PriceHonesty => "价格诚信",
+        }
+    }
+}
+
+/// 事故严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IncidentSeverity {
+    /// 轻微：短时故障，未影响交付
+    Minor,
+    /// 严重：影响客户使用，如长时间断网
+    Major,
+    /// 重大：数据丢失、违约停机等
+    Critical,
+}
+
+impl IncidentSeverity {
+    pub const ALL: [IncidentSeverity; 3] = [IncidentSeverity::Minor, IncidentSeverity::Major, IncidentSeverity::Critical];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            IncidentSeverity::Minor => "minor",
+            IncidentSeverity::Major => "major",
+            IncidentSeverity::Critical => "critical",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            IncidentSeverity::Minor => "轻微",
+            IncidentSeverity::Major => "严重",
+            IncidentSeverity::Critical => "重大",
+        }
+    }
+
+    /// 每起事故从综合评分中扣除的分数
+    pub fn penalty(self) -> f64 {
+        match self {
+            IncidentSeverity::Minor => 0.25,
+            IncidentSeverity::Major => 0.5,
+            IncidentSeverity::Critical => 1.0,
+        }
+    }
+}
+
+impl std::str::FromStr for IncidentSeverity {
+    type Err = String;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        <IncidentSeverity as ValueEnum>::from_str(s, true).map_err(|_| format!("未知事故级别: {}", s))
+    }
+}
+
+/// 一个供应商的评分
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Rating {
+    pub supplier_id: i64,
+    pub delivery: Option<u8>,
+    pub stability: Option<u8>,
+    pub support: Option<u8>,
+    pub price_honesty: Option<u8>,
+    pub note: Option<String>,
+    /// 最近一次打分的本地时间
+    pub updated_at: String,
+}
+
+impl Rating {
+    /// 某一项的分数，未打分时为 `None`
+    pub fn get(&self, dimension: RatingDimension) -> Option<u8> {
+        match dimension {
+            RatingDimension::Delivery => self.delivery,
+            RatingDimension::Stability => self.stability,
+            RatingDimension::Support => self.support,
+            RatingDimension::PriceHonesty => self.price_honesty,
+        }
+    }
+
+    /// 已打分各项的平均分，没有打分时为 `None`
+    pub fn average(&self) -> Option<f64> {
+        let scores: Vec<f64> = RatingDimension::ALL.iter().filter_map(|d| self.get(*d)).map(f64::from).collect();
+        (!scores.is_empty()).then(|| scores.iter().sum::<f64>() / scores.len() as f64)
+    }
+}
+
+/// 打分的参数，只修改为 `Some` 的项
+#[derive(Debug, Clone, Default)]
+pub struct RatingUpdate {
+    pub delivery: Option<u8>,
+    pub stability: Option<u8>,
+    pub support: Option<u8>,
+    pub price_honesty: Option<u8>,
+    pub note: Option<String>,
+}
+
+impl RatingUpdate {
+    /// 至少修改一项，分数必须在 1–5 之间
+    pub fn validate(&self) -> Result<()> {
+        let scores = [
+            (RatingDimension::Delivery, self.delivery),
+            (RatingDimension::Stability, self.stability),
+            (RatingDimension::Support, self.support),
+            (RatingDimension::PriceHonesty, self.price_honesty),
+        ];
+        if scores.iter().all(|(_, v)| v.is_none()) && self.note.is_none() {
+            return Err(Error::Validation("请至少指定一项评分或备注".to_string()));
+        }
+        for (dimension, value) in scores {
+            if let Some(v) = value.filter(|v| !(MIN_RATING..=MAX_RATING).contains(v)) {
+                return Err(Error::Validation(format!(
+                    "{}评分必须在 {} 到 {} 之间: {}", dimension.label(), MIN_RATING, MAX_RATING, v
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 一起事故
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Incident {
+    pub id: i64,
+    pub supplier_id: i64,
+    pub occurred_on: Date,
+    pub severity: IncidentSeverity,
+    pub description: String,
+    /// 记录时间（本地时间）
+    pub created_at: String,
+}
+
+/// 新增事故的参数
+#[derive(Debug, Clone)]
+pub struct NewIncident {
+    pub supplier_id: i64,
+    pub occurred_on: Date,
+    pub severity: IncidentSeverity,
+    pub description: String,
+}
+
+impl NewIncident {
+    /// 描述不能为空，发生日期不能晚于今天
+    pub fn validate(&self, today: Date) -> Result<()> {
+        if self.description.trim().is_empty() {
+            return Err(Error::Validation("事故描述不能为空".to_string()));
+        }
+        if self.occurred_on > today {
+            return Err(Error::Validation(format!("事故日期 {} 晚于今天 {}", self.occurred_on, today)));
+        }
+        Ok(())
+    }
+}
+
+/// 事故是否计入 `today` 的综合评分
+pub fn counts_on(incident: &Incident, today: Date) -> bool {
+    incident.occurred_on > today.add_days(-i64::from(INCIDENT_WINDOW_DAYS))
+}
+
+/// 事故扣分合计，只计最近 [`INCIDENT_WINDOW_DAYS`] 天内的事故
+pub fn incident_penalty(incidents: &[Incident], today: Date) -> f64 {
+    incidents.iter().filter(|i| counts_on(i, today)).map(|i| i.severity.penalty()).sum()
+}
+
+/// 综合评分：平均分减去事故扣分，最低为0，保留两位小数；没有打分时为 `None`
+pub fn score(rating: &Rating, incidents: &[Incident], today: Date) -> Option<f64> {
+    let average = rating.average()?;
+    let score = (average - incident_penalty(incidents, today)).max(0.0);
+    Some((score * 100.0).round() / 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> Date {
+        s.parse().unwrap()
+    }
+
+    fn rating(delivery: Option<u8>, stability: Option<u8>, support: Option<u8>, price_honesty: Option<u8>) -> Rating {
+        Rating { supplier_id: 1, delivery, stability, support, price_honesty, note: None, updated_at: "2024-03-01 09:00:00".to_string() }
+    }
+
+    fn incident(occurred_on: &str, severity: IncidentSeverity) -> Incident {
+        Incident {
+            id: 1,
+            supplier_id: 1,
+            occurred_on: date(occurred_on),
+            severity,
+            description: "断网".to_string(),
+            created_at: "2024-03-01 09:00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_validate_rating_update() {
+        assert!(RatingUpdate { delivery: Some(1), price_honesty: Some(5), ..Default::default() }.validate().is_ok());
+        assert!(RatingUpdate { note: Some("响应慢".to_string()), ..Default::default() }.validate().is_ok());
+        assert!(matches!(RatingUpdate::default().validate(), Err(Error::Validation(_))));
+        let Err(Error::Validation(message)) = (RatingUpdate { support: Some(6), ..Default::default() }).validate() else {
+            panic!("超出范围的评分应被拒绝");
+        };
+        assert!(message.contains("支持"));
+        assert!(RatingUpdate { stability: Some(0), ..Default::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_new_incident() {
+        let today = date("2024-03-01");
+        let new = NewIncident { supplier_id: 1, occurred_on: today, severity: IncidentSeverity::Minor, description: "断网".to_string() };
+        assert!(new.validate(today).is_ok());
+        assert!(matches!(NewIncident { description: " ".to_string(), ..new.clone() }.validate(today), Err(Error::Validation(_))));
+        assert!(matches!(NewIncident { occurred_on: today.add_days(1), ..new }.validate(today), Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn test_average_of_rated_dimensions() {
+        assert_eq!(rating(Some(5), Some(4), Some(3), None).average(), Some(4.0));
+        assert_eq!(rating(None, None, None, Some(2)).average(), Some(2.0));
+        assert_eq!(rating(None, None, None, None).average(), None);
+        assert_eq!(rating(Some(5), None, None, None).get(RatingDimension::Stability), None);
+    }
+
+    #[test]
+    fn test_incident_window() {
+        let today = date("2024-03-01");
+        assert!(counts_on(&incident("2024-02-01", IncidentSeverity::Minor), today));
+        // 2024 年是闰年，365 天前是 2023-03-02，当天的事故不再计入
+        assert!(counts_on(&incident("2023-03-03", IncidentSeverity::Minor), today));
+        assert!(!counts_on(&incident("2023-03-02", IncidentSeverity::Minor), today));
+    }
+
+    #[test]
+    fn test_incident_penalty() {
+        let today = date("2024-03-01");
+        let incidents = [
+            incident("2024-02-01", IncidentSeverity::Minor),
+            incident("2024-01-01", IncidentSeverity::Major),
+            incident("2023-06-01", IncidentSeverity::Critical),
+            incident("2022-01-01", IncidentSeverity::Critical),
+        ];
+        assert_eq!(incident_penalty(&incidents, today), 1.75);
+        assert_eq!(incident_penalty(&[], today), 0.0);
+    }
+
+    #[test]
+    fn test_score() {
+        let today = date("2024-03-01");
+        let good = rating(Some(5), Some(4), Some(5), Some(4));
+        assert_eq!(score(&good, &[], today), Some(4.5));
+        assert_eq!(score(&good, &[incident("2024-02-01", IncidentSeverity::Major)], today), Some(4.0));
+        // 最低为0，保留两位小数
+        let critical = [incident("2024-02-01", IncidentSeverity::Critical), incident("2024-02-02", IncidentSeverity::Critical)];
+        assert_eq!(score(&rating(Some(1), None, None, None), &critical, today), Some(0.0));
+        assert_eq!(score(&rating(Some(5), Some(4), Some(4), None), &[], today), Some(4.33));
+        assert_eq!(score(&rating(None, None, None, None), &critical, today), None);
+    }
+
+    #[test]
+    fn test_severity_from_str() {
+        assert_eq!("Major".parse::<IncidentSeverity>(), Ok(IncidentSeverity::Major));
+        for severity in IncidentSeverity::ALL {
+            assert_eq!(severity.name().parse::<IncidentSeverity>(), Ok(severity));
+        }
+        assert!("fatal".parse::<IncidentSeverity>().unwrap_err().contains("fatal"));
+    }
+}
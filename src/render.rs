@@ -0,0 +1,569 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::{Column, SupplierRow};
+
+/// 表格、Markdown 和 HTML 输出共用的列标题
+pub const TABLE_HEADERS: [&str; 18] = [
+    "ID", "联系人", "微信", "手机", "数量", "地点", "价格", "带宽价", "存储价", "合同期", "违约金", "付款", "服务器名", "配置", "租赁", "组网", "Profile", "评分"
+];
+
+/// 查询结果的输出格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// 终端表格（默认）
+    #[default]
+    Table,
+    Json,
+    Csv,
+    /// GitHub 风格的 Markdown 表格
+    Markdown,
+    /// 自带样式、可点击列头排序的独立 HTML 页面
+    Html,
+}
+
+/// 将一条记录转为按 `TABLE_HEADERS` 顺序排列的单元格文本
+fn supplier_cells(s: &SupplierRow) -> Vec<String> {
+    vec![
+        s.id.to_string(),
+        s.contact.as_deref().unwrap_or("").to_string(),
+        s.wechat.as_deref().unwrap_or("").to_string(),
+        s.phone.as_deref().unwrap_or("").to_string(),
+        s.quantity.map(|v| v.to_string()).unwrap_or_default(),
+        s.location.as_deref().unwrap_or("").to_string(),
+        s.price.map(|v| format!("{:.2}", v)).unwrap_or_default(),
+        s.bandwidth_price.map(|v| format!("{:.2}", v)).unwrap_or_default(),
+        s.storage_price.map(|v| format!("{:.2}", v)).unwrap_or_default(),
+        s.min_contract_period.as_deref().unwrap_or("").to_string(),
+        s.breach_penalties.as_deref().unwrap_or("").to_string(),
+        s.payment_terms.as_deref().unwrap_or("").to_string(),
+        s.server_name.as_deref().unwrap_or("").to_string(),
+        s.server_config.as_deref().unwrap_or("").to_string(),
+        s.rental_model.as_deref().unwrap_or("").to_string(),
+        s.networking_category.as_deref().unwrap_or("").to_string(),
+        s.profile.as_deref().unwrap_or("").to_string(),
+        s.score.map(|v| format!("{:.2}", v)).unwrap_or_default(),
+    ]
+}
+
+/// 按列选择取出单元格
+fn selected_cells(s: &SupplierRow, columns: &[Column]) -> Vec<String> {
+    let all = supplier_cells(s);
+    columns.iter().map(|c| all[c.index()].clone()).collect()
+}
+
+/// 所选列的标题，指定币种时价格列标题附带币种，如 `价格(CNY)`
+fn selected_headers(columns: &[Column], currency: Option<&str>) -> Vec<String> {
+    columns.iter()
+        .map(|c| match currency {
+            Some(cur) if c.is_price() => format!("{}({})", TABLE_HEADERS[c.index()], cur),
+            _ => TABLE_HEADERS[c.index()].to_string(),
+        })
+        .collect()
+}
+
+/// 表格布局
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum TableLayout {
+    /// 终端足够宽时横向表格，否则逐条纵向显示
+    #[default]
+    Auto,
+    /// 横向表格，超宽单元格以省略号截断
+    Horizontal,
+    /// 每条记录一个块，适合窄终端
+    Vertical,
+}
+
+/// 表头着色
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// 输出到终端且未设置 NO_COLOR 时着色
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// 表格渲染选项
+#[derive(Debug, Clone, Default)]
+pub struct TableOptions {
+    /// 可用的最大显示宽度，`None` 表示不限制（如输出被重定向到文件）
+    pub max_width: Option<usize>,
+    pub layout: TableLayout,
+    pub color: bool,
+    /// 要显示的列，空表示全部
+    pub columns: Vec<Column>,
+    /// 价格币种，显示在价格列标题中
+    pub currency: Option<String>,
+}
+
+impl TableOptions {
+    /// 根据当前终端推断宽度和是否着色，`width` 可显式覆盖终端宽度
+    pub fn detect(layout: TableLayout, color: ColorChoice, width: Option<usize>) -> Self {
+        use std::io::IsTerminal;
+        let is_tty = std::io::stdout().is_terminal();
+        let max_width = width.or_else(|| {
+            if !is_tty {
+                return None;
+            }
+            terminal_size::terminal_size()
+                .map(|(w, _)| w.0 as usize)
+                .or_else(|| std::env::var("COLUMNS").ok().and_then(|c| c.parse().ok()))
+        });
+        let color = match color {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => is_tty && std::env::var_os("NO_COLOR").is_none(),
+        };
+        Self { max_width, layout, color, ..Default::default() }
+    }
+}
+
+const HEADER_COLOR: &str = "\x1b[1;36m";
+const COLOR_RESET: &str = "\x1b[0m";
+const ELLIPSIS: char = '…';
+/// 截断后每列至少保留的显示宽度
+const MIN_COL_WIDTH: usize = 4;
+
+/// 按终端显示宽度计算，中日韩文字占两列
+fn display_width(s: &str) -> usize {
+    UnicodeWidthStr::width(s)
+}
+
+/// 按显示宽度截断，超出时以省略号结尾
+fn truncate_to_width(s: &str, width: usize) -> String {
+    if display_width(s) <= width {
+        return s.to_string();
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for c in s.chars() {
+        let w = UnicodeWidthChar::width(c).unwrap_or(0);
+        if used + w + 1 > width {
+            break;
+        }
+        out.push(c);
+        used += w;
+    }
+    out.push(ELLIPSIS);
+    out
+}
+
+/// 按显示宽度在右侧补齐空格
+fn pad_to_width(s: &str, width: usize) -> String {
+    let w = display_width(s);
+    let mut out = s.to_string();
+    out.push_str(&" ".repeat(width.saturating_sub(w)));
+    out
+}
+
+/// 在总宽度超限时依次收窄最宽的文本列，数值列保持完整；
+/// 文本列全部收窄到下限仍放不下时返回 `None`
+fn fit_widths(natural: &[usize], numeric: &[bool], max_width: usize) -> Option<Vec<usize>> {
+    // 每列后有一个空格分隔
+    let total = |w: &[usize]| w.iter().sum::<usize>() + w.len();
+    let mut widths = natural.to_vec();
+    while total(&widths) > max_width {
+        let (idx, _) = widths
+            .iter()
+            .enumerate()
+            .filter(|(i, w)| !numeric[*i] && **w > MIN_COL_WIDTH)
+            .max_by_key(|(_, w)| **w)?;
+        widths[idx] -= 1;
+    }
+    Some(widths)
+}
+
+fn paint_header(text: &str, color: bool) -> String {
+    if color {
+        format!("{}{}{}", HEADER_COLOR, text, COLOR_RESET)
+    } else {
+        text.to_string()
+    }
+}
+
+fn render_horizontal(headers: &[String], data: &[Vec<String>], widths: &[usize], color: bool) -> String {
+    let mut out = String::new();
+    // 表头
+    for (i, h) in headers.iter().enumerate() {
+        let cell = pad_to_width(&truncate_to_width(h, widths[i]), widths[i]);
+        out.push_str(&paint_header(&cell, color));
+        out.push(' ');
+    }
+    out.push('\n');
+    // 分隔线
+    for w in widths {
+        out.push_str(&"-".repeat(*w + 1));
+    }
+    out.push('\n');
+    // 数据
+    for row in data {
+        for (i, cell) in row.iter().enumerate() {
+            out.push_str(&pad_to_width(&truncate_to_width(cell, widths[i]), widths[i]));
+            out.push(' ');
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn render_vertical(headers: &[String], data: &[Vec<String>], max_width: Option<usize>, color: bool) -> String {
+    let label_width = headers.iter().map(|h| display_width(h)).max().unwrap_or(0);
+    // 标签列 + " : "
+    let value_width = max_width.map(|w| w.saturating_sub(label_width + 3).max(MIN_COL_WIDTH));
+    let mut out = String::new();
+    for (n, row) in data.iter().enumerate() {
+        let title = format!("-[ 记录 {} ]", n + 1);
+        let rule_width = max_width.unwrap_or(40).min(40);
+        out.push_str(&title);
+        out.push_str(&"-".repeat(rule_width.saturating_sub(display_width(&title))));
+        out.push('\n');
+        for (h, cell) in headers.iter().zip(row) {
+            let value = match value_width {
+                Some(w) => truncate_to_width(cell, w),
+                None => cell.clone(),
+            };
+            out.push_str(&paint_header(&pad_to_width(h, label_width), color));
+            out.push_str(" : ");
+            out.push_str(&value);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// 按选项渲染表格，列宽按显示宽度计算
+pub fn render_table(rows: &[SupplierRow], opts: &TableOptions) -> String {
+    let columns = Column::resolve(&opts.columns);
+    let headers = selected_headers(&columns, opts.currency.as_deref());
+    let numeric: Vec<bool> = columns.iter().map(Column::is_numeric).collect();
+    let mut natural: Vec<usize> = headers.iter().map(|h| display_width(h).max(2)).collect();
+    let mut data: Vec<Vec<String>> = Vec::new();
+    for s in rows {
+        let row = selected_cells(s, &columns);
+        for (i, cell) in row.iter().enumerate() {
+            natural[i] = natural[i].max(display_width(cell));
+        }
+        data.push(row);
+    }
+    if data.is_empty() {
+        let mut out = render_horizontal(&headers, &data, &natural, opts.color);
+        out.push_str("无供应商信息。\n");
+        return out;
+    }
+    layout_grid(&headers, &data, &natural, &numeric, opts)
+}
+
+/// 按选项渲染任意表格（如合同、付款列表），宽度适配、截断、纵向布局和表头着色与供应商表格相同；
+/// `numeric` 标记不参与收窄的数值列，`opts` 中的列选择和币种不适用
+pub fn render_grid(headers: &[&str], data: &[Vec<String>], numeric: &[bool], opts: &TableOptions) -> String {
+    let headers: Vec<String> = headers.iter().map(|h| h.to_string()).collect();
+    let mut natural: Vec<usize> = headers.iter().map(|h| display_width(h).max(2)).collect();
+    for row in data {
+        for (i, cell) in row.iter().enumerate() {
+            natural[i] = natural[i].max(display_width(cell));
+        }
+    }
+    layout_grid(&headers, data, &natural, numeric, opts)
+}
+
+fn layout_grid(headers: &[String], data: &[Vec<String>], natural: &[usize], numeric: &[bool], opts: &TableOptions) -> String {
+    match (opts.layout, opts.max_width) {
+        (TableLayout::Vertical, w) => render_vertical(headers, data, w, opts.color),
+        (_, None) => render_horizontal(headers, data, natural, opts.color),
+        (layout, Some(max_width)) => match fit_widths(natural, numeric, max_width) {
+            Some(widths) => render_horizontal(headers, data, &widths, opts.color),
+            None if layout == TableLayout::Auto => render_vertical(headers, data, Some(max_width), opts.color),
+            // 强制横向时文本列保留下限宽度、数值列保持完整，允许整体超出终端
+            None => {
+                let widths: Vec<usize> = natural.iter()
+                    .zip(numeric)
+                    .map(|(w, num)| if *num { *w } else { (*w).min(MIN_COL_WIDTH) })
+                    .collect();
+                render_horizontal(headers, data, &widths, opts.color)
+            }
+        },
+    }
+}
+
+/// 表格格式化输出，自动适配当前终端
+pub fn print_suppliers_table_v2(rows: &[SupplierRow]) {
+    let opts = TableOptions::detect(TableLayout::Auto, ColorChoice::Auto, None);
+    print!("{}", render_table(rows, &opts));
+}
+
+/// JSON格式化输出，`columns` 为空时输出全部字段
+pub fn print_suppliers_json(rows: &[SupplierRow], columns: &[Column]) {
+    let result = if columns.is_empty() {
+        serde_json::to_string_pretty(rows)
+    } else {
+        let selected: Vec<serde_json::Map<String, serde_json::Value>> = rows.iter()
+            .map(|row| {
+                let mut full = match serde_json::to_value(row) {
+                    Ok(serde_json::Value::Object(map)) => map,
+                    _ => serde_json::Map::new(),
+                };
+                columns.iter()
+                    .map(|c| (c.name().to_string(), full.remove(c.name()).unwrap_or(serde_json::Value::Null)))
+                    .collect()
+            })
+            .collect();
+        serde_json::to_string_pretty(&selected)
+    };
+    match result {
+        Ok(json) => println!("{}", json),
+        Err(e) => eprintln!("JSON序列化错误: {}", e),
+    }
+}
+
+/// 转义 Markdown 表格单元格中的反斜杠、`|` 和换行
+pub(crate) fn escape_markdown(cell: &str) -> String {
+    cell.replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+/// 生成 GitHub 风格的 Markdown 管道表格，数值列右对齐；使用选项中的列选择和币种
+pub fn render_markdown(rows: &[SupplierRow], opts: &TableOptions) -> String {
+    let columns = Column::resolve(&opts.columns);
+    let mut out = String::new();
+    out.push_str(&format!("| {} |\n", selected_headers(&columns, opts.currency.as_deref()).join(" | ")));
+    let aligns: Vec<&str> = columns.iter()
+        .map(|c| if c.is_numeric() { "---:" } else { "---" })
+        .collect();
+    out.push_str(&format!("| {} |\n", aligns.join(" | ")));
+    for s in rows {
+        let cells: Vec<String> = selected_cells(s, &columns).iter().map(|c| escape_markdown(c)).collect();
+        out.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+    out
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+pub(crate) const HTML_STYLE: &str = r#"body { font-family: -apple-system, "Segoe UI", "PingFang SC", "Microsoft YaHei", sans-serif; margin: 24px; color: #222; }
+table { border-collapse: collapse; font-size: 14px; }
+th, td { border: 1px solid #ddd; padding: 6px 10px; white-space: nowrap; }
+th { background: #f4f6f8; cursor: pointer; user-select: none; position: sticky; top: 0; }
+th[data-dir="asc"]::after { content: " ▲"; }
+th[data-dir="desc"]::after { content: " ▼"; }
+tbody tr:nth-child(even) { background: #fafafa; }
+tbody tr:hover { background: #eef5ff; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }"#;
+
+pub(crate) const HTML_SCRIPT: &str = r#"document.querySelectorAll("th").forEach(function (th, col) {
+  th.addEventListener("click", function () {
+    var tbody = th.closest("table").tBodies[0];
+    var dir = th.dataset.dir === "asc" ? "desc" : "asc";
+    th.parentNode.querySelectorAll("th").forEach(function (h) { delete h.dataset.dir; });
+    th.dataset.dir = dir;
+    var numeric = th.dataset.type === "num";
+    var rows = Array.prototype.slice.call(tbody.rows);
+    rows.sort(function (a, b) {
+      var x = a.cells[col].textContent, y = b.cells[col].textContent;
+      var r = numeric ? (parseFloat(x) || 0) - (parseFloat(y) || 0) : x.localeCompare(y, "zh-CN");
+      return dir === "asc" ? r : -r;
+    });
+    rows.forEach(function (r) { tbody.appendChild(r); });
+  });
+});"#;
+
+/// 生成自带样式和列排序脚本的独立 HTML 页面；使用选项中的列选择和币种
+pub fn render_html(rows: &[SupplierRow], opts: &TableOptions) -> String {
+    let columns = Column::resolve(&opts.columns);
+    let numeric: Vec<bool> = columns.iter().map(Column::is_numeric).collect();
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str("<title>供应商列表</title>\n");
+    out.push_str(&format!("<style>\n{}\n</style>\n", HTML_STYLE));
+    out.push_str("</head>\n<body>\n");
+    out.push_str(&format!("<p>共 {} 条记录，点击列头排序</p>\n", rows.len()));
+    out.push_str("<table>\n<thead>\n<tr>");
+    for (i, h) in selected_headers(&columns, opts.currency.as_deref()).iter().enumerate() {
+        if numeric[i] {
+            out.push_str(&format!("<th data-type=\"num\">{}</th>", escape_html(h)));
+        } else {
+            out.push_str(&format!("<th>{}</th>", escape_html(h)));
+        }
+    }
+    out.push_str("</tr>\n</thead>\n<tbody>\n");
+    for s in rows {
+        out.push_str("<tr>");
+        for (i, cell) in selected_cells(s, &columns).iter().enumerate() {
+            if numeric[i] {
+                out.push_str(&format!("<td class=\"num\">{}</td>", escape_html(cell)));
+            } else {
+                out.push_str(&format!("<td>{}</td>", escape_html(cell)));
+            }
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</tbody>\n</table>\n");
+    out.push_str(&format!("<script>\n{}\n</script>\n", HTML_SCRIPT));
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Supplier;
+
+    // 测试Markdown和HTML渲染：列头与表格一致，特殊字符被转义
+    #[test]
+    fn test_render_markdown_and_html() {
+        let rows = vec![
+            Supplier {
+                contact: Some("张三".to_string()),
+                server_config: Some("8卡|NVLink <A100>".to_string()),
+                price: Some(1000.0),
+                ..Default::default()
+            }.to_row(7),
+        ];
+
+        let md = render_markdown(&rows, &TableOptions::default());
+        let lines: Vec<&str> = md.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("| ID | 联系人 | 微信 |"));
+        assert!(lines[1].starts_with("| ---: | --- |"));
+        assert!(lines[2].contains("| 7 | 张三 |"));
+        assert!(lines[2].contains("8卡\\|NVLink <A100>"));
+        assert!(lines[2].contains("| 1000.00 |"));
+
+        let html = render_html(&rows, &TableOptions::default());
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<th data-type=\"num\">价格</th>"));
+        assert!(html.contains("<td>8卡|NVLink &lt;A100&gt;</td>"));
+        assert!(html.contains("<script>"));
+    }
+
+    // 测试表格按显示宽度对齐、超宽截断和纵向布局
+    #[test]
+    fn test_render_table_layout() {
+        let rows = vec![
+            Supplier {
+                contact: Some("北京联系人".to_string()),
+                location: Some("北京".to_string()),
+                price: Some(1000.0),
+                ..Default::default()
+            }.to_row(1),
+            Supplier {
+                contact: Some("Li".to_string()),
+                location: Some("Shanghai".to_string()),
+                server_config: Some("8x A100 80G NVLink 2TB RAM".to_string()),
+                ..Default::default()
+            }.to_row(2),
+        ];
+
+        // 不限宽度：每行显示宽度一致，CJK 按两列计算
+        let opts = TableOptions::default();
+        let out = render_table(&rows, &opts);
+        let widths: Vec<usize> = out.lines().map(UnicodeWidthStr::width).collect();
+        assert!(widths.iter().all(|w| *w == widths[0]), "各行宽度应一致: {:?}", widths);
+        assert!(!out.contains('…'));
+
+        // 限制宽度：文本列被截断，数值列保持完整
+        let opts = TableOptions { max_width: Some(90), ..Default::default() };
+        let out = render_table(&rows, &opts);
+        assert!(out.lines().all(|l| UnicodeWidthStr::width(l) <= 90));
+        assert!(out.contains('…'));
+        assert!(out.contains("1000.00"));
+
+        // 过窄时自动改为纵向布局
+        let opts = TableOptions { max_width: Some(30), ..Default::default() };
+        let out = render_table(&rows, &opts);
+        assert!(out.contains("-[ 记录 1 ]"));
+        assert!(out.contains("联系人   : 北京联系人"));
+        assert!(out.lines().all(|l| UnicodeWidthStr::width(l) <= 30));
+
+        // 着色只作用于表头
+        let opts = TableOptions { color: true, ..Default::default() };
+        let out = render_table(&rows, &opts);
+        assert!(out.lines().next().unwrap().contains("\x1b[1;36m"));
+        assert!(!out.lines().nth(2).unwrap().contains('\x1b'));
+    }
+
+    // 测试通用表格：数值列不截断，过窄时纵向显示
+    #[test]
+    fn test_render_grid() {
+        let headers = ["ID", "说明", "金额"];
+        let numeric = [true, false, true];
+        let data = vec![vec!["1".to_string(), "首期租金（含带宽）".to_string(), "12000.00".to_string()]];
+
+        let out = render_grid(&headers, &data, &numeric, &TableOptions::default());
+        assert!(out.starts_with("ID"));
+        assert!(out.contains("首期租金（含带宽）"));
+
+        let opts = TableOptions { max_width: Some(24), ..Default::default() };
+        let out = render_grid(&headers, &data, &numeric, &opts);
+        assert!(out.contains('…'));
+        assert!(out.contains("12000.00"));
+        assert!(out.lines().all(|l| UnicodeWidthStr::width(l) <= 24));
+
+        let opts = TableOptions { layout: TableLayout::Vertical, ..Default::default() };
+        let out = render_grid(&headers, &data, &numeric, &opts);
+        assert!(out.contains("金额 : 12000.00"));
+    }
+
+    #[test]
+    fn test_render_selected_columns_in_order() {
+        let rows = vec![Supplier { contact: Some("张三".to_string()), price: Some(10.0), ..Default::default() }.to_row(1)];
+        let columns: Vec<Column> = vec!["price".parse().unwrap(), "联系人".parse().unwrap()];
+        let opts = TableOptions { columns, ..Default::default() };
+        assert!(render_markdown(&rows, &opts).starts_with("| 价格 | 联系人 |\n| ---: | --- |\n| 10.00 | 张三 |"));
+
+        // 币种显示在价格列标题中
+        let opts = TableOptions { currency: Some("CNY".to_string()), ..opts };
+        assert!(render_markdown(&rows, &opts).starts_with("| 价格(CNY) | 联系人 |"));
+    }
+
+    #[test]
+    fn test_render_repeated_column() {
+        // 同一列选择两次时两处都有内容
+        let rows = vec![Supplier { contact: Some("张三".to_string()), price: Some(10.0), ..Default::default() }.to_row(1)];
+        let columns: Vec<Column> = vec!["price".parse().unwrap(), "price".parse().unwrap()];
+        let opts = TableOptions { columns, ..Default::default() };
+        assert!(render_markdown(&rows, &opts).ends_with("| 10.00 | 10.00 |\n"));
+    }
+
+    #[test]
+    fn test_forced_horizontal_keeps_numeric_columns() {
+        let rows = vec![Supplier {
+            contact: Some("北京联系人".to_string()),
+            server_config: Some("8x A100 80G NVLink 2TB RAM".to_string()),
+            price: Some(123456.78),
+            ..Default::default()
+        }.to_row(1)];
+        let opts = TableOptions { max_width: Some(10), layout: TableLayout::Horizontal, ..Default::default() };
+        let out = render_table(&rows, &opts);
+        assert!(out.contains("123456.78"));
+        assert!(out.contains('…'));
+    }
+
+    #[test]
+    fn test_numeric_and_price_columns() {
+        let numeric: Vec<&str> = Column::all().into_iter().chain([Column::Profile, Column::Score])
+            .filter(Column::is_numeric)
+            .map(|c| c.name())
+            .collect();
+        assert_eq!(numeric, ["id", "quantity", "price", "bandwidth_price", "storage_price", "score"]);
+        let prices: Vec<&str> = Column::all().into_iter().filter(Column::is_price).map(|c| c.name()).collect();
+        assert_eq!(prices, ["price", "bandwidth_price", "storage_price"]);
+    }
+}
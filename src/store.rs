@@ -0,0 +1,1524 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use rusqlite::backup::Backup;
+use rusqlite::functions::FunctionFlags;
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OpenFlags, OptionalExtension, Row, Transaction, TransactionBehavior};
+use serde::Serialize;
+
+use crate::auth::{self, Identity, Role, User};
+use crate::catalog;
+use crate::contract::{Contract, ContractStatus, NewContract};
+use crate::crypto::{self, FieldCipher};
+use crate::dates::Date;
+use crate::csv_io::{self, CsvOptions};
+use crate::error::{Error, Result};
+use crate::matching::{text_matches, MatchMode, Synonyms};
+use crate::model::{Column, Supplier, SupplierField, SupplierRow};
+use crate::notify::Notification;
+use crate::payment::{self, Payment, PaymentCadence, SupplierBalance};
+use crate::pricing::{self, MarkupKind, PriceQuote, PricingRule};
+use crate::query::{QueryBuilder, SavedView};
+use crate::quotation::{Quotation, QuoteRequest, QUOTE_NO_PREFIX};
+use crate::rating::{self, Incident, IncidentSeverity, NewIncident, Rating, RatingDimension, RatingUpdate};
+use crate::xlsx;
+
+/// 建表SQL，字段注释以SQL注释形式写在建表语句中
+const SCHEMA: &str = r#"
+-- 供应商表结构定义
+CREATE TABLE IF NOT EXISTS suppliers (
+    id INTEGER PRIMARY KEY AUTOINCREMENT, -- 主键，自增
+    contact TEXT NOT NULL,               -- 联系人
+    wechat TEXT,                         -- 微信
+    phone TEXT,                          -- 电话
+    quantity INTEGER,                    -- 数量
+    location TEXT,                       -- 地点
+    price REAL,                          -- 价格
+    bandwidth_price REAL,                -- 带宽价格
+    storage_price REAL,                  -- 存储价格
+    min_contract_period TEXT,            -- 最短合同期
+    breach_penalties TEXT,               -- 违约金
+    payment_terms TEXT,                  -- 付款方式
+    server_name TEXT,                    -- 服务器名称
+    server_config TEXT,                  -- 服务器配置
+    rental_model TEXT,                   -- 租赁模式
+    networking_category TEXT             -- 网络类型
+);
+
+-- 自定义同义词，与内置同义词一起用于地点和服务器名筛选
+CREATE TABLE IF NOT EXISTS synonyms (
+    field TEXT NOT NULL,                 -- 字段名（location 或 server_name）
+    canonical TEXT NOT NULL,             -- 标准写法
+    alias TEXT NOT NULL,                 -- 别名
+    PRIMARY KEY (field, alias)
+);
+
+-- 命名视图，保存查询的筛选条件、排序和列
+CREATE TABLE IF NOT EXISTS views (
+    name TEXT PRIMARY KEY,               -- 视图名称
+    args TEXT NOT NULL,                  -- SavedView（JSON对象）
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+-- 审计日志，如查看未脱敏的个人信息
+CREATE TABLE IF NOT EXISTS audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%S', 'now', 'localtime')),
+    actor TEXT NOT NULL,                 -- 操作人
+    action TEXT NOT NULL,                -- 操作类型
+    detail TEXT NOT NULL                 -- 操作详情
+);
+
+-- 本地用户，为空时不做权限检查
+CREATE TABLE IF NOT EXISTS users (
+    name TEXT PRIMARY KEY,               -- 用户名
+    role TEXT NOT NULL,                  -- 角色：admin、editor、viewer
+    password_hash TEXT NOT NULL,         -- 口令哈希
+    token_digest TEXT UNIQUE,            -- 令牌的 SHA-256 摘要
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%S', 'now', 'localtime'))
+);
+
+-- 敏感字段加密参数，未加密的数据库中为空
+CREATE TABLE IF NOT EXISTS encryption (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    salt TEXT NOT NULL,                  -- 派生密钥的盐（Base64）
+    verifier TEXT NOT NULL               -- 口令校验值
+);
+
+-- 对外目录中的报价编号，首次发布时分配，之后保持不变
+CREATE TABLE IF NOT EXISTS offer_codes (
+    supplier_id INTEGER PRIMARY KEY,     -- 供应商ID（供应商删除后保留，编号不会复用）
+    code TEXT NOT NULL UNIQUE,           -- 报价编号，如 OF-7KQ2M9XA
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%S', 'now', 'localtime'))
+);
+
+-- 定价规则，条件为空时匹配任意值
+CREATE TABLE IF NOT EXISTS pricing_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    location TEXT,                       -- 地点
+    networking_category TEXT,            -- 网络类型
+    rental_model TEXT,                   -- 租赁模式
+    kind TEXT NOT NULL,                  -- 加价方式：percent、fixed
+    value REAL NOT NULL,                 -- 百分比或每台金额
+    min_margin REAL                      -- 每台最低毛利
+);
+
+-- 客户报价单
+CREATE TABLE IF NOT EXISTS quotes (
+    quote_no TEXT PRIMARY KEY,           -- 报价单编号，如 Q20240101-001
+    supplier_id INTEGER NOT NULL,        -- 供应商ID
+    customer TEXT NOT NULL,              -- 客户
+    quantity INTEGER NOT NULL,           -- 数量
+    months INTEGER NOT NULL,             -- 租期月数
+    total REAL NOT NULL,                 -- 总价
+    total_cost REAL NOT NULL,            -- 总成本
+    created_on TEXT NOT NULL,            -- 报价日期
+    valid_until TEXT NOT NULL,           -- 有效期至
+    detail TEXT NOT NULL                 -- 完整报价单（JSON），用于重新生成
+);
+
+-- 与供应商签订的合同
+CREATE TABLE IF NOT EXISTS contracts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    supplier_id INTEGER NOT NULL,        -- 供应商ID
+    quote_no TEXT,                       -- 对应的客户报价单
+    quantity INTEGER NOT NULL,           -- 数量
+    agreed_price REAL NOT NULL,          -- 约定单价（每台每月）
+    start_date TEXT NOT NULL,            -- 开始日期
+    end_date TEXT NOT NULL,              -- 结束日期（不含）
+    status TEXT NOT NULL DEFAULT 'active', -- 状态：active、terminated
+    min_contract_period TEXT,            -- 签约时的最短合同期
+    payment_terms TEXT,                  -- 签约时的付款方式
+    breach_penalties TEXT,               -- 签约时的违约条款
+    renewals INTEGER NOT NULL DEFAULT 0, -- 续约次数
+    terminated_on TEXT,                  -- 终止日期
+    termination_cost REAL,               -- 终止时估算的违约金
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%S', 'now', 'localtime'))
+);
+
+-- 合同的付款计划，每期一行
+CREATE TABLE IF NOT EXISTS payments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    contract_id INTEGER NOT NULL,        -- 合同ID
+    supplier_id INTEGER NOT NULL,        -- 供应商ID
+    cadence TEXT NOT NULL,               -- 付款周期：monthly、quarterly、semi-annual、annual、upfront
+    period_start TEXT NOT NULL,          -- 本期开始日期
+    period_end TEXT NOT NULL,            -- 本期结束日期（不含）
+    due_date TEXT NOT NULL,              -- 到期日
+    amount REAL NOT NULL,                -- 金额
+    paid_on TEXT,                        -- 付款日期，未付时为空
+    UNIQUE (contract_id, period_start)
+);
+CREATE INDEX IF NOT EXISTS idx_payments_due ON payments (paid_on, due_date);
+
+-- 已发送的提醒，避免 notify check 重复发送
+CREATE TABLE IF NOT EXISTS notifications (
+    key TEXT PRIMARY KEY,                -- 去重键，如 contract_expiring:3:2024-03-01
+    kind TEXT NOT NULL,                  -- 提醒类别
+    title TEXT NOT NULL,                 -- 标题
+    sent_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%S', 'now', 'localtime'))
+);
+
+-- 供应商评分，每项 1-5 分，未打分的项为空
+CREATE TABLE IF NOT EXISTS supplier_ratings (
+    supplier_id INTEGER PRIMARY KEY,     -- 供应商ID
+    delivery INTEGER,                    -- 交付
+    stability INTEGER,                   -- 稳定性
+    support INTEGER,                     -- 支持
+    price_honesty INTEGER,               -- 价格诚信
+    note TEXT,                           -- 备注
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%S', 'now', 'localtime'))
+);
+
+-- 供应商事故记录，最近一年内的事故从综合评分中扣分
+CREATE TABLE IF NOT EXISTS supplier_incidents (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    supplier_id INTEGER NOT NULL,        -- 供应商ID
+    occurred_on TEXT NOT NULL,           -- 发生日期
+    severity TEXT NOT NULL,              -- 严重程度：minor、major、critical
+    description TEXT NOT NULL,           -- 描述
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%S', 'now', 'localtime'))
+);
+CREATE INDEX IF NOT EXISTS idx_supplier_incidents_supplier ON supplier_incidents (supplier_id, occurred_on);
+"#;
+
+/// 表结构版本，保存在 `PRAGMA user_version` 中；表结构有不兼容变化时递增
+///
+/// 版本 2：增加 `encryption` 表，加密后的联系人等字段旧版本程序无法读取。
+pub const SCHEMA_VERSION: i32 = 2;
+
+/// 其他进程持有写锁时的默认等待时间
+pub const DEFAULT_BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 预编译语句缓存容量
+const STATEMENT_CACHE_CAPACITY: usize = 32;
+
+/// 在线备份每步复制的页数及步间暂停，暂停期间其他连接可以继续写入
+const BACKUP_PAGES_PER_STEP: i32 = 100;
+const BACKUP_STEP_PAUSE: Duration = Duration::from_millis(10);
+
+const CONTRACT_COLUMNS: &str = "id, supplier_id, quote_no, quantity, agreed_price, start_date, end_date, status, min_contract_period, payment_terms, breach_penalties, renewals, terminated_on, termination_cost";
+
+const PAYMENT_COLUMNS: &str = "id, contract_id, supplier_id, cadence, period_start, period_end, due_date, amount, paid_on";
+
+const SELECT_COLUMNS: &str = "id, contact, wechat, phone, quantity, location, price, bandwidth_price, storage_price, min_contract_period, breach_penalties, payment_terms, server_name, server_config, rental_model, networking_category, score";
+
+const RATING_COLUMNS: &str = "supplier_id, delivery, stability, support, price_honesty, note, updated_at";
+
+const INCIDENT_COLUMNS: &str = "id, supplier_id, occurred_on, severity, description, created_at";
+
+/// 加密模式下加密存储的字段
+pub const ENCRYPTED_FIELDS: [SupplierField; 3] = [SupplierField::ContactPerson, SupplierField::Wechat, SupplierField::Phone];
+
+/// 加密数据库未提供口令时的提示
+const LOCKED_MESSAGE: &str = "数据库已加密，请通过环境变量 WEI_ASSISTANT_PASSPHRASE 或 --passphrase-file 提供口令";
+
+/// 供应商数据存储，封装数据库连接及增删改查
+///
+/// 每个实例持有一个连接，整个命令执行期间复用；语句通过连接的预编译缓存复用，
+/// 批量写入在单个事务中完成。
+pub struct SupplierStore {
+    conn: Connection,
+    /// 数据库是否处于加密模式
+    encrypted: bool,
+    /// 已解锁时的加密器
+    cipher: Option<FieldCipher>,
+}
+
+/// 一条审计日志
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AuditEntry {
+    pub id: i64,
+    /// 本地时间，如 2024-01-01 09:30:00
+    pub at: String,
+    pub actor: String,
+    pub action: String,
+    pub detail: String,
+}
+
+fn row_to_supplier(row: &Row) -> rusqlite::Result<SupplierRow> {
+    Ok(SupplierRow {
+        id: row.get(0)?,
+        contact: row.get(1)?,
+        wechat: row.get(2)?,
+        phone: row.get(3)?,
+        quantity: row.get(4)?,
+        location: row.get(5)?,
+        price: row.get(6)?,
+        bandwidth_price: row.get(7)?,
+        storage_price: row.get(8)?,
+        min_contract_period: row.get(9)?,
+        breach_penalties: row.get(10)?,
+        payment_terms: row.get(11)?,
+        server_name: row.get(12)?,
+        server_config: row.get(13)?,
+        rental_model: row.get(14)?,
+        networking_category: row.get(15)?,
+        profile: None,
+        score: row.get(16)?,
+    })
+}
+
+fn row_to_rating(row: &Row) -> rusqlite::Result<Rating> {
+    Ok(Rating {
+        supplier_id: row.get(0)?,
+        delivery: row.get(1)?,
+        stability: row.get(2)?,
+        support: row.get(3)?,
+        price_honesty: row.get(4)?,
+        note: row.get(5)?,
+        updated_at: row.get(6)?,
+    })
+}
+
+fn row_to_incident(row: &Row) -> rusqlite::Result<Incident> {
+    let severity: String = row.get(3)?;
+    Ok(Incident {
+        id: row.get(0)?,
+        supplier_id: row.get(1)?,
+        occurred_on: row.get(2)?,
+        severity: severity
+            .parse()
+            .map_err(|e: String| rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, e.into()))?,
+        description: row.get(4)?,
+        created_at: row.get(5)?,
+    })
+}
+
+fn row_to_payment(row: &Row) -> rusqlite::Result<Payment> {
+    let cadence: String = row.get(3)?;
+    Ok(Payment {
+        id: row.get(0)?,
+        contract_id: row.get(1)?,
+        supplier_id: row.get(2)?,
+        cadence: cadence
+            .parse()
+            .map_err(|e: String| rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, e.into()))?,
+        period_start: row.get(4)?,
+        period_end: row.get(5)?,
+        due_date: row.get(6)?,
+        amount: row.get(7)?,
+        paid_on: row.get(8)?,
+    })
+}
+
+fn row_to_contract(row: &Row) -> rusqlite::Result<Contract> {
+    let status: String = row.get(7)?;
+    Ok(Contract {
+        id: row.get(0)?,
+        supplier_id: row.get(1)?,
+        quote_no: row.get(2)?,
+        quantity: row.get(3)?,
+        agreed_price: row.get(4)?,
+        start_date: row.get(5)?,
+        end_date: row.get(6)?,
+        status: status
+            .parse()
+            .map_err(|e: String| rusqlite::Error::FromSqlConversionFailure(7, rusqlite::types::Type::Text, e.into()))?,
+        min_contract_period: row.get(8)?,
+        payment_terms: row.get(9)?,
+        breach_penalties: row.get(10)?,
+        renewals: row.get(11)?,
+        terminated_on: row.get(12)?,
+        termination_cost: row.get(13)?,
+    })
+}
+
+/// 取出字段值，`None` 表示该字段未提供
+fn field_value(s: &Supplier, field: SupplierField) -> Option<Value> {
+    let text = |v: &Option<String>| v.clone().map(Value::Text);
+    match field {
+        SupplierField::ContactPerson => text(&s.contact),
+        SupplierField::Wechat => text(&s.wechat),
+        SupplierField::Phone => text(&s.phone),
+        SupplierField::Quantity => s.quantity.map(|v| Value::Integer(v.into())),
+        SupplierField::Location => text(&s.location),
+        SupplierField::Price => s.price.map(Value::Real),
+        SupplierField::BandwidthPrice => s.bandwidth_price.map(Value::Real),
+        SupplierField::StoragePrice => s.storage_price.map(Value::Real),
+        SupplierField::MinContractPeriod => text(&s.min_contract_period),
+        SupplierField::BreachPenalties => text(&s.breach_penalties),
+        SupplierField::PaymentTerms => text(&s.payment_terms),
+        SupplierField::ServerName => text(&s.server_name),
+        SupplierField::ServerConfig => text(&s.server_config),
+        SupplierField::RentalModel => text(&s.rental_model),
+        SupplierField::NetworkingCategory => text(&s.networking_category),
+    }
+}
+
+/// 取出待写入的字段值，提供了加密器时加密敏感字段
+fn stored_value(s: &Supplier, field: SupplierField, cipher: Option<&FieldCipher>) -> Option<Value> {
+    match (field_value(s, field), cipher) {
+        (Some(Value::Text(text)), Some(cipher)) if ENCRYPTED_FIELDS.contains(&field) => Some(Value::Text(cipher.encrypt(&text))),
+        (value, _) => value,
+    }
+}
+
+/// 插入一条供应商记录，`conn` 可以是普通连接或事务
+fn insert(conn: &Connection, s: &Supplier, cipher: Option<&FieldCipher>) -> Result<i64> {
+    if s.contact.is_none() {
+        return Err(Error::Validation("至少需要提供联系人字段".to_string()));
+    }
+    let columns: Vec<&str> = SupplierField::ALL.iter().map(|f| f.column()).collect();
+    let placeholders = vec!["?"; columns.len()].join(", ");
+    let sql = format!("INSERT INTO suppliers ({}) VALUES ({})", columns.join(", "), placeholders);
+    let values = SupplierField::ALL.iter().map(|f| stored_value(s, *f, cipher).unwrap_or(Value::Null));
+    conn.prepare_cached(&sql)?.execute(params_from_iter(values))?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// 读取数据库的表结构版本
+fn schema_version(conn: &Connection) -> Result<i32> {
+    Ok(conn.pragma_query_value(None, "user_version", |row| row.get(0))?)
+}
+
+/// 执行 `PRAGMA integrity_check`，返回发现的问题，无问题时为空
+fn integrity_problems(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    let mut problems = Vec::new();
+    for row in rows {
+        let msg = row?;
+        if msg != "ok" {
+            problems.push(msg);
+        }
+    }
+    Ok(problems)
+}
+
+/// 用在线备份 API 把 `from` 的主数据库复制到 `to`
+fn copy_database(from: &Connection, to: &mut Connection) -> Result<()> {
+    Backup::new(from, to)?.run_to_completion(BACKUP_PAGES_PER_STEP, BACKUP_STEP_PAUSE, None)?;
+    Ok(())
+}
+
+/// 读取加密参数，未加密时为 `None`
+fn encryption_params(conn: &Connection) -> Result<Option<(String, String)>> {
+    Ok(conn
+        .prepare_cached("SELECT salt, verifier FROM encryption WHERE id = 1")?
+        .query_row([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .optional()?)
+}
+
+/// 用 `from` 解密、`to` 重新加密全部敏感字段；`None` 表示明文
+fn reencrypt_all(tx: &Transaction, from: Option<&FieldCipher>, to: Option<&FieldCipher>) -> Result<()> {
+    let columns: Vec<&str> = ENCRYPTED_FIELDS.iter().map(|f| f.column()).collect();
+    let mut rows = Vec::new();
+    {
+        let mut stmt = tx.prepare(&format!("SELECT id, {} FROM suppliers", columns.join(", ")))?;
+        let mut query = stmt.query([])?;
+        while let Some(row) = query.next()? {
+            let id: i64 = row.get(0)?;
+            let mut values = Vec::new();
+            for i in 0..columns.len() {
+                let value: Option<String> = row.get(i + 1)?;
+                let plain = match (value, from) {
+                    (Some(v), Some(cipher)) => Some(cipher.decrypt(&v)?),
+                    (value, _) => value,
+                };
+                values.push(match (plain, to) {
+                    (Some(v), Some(cipher)) => Value::Text(cipher.encrypt(&v)),
+                    (Some(v), None) => Value::Text(v),
+                    (None, _) => Value::Null,
+                });
+            }
+            values.push(Value::Integer(id));
+            rows.push(values);
+        }
+    }
+    let sets: Vec<String> = columns.iter().map(|c| format!("{} = ?", c)).collect();
+    let mut stmt = tx.prepare(&format!("UPDATE suppliers SET {} WHERE id = ?", sets.join(", ")))?;
+    for values in rows {
+        stmt.execute(params_from_iter(values))?;
+    }
+    Ok(())
+}
+
+fn user_not_found(name: &str) -> Error {
+    Error::NotFound(format!("未找到用户 {}", name))
+}
+
+fn parse_role(role: String) -> rusqlite::Result<Role> {
+    role.parse()
+        .map_err(|e: String| rusqlite::Error::FromSqlConversionFailure(1, rusqlite::types::Type::Text, e.into()))
+}
+
+fn parse_markup_kind(kind: String) -> rusqlite::Result<MarkupKind> {
+    kind.parse()
+        .map_err(|e: String| rusqlite::Error::FromSqlConversionFailure(4, rusqlite::types::Type::Text, e.into()))
+}
+
+fn supplier_not_found(id: i64) -> Error {
+    Error::NotFound(format!("未找到ID为 {} 的供应商", id))
+}
+
+/// 注册查询中使用的自定义SQL函数
+///
+/// `text_match(mode, value, pattern)`：按匹配方式比较文本，`value` 为 NULL 时返回 NULL。
+fn register_functions(conn: &Connection) -> Result<()> {
+    conn.create_scalar_function(
+        "text_match",
+        3,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            let mode: String = ctx.get(0)?;
+            let mode = mode.parse::<MatchMode>().map_err(|e| rusqlite::Error::UserFunctionError(e.into()))?;
+            let value: Option<String> = ctx.get(1)?;
+            let pattern: String = ctx.get(2)?;
+            Ok(value.map(|v| text_matches(mode, &v, &pattern)))
+        },
+    )?;
+    Ok(())
+}
+
+/// 注册 `decrypt_field(value)`：解密敏感字段，NULL 原样返回；`encrypted` 为数据库是否记录了加密参数，
+/// 未加密时所有值都是明文（即使以 `enc:` 开头），加密但未提供加密器时遇到密文报错
+///
+/// 连接上的临时视图 `supplier_plain` 通过该函数给出解密后的供应商表，查询都基于该视图，
+/// 因此筛选和排序对加密字段同样有效。
+fn register_decrypt(conn: &Connection, encrypted: bool, cipher: Option<FieldCipher>) -> Result<()> {
+    conn.create_scalar_function(
+        "decrypt_field",
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        move |ctx| {
+            let value: Option<String> = ctx.get(0)?;
+            match (value, &cipher) {
+                (Some(v), Some(cipher)) => cipher.decrypt(&v).map(Some).map_err(|e| rusqlite::Error::UserFunctionError(e.into())),
+                (Some(v), None) if encrypted && crypto::is_ciphertext(&v) => Err(rusqlite::Error::UserFunctionError(LOCKED_MESSAGE.into())),
+                (value, _) => Ok(value),
+            }
+        },
+    )?;
+    Ok(())
+}
+
+/// 综合评分的SQL表达式，与 [`rating::score`] 的计算一致；供应商表的别名为 `s`
+fn score_sql() -> String {
+    let count = RatingDimension::ALL.iter().map(|d| format!("(r.{} IS NOT NULL)", d.column())).collect::<Vec<_>>().join(" + ");
+    let sum = RatingDimension::ALL.iter().map(|d| format!("COALESCE(r.{}, 0)", d.column())).collect::<Vec<_>>().join(" + ");
+    let penalties: String = IncidentSeverity::ALL.iter().map(|s| format!(" WHEN '{}' THEN {}", s.name(), s.penalty())).collect();
+    // 多参数 MAX 遇到 NULL（没有打分）时返回 NULL；CAST 使视图列具有 REAL 亲和性
+    format!(
+        "CAST((SELECT ROUND(MAX(0.0, ({sum}) * 1.0 / NULLIF({count}, 0) - \
+            (SELECT TOTAL(CASE i.severity{penalties} END) FROM main.supplier_incidents i \
+             WHERE i.supplier_id = s.id AND i.occurred_on > date('now', 'localtime', '-{days} days'))), 2) \
+         FROM main.supplier_ratings r WHERE r.supplier_id = s.id) AS REAL)",
+        days = rating::INCIDENT_WINDOW_DAYS,
+    )
+}
+
+/// 解析 `views` 表中保存的视图；早期版本保存的是命令行参数数组，无法还原时提示重新保存
+fn parse_view(name: &str, json: &str) -> Result<SavedView> {
+    if json.trim_start().starts_with('[') {
+        return Err(Error::Validation(format!("视图 {} 是旧版本保存的命令行参数，请用 view save 重新保存", name)));
+    }
+    Ok(serde_json::from_str(json)?)
+}
+
+/// 创建解密视图 `supplier_plain`，列与 `suppliers` 表相同，另加综合评分列 `score`
+fn create_plain_view(conn: &Connection) -> Result<()> {
+    let columns: Vec<String> = SupplierField::ALL
+        .iter()
+        .map(|f| match ENCRYPTED_FIELDS.contains(f) {
+            true => format!("decrypt_field({0}) AS {0}", f.column()),
+            false => f.column().to_string(),
+        })
+        .collect();
+    conn.execute_batch(&format!(
+        "CREATE TEMP VIEW IF NOT EXISTS supplier_plain AS SELECT id, {}, {} AS score FROM main.suppliers s",
+        columns.join(", "),
+        score_sql()
+    ))?;
+    Ok(())
+}
+
+/// 快照目录中的快照文件，从旧到新排列
+pub fn list_snapshots(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "db"))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+impl SupplierStore {
+    /// 打开（必要时创建）数据库文件并确保表结构存在
+    ///
+    /// 文件数据库启用WAL模式，多个命令行进程可同时读写；遇到写锁时最多等待
+    /// [`DEFAULT_BUSY_TIMEOUT`]。
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
+        Self::init(conn)
+    }
+
+    /// 打开内存数据库，主要用于测试
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.busy_timeout(DEFAULT_BUSY_TIMEOUT)?;
+        register_functions(&conn)?;
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+        conn.execute_batch(SCHEMA)?;
+        let version = schema_version(&conn)?;
+        if version > SCHEMA_VERSION {
+            return Err(Error::Validation(format!(
+                "数据库表结构版本 {} 高于本程序支持的版本 {}，请升级程序", version, SCHEMA_VERSION
+            )));
+        }
+        if version < SCHEMA_VERSION {
+            conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        }
+        let encrypted = encryption_params(&conn)?.is_some();
+        register_decrypt(&conn, encrypted, None)?;
+        create_plain_view(&conn)?;
+        Ok(Self { conn, encrypted, cipher: None })
+    }
+
+    /// 敏感字段是否加密存储
+    pub fn is_encrypted(&self) -> bool {
+        self.encrypted
+    }
+
+    /// 用口令解锁加密数据库，之后读写时自动解密、加密敏感字段；未加密时不做任何事
+    pub fn unlock(&mut self, passphrase: &str) -> Result<()> {
+        let Some((salt, verifier)) = encryption_params(&self.conn)? else {
+            return Ok(());
+        };
+        let cipher = FieldCipher::derive(passphrase, &salt)?;
+        if !cipher.verify(&verifier) {
+            return Err(Error::Validation("口令错误".to_string()));
+        }
+        self.set_cipher(Some(cipher))
+    }
+
+    /// 开启加密：用口令派生的密钥加密全部联系人、微信和电话
+    pub fn encrypt(&mut self, passphrase: &str) -> Result<()> {
+        if self.encrypted {
+            return Err(Error::Validation("数据库已经是加密模式，更换口令请使用 rekey".to_string()));
+        }
+        let salt = crypto::new_salt();
+        let cipher = FieldCipher::derive(passphrase, &salt)?;
+        let tx = Transaction::new_unchecked(&self.conn, TransactionBehavior::Immediate)?;
+        reencrypt_all(&tx, None, Some(&cipher))?;
+        tx.execute("INSERT INTO encryption (id, salt, verifier) VALUES (1, ?, ?)", [&salt, &cipher.verifier()])?;
+        tx.commit()?;
+        self.encrypted = true;
+        self.set_cipher(Some(cipher))?;
+        self.purge_free_pages()
+    }
+
+    /// 关闭加密，敏感字段恢复为明文；需要先解锁
+    pub fn decrypt(&mut self) -> Result<()> {
+        if !self.encrypted {
+            return Err(Error::Validation("数据库未加密".to_string()));
+        }
+        let cipher = self.require_cipher()?.cloned();
+        let tx = Transaction::new_unchecked(&self.conn, TransactionBehavior::Immediate)?;
+        reencrypt_all(&tx, cipher.as_ref(), None)?;
+        tx.execute("DELETE FROM encryption", [])?;
+        tx.commit()?;
+        self.encrypted = false;
+        self.set_cipher(None)
+    }
+
+    /// 更换口令：用新口令派生的密钥重新加密全部敏感字段；需要先解锁
+    pub fn rekey(&mut self, new_passphrase: &str) -> Result<()> {
+        if !self.encrypted {
+            return Err(Error::Validation("数据库未加密，请先使用 encrypt".to_string()));
+        }
+        let old = self.require_cipher()?.cloned();
+        let salt = crypto::new_salt();
+        let cipher = FieldCipher::derive(new_passphrase, &salt)?;
+        let tx = Transaction::new_unchecked(&self.conn, TransactionBehavior::Immediate)?;
+        reencrypt_all(&tx, old.as_ref(), Some(&cipher))?;
+        tx.execute("UPDATE encryption SET salt = ?, verifier = ? WHERE id = 1", [&salt, &cipher.verifier()])?;
+        tx.commit()?;
+        self.set_cipher(Some(cipher))?;
+        self.purge_free_pages()
+    }
+
+    fn set_cipher(&mut self, cipher: Option<FieldCipher>) -> Result<()> {
+        // 重新注册函数会使引用旧函数的缓存语句失效
+        self.conn.flush_prepared_statement_cache();
+        register_decrypt(&self.conn, self.encrypted, cipher.clone())?;
+        self.cipher = cipher;
+        Ok(())
+    }
+
+    /// 读写敏感字段前调用：加密数据库未解锁时报错，返回写入时使用的加密器
+    fn require_cipher(&self) -> Result<Option<&FieldCipher>> {
+        if self.encrypted && self.cipher.is_none() {
+            return Err(Error::Validation(LOCKED_MESSAGE.to_string()));
+        }
+        Ok(self.cipher.as_ref())
+    }
+
+    /// 整理数据库文件并清空WAL，避免旧的明文或旧密钥的密文残留在空闲页中
+    fn purge_free_pages(&self) -> Result<()> {
+        self.conn.execute_batch("VACUUM")?;
+        if self.path().is_some() {
+            self.conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+        }
+        Ok(())
+    }
+
+    /// 数据库文件路径，内存数据库为 `None`
+    pub fn path(&self) -> Option<&Path> {
+        self.conn.path().filter(|p| !p.is_empty()).map(Path::new)
+    }
+
+    /// 表结构版本
+    pub fn schema_version(&self) -> Result<i32> {
+        schema_version(&self.conn)
+    }
+
+    /// 完整性检查，返回发现的问题，无问题时为空
+    pub fn integrity_check(&self) -> Result<Vec<String>> {
+        integrity_problems(&self.conn)
+    }
+
+    /// 使用 SQLite 在线备份 API 备份到 `path`，备份期间其他进程可以继续读写
+    ///
+    /// `path` 已存在时其内容会被覆盖。
+    pub fn backup<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut dst = Connection::open(path)?;
+        copy_database(&self.conn, &mut dst)
+    }
+
+    /// 用备份文件替换当前数据库的全部内容
+    ///
+    /// 恢复前检查备份文件：必须通过完整性检查、包含供应商表，且表结构版本不高于
+    /// [`SCHEMA_VERSION`]。版本较低的备份恢复后会自动补齐新增的表。
+    pub fn restore<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        if !path.is_file() {
+            return Err(Error::NotFound(format!("备份文件不存在: {}", path.display())));
+        }
+        let src = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let problems = integrity_problems(&src)?;
+        if !problems.is_empty() {
+            return Err(Error::Validation(format!("备份文件未通过完整性检查: {}", problems.join("; "))));
+        }
+        let has_suppliers: bool = src
+            .prepare("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'suppliers'")?
+            .exists([])?;
+        if !has_suppliers {
+            return Err(Error::Validation(format!("{} 不是供应商数据库备份", path.display())));
+        }
+        let version = schema_version(&src)?;
+        if version > SCHEMA_VERSION {
+            return Err(Error::Validation(format!(
+                "备份的表结构版本 {} 高于本程序支持的版本 {}，请升级程序后再恢复", version, SCHEMA_VERSION
+            )));
+        }
+        copy_database(&src, &mut self.conn)?;
+        // 恢复后连接上缓存的语句可能引用旧的表结构
+        self.conn.flush_prepared_statement_cache();
+        self.conn.execute_batch(SCHEMA)?;
+        if version < SCHEMA_VERSION {
+            self.conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        }
+        // 备份可能使用不同的口令或未加密，需要重新解锁
+        self.encrypted = encryption_params(&self.conn)?.is_some();
+        self.set_cipher(None)
+    }
+
+    /// 在 `dir` 中保存一份快照，文件名形如 `20240101-093000123-delete.db`，只保留最近 `keep` 份
+    pub fn snapshot(&self, dir: &Path, label: &str, keep: usize) -> Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        // 文件名以精确到毫秒的时间开头，按文件名排序即按时间排序；同一毫秒内重复时稍等再取
+        let path = loop {
+            let stamp: String = self.conn.query_row(
+                "SELECT strftime('%Y%m%d-%H%M%S', 'now', 'localtime') || substr(strftime('%f', 'now'), 3, 4)",
+                [],
+                |row| row.get(0),
+            )?;
+            let path = dir.join(format!("{}-{}.db", stamp.replace('.', ""), label));
+            if !path.exists() {
+                break path;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        };
+        self.backup(&path)?;
+
+        let snapshots = list_snapshots(dir)?;
+        for old in snapshots.iter().take(snapshots.len().saturating_sub(keep)) {
+            fs::remove_file(old)?;
+        }
+        Ok(path)
+    }
+
+    /// 修改写锁等待时间
+    pub fn set_busy_timeout(&self, timeout: Duration) -> Result<()> {
+        self.conn.busy_timeout(timeout)?;
+        Ok(())
+    }
+
+    /// 新增供应商，返回新记录的ID
+    pub fn add(&self, s: &Supplier) -> Result<i64> {
+        insert(&self.conn, s, self.require_cipher()?)
+    }
+
+    /// 在单个事务中批量新增供应商，任一条失败时全部回滚
+    pub fn add_many<'a, I>(&self, suppliers: I) -> Result<Vec<i64>>
+    where
+        I: IntoIterator<Item = &'a Supplier>,
+    {
+        // 立即获取写锁，避免在WAL模式下由读事务升级为写事务时直接返回 SQLITE_BUSY
+        let cipher = self.require_cipher()?;
+        let tx = Transaction::new_unchecked(&self.conn, TransactionBehavior::Immediate)?;
+        let ids = suppliers
+            .into_iter()
+            .map(|s| insert(&tx, s, cipher))
+            .collect::<Result<Vec<_>>>()?;
+        tx.commit()?;
+        Ok(ids)
+    }
+
+    /// 按ID获取供应商
+    pub fn get(&self, id: i64) -> Result<SupplierRow> {
+        self.require_cipher()?;
+        let sql = format!("SELECT {} FROM supplier_plain WHERE id = ?", SELECT_COLUMNS);
+        self.conn
+            .prepare_cached(&sql)?
+            .query_row([id], row_to_supplier)
+            .optional()?
+            .ok_or_else(|| supplier_not_found(id))
+    }
+
+    /// 按查询构建器中的条件筛选供应商，地点和服务器名自动应用同义词
+    pub fn query(&self, builder: QueryBuilder) -> Result<Vec<SupplierRow>> {
+        self.require_cipher()?;
+        let (where_sql, params) = builder.with_synonyms(self.synonyms()?).build()?;
+        let sql = format!("SELECT {} FROM supplier_plain {}", SELECT_COLUMNS, where_sql);
+        let mut stmt = self.conn.prepare_cached(&sql)?;
+        let rows = stmt
+            .query_map(params_from_iter(params.iter()), row_to_supplier)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(rows)
+    }
+
+    /// 更新供应商，仅覆盖 `changes` 中为 `Some` 的字段
+    pub fn update(&self, id: i64, changes: &Supplier) -> Result<()> {
+        let cipher = self.require_cipher()?;
+        let mut sets = Vec::new();
+        let mut values = Vec::new();
+        for field in SupplierField::ALL {
+            if let Some(v) = stored_value(changes, field, cipher) {
+                sets.push(format!("{} = ?", field.column()));
+                values.push(v);
+            }
+        }
+        if sets.is_empty() {
+            return Err(Error::Validation("没有需要更新的字段".to_string()));
+        }
+        values.push(Value::Integer(id));
+        let sql = format!("UPDATE suppliers SET {} WHERE id = ?", sets.join(", "));
+        match self.conn.execute(&sql, params_from_iter(values))? {
+            0 => Err(supplier_not_found(id)),
+            _ => Ok(()),
+        }
+    }
+
+    /// 检查供应商能否删除：不存在时返回 [`Error::NotFound`]，有履行中的合同时返回 [`Error::Validation`]
+    pub fn check_delete(&self, id: i64) -> Result<()> {
+        self.require_supplier(id)?;
+        let active: i64 = self
+            .conn
+            .prepare_cached("SELECT COUNT(*) FROM contracts WHERE supplier_id = ? AND status = 'active'")?
+            .query_row([id], |row| row.get(0))?;
+        if active > 0 {
+            return Err(Error::Validation(format!("供应商 {} 还有 {} 份履行中的合同，请先终止后再删除", id, active)));
+        }
+        Ok(())
+    }
+
+    /// 删除供应商及其评分和事故记录；有履行中的合同时拒绝删除
+    pub fn delete(&self, id: i64) -> Result<()> {
+        let tx = Transaction::new_unchecked(&self.conn, TransactionBehavior::Immediate)?;
+        self.check_delete(id)?;
+        tx.prepare_cached("DELETE FROM supplier_ratings WHERE supplier_id = ?")?.execute([id])?;
+        tx.prepare_cached("DELETE FROM supplier_incidents WHERE supplier_id = ?")?.execute([id])?;
+        if tx.prepare_cached("DELETE FROM suppliers WHERE id = ?")?.execute([id])? == 0 {
+            return Err(supplier_not_found(id));
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 供应商不存在时返回 [`Error::NotFound`]
+    fn require_supplier(&self, id: i64) -> Result<()> {
+        let exists: bool =
+            self.conn.prepare_cached("SELECT EXISTS (SELECT 1 FROM suppliers WHERE id = ?)")?.query_row([id], |row| row.get(0))?;
+        if exists {
+            Ok(())
+        } else {
+            Err(supplier_not_found(id))
+        }
+    }
+
+    /// 内置同义词与数据库中自定义同义词的合集
+    pub fn synonyms(&self) -> Result<Synonyms> {
+        let mut synonyms = Synonyms::builtin();
+        let mut stmt = self.conn.prepare_cached("SELECT field, canonical, alias FROM synonyms ORDER BY rowid")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))?;
+        for row in rows {
+            let (field, canonical, alias) = row?;
+            if let Ok(field) = field.parse::<SupplierField>() {
+                synonyms.insert(field, &canonical, &alias);
+            }
+        }
+        Ok(synonyms)
+    }
+
+    /// 添加自定义同义词，仅支持地点和服务器名
+    pub fn add_synonym(&self, field: SupplierField, canonical: &str, alias: &str) -> Result<()> {
+        if !Synonyms::applies_to(field) {
+            return Err(Error::Validation(format!("字段 {} 不支持同义词，仅支持 location 和 server_name", field.column())));
+        }
+        self.conn
+            .prepare_cached("INSERT OR REPLACE INTO synonyms (field, canonical, alias) VALUES (?, ?, ?)")?
+            .execute([field.column(), canonical, alias])?;
+        Ok(())
+    }
+
+    /// 保存命名视图，同名视图会被覆盖
+    pub fn save_view(&self, name: &str, view: &SavedView) -> Result<()> {
+        if name.trim().is_empty() {
+            return Err(Error::Validation("视图名称不能为空".to_string()));
+        }
+        self.conn
+            .prepare_cached("INSERT OR REPLACE INTO views (name, args) VALUES (?, ?)")?
+            .execute([name, &serde_json::to_string(view)?])?;
+        Ok(())
+    }
+
+    /// 读取视图
+    pub fn get_view(&self, name: &str) -> Result<SavedView> {
+        let args: String = self.conn
+            .prepare_cached("SELECT args FROM views WHERE name = ?")?
+            .query_row([name], |row| row.get(0))
+            .optional()?
+            .ok_or_else(|| Error::NotFound(format!("未找到视图 {}", name)))?;
+        parse_view(name, &args)
+    }
+
+    /// 按名称列出全部视图
+    pub fn list_views(&self) -> Result<Vec<(String, SavedView)>> {
+        let mut stmt = self.conn.prepare_cached("SELECT name, args FROM views ORDER BY name")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+        let mut views = Vec::new();
+        for row in rows {
+            let (name, args) = row?;
+            let view = parse_view(&name, &args)?;
+            views.push((name, view));
+        }
+        Ok(views)
+    }
+
+    /// 删除视图
+    pub fn delete_view(&self, name: &str) -> Result<()> {
+        match self.conn.prepare_cached("DELETE FROM views WHERE name = ?")?.execute([name])? {
+            0 => Err(Error::NotFound(format!("未找到视图 {}", name))),
+            _ => Ok(()),
+        }
+    }
+
+    /// 追加一条审计日志，返回日志ID
+    pub fn record_audit(&self, actor: &str, action: &str, detail: &str) -> Result<i64> {
+        self.conn
+            .prepare_cached("INSERT INTO audit_log (actor, action, detail) VALUES (?, ?, ?)")?
+            .execute([actor, action, detail])?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// 最近的 `limit` 条审计日志，从新到旧
+    pub fn audit_log(&self, limit: usize) -> Result<Vec<AuditEntry>> {
+        let mut stmt = self.conn.prepare_cached("SELECT id, at, actor, action, detail FROM audit_log ORDER BY id DESC LIMIT ?")?;
+        let rows = stmt.query_map([limit as i64], |row| {
+            Ok(AuditEntry { id: row.get(0)?, at: row.get(1)?, actor: row.get(2)?, action: row.get(3)?, detail: row.get(4)? })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// 是否存在用户；没有用户时不做权限检查
+    pub fn has_users(&self) -> Result<bool> {
+        Ok(self.conn.prepare_cached("SELECT 1 FROM users LIMIT 1")?.exists([])?)
+    }
+
+    /// 按用户名列出全部用户
+    pub fn list_users(&self) -> Result<Vec<User>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT name, role, token_digest IS NOT NULL, created_at FROM users ORDER BY name",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(User { name: row.get(0)?, role: parse_role(row.get(1)?)?, has_token: row.get(2)?, created_at: row.get(3)? })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    fn user_role(&self, name: &str) -> Result<Role> {
+        self.conn
+            .prepare_cached("SELECT role FROM users WHERE name = ?")?
+            .query_row([name], |row| parse_role(row.get(0)?))
+            .optional()?
+            .ok_or_else(|| user_not_found(name))
+    }
+
+    /// 修改或删除 `name` 后仍需至少保留一个 admin，除非不再有任何用户
+    fn ensure_admin_remains(&self, name: &str, removing: bool) -> Result<()> {
+        let others: i64 = self.conn.prepare_cached("SELECT COUNT(*) FROM users WHERE name != ?")?.query_row([name], |r| r.get(0))?;
+        let other_admins: i64 = self
+            .conn
+            .prepare_cached("SELECT COUNT(*) FROM users WHERE name != ? AND role = 'admin'")?
+            .query_row([name], |r| r.get(0))?;
+        if other_admins == 0 && (others > 0 || !removing) {
+            return Err(Error::Validation(format!("{} 是唯一的 admin，请先把其他用户设为 admin", name)));
+        }
+        Ok(())
+    }
+
+    /// 添加用户；第一个用户必须是 admin
+    pub fn add_user(&self, name: &str, role: Role, password: &str) -> Result<()> {
+        auth::validate_user_name(name)?;
+        if !self.has_users()? && role != Role::Admin {
+            return Err(Error::Validation("第一个用户必须是 admin".to_string()));
+        }
+        if self.user_role(name).is_ok() {
+            return Err(Error::Validation(format!("用户 {} 已存在", name)));
+        }
+        let hash = auth::hash_password(password)?;
+        self.conn
+            .prepare_cached("INSERT INTO users (name, role, password_hash) VALUES (?, ?, ?)")?
+            .execute([name, role.name(), &hash])?;
+        Ok(())
+    }
+
+    /// 修改用户角色，不能去掉最后一个 admin
+    pub fn set_user_role(&self, name: &str, role: Role) -> Result<()> {
+        if self.user_role(name)? == Role::Admin && role != Role::Admin {
+            self.ensure_admin_remains(name, false)?;
+        }
+        self.conn.prepare_cached("UPDATE users SET role = ? WHERE name = ?")?.execute([role.name(), name])?;
+        Ok(())
+    }
+
+    /// 修改用户口令
+    pub fn set_user_password(&self, name: &str, password: &str) -> Result<()> {
+        self.user_role(name)?;
+        let hash = auth::hash_password(password)?;
+        self.conn.prepare_cached("UPDATE users SET password_hash = ? WHERE name = ?")?.execute([&hash, name])?;
+        Ok(())
+    }
+
+    /// 删除用户；删除最后一个用户后不再做权限检查
+    pub fn remove_user(&self, name: &str) -> Result<()> {
+        if self.user_role(name)? == Role::Admin {
+            self.ensure_admin_remains(name, true)?;
+        }
+        self.conn.prepare_cached("DELETE FROM users WHERE name = ?")?.execute([name])?;
+        Ok(())
+    }
+
+    /// 为用户签发新令牌并返回令牌原文，旧令牌失效；数据库中只保存摘要
+    pub fn issue_token(&self, name: &str) -> Result<String> {
+        self.user_role(name)?;
+        let (token, digest) = auth::new_token();
+        self.conn.prepare_cached("UPDATE users SET token_digest = ? WHERE name = ?")?.execute([&digest, name])?;
+        Ok(token)
+    }
+
+    /// 吊销用户的令牌
+    pub fn revoke_token(&self, name: &str) -> Result<()> {
+        self.user_role(name)?;
+        self.conn.prepare_cached("UPDATE users SET token_digest = NULL WHERE name = ?")?.execute([name])?;
+        Ok(())
+    }
+
+    /// 以用户名和口令认证
+    pub fn authenticate_password(&self, name: &str, password: &str) -> Result<Identity> {
+        let user = self
+            .conn
+            .prepare_cached("SELECT role, password_hash FROM users WHERE name = ?")?
+            .query_row([name], |row| Ok((parse_role(row.get(0)?)?, row.get::<_, String>(1)?)))
+            .optional()?;
+        match user {
+            Some((role, hash)) if auth::verify_password(password, &hash) => Ok(Identity { name: name.to_string(), role }),
+            Some(_) => Err(Error::PermissionDenied("用户名或口令错误".to_string())),
+            None => {
+                auth::verify_unknown_user(password);
+                Err(Error::PermissionDenied("用户名或口令错误".to_string()))
+            }
+        }
+    }
+
+    /// 以令牌认证
+    pub fn authenticate_token(&self, token: &str) -> Result<Identity> {
+        self.conn
+            .prepare_cached("SELECT name, role FROM users WHERE token_digest = ?")?
+            .query_row([auth::token_digest(token)], |row| Ok(Identity { name: row.get(0)?, role: parse_role(row.get(1)?)? }))
+            .optional()?
+            .ok_or_else(|| Error::PermissionDenied("令牌无效或已被吊销".to_string()))
+    }
+
+    /// 供应商的报价编号，没有编号的供应商在同一事务中分配新编号
+    pub fn offer_codes(&self, ids: &[i64]) -> Result<HashMap<i64, String>> {
+        let tx = Transaction::new_unchecked(&self.conn, TransactionBehavior::Immediate)?;
+        let mut codes = HashMap::new();
+        {
+            let mut select = tx.prepare_cached("SELECT code FROM offer_codes WHERE supplier_id = ?")?;
+            let mut insert = tx.prepare_cached("INSERT OR IGNORE INTO offer_codes (supplier_id, code) VALUES (?, ?)")?;
+            for &id in ids {
+                let code = match select.query_row([id], |row| row.get::<_, String>(0)).optional()? {
+                    Some(code) => code,
+                    // 随机编号极少重复，重复时 INSERT OR IGNORE 不插入，换一个重试
+                    None => loop {
+                        let code = catalog::new_offer_code();
+                        if insert.execute(params![id, code])? == 1 {
+                            break code;
+                        }
+                    },
+                };
+                codes.insert(id, code);
+            }
+        }
+        tx.commit()?;
+        Ok(codes)
+    }
+
+    /// 按报价编号查找对应的供应商，编号不区分大小写
+    pub fn lookup_offer(&self, code: &str) -> Result<SupplierRow> {
+        let code = code.trim().to_uppercase();
+        let id: i64 = self
+            .conn
+            .prepare_cached("SELECT supplier_id FROM offer_codes WHERE code = ?")?
+            .query_row([&code], |row| row.get(0))
+            .optional()?
+            .ok_or_else(|| Error::NotFound(format!("未找到报价编号 {}", code)))?;
+        self.get(id).map_err(|e| match e {
+            Error::NotFound(_) => Error::NotFound(format!("报价编号 {} 对应的供应商（ID {}）已删除", code, id)),
+            e => e,
+        })
+    }
+
+    /// 按供应商ID或报价编号（如 OF-7KQ2M9XA）查找供应商
+    pub fn find_offer(&self, offer: &str) -> Result<SupplierRow> {
+        let offer = offer.trim();
+        if offer.to_uppercase().starts_with(catalog::OFFER_CODE_PREFIX) {
+            return self.lookup_offer(offer);
+        }
+        let id = offer
+            .parse()
+            .map_err(|_| Error::Validation(format!("无效的报价: {}，应为供应商ID或报价编号", offer)))?;
+        self.get(id)
+    }
+
+    /// 新增定价规则，返回规则ID；`rule.id` 被忽略
+    pub fn add_pricing_rule(&self, rule: &PricingRule) -> Result<i64> {
+        rule.validate()?;
+        self.conn
+            .prepare_cached(
+                "INSERT INTO pricing_rules (location, networking_category, rental_model, kind, value, min_margin) VALUES (?, ?, ?, ?, ?, ?)",
+            )?
+            .execute(params![rule.location, rule.networking_category, rule.rental_model, rule.kind.name(), rule.value, rule.min_margin])?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// 按ID列出全部定价规则
+    pub fn pricing_rules(&self) -> Result<Vec<PricingRule>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, location, networking_category, rental_model, kind, value, min_margin FROM pricing_rules ORDER BY id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(PricingRule {
+                id: row.get(0)?,
+                location: row.get(1)?,
+                networking_category: row.get(2)?,
+                rental_model: row.get(3)?,
+                kind: parse_markup_kind(row.get(4)?)?,
+                value: row.get(5)?,
+                min_margin: row.get(6)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// 删除定价规则
+    pub fn remove_pricing_rule(&self, id: i64) -> Result<()> {
+        match self.conn.prepare_cached("DELETE FROM pricing_rules WHERE id = ?")?.execute([id])? {
+            0 => Err(Error::NotFound(format!("未找到ID为 {} 的定价规则", id))),
+            _ => Ok(()),
+        }
+    }
+
+    /// 适用于该供应商的定价规则，见 [`pricing::select_rule`]
+    pub fn pricing_rule_for(&self, row: &SupplierRow) -> Result<Option<PricingRule>> {
+        let rules = self.pricing_rules()?;
+        Ok(pricing::select_rule(&rules, row, &self.synonyms()?).cloned())
+    }
+
+    /// 生成并保存报价单，编号按报价日期顺序分配
+    pub fn create_quote(&self, row: &SupplierRow, price: &PriceQuote, request: &QuoteRequest) -> Result<Quotation> {
+        let offer_code = self.offer_codes(&[row.id])?.remove(&row.id).unwrap_or_default();
+        let tx = Transaction::new_unchecked(&self.conn, TransactionBehavior::Immediate)?;
+        let (today, stamp, valid_until): (String, String, String) = tx.query_row(
+            "SELECT date('now', 'localtime'), strftime('%Y%m%d', 'now', 'localtime'), date('now', 'localtime', ?)",
+            [format!("+{} days", request.valid_days)],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+        let prefix = format!("{}{}-", QUOTE_NO_PREFIX, stamp);
+        let count: i64 = tx
+            .prepare_cached("SELECT COUNT(*) FROM quotes WHERE quote_no LIKE ? || '%'")?
+            .query_row([&prefix], |r| r.get(0))?;
+        let quote_no = format!("{}{:03}", prefix, count + 1);
+        let quotation = Quotation::new(quote_no, today, valid_until, offer_code, row, price, request)?;
+        tx.prepare_cached(
+            "INSERT INTO quotes (quote_no, supplier_id, customer, quantity, months, total, total_cost, created_on, valid_until, detail) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )?
+        .execute(params![
+            quotation.quote_no,
+            quotation.supplier_id,
+            quotation.customer,
+            quotation.quantity,
+            quotation.months,
+            quotation.total,
+            quotation.total_cost,
+            quotation.created_on,
+            quotation.valid_until,
+            serde_json::to_string(&quotation)?,
+        ])?;
+        tx.commit()?;
+        Ok(quotation)
+    }
+
+    /// 按编号读取报价单
+    pub fn get_quote(&self, quote_no: &str) -> Result<Quotation> {
+        let detail: String = self
+            .conn
+            .prepare_cached("SELECT detail FROM quotes WHERE quote_no = ?")?
+            .query_row([quote_no.trim().to_uppercase()], |row| row.get(0))
+            .optional()?
+            .ok_or_else(|| Error::NotFound(format!("未找到报价单 {}", quote_no)))?;
+        Ok(serde_json::from_str(&detail)?)
+    }
+
+    /// 全部报价单，从新到旧
+    pub fn list_quotes(&self) -> Result<Vec<Quotation>> {
+        let mut stmt = self.conn.prepare_cached("SELECT detail FROM quotes ORDER BY created_on DESC, quote_no DESC")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        let mut quotes = Vec::new();
+        for detail in rows {
+            quotes.push(serde_json::from_str(&detail?)?);
+        }
+        Ok(quotes)
+    }
+
+    /// 当天日期（本地时间）
+    pub fn today(&self) -> Result<Date> {
+        let today: String = self.conn.query_row("SELECT date('now', 'localtime')", [], |row| row.get(0))?;
+        today.parse()
+    }
+
+    /// 新建合同，返回合同ID
+    pub fn create_contract(&self, contract: &NewContract) -> Result<i64> {
+        contract.validate()?;
+        self.conn
+            .prepare_cached(
+                "INSERT INTO contracts (supplier_id, quote_no, quantity, agreed_price, start_date, end_date, min_contract_period, payment_terms, breach_penalties) \
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )?
+            .execute(params![
+                contract.supplier_id,
+                contract.quote_no,
+                contract.quantity,
+                contract.agreed_price,
+                contract.start_date,
+                contract.end_date,
+                contract.min_contract_period,
+                contract.payment_terms,
+                contract.breach_penalties,
+            ])?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// 按ID读取合同
+    pub fn get_contract(&self, id: i64) -> Result<Contract> {
+        self.conn
+            .prepare_cached(&format!("SELECT {} FROM contracts WHERE id = ?", CONTRACT_COLUMNS))?
+            .query_row([id], row_to_contract)
+            .optional()?
+            .ok_or_else(|| Error::NotFound(format!("未找到ID为 {} 的合同", id)))
+    }
+
+    /// 按ID列出合同，`status` 为 `None` 时列出全部
+    pub fn list_contracts(&self, status: Option<ContractStatus>) -> Result<Vec<Contract>> {
+        let mut stmt = self
+            .conn
+            .prepare_cached(&format!("SELECT {} FROM contracts WHERE ?1 IS NULL OR status = ?1 ORDER BY id", CONTRACT_COLUMNS))?;
+        let rows = stmt.query_map([status.map(|s| s.name())], row_to_contract)?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// 读取履行中的合同，已终止时报错
+    fn active_contract(&self, id: i64) -> Result<Contract> {
+        let contract = self.get_contract(id)?;
+        if contract.status != ContractStatus::Active {
+            return Err(Error::Validation(format!("合同 {} 已于 {} 终止", id, contract.terminated_on.map(|d| d.to_string()).unwrap_or_default())));
+        }
+        Ok(contract)
+    }
+
+    /// 续约：结束日期顺延 `months` 个月，可同时修改约定单价
+    pub fn renew_contract(&self, id: i64, months: u32, agreed_price: Option<f64>) -> Result<Contract> {
+        let contract = self.active_contract(id)?;
+        if months == 0 {
+            return Err(Error::Validation("续约月数必须大于0".to_string()));
+        }
+        if let Some(price) = agreed_price {
+            if !price.is_finite() || price < 0.0 {
+                return Err(Error::Validation(format!("约定单价必须是不小于0的数字: {}", price)));
+            }
+        }
+        let end_date = contract.end_date.add_months(months as i32);
+        self.conn
+            .prepare_cached("UPDATE contracts SET end_date = ?, agreed_price = ?, renewals = renewals + 1 WHERE id = ?")?
+            .execute(params![end_date, agreed_price.unwrap_or(contract.agreed_price), id])?;
+        self.get_contract(id)
+    }
+
+    /// 在 `on` 终止合同，记录估算的违约金；`on` 不能早于开始日期
+    pub fn terminate_contract(&self, id: i64, on: Date) -> Result<Contract> {
+        let contract = self.active_contract(id)?;
+        if on < contract.start_date {
+            return Err(Error::Validation(format!("终止日期 {} 早于合同开始日期 {}", on, contract.start_date)));
+        }
+        let estimate = contract.termination_estimate(on);
+        let tx = Transaction::new_unchecked(&self.conn, TransactionBehavior::Immediate)?;
+        tx.prepare_cached("UPDATE contracts SET status = ?, terminated_on = ?, termination_cost = ? WHERE id = ?")?
+            .execute(params![ContractStatus::Terminated.name(), on, estimate.penalty, id])?;
+        // 终止后开始的各期不再需要付款
+        tx.prepare_cached("DELETE FROM payments WHERE contract_id = ? AND paid_on IS NULL AND period_start >= ?")?
+            .execute(params![id, on])?;
+        tx.commit()?;
+        self.get_contract(id)
+    }
+
+    /// 为履行中的合同生成付款计划，返回新增的期数
+    ///
+    /// 已有付款计划时从最后一期的结束日期继续，因此续约后再次生成只会补上新增的各期。
+    /// 付款周期依次取 `cadence`、合同的付款方式、上次生成时使用的周期，都没有时报错。
+    pub fn generate_payments(&self, contract_id: i64, cadence: Option<PaymentCadence>) -> Result<usize> {
+        let contract = self.active_contract(contract_id)?;
+        let tx = Transaction::new_unchecked(&self.conn, TransactionBehavior::Immediate)?;
+        let last: Option<(Date, String)> = tx
+            .prepare_cached("SELECT period_end, cadence FROM payments WHERE contract_id = ? ORDER BY period_end DESC LIMIT 1")?
+            .query_row([contract_id], |row| Ok((row.get(0)?, row.get(1)?)))
+            .optional()?;
+        let cadence = match cadence.or_else(|| contract.payment_terms.as_deref().and_then(payment::parse_cadence)) {
+            Some(c) => c,
+            None => match &last {
+                Some((_, name)) => name.parse().map_err(Error::Validation)?,
+                None => {
+                    return Err(Error::Validation(format!(
+                        "无法识别合同 {} 的付款方式：{}，请指定付款周期",
+                        contract_id,
+                        contract.payment_terms.as_deref().unwrap_or("未填写")
+                    )))
+                }
+            },
+        };
+        let installments = payment::schedule(&contract, cadence, last.map(|(end, _)| end));
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO payments (contract_id, supplier_id, cadence, period_start, period_end, due_date, amount) VALUES (?, ?, ?, ?, ?, ?, ?)",
+            )?;
+            for item in &installments {
+                stmt.execute(params![
+                    contract_id,
+                    contract.supplier_id,
+                    cadence.name(),
+                    item.period_start,
+                    item.period_end,
+                    item.due_date,
+                    item.amount
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(installments.len())
+    }
+
+    /// 列出付款，`contract_id` 为 `None` 时列出全部；按到期日排序
+    pub fn list_payments(&self, contract_id: Option<i64>) -> Result<Vec<Payment>> {
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT {} FROM payments WHERE ?1 IS NULL OR contract_id = ?1 ORDER BY due_date, id",
+            PAYMENT_COLUMNS
+        ))?;
+        let rows = stmt.query_map([contract_id], row_to_payment)?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// 到期日不晚于 `until` 的未付款项，包括已逾期的
+    pub fn payments_due(&self, until: Date) -> Result<Vec<Payment>> {
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT {} FROM payments WHERE paid_on IS NULL AND due_date <= ? ORDER BY due_date, id",
+            PAYMENT_COLUMNS
+        ))?;
+        let rows = stmt.query_map([until], row_to_payment)?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// 标记付款已于 `on` 支付
+    pub fn mark_payment_paid(&self, id: i64, on: Date) -> Result<Payment> {
+        let payment = self
+            .conn
+            .prepare_cached(&format!("SELECT {} FROM payments WHERE id = ?", PAYMENT_COLUMNS))?
+            .query_row([id], row_to_payment)
+            .optional()?
+            .ok_or_else(|| Error::NotFound(format!("未找到ID为 {} 的付款", id)))?;
+        if let Some(paid_on) = payment.paid_on {
+            return Err(Error::Validation(format!("付款 {} 已于 {} 支付", id, paid_on)));
+        }
+        self.conn.prepare_cached("UPDATE payments SET paid_on = ? WHERE id = ?")?.execute(params![on, id])?;
+        Ok(Payment { paid_on: Some(on), ..payment })
+    }
+
+    /// 各供应商的未付余额，按已到期金额从高到低排列；`today` 用于区分已到期和逾期
+    pub fn outstanding_balances(&self, today: Date) -> Result<Vec<SupplierBalance>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT supplier_id, \
+                    TOTAL(CASE WHEN due_date <= ?1 THEN amount END), \
+                    TOTAL(CASE WHEN due_date < ?1 THEN amount END), \
+                    TOTAL(CASE WHEN due_date > ?1 THEN amount END), \
+                    MIN(due_date) \
+             FROM payments WHERE paid_on IS NULL GROUP BY supplier_id",
+        )?;
+        let round = |v: f64| (v * 100.0).round() / 100.0;
+        let rows = stmt.query_map([today], |row| {
+            Ok(SupplierBalance {
+                supplier_id: row.get(0)?,
+                due: round(row.get(1)?),
+                overdue: round(row.get(2)?),
+                upcoming: round(row.get(3)?),
+                next_due_date: row.get(4)?,
+            })
+        })?;
+        let mut balances = rows.collect::<rusqlite::Result<Vec<_>>>()?;
+        balances.sort_by(|a, b| b.due.total_cmp(&a.due).then(a.supplier_id.cmp(&b.supplier_id)));
+        Ok(balances)
+    }
+
+    /// 去掉已发送过的提醒
+    pub fn unsent_notifications(&self, notifications: Vec<Notification>) -> Result<Vec<Notification>> {
+        let mut stmt = self.conn.prepare_cached("SELECT 1 FROM notifications WHERE key = ?")?;
+        let mut unsent = Vec::new();
+        for n in notifications {
+            if !stmt.exists([&n.key])? {
+                unsent.push(n);
+            }
+        }
+        Ok(unsent)
+    }
+
+    /// 记录已发送的提醒，再次发送同一提醒时更新发送时间
+    pub fn record_notifications(&self, notifications: &[Notification]) -> Result<()> {
+        let tx = Transaction::new_unchecked(&self.conn, TransactionBehavior::Immediate)?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO notifications (key, kind, title) VALUES (?1, ?2, ?3) \
+                 ON CONFLICT (key) DO UPDATE SET sent_at = strftime('%Y-%m-%d %H:%M:%S', 'now', 'localtime')",
+            )?;
+            for n in notifications {
+                stmt.execute(params![n.key, n.kind.name(), n.title])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 为供应商打分，只修改 `update` 中为 `Some` 的项，返回修改后的评分
+    pub fn set_rating(&self, supplier_id: i64, update: &RatingUpdate) -> Result<Rating> {
+        update.validate()?;
+        self.require_supplier(supplier_id)?;
+        self.conn
+            .prepare_cached(
+                "INSERT INTO supplier_ratings (supplier_id, delivery, stability, support, price_honesty, note) VALUES (?, ?, ?, ?, ?, ?) \
+                 ON CONFLICT (supplier_id) DO UPDATE SET \
+                 delivery = COALESCE(excluded.delivery, delivery), stability = COALESCE(excluded.stability, stability), \
+                 support = COALESCE(excluded.support, support), price_honesty = COALESCE(excluded.price_honesty, price_honesty), \
+                 note = COALESCE(excluded.note, note), updated_at = strftime('%Y-%m-%d %H:%M:%S', 'now', 'localtime')",
+            )?
+            .execute(params![supplier_id, update.delivery, update.stability, update.support, update.price_honesty, update.note])?;
+        self.get_rating(supplier_id)?.ok_or_else(|| Error::NotFound(format!("未找到供应商 {} 的评分", supplier_id)))
+    }
+
+    /// 读取供应商的评分，没有打分时为 `None`
+    pub fn get_rating(&self, supplier_id: i64) -> Result<Option<Rating>> {
+        Ok(self
+            .conn
+            .prepare_cached(&format!("SELECT {} FROM supplier_ratings WHERE supplier_id = ?", RATING_COLUMNS))?
+            .query_row([supplier_id], row_to_rating)
+            .optional()?)
+    }
+
+    /// 列出全部评分，按供应商ID排序
+    pub fn list_ratings(&self) -> Result<Vec<Rating>> {
+        let mut stmt = self.conn.prepare_cached(&format!("SELECT {} FROM supplier_ratings ORDER BY supplier_id", RATING_COLUMNS))?;
+        let rows = stmt.query_map([], row_to_rating)?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// 记录一起事故，返回事故ID；发生日期不能晚于今天
+    pub fn add_incident(&self, incident: &NewIncident) -> Result<i64> {
+        incident.validate(self.today()?)?;
+        self.require_supplier(incident.supplier_id)?;
+        self.conn
+            .prepare_cached("INSERT INTO supplier_incidents (supplier_id, occurred_on, severity, description) VALUES (?, ?, ?, ?)")?
+            .execute(params![incident.supplier_id, incident.occurred_on, incident.severity.name(), incident.description.trim()])?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// 列出事故，`supplier_id` 为 `None` 时列出全部；最近发生的在前
+    pub fn list_incidents(&self, supplier_id: Option<i64>) -> Result<Vec<Incident>> {
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT {} FROM supplier_incidents WHERE ?1 IS NULL OR supplier_id = ?1 ORDER BY occurred_on DESC, id DESC",
+            INCIDENT_COLUMNS
+        ))?;
+        let rows = stmt.query_map([supplier_id], row_to_incident)?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// 删除事故记录
+    pub fn remove_incident(&self, id: i64) -> Result<()> {
+        match self.conn.prepare_cached("DELETE FROM supplier_incidents WHERE id = ?")?.execute([id])? {
+            0 => Err(Error::NotFound(format!("未找到ID为 {} 的事故", id))),
+            _ => Ok(()),
+        }
+    }
+
+    /// 将筛选结果导出为CSV文件，返回导出的记录数
+    pub fn export_csv(&self, builder: QueryBuilder, path: &str, opts: &CsvOptions) -> Result<usize> {
+        let rows = self.query(builder)?;
+        csv_io::export_suppliers_to_csv(&rows, &[], path, opts)?;
+        Ok(rows.len())
+    }
+
+    /// 将筛选结果导出为Excel文件，`columns` 为空时导出全部列，返回导出的记录数
+    pub fn export_xlsx(&self, builder: QueryBuilder, columns: &[Column], path: &str, with_stats: bool) -> Result<usize> {
+        let rows = self.query(builder)?;
+        xlsx::export_suppliers_to_xlsx(&rows, columns, path, with_stats)?;
+        Ok(rows.len())
+    }
+}
@@ -0,0 +1,312 @@
+use std::io;
+
+use calamine::{open_workbook_auto, Data, Reader};
+use rust_xlsxwriter::{Format, Workbook, Worksheet, XlsxError};
+
+use crate::error::Error;
+use crate::import::{CellValue, ColumnMapping, ImportReport};
+use crate::model::{Column, SupplierField, EXPORT_HEADERS};
+use crate::SupplierRow;
+
+/// 一个单元格：整数、两位小数或文本，`None` 时留空
+enum Cell<'a> {
+    Int(Option<f64>),
+    Decimal(Option<f64>),
+    Text(Option<&'a str>),
+}
+
+fn cell(s: &SupplierRow, column: Column) -> Cell<'_> {
+    match column {
+        Column::Id => Cell::Int(Some(s.id as f64)),
+        Column::Profile => Cell::Text(s.profile.as_deref()),
+        Column::Score => Cell::Decimal(s.score),
+        Column::Field(field) => match field {
+            SupplierField::ContactPerson => Cell::Text(s.contact.as_deref()),
+            SupplierField::Wechat => Cell::Text(s.wechat.as_deref()),
+            SupplierField::Phone => Cell::Text(s.phone.as_deref()),
+            SupplierField::Quantity => Cell::Int(s.quantity.map(f64::from)),
+            SupplierField::Location => Cell::Text(s.location.as_deref()),
+            SupplierField::Price => Cell::Decimal(s.price),
+            SupplierField::BandwidthPrice => Cell::Decimal(s.bandwidth_price),
+            SupplierField::StoragePrice => Cell::Decimal(s.storage_price),
+            SupplierField::MinContractPeriod => Cell::Text(s.min_contract_period.as_deref()),
+            SupplierField::BreachPenalties => Cell::Text(s.breach_penalties.as_deref()),
+            SupplierField::PaymentTerms => Cell::Text(s.payment_terms.as_deref()),
+            SupplierField::ServerName => Cell::Text(s.server_name.as_deref()),
+            SupplierField::ServerConfig => Cell::Text(s.server_config.as_deref()),
+            SupplierField::RentalModel => Cell::Text(s.rental_model.as_deref()),
+            SupplierField::NetworkingCategory => Cell::Text(s.networking_category.as_deref()),
+        },
+    }
+}
+
+/// 按地点汇总的统计行
+struct LocationStats {
+    location: String,
+    suppliers: u32,
+    total_quantity: i64,
+    price_sum: f64,
+    price_count: u32,
+    min_price: Option<f64>,
+    max_price: Option<f64>,
+}
+
+impl LocationStats {
+    fn new(location: &str) -> Self {
+        Self {
+            location: location.to_string(),
+            suppliers: 0,
+            total_quantity: 0,
+            price_sum: 0.0,
+            price_count: 0,
+            min_price: None,
+            max_price: None,
+        }
+    }
+
+    fn add(&mut self, s: &SupplierRow) {
+        self.suppliers += 1;
+        self.total_quantity += s.quantity.unwrap_or(0) as i64;
+        if let Some(p) = s.price {
+            self.price_sum += p;
+            self.price_count += 1;
+            self.min_price = Some(self.min_price.map_or(p, |m| m.min(p)));
+            self.max_price = Some(self.max_price.map_or(p, |m| m.max(p)));
+        }
+    }
+
+    fn avg_price(&self) -> Option<f64> {
+        if self.price_count == 0 {
+            None
+        } else {
+            Some(self.price_sum / self.price_count as f64)
+        }
+    }
+}
+
+/// 按地点分组统计，最后附加一行“合计”
+fn collect_stats(rows: &[SupplierRow]) -> Vec<LocationStats> {
+    let mut groups: Vec<LocationStats> = Vec::new();
+    let mut total = LocationStats::new("合计");
+    for s in rows {
+        let location = s.location.as_deref().unwrap_or("未知");
+        match groups.iter_mut().find(|g| g.location == location) {
+            Some(g) => g.add(s),
+            None => {
+                let mut g = LocationStats::new(location);
+                g.add(s);
+                groups.push(g);
+            }
+        }
+        total.add(s);
+    }
+    groups.sort_by(|a, b| a.location.cmp(&b.location));
+    groups.push(total);
+    groups
+}
+
+fn write_opt_number(sheet: &mut Worksheet, row: u32, col: u16, v: Option<f64>, fmt: &Format) -> Result<(), XlsxError> {
+    if let Some(v) = v {
+        sheet.write_number_with_format(row, col, v, fmt)?;
+    }
+    Ok(())
+}
+
+fn write_opt_string(sheet: &mut Worksheet, row: u32, col: u16, v: Option<&str>) -> Result<(), XlsxError> {
+    if let Some(v) = v {
+        sheet.write_string(row, col, v)?;
+    }
+    Ok(())
+}
+
+fn write_suppliers_sheet(sheet: &mut Worksheet, rows: &[SupplierRow], columns: &[Column]) -> Result<(), XlsxError> {
+    let header_fmt = Format::new().set_bold();
+    let int_fmt = Format::new().set_num_format("0");
+    let money_fmt = Format::new().set_num_format("0.00");
+
+    sheet.set_name("供应商")?;
+    let columns = Column::resolve(columns);
+    for (col, c) in columns.iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, EXPORT_HEADERS[c.index()], &header_fmt)?;
+    }
+    for (i, s) in rows.iter().enumerate() {
+        let r = i as u32 + 1;
+        for (col, c) in columns.iter().enumerate() {
+            let col = col as u16;
+            match cell(s, *c) {
+                Cell::Int(v) => write_opt_number(sheet, r, col, v, &int_fmt)?,
+                Cell::Decimal(v) => write_opt_number(sheet, r, col, v, &money_fmt)?,
+                Cell::Text(v) => write_opt_string(sheet, r, col, v)?,
+            }
+        }
+    }
+    // 冻结表头并按内容自动调整列宽
+    sheet.set_freeze_panes(1, 0)?;
+    sheet.autofit();
+    Ok(())
+}
+
+fn write_stats_sheet(sheet: &mut Worksheet, rows: &[SupplierRow]) -> Result<(), XlsxError> {
+    let header_fmt = Format::new().set_bold();
+    let int_fmt = Format::new().set_num_format("0");
+    let money_fmt = Format::new().set_num_format("0.00");
+
+    sheet.set_name("统计")?;
+    let headers = ["地点", "供应商数", "总数量", "平均价格", "最低价格", "最高价格"];
+    for (col, h) in headers.iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, *h, &header_fmt)?;
+    }
+    for (i, g) in collect_stats(rows).iter().enumerate() {
+        let r = i as u32 + 1;
+        sheet.write_string(r, 0, &g.location)?;
+        sheet.write_number_with_format(r, 1, g.suppliers, &int_fmt)?;
+        sheet.write_number_with_format(r, 2, g.total_quantity as f64, &int_fmt)?;
+        write_opt_number(sheet, r, 3, g.avg_price(), &money_fmt)?;
+        write_opt_number(sheet, r, 4, g.min_price, &money_fmt)?;
+        write_opt_number(sheet, r, 5, g.max_price, &money_fmt)?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+    sheet.autofit();
+    Ok(())
+}
+
+/// 导出为Excel工作簿，`columns` 为空时导出全部列；数量、价格和评分写为数值单元格，
+/// `with_stats` 时附加按地点汇总的统计表
+pub fn export_suppliers_to_xlsx(rows: &[SupplierRow], columns: &[Column], path: &str, with_stats: bool) -> Result<(), XlsxError> {
+    let mut workbook = Workbook::new();
+    write_suppliers_sheet(workbook.add_worksheet(), rows, columns)?;
+    if with_stats {
+        write_stats_sheet(workbook.add_worksheet(), rows)?;
+    }
+    workbook.save(path)?;
+    Ok(())
+}
+
+fn cell_value(cell: &Data) -> CellValue {
+    match cell {
+        Data::Empty => CellValue::Empty,
+        Data::Int(i) => CellValue::Number(*i as f64),
+        Data::Float(f) => CellValue::Number(*f),
+        other => CellValue::Text(other.to_string()),
+    }
+}
+
+/// 打开或读取工作簿失败：文件本身读不到时为 [`Error::Io`]，内容无法解析时为 [`Error::Parse`]
+fn workbook_error(context: String, e: calamine::Error) -> Error {
+    // calamine 会把文件读取错误包在各格式自己的错误类型里，需沿错误链查找
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(&e);
+    while let Some(err) = source {
+        if let Some(io_err) = err.downcast_ref::<io::Error>() {
+            return Error::Io(io::Error::new(io_err.kind(), format!("{}: {}", context, io_err)));
+        }
+        source = err.source();
+    }
+    Error::Parse(format!("{}: {}", context, e))
+}
+
+/// 从Excel工作表读取供应商记录，首行为表头；未指定 `sheet` 时读取第一个工作表
+pub fn read_suppliers_from_xlsx(path: &str, sheet: Option<&str>, mapping: &ColumnMapping) -> crate::Result<ImportReport> {
+    let mut workbook = open_workbook_auto(path).map_err(|e| workbook_error(format!("无法打开Excel文件 {}", path), e))?;
+    let range = match sheet {
+        Some(name) => workbook.worksheet_range(name).map_err(|e| workbook_error(format!("无法读取工作表 {}", name), e))?,
+        None => workbook
+            .worksheet_range_at(0)
+            .ok_or_else(|| Error::Parse("Excel文件中没有工作表".to_string()))?
+            .map_err(|e| workbook_error("无法读取工作表".to_string(), e))?,
+    };
+
+    let mut rows = range.rows();
+    let headers = rows.next().ok_or_else(|| Error::Parse("工作表为空".to_string()))?;
+    let columns = mapping.resolve_columns(headers.iter().map(|h| cell_value(h).into_text().unwrap_or_default()))?;
+
+    let mut report = ImportReport::default();
+    for (i, cells) in rows.enumerate() {
+        report.push_row(i + 2, &columns, cells.iter().map(cell_value).collect(), false);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 测试导出Excel文件
+    #[test]
+    fn test_export_xlsx() {
+        let rows = vec![
+            SupplierRow {
+                id: 1,
+                contact: Some("张三".to_string()),
+                wechat: Some("wxid".to_string()),
+                phone: Some("12345678901".to_string()),
+                quantity: Some(10),
+                location: Some("北京".to_string()),
+                price: Some(1000.0),
+                bandwidth_price: Some(100.0),
+                storage_price: None,
+                min_contract_period: None,
+                breach_penalties: None,
+                payment_terms: Some("月付".to_string()),
+                server_name: Some("服务器A".to_string()),
+                server_config: None,
+                rental_model: None,
+                networking_category: None,
+                profile: None,
+                score: None,
+            },
+        ];
+
+        let xlsx_file = tempfile::Builder::new().suffix(".xlsx").tempfile().unwrap();
+        let xlsx_path = xlsx_file.path().to_str().unwrap();
+
+        let export_result = export_suppliers_to_xlsx(&rows, &[], xlsx_path, true);
+        assert!(export_result.is_ok());
+
+        // xlsx 是 zip 容器，检查文件头
+        let content = std::fs::read(xlsx_path).unwrap();
+        assert!(content.starts_with(b"PK"));
+    }
+
+    // 测试Excel导入：中文表头别名、自定义映射、数值转换和行级错误
+    #[test]
+    fn test_import_xlsx() {
+        let xlsx_file = tempfile::Builder::new().suffix(".xlsx").tempfile().unwrap();
+        let xlsx_path = xlsx_file.path().to_str().unwrap();
+
+        let mut workbook = rust_xlsxwriter::Workbook::new();
+        let sheet = workbook.add_worksheet();
+        sheet.set_name("报价").unwrap();
+        for (col, h) in ["联系人", "手机", "数量", "单价", "带宽价", "组网"].iter().enumerate() {
+            sheet.write_string(0, col as u16, *h).unwrap();
+        }
+        sheet.write_string(1, 0, "张三").unwrap();
+        sheet.write_number(1, 1, 13800138000.0).unwrap();
+        sheet.write_number(1, 2, 8).unwrap();
+        sheet.write_number(1, 3, 1200.5).unwrap();
+        sheet.write_string(1, 4, "1,000元").unwrap();
+        sheet.write_string(1, 5, "RoCE").unwrap();
+        sheet.write_string(2, 0, "李四").unwrap();
+        sheet.write_string(2, 2, "八台").unwrap();
+        sheet.write_string(3, 1, "13900139000").unwrap();
+        workbook.save(xlsx_path).unwrap();
+
+        let mapping = ColumnMapping::from_rules(&["单价=price".to_string()]).unwrap();
+        let report = read_suppliers_from_xlsx(xlsx_path, Some("报价"), &mapping).unwrap();
+
+        assert_eq!(report.suppliers.len(), 1);
+        let (row_no, s) = &report.suppliers[0];
+        assert_eq!(*row_no, 2);
+        assert_eq!(s.contact.as_deref(), Some("张三"));
+        assert_eq!(s.phone.as_deref(), Some("13800138000"));
+        assert_eq!(s.quantity, Some(8));
+        assert_eq!(s.price, Some(1200.5));
+        assert_eq!(s.bandwidth_price, Some(1000.0));
+        assert_eq!(s.networking_category.as_deref(), Some("RoCE"));
+
+        assert_eq!(report.errors.len(), 2);
+        assert_eq!(report.errors[0].row, 3);
+        assert!(report.errors[0].message.contains("数量"));
+        assert_eq!(report.errors[1].row, 4);
+        assert!(report.errors[1].message.contains("联系人"));
+    }
+}
@@ -6,15 +6,13 @@ use std::env;
 use std::path::Path;
 use std::sync::Mutex;
 use once_cell::sync::Lazy;
-use rand;
 
 // 测试互斥锁，确保不同测试不会同时操作同一个数据库文件
 static TEST_MUTEX: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
 
 // 获取正确的二进制名称
 fn get_bin_name() -> String {
-    let manifest = env::var("CARGO_PKG_NAME").unwrap_or_else(|_| "wei-assistant-gpu".to_string());
-    manifest
+    env::var("CARGO_PKG_NAME").unwrap_or_else(|_| "wei-assistant-gpu".to_string())
 }
 
 // 辅助函数：创建测试数据库文件
@@ -66,9 +64,43 @@ fn create_test_db() -> io::Result<String> {
     Ok(db_path)
 }
 
+/// 临时目录中的配置文件和数据库，命令都带上 `--config` 和 `--db`
+struct TestEnv {
+    dir: tempfile::TempDir,
+    config: String,
+    db: String,
+}
+
+impl TestEnv {
+    /// `config` 为配置文件内容
+    fn new(config: &str) -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("config.toml");
+        std::fs::write(&config_path, config).unwrap();
+        let config = config_path.to_str().unwrap().to_string();
+        let db = dir.path().join("test.db").to_str().unwrap().to_string();
+        Self { dir, config, db }
+    }
+
+    /// 临时目录中的文件路径
+    fn path(&self, name: &str) -> String {
+        self.dir.path().join(name).to_str().unwrap().to_string()
+    }
+
+    fn command(&self) -> Command {
+        let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+        cmd.args(["--config", &self.config, "--db", &self.db]);
+        cmd
+    }
+
+    fn run(&self, args: &[&str]) -> assert_cmd::assert::Assert {
+        self.command().args(args).assert()
+    }
+}
+
 #[test]
 fn test_cli_help() {
-    let mut cmd = Command::cargo_bin(&get_bin_name()).unwrap();
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
     let assert = cmd.arg("--help").assert();
     assert.success()
           .stdout(predicate::str::contains("供应商信息管理"))
@@ -78,7 +110,7 @@ fn test_cli_help() {
 
 #[test]
 fn test_add_command_help() {
-    let mut cmd = Command::cargo_bin(&get_bin_name()).unwrap();
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
     cmd.arg("add").arg("--help")
         .assert()
         .success()
@@ -89,7 +121,7 @@ fn test_add_command_help() {
 
 #[test]
 fn test_query_command_help() {
-    let mut cmd = Command::cargo_bin(&get_bin_name()).unwrap();
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
     cmd.arg("query").arg("--help")
         .assert()
         .success()
@@ -127,7 +159,7 @@ fn test_add_and_query_complete_workflow() {
     drop(conn);
     
     // 添加供应商
-    let mut cmd = Command::cargo_bin(&get_bin_name()).unwrap();
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
     cmd.env("DB_FILE", &db_path)
         .arg("add")
         .arg("--contact").arg("张三")
@@ -138,7 +170,7 @@ fn test_add_and_query_complete_workflow() {
         .success();
     
     // 查询供应商
-    let mut cmd = Command::cargo_bin(&get_bin_name()).unwrap();
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
     cmd.env("DB_FILE", &db_path)
         .arg("query")
         .arg("--contact").arg("张三")
@@ -187,7 +219,7 @@ fn test_json_add_workflow() {
     }"#;
     
     // 添加供应商
-    let mut cmd = Command::cargo_bin(&get_bin_name()).unwrap();
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
     cmd.env("DB_FILE", &db_path)
         .arg("add")
         .arg("--json").arg(json)
@@ -195,7 +227,7 @@ fn test_json_add_workflow() {
         .success();
     
     // 查询供应商
-    let mut cmd = Command::cargo_bin(&get_bin_name()).unwrap();
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
     cmd.env("DB_FILE", &db_path)
         .arg("query")
         .arg("--json")
@@ -207,119 +239,894 @@ fn test_json_add_workflow() {
 }
 
 #[test]
-fn test_multiple_suppliers() {
-    // 创建测试数据库
+fn test_query_output_formats() {
     let db_path = create_test_db().unwrap();
-    
-    // 初始化数据库表结构
-    let conn = Connection::open(&db_path).unwrap();
-    let create_table_sql = "CREATE TABLE IF NOT EXISTS suppliers (
-        id INTEGER PRIMARY KEY AUTOINCREMENT,
-        contact TEXT,
-        wechat TEXT,
-        phone TEXT,
-        quantity INTEGER,
-        location TEXT,
-        price REAL,
-        bandwidth_price REAL,
-        storage_price REAL,
-        min_contract_period TEXT,
-        breach_penalties TEXT,
-        payment_terms TEXT,
-        server_name TEXT,
-        server_config TEXT,
-        rental_model TEXT,
-        networking_category TEXT
-    );";
-    conn.execute_batch(create_table_sql).unwrap();
-    drop(conn);
-    
-    // 添加多个供应商
-    for i in 1..=3 {
-        let contact = format!("测试{}", i);
-        let wechat = format!("wx{}", i);
-        let phone = format!("1380013800{}", i);
-        
-        let mut cmd = Command::cargo_bin(&get_bin_name()).unwrap();
+
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("add")
+        .arg("--contact").arg("格式测试")
+        .arg("--location").arg("上海")
+        .arg("--price").arg("1500")
+        .assert()
+        .success();
+
+    // Markdown 管道表格
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("query")
+        .arg("--format").arg("markdown")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("| ID | 联系人 |"))
+        .stdout(predicate::str::contains("| 格式测试 |"));
+
+    // 独立 HTML 页面
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("query")
+        .arg("--format").arg("html")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("<table>"))
+        .stdout(predicate::str::contains("<td>格式测试</td>"));
+
+    // CSV 输出到标准输出
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("query")
+        .arg("--format").arg("csv")
+        .arg("--csv-headers").arg("en")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("id,contact,"))
+        .stdout(predicate::str::contains("格式测试,"));
+}
+
+/// 写一个经纪人报价单式的 xlsx：能解析为数字的单元格写成数值
+fn write_quote_xlsx(path: &str, sheet: &str, rows: &[&[&str]]) {
+    let mut workbook = rust_xlsxwriter::Workbook::new();
+    let worksheet = workbook.add_worksheet().set_name(sheet).unwrap();
+    for (r, row) in rows.iter().enumerate() {
+        for (c, cell) in row.iter().enumerate() {
+            match cell.parse::<f64>() {
+                Ok(v) => worksheet.write_number(r as u32, c as u16, v).unwrap(),
+                Err(_) => worksheet.write_string(r as u32, c as u16, *cell).unwrap(),
+            };
+        }
+    }
+    workbook.save(path).unwrap();
+}
+
+/// 第 2 行有效，第 3 行单价无法解析，第 4 行缺少联系人；单价列需要 `--map 单价=price`
+const BROKER_QUOTE: &[&[&str]] = &[
+    &["联系人", "位置", "单价", "数量", "带宽价"],
+    &["张三", "北京", "1200", "8", "1.5"],
+    &["李四", "上海", "面议", "4", ""],
+    &["", "深圳", "900", "2", ""],
+];
+
+#[test]
+fn test_import_xlsx_dry_run_writes_nothing() {
+    let env = TestEnv::new("");
+    let xlsx = env.path("quote.xlsx");
+    write_quote_xlsx(&xlsx, "报价", BROKER_QUOTE);
+    env.run(&["import", "--xlsx", &xlsx, "--map", "单价=price", "--dry-run"])
+        .success()
+        .stdout(predicate::str::contains("张三"))
+        .stdout(predicate::str::contains("1200"))
+        .stdout(predicate::str::contains("预览：可导入 1 条，失败 2 条（未写入数据库）"))
+        .stderr(predicate::str::contains("第3行: 单价列: 无法解析为数字: 面议"))
+        .stderr(predicate::str::contains("第4行: 缺少联系人"));
+    env.run(&["query"]).success().stdout(predicate::str::contains("没有找到符合条件的供应商"));
+}
+
+#[test]
+fn test_import_xlsx() {
+    let env = TestEnv::new("");
+    let xlsx = env.path("quote.xlsx");
+    write_quote_xlsx(&xlsx, "报价", BROKER_QUOTE);
+    env.run(&["import", "--xlsx", &xlsx, "--sheet", "报价", "--map", "单价=price"])
+        .success()
+        .stdout(predicate::str::contains("导入完成：成功 1 条，失败 2 条"));
+    env.run(&["query", "--columns", "contact,location,price,quantity,bandwidth_price", "--format", "csv"])
+        .success()
+        .stdout("联系人,位置,价格,数量,带宽价\n张三,北京,1200,8,1.5\n");
+    env.run(&["import", "--xlsx", &xlsx, "--sheet", "Sheet9"]).code(4).stderr(predicate::str::contains("Sheet9"));
+    env.run(&["import", "--xlsx", &xlsx, "--map", "单价"]).code(3);
+}
+
+#[test]
+fn test_import_error_exit_codes() {
+    let env = TestEnv::new("");
+    // 文件不存在属于读写错误，不是解析错误
+    let missing_xlsx = env.path("missing.xlsx");
+    env.run(&["import", "--xlsx", &missing_xlsx]).code(6).stderr(predicate::str::contains("missing.xlsx"));
+    let missing_csv = env.path("missing.csv");
+    env.run(&["import", "--csv", &missing_csv]).code(6).stderr(predicate::str::contains("missing.csv"));
+    // 内容不是有效的工作簿才是解析错误
+    let broken = env.path("broken.xlsx");
+    std::fs::write(&broken, "不是Excel文件").unwrap();
+    env.run(&["import", "--xlsx", &broken]).code(4);
+    // 缺少联系人列属于输入校验错误
+    let csv = env.path("no_contact.csv");
+    std::fs::write(&csv, "位置,价格\n北京,1200\n").unwrap();
+    env.run(&["import", "--csv", &csv]).code(3).stderr(predicate::str::contains("缺少联系人列"));
+}
+
+#[test]
+fn test_import_csv_dry_run_writes_nothing() {
+    let env = TestEnv::new("");
+    let csv = env.path("quote.csv");
+    std::fs::write(&csv, "联系人,位置,价格\n张三,北京,1200\n李四,上海,面议\n").unwrap();
+    env.run(&["import", "--csv", &csv, "--dry-run"])
+        .success()
+        .stdout(predicate::str::contains("预览：可导入 1 条，失败 1 条（未写入数据库）"))
+        .stderr(predicate::str::contains("第3行: 价格列: 无法解析为数字: 面议"));
+    env.run(&["query"]).success().stdout(predicate::str::contains("没有找到符合条件的供应商"));
+    env.run(&["import", "--csv", &csv]).success().stdout(predicate::str::contains("导入完成：成功 1 条，失败 1 条"));
+    env.run(&["import", "--sheet", "报价", "--csv", &csv]).code(2);
+}
+
+/// 两条带小数价格的资源，用于 CSV 导出后再导入
+fn csv_env() -> TestEnv {
+    let env = TestEnv::new("");
+    env.run(&["add", "--contact", "张三", "--location", "北京", "--price", "1200.5", "--quantity", "8"]).success();
+    env.run(&["add", "--contact", "李四", "--location", "上海", "--price", "900"]).success();
+    env
+}
+
+/// 用相同的 CSV 参数导入到新的数据库，返回导入后的 CSV 查询结果
+fn reimport_csv(path: &str, csv_args: &[&str]) -> String {
+    let target = TestEnv::new("");
+    target.run(&[&["import", "--csv", path][..], csv_args].concat()).success().stdout(predicate::str::contains("导入完成：成功 2 条，失败 0 条"));
+    let output = target.run(&["query", "--columns", "contact,location,price,quantity", "--format", "csv"]).success().get_output().stdout.clone();
+    String::from_utf8(output).unwrap()
+}
+
+const REIMPORTED: &str = "联系人,位置,价格,数量\n张三,北京,1200.5,8\n李四,上海,900,\n";
+
+#[test]
+fn test_csv_delimiter_and_decimal_comma_round_trip() {
+    let env = csv_env();
+    let csv = env.path("out.csv");
+    let args = ["--csv-delimiter", "semicolon", "--csv-decimals", "2", "--csv-decimal-comma"];
+    env.run(&[&["query", "--columns", "contact,location,price,quantity", "--export-csv", &csv][..], &args].concat()).success();
+    assert_eq!(std::fs::read_to_string(&csv).unwrap(), "联系人;位置;价格;数量\n张三;北京;1200,50;8\n李四;上海;900,00;\n");
+    assert_eq!(reimport_csv(&csv, &args), REIMPORTED);
+    // 选项冲突在查询前检查，表格和提示都不会输出
+    let rejected = env.path("rejected.csv");
+    env.run(&["query", "--export-csv", &rejected, "--csv-decimal-comma"])
+        .code(3)
+        .stdout("")
+        .stderr(predicate::str::contains("逗号小数点"));
+    assert!(!std::path::Path::new(&rejected).exists());
+}
+
+#[test]
+fn test_csv_tab_delimiter_round_trip() {
+    let env = csv_env();
+    let csv = env.path("out.tsv");
+    env.run(&["query", "--columns", "contact,location,price,quantity", "--export-csv", &csv, "--csv-delimiter", "tab"]).success();
+    assert!(std::fs::read_to_string(&csv).unwrap().starts_with("联系人\t位置\t价格\t数量\n"));
+    assert_eq!(reimport_csv(&csv, &["--csv-delimiter", "tab"]), REIMPORTED);
+}
+
+#[test]
+fn test_csv_gbk_round_trip() {
+    let env = csv_env();
+    let csv = env.path("gbk.csv");
+    env.run(&["query", "--export-csv", &csv, "--csv-encoding", "gbk"]).success();
+    let bytes = std::fs::read(&csv).unwrap();
+    assert!(String::from_utf8(bytes.clone()).is_err());
+    let (text, _, had_errors) = encoding_rs::GBK.decode(&bytes);
+    assert!(!had_errors && text.contains("1,张三,,,8,北京,1200.5,"));
+    assert_eq!(reimport_csv(&csv, &["--csv-encoding", "gbk"]), REIMPORTED);
+    // 按 UTF-8 读取 GBK 文件时提示改用 --csv-encoding gbk
+    let target = TestEnv::new("");
+    target.run(&["import", "--csv", &csv]).code(4).stderr(predicate::str::contains("--csv-encoding gbk"));
+}
+
+#[test]
+fn test_csv_bom_round_trip() {
+    let env = csv_env();
+    let csv = env.path("bom.csv");
+    env.run(&["query", "--export-csv", &csv, "--csv-bom"]).success();
+    assert!(std::fs::read(&csv).unwrap().starts_with(b"\xEF\xBB\xBFID,"));
+    // 读取时去掉 BOM，第一列表头仍能识别
+    assert_eq!(reimport_csv(&csv, &[]), REIMPORTED);
+    env.run(&["query", "--format", "csv", "--csv-bom", "--csv-encoding", "gbk"]).code(3).stdout("").stderr(predicate::str::contains("BOM"));
+    env.run(&["import", "--csv", &csv, "--csv-bom", "--csv-encoding", "gbk"]).code(3);
+}
+
+#[test]
+fn test_csv_english_headers_round_trip() {
+    let env = csv_env();
+    let csv = env.path("en.csv");
+    env.run(&["query", "--export-csv", &csv, "--csv-headers", "en"]).success();
+    assert!(std::fs::read_to_string(&csv).unwrap().starts_with("id,contact,wechat,phone,quantity,location,price,"));
+    assert_eq!(reimport_csv(&csv, &[]), REIMPORTED);
+}
+
+/// 一条中文资源和一条英文资源，配置列较长
+fn table_env() -> TestEnv {
+    let env = TestEnv::new("");
+    env.run(&["add", "--contact", "张三", "--location", "北京", "--price", "1200", "--server-config", "8×A100 80G NVLink 双路 EPYC"]).success();
+    env.run(&["add", "--contact", "Bob", "--location", "Shanghai", "--price", "900"]).success();
+    env
+}
+
+const TABLE_COLUMNS: &str = "id,contact,location,price,server_config";
+
+fn table_stdout(env: &TestEnv, args: &[&str]) -> String {
+    let output = env.run(&[&["query", "--columns", TABLE_COLUMNS][..], args].concat()).success().get_output().stdout.clone();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn test_query_table_aligns_cjk_by_display_width() {
+    let env = table_env();
+    // 非终端输出时不截断；中文按两列宽对齐
+    env.run(&["query", "--columns", "id,contact,location,price"]).success().stdout(
+        "ID 联系人 地点     价格    \n\
+         ---------------------------\n\
+         1  张三   北京     1200.00 \n\
+         2  Bob    Shanghai 900.00  \n",
+    );
+}
+
+#[test]
+fn test_query_table_width_truncates_text_columns() {
+    let env = table_env();
+    let table = table_stdout(&env, &["--width", "40"]);
+    assert!(table.lines().all(|line| unicode_width::UnicodeWidthStr::width(line) <= 40), "{}", table);
+    // 文本列以省略号截断，价格列保持完整
+    assert!(table.contains("8×A100 80G …"));
+    assert!(table.contains("1200.00"));
+    assert!(!table.contains("EPYC"));
+}
+
+#[test]
+fn test_query_layout_vertical() {
+    let env = table_env();
+    let vertical = table_stdout(&env, &["--layout", "vertical"]);
+    assert!(vertical.starts_with("-[ 记录 1 ]"));
+    assert!(vertical.contains("联系人 : 张三\n"));
+    assert!(vertical.contains("配置   : 8×A100 80G NVLink 双路 EPYC\n"));
+    assert!(vertical.contains("-[ 记录 2 ]"));
+    // 纵向布局时按宽度截断取值
+    assert!(table_stdout(&env, &["--layout", "vertical", "--width", "20"]).contains("配置   : 8×A100 80G…\n"));
+}
+
+#[test]
+fn test_query_layout_auto_falls_back_to_vertical() {
+    let env = table_env();
+    // 收窄到下限仍放不下时，auto 改为纵向，horizontal 保持横向
+    assert!(table_stdout(&env, &["--width", "20"]).starts_with("-[ 记录 1 ]"));
+    let horizontal = table_stdout(&env, &["--width", "20", "--layout", "horizontal"]);
+    assert!(horizontal.starts_with("ID 联… "));
+    assert!(!horizontal.contains("记录"));
+    env.run(&["query", "--layout", "diagonal"]).code(2);
+}
+
+#[test]
+fn test_query_table_color() {
+    let env = table_env();
+    assert!(table_stdout(&env, &["--color", "always"]).starts_with("\x1b[1;36mID"));
+    assert!(!table_stdout(&env, &[]).contains('\x1b'));
+    assert!(!table_stdout(&env, &["--color", "never", "--layout", "vertical"]).contains('\x1b'));
+}
+
+#[test]
+fn test_update_and_delete() {
+    let db_path = create_test_db().unwrap();
+
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("add")
+        .arg("--contact").arg("修改测试")
+        .arg("--price").arg("1500")
+        .assert()
+        .success();
+
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("update")
+        .arg("--id").arg("1")
+        .arg("--price").arg("1300")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("供应商 1 已更新"));
+
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("query")
+        .arg("--price").arg("1300")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("修改测试"));
+
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("delete")
+        .arg("--id").arg("1")
+        .assert()
+        .success();
+
+    // 再次删除时记录已不存在
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("delete")
+        .arg("--id").arg("1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("未找到ID为 1 的供应商"));
+}
+
+#[test]
+fn test_query_numeric_ranges() {
+    let db_path = create_test_db().unwrap();
+
+    for (contact, price, quantity) in [("范围A", "1000.0000001", "4"), ("范围B", "1500", "8"), ("范围C", "2100.5", "16")] {
+        let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
         cmd.env("DB_FILE", &db_path)
             .arg("add")
-            .arg("--contact").arg(&contact)
-            .arg("--wechat").arg(&wechat)
-            .arg("--phone").arg(&phone)
+            .arg("--contact").arg(contact)
+            .arg("--price").arg(price)
+            .arg("--quantity").arg(quantity)
             .assert()
             .success();
     }
-    
-    // 查询所有供应商
-    let mut cmd = Command::cargo_bin(&get_bin_name()).unwrap();
+
+    // 精确匹配容忍舍入误差
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("query").arg("--price").arg("1000").arg("--format").arg("csv")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("范围A"))
+        .stdout(predicate::str::contains("范围B").not());
+
+    // 闭区间，边界值本身也包含在内
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
     cmd.env("DB_FILE", &db_path)
         .arg("query")
+        .arg("--price-min").arg("1000")
+        .arg("--price-max").arg("1500")
+        .arg("--quantity-min").arg("8")
+        .arg("--format").arg("csv")
         .assert()
         .success()
-        .stdout(predicate::str::contains("测试1"))
-        .stdout(predicate::str::contains("测试2"))
-        .stdout(predicate::str::contains("测试3"));
+        .stdout(predicate::str::contains("范围B"))
+        .stdout(predicate::str::contains("范围A").not())
+        .stdout(predicate::str::contains("范围C").not());
+
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("query").arg("--price-max").arg("1000").arg("--format").arg("csv")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("范围A"));
+
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("query").arg("--price-min").arg("2000").arg("--price-max").arg("1000")
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("--price-min 不能大于 --price-max"));
 }
 
 #[test]
-fn test_invalid_args() {
-    // 测试添加供应商时缺少必要参数
+fn test_query_filter_expression() {
     let db_path = create_test_db().unwrap();
-    
-    // 初始化数据库表结构
-    let conn = Connection::open(&db_path).unwrap();
-    let create_table_sql = "CREATE TABLE IF NOT EXISTS suppliers (
-        id INTEGER PRIMARY KEY AUTOINCREMENT,
-        contact TEXT,
-        wechat TEXT,
-        phone TEXT,
-        quantity INTEGER,
-        location TEXT,
-        price REAL,
-        bandwidth_price REAL,
-        storage_price REAL,
-        min_contract_period TEXT,
-        breach_penalties TEXT,
-        payment_terms TEXT,
-        server_name TEXT,
-        server_config TEXT,
-        rental_model TEXT,
-        networking_category TEXT
-    );";
-    conn.execute_batch(create_table_sql).unwrap();
-    drop(conn);
-    
-    let mut cmd = Command::cargo_bin(&get_bin_name()).unwrap();
+
+    for (contact, location, price) in [("表达式A", "北京", "1200"), ("表达式B", "上海", "1400"), ("表达式C", "上海", "1800"), ("表达式D", "广州", "900")] {
+        let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+        cmd.env("DB_FILE", &db_path)
+            .arg("add")
+            .arg("--contact").arg(contact)
+            .arg("--location").arg(location)
+            .arg("--price").arg(price)
+            .assert()
+            .success();
+    }
+
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
     cmd.env("DB_FILE", &db_path)
-        .arg("add")
+        .arg("query")
+        .arg("--filter").arg("(location in [北京,上海]) and price < 1500")
+        .arg("--format").arg("csv")
         .assert()
-        .failure();
+        .success()
+        .stdout(predicate::str::contains("表达式A"))
+        .stdout(predicate::str::contains("表达式B"))
+        .stdout(predicate::str::contains("表达式C").not())
+        .stdout(predicate::str::contains("表达式D").not());
+
+    // 与普通字段参数同时使用时以 AND 组合；值中的引号不会破坏SQL
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("query")
+        .arg("--location").arg("上海")
+        .arg("--filter").arg("price > 1500 or contact = \"x' OR 1=1 --\"")
+        .arg("--format").arg("csv")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("表达式C"))
+        .stdout(predicate::str::contains("表达式B").not());
+
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("query")
+        .arg("--filter").arg("(location = 北京")
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains("筛选表达式错误"));
 }
 
 #[test]
-fn test_error_handling() {
-    // 测试不存在的命令
-    let mut cmd = Command::cargo_bin(&get_bin_name()).unwrap();
-    cmd.arg("not-exist-command")
+fn test_query_filter_rejects_non_numeric_values() {
+    let db_path = create_test_db().unwrap();
+
+    // 数值字段的值（包括 in 列表中的每一项）必须是数字，数量必须是整数
+    for (expr, field) in [
+        ("price < abc", "price"),
+        ("bandwidth_price >= \"1,5\"", "bandwidth_price"),
+        ("storage_price in [1, x]", "storage_price"),
+        ("quantity > 1.5", "quantity"),
+        ("location = 北京 or quantity in [1, 二]", "quantity"),
+    ] {
+        let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+        cmd.env("DB_FILE", &db_path)
+            .arg("query")
+            .arg("--filter").arg(expr)
+            .assert()
+            .code(3)
+            .stderr(predicate::str::contains(format!("字段 {} 的值必须是", field)));
+    }
+
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("query")
+        .arg("--filter").arg("quantity in [1, 2] and price < 1500.5")
         .assert()
-        .failure();
-    
-    // 测试添加供应商时缺少必要参数
+        .success();
+}
+
+#[test]
+fn test_query_match_modes() {
     let db_path = create_test_db().unwrap();
-    let mut cmd = Command::cargo_bin(&get_bin_name()).unwrap();
+
+    for (contact, location, server) in [("匹配A", "北京市", "RTX 4090"), ("匹配B", "Beijing", "rtx4090"), ("匹配C", "上海", "A100-80G")] {
+        let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+        cmd.env("DB_FILE", &db_path)
+            .arg("add")
+            .arg("--contact").arg(contact)
+            .arg("--location").arg(location)
+            .arg("--server-name").arg(server)
+            .assert()
+            .success();
+    }
+
+    // 同义词：北京 同时匹配 北京市 和 Beijing
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
     cmd.env("DB_FILE", &db_path)
-        .arg("add") // 没有提供任何参数
+        .arg("query").arg("--location").arg("北京").arg("--format").arg("csv")
         .assert()
-        .failure();
-    
-    // 测试JSON格式错误
-    let mut cmd = Command::cargo_bin(&get_bin_name()).unwrap();
+        .success()
+        .stdout(predicate::str::contains("匹配A"))
+        .stdout(predicate::str::contains("匹配B"))
+        .stdout(predicate::str::contains("匹配C").not());
+
+    // 忽略大小写与空格
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
     cmd.env("DB_FILE", &db_path)
-        .arg("add")
-        .arg("--json").arg("{invalid json}")
+        .arg("query").arg("--server-name").arg("RTX4090").arg("--match").arg("icase").arg("--format").arg("csv")
         .assert()
-        .failure();
-}
+        .success()
+        .stdout(predicate::str::contains("匹配A"))
+        .stdout(predicate::str::contains("匹配B"));
+
+    // 前缀匹配同样作用于 --filter 表达式
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("query").arg("--filter").arg("server_name = a100").arg("--match").arg("prefix").arg("--format").arg("csv")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("匹配C"))
+        .stdout(predicate::str::contains("匹配A").not());
+
+    // 自定义同义词
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("synonym").arg("add").arg("--field").arg("location").arg("--canonical").arg("上海").arg("--alias").arg("魔都")
+        .assert()
+        .success();
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("query").arg("--location").arg("魔都").arg("--format").arg("csv")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("匹配C"));
+}
+
+#[test]
+fn test_saved_views() {
+    let db_path = create_test_db().unwrap();
+
+    for (contact, location, price) in [("视图A", "北京", "1200"), ("视图B", "北京", "900"), ("视图C", "上海", "800")] {
+        let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+        cmd.env("DB_FILE", &db_path)
+            .arg("add")
+            .arg("--contact").arg(contact)
+            .arg("--location").arg(location)
+            .arg("--price").arg(price)
+            .assert()
+            .success();
+    }
+
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .args(["view", "save", "bj", "--", "query", "--location", "北京", "--sort", "price:desc", "--columns", "contact,price"])
+        .assert()
+        .success();
+
+    // 无效参数不会被保存
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .args(["view", "save", "bad", "--", "--sort", "nope"])
+        .assert()
+        .code(3);
+
+    // 导出和显示隐私不属于视图，不能保存
+    for flag in [&["--export-csv", "out.csv"][..], &["--reveal-pii"][..], &["--format", "csv"][..]] {
+        let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+        cmd.env("DB_FILE", &db_path)
+            .args(["view", "save", "leak", "--", "--location", "北京"])
+            .args(flag)
+            .assert()
+            .code(3)
+            .stderr(predicate::str::contains("view run"));
+    }
+
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .args(["view", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("bj\t--filter \"location = 北京\" --sort price:desc --columns contact,price"))
+        .stdout(predicate::str::contains("bad").not())
+        .stdout(predicate::str::contains("leak").not());
+
+    // 输出与直接执行 query 相同，输出格式在执行时指定
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .args(["view", "run", "bj", "--format", "csv"])
+        .assert()
+        .success()
+        .stdout("联系人,价格\n视图A,1200\n视图B,900\n");
+
+    // 追加的排序覆盖保存的排序，追加的条件与视图叠加
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .args(["view", "run", "bj", "--format", "csv", "--sort", "price"])
+        .assert()
+        .success()
+        .stdout("联系人,价格\n视图B,900\n视图A,1200\n");
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .args(["view", "run", "bj", "--format", "csv", "--price-max", "1000"])
+        .assert()
+        .success()
+        .stdout("联系人,价格\n视图B,900\n");
+
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .args(["view", "delete", "bj"])
+        .assert()
+        .success();
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .args(["view", "run", "bj"])
+        .assert()
+        .code(5);
+}
+
+#[test]
+fn test_config_key_help_lists_all_keys() {
+    let env = TestEnv::new("");
+    for action in ["get", "set"] {
+        let mut assert = env.run(&["config", action, "--help"]).success();
+        for key in wei_assistant_gpu::config::CONFIG_KEYS {
+            assert = assert.stdout(predicate::str::contains(key));
+        }
+    }
+}
+
+#[test]
+fn test_config_file_defaults_and_precedence() {
+    let db_path = create_test_db().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("config.toml");
+    let config = config_path.to_str().unwrap();
+
+    for (key, value) in [("db", db_path.as_str()), ("defaults.format", "csv"), ("defaults.columns", "contact,price"), ("defaults.sort", "price:desc")] {
+        let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+        cmd.env_remove("DB_FILE")
+            .args(["--config", config, "config", "set", key, value])
+            .assert()
+            .success();
+    }
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.args(["--config", config, "config", "set", "defaults.sort", "nope"])
+        .assert()
+        .code(3);
+
+    for (contact, price) in [("配置A", "100"), ("配置B", "200")] {
+        let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+        cmd.env_remove("DB_FILE")
+            .args(["--config", config, "add", "--contact", contact, "--price", price])
+            .assert()
+            .success();
+    }
+
+    // 配置文件中的数据库、格式、列和排序生效
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env_remove("DB_FILE")
+        .args(["--config", config, "query"])
+        .assert()
+        .success()
+        .stdout("联系人,价格\n配置B,200\n配置A,100\n");
+
+    // 命令行参数优先于配置文件
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env_remove("DB_FILE")
+        .args(["--config", config, "query", "--sort", "price", "--columns", "contact"])
+        .assert()
+        .success()
+        .stdout("联系人\n配置A\n配置B\n");
+
+    // 数据库路径：--db > DB_FILE > 配置文件
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", "env.db")
+        .args(["--config", config, "config", "get", "db"])
+        .assert()
+        .success()
+        .stdout("env.db\n");
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", "env.db")
+        .args(["--config", config, "--db", "flag.db", "config", "get", "db"])
+        .assert()
+        .success()
+        .stdout("flag.db\n");
+
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.args(["--config", config, "config", "get", "defaults.currency"])
+        .assert()
+        .code(5);
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.args(["--config", dir.path().join("missing.toml").to_str().unwrap(), "query"])
+        .assert()
+        .code(5);
+}
+
+#[test]
+fn test_profiles_and_all_profiles_query() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("config.toml");
+    let config = config_path.to_str().unwrap();
+
+    // 相对路径相对于配置文件所在目录
+    for (name, db) in [("cn-east", "east.db"), ("cn-west", "west.db")] {
+        let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+        cmd.args(["--config", config, "profile", "add", name, db]).assert().success();
+    }
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.args(["--config", config, "profile", "use", "cn-south"]).assert().code(5);
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.args(["--config", config, "profile", "use", "cn-east"]).assert().success();
+
+    // 默认使用 profile use 选择的库，--profile 临时切换
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env_remove("DB_FILE")
+        .args(["--config", config, "add", "--contact", "华东A", "--price", "300"])
+        .assert()
+        .success();
+    for (contact, price) in [("华西B", "200"), ("华西C", "400")] {
+        let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+        cmd.env_remove("DB_FILE")
+            .args(["--config", config, "--profile", "cn-west", "add", "--contact", contact, "--price", price])
+            .assert()
+            .success();
+    }
+
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env_remove("DB_FILE")
+        .args(["--config", config, "query", "--format", "csv", "--columns", "contact"])
+        .assert()
+        .success()
+        .stdout("联系人\n华东A\n");
+
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.args(["--config", config, "profile", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("* cn-east"))
+        .stdout(predicate::str::contains("  cn-west"));
+
+    // 跨 profile 查询：合并后统一排序，并在最前增加 profile 列
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.args(["--config", config, "query", "--all-profiles", "--sort", "price:desc", "--columns", "contact,price", "--format", "csv"])
+        .assert()
+        .success()
+        .stdout("Profile,联系人,价格\ncn-west,华西C,400\ncn-east,华东A,300\ncn-west,华西B,200\n");
+
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.args(["--config", config, "--profile", "cn-north", "query"])
+        .assert()
+        .code(5);
+}
+
+#[test]
+fn test_multiple_suppliers() {
+    // 创建测试数据库
+    let db_path = create_test_db().unwrap();
+    
+    // 初始化数据库表结构
+    let conn = Connection::open(&db_path).unwrap();
+    let create_table_sql = "CREATE TABLE IF NOT EXISTS suppliers (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        contact TEXT,
+        wechat TEXT,
+        phone TEXT,
+        quantity INTEGER,
+        location TEXT,
+        price REAL,
+        bandwidth_price REAL,
+        storage_price REAL,
+        min_contract_period TEXT,
+        breach_penalties TEXT,
+        payment_terms TEXT,
+        server_name TEXT,
+        server_config TEXT,
+        rental_model TEXT,
+        networking_category TEXT
+    );";
+    conn.execute_batch(create_table_sql).unwrap();
+    drop(conn);
+    
+    // 添加多个供应商
+    for i in 1..=3 {
+        let contact = format!("测试{}", i);
+        let wechat = format!("wx{}", i);
+        let phone = format!("1380013800{}", i);
+        
+        let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+        cmd.env("DB_FILE", &db_path)
+            .arg("add")
+            .arg("--contact").arg(&contact)
+            .arg("--wechat").arg(&wechat)
+            .arg("--phone").arg(&phone)
+            .assert()
+            .success();
+    }
+    
+    // 查询所有供应商
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("query")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("测试1"))
+        .stdout(predicate::str::contains("测试2"))
+        .stdout(predicate::str::contains("测试3"));
+}
+
+#[test]
+fn test_invalid_args() {
+    // 测试添加供应商时缺少必要参数
+    let db_path = create_test_db().unwrap();
+    
+    // 初始化数据库表结构
+    let conn = Connection::open(&db_path).unwrap();
+    let create_table_sql = "CREATE TABLE IF NOT EXISTS suppliers (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        contact TEXT,
+        wechat TEXT,
+        phone TEXT,
+        quantity INTEGER,
+        location TEXT,
+        price REAL,
+        bandwidth_price REAL,
+        storage_price REAL,
+        min_contract_period TEXT,
+        breach_penalties TEXT,
+        payment_terms TEXT,
+        server_name TEXT,
+        server_config TEXT,
+        rental_model TEXT,
+        networking_category TEXT
+    );";
+    conn.execute_batch(create_table_sql).unwrap();
+    drop(conn);
+    
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("add")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_error_handling() {
+    // 测试不存在的命令
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.arg("not-exist-command")
+        .assert()
+        .failure();
+    
+    // 测试添加供应商时缺少必要参数
+    let db_path = create_test_db().unwrap();
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("add") // 没有提供任何参数
+        .assert()
+        .failure();
+    
+    // 测试JSON格式错误
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("add")
+        .arg("--json").arg("{invalid json}")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_error_exit_codes() {
+    let db_path = create_test_db().unwrap();
+
+    // 校验错误：缺少联系人
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("add")
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("错误: 至少需要提供联系人字段"));
+
+    // 解析错误：JSON格式不正确，以JSON输出错误
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("add")
+        .arg("--json").arg("{invalid json}")
+        .arg("--error-format").arg("json")
+        .assert()
+        .code(4)
+        .stderr(predicate::str::starts_with(r#"{"error":{"code":4,"kind":"parse","message":"JSON解析失败"#));
+
+    // 记录不存在
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("--error-format").arg("json")
+        .arg("delete")
+        .arg("--id").arg("42")
+        .assert()
+        .code(5)
+        .stderr(predicate::str::contains(r#""kind":"not_found""#));
+
+    // 导出路径不可写
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("add").arg("--contact").arg("导出测试")
+        .assert()
+        .success();
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
+    cmd.env("DB_FILE", &db_path)
+        .arg("query")
+        .arg("--export-csv").arg("/non/existent/dir/out.csv")
+        .assert()
+        .code(6);
+}
 
 #[test]
 fn test_invalid_db_file() {
@@ -330,11 +1137,735 @@ fn test_invalid_db_file() {
         .as_millis();
     let invalid_path = format!("/non/existent/path/db_{}_{}.sqlite", unique_id, rand::random::<u32>());
     
-    let mut cmd = Command::cargo_bin(&get_bin_name()).unwrap();
+    let mut cmd = Command::cargo_bin(get_bin_name()).unwrap();
     let assert = cmd
         .env("DB_FILE", &invalid_path)
         .arg("query")
         .assert();
         
-    assert.failure();
-} 
\ No newline at end of file
+    assert.code(7);
+} 
+#[test]
+fn test_db_backup_and_restore() {
+    let env = TestEnv::new("");
+    let backup = env.path("backup.db");
+
+    env.run(&["add", "--contact", "备份测试"]).success();
+    env.run(&["db", "backup", &backup]).success();
+    env.run(&["add", "--contact", "备份之后"]).success();
+    env.run(&["db", "restore", &backup]).success().stdout(predicate::str::contains("恢复前的数据已保存到"));
+    env.run(&["query", "--format", "csv"])
+        .success()
+        .stdout(predicate::str::contains("备份测试"))
+        .stdout(predicate::str::contains("备份之后").not());
+}
+
+#[test]
+fn test_db_backup_refuses_to_overwrite() {
+    let env = TestEnv::new("");
+    let backup = env.path("backup.db");
+
+    env.run(&["db", "backup", &backup]).success();
+    env.run(&["db", "backup", &backup]).code(3);
+    env.run(&["db", "backup", &backup, "--force"]).success();
+}
+
+#[test]
+fn test_db_restore_missing_file() {
+    let env = TestEnv::new("");
+    env.run(&["db", "restore", "/non/existent/backup.db"]).code(5);
+}
+
+#[test]
+fn test_db_integrity_check() {
+    let env = TestEnv::new("");
+    env.run(&["add", "--contact", "张三"]).success();
+    env.run(&["db", "integrity-check"]).success().stdout(predicate::str::contains("完整性检查通过"));
+}
+
+#[test]
+fn test_db_snapshots_before_delete() {
+    // 删除前自动保存快照，超出 snapshots.keep 的旧快照被清理
+    let env = TestEnv::new("[snapshots]\nkeep = 2\n");
+    env.run(&["add", "--contact", "张三"]).success();
+    env.run(&["add", "--contact", "李四"]).success();
+    env.run(&["add", "--contact", "王五"]).success();
+    for id in ["3", "2", "1"] {
+        env.run(&["delete", "--id", id]).success();
+    }
+    env.run(&["delete", "--id", "1"]).code(5);
+    assert_eq!(std::fs::read_dir(format!("{}.snapshots", env.db)).unwrap().count(), 2);
+    env.run(&["db", "snapshots"]).success().stdout(predicate::str::contains("-delete"));
+}
+
+#[test]
+fn test_db_no_snapshot_when_nothing_to_write() {
+    // 删除不存在的供应商、导入时全部行失败，都不保存快照
+    let env = TestEnv::new("[snapshots]\nkeep = 5\n");
+    env.run(&["add", "--contact", "张三"]).success();
+    env.run(&["delete", "--id", "99"]).code(5);
+    let csv = env.path("bad.csv");
+    std::fs::write(&csv, "联系人,地点\n,北京\n").unwrap();
+    env.run(&["import", "--csv", &csv]).success().stdout(predicate::str::contains("成功 0 条，失败 1 条"));
+    let snapshots = std::fs::read_dir(format!("{}.snapshots", env.db)).map(|dir| dir.count()).unwrap_or(0);
+    assert_eq!(snapshots, 0);
+
+    env.run(&["delete", "--id", "1"]).success();
+    assert_eq!(std::fs::read_dir(format!("{}.snapshots", env.db)).unwrap().count(), 1);
+}
+
+/// 以 `passphrase`（为 `None` 时不提供口令）执行命令
+fn run_with_passphrase(env: &TestEnv, passphrase: Option<&str>, args: &[&str]) -> assert_cmd::assert::Assert {
+    let mut cmd = env.command();
+    cmd.env_remove("WEI_ASSISTANT_PASSPHRASE");
+    if let Some(p) = passphrase {
+        cmd.env("WEI_ASSISTANT_PASSPHRASE", p);
+    }
+    cmd.args(args).assert()
+}
+
+/// 一条带电话的记录，以“旧口令”加密
+fn encrypted_env() -> TestEnv {
+    let env = TestEnv::new("");
+    run_with_passphrase(&env, None, &["add", "--contact", "张三", "--phone", "13800000000"]).success();
+    run_with_passphrase(&env, Some("旧口令"), &["db", "encrypt"]).success();
+    env
+}
+
+#[test]
+fn test_db_encrypt_requires_passphrase() {
+    let env = TestEnv::new("");
+    run_with_passphrase(&env, None, &["db", "encrypt"]).code(3);
+}
+
+#[test]
+fn test_db_encrypt_hides_plaintext() {
+    let env = encrypted_env();
+    let raw = std::fs::read(&env.db).unwrap();
+    assert!(!String::from_utf8_lossy(&raw).contains("13800000000"), "加密后文件中不应有明文电话");
+}
+
+#[test]
+fn test_db_encrypted_query_needs_passphrase() {
+    // 未提供口令或口令错误时不能读取，提供口令后透明解密，筛选同样有效
+    let env = encrypted_env();
+    run_with_passphrase(&env, None, &["query"]).code(3).stderr(predicate::str::contains("数据库已加密"));
+    run_with_passphrase(&env, Some("错误"), &["query"]).code(3).stderr(predicate::str::contains("口令错误"));
+    run_with_passphrase(&env, Some("旧口令"), &["query", "--contact", "张三", "--format", "csv"])
+        .success()
+        .stdout(predicate::str::contains("13800000000"));
+}
+
+#[test]
+fn test_db_rekey_and_decrypt() {
+    let env = encrypted_env();
+    let new_key = env.path("new.key");
+    std::fs::write(&new_key, "新口令\n").unwrap();
+
+    run_with_passphrase(&env, Some("旧口令"), &["db", "rekey"]).code(3);
+    run_with_passphrase(&env, Some("旧口令"), &["db", "rekey", "--new-passphrase-file", &new_key]).success();
+    run_with_passphrase(&env, Some("旧口令"), &["query"]).code(3);
+    run_with_passphrase(&env, None, &["--passphrase-file", &new_key, "db", "decrypt"]).success();
+    run_with_passphrase(&env, None, &["query", "--format", "csv"]).success().stdout(predicate::str::contains("张三"));
+}
+
+#[test]
+fn test_db_plaintext_enc_prefix_stays_readable() {
+    // 未加密的数据库中，以 enc: 开头的取值只是普通文本
+    let env = TestEnv::new("");
+    run_with_passphrase(&env, None, &["add", "--contact", "张三", "--wechat", "enc:wx123"]).success();
+    run_with_passphrase(&env, None, &["query", "--format", "csv"]).success().stdout(predicate::str::contains("enc:wx123"));
+}
+
+/// 一条带电话和微信的记录
+fn pii_env(config: &str) -> TestEnv {
+    let env = TestEnv::new(config);
+    env.run(&["add", "--contact", "张三", "--phone", "13812348000", "--wechat", "wxid_abc123"]).success();
+    env
+}
+
+#[test]
+fn test_mask_pii_in_every_format() {
+    let env = pii_env("");
+    for format in ["table", "json", "csv", "markdown", "html"] {
+        env.run(&["query", "--mask-pii", "--format", format])
+            .success()
+            .stdout(predicate::str::contains("138****8000"))
+            .stdout(predicate::str::contains("wxi****c123"))
+            .stdout(predicate::str::contains("13812348000").not());
+    }
+}
+
+#[test]
+fn test_mask_pii_in_xlsx_export() {
+    use calamine::{open_workbook_auto, Data, Reader};
+
+    let env = pii_env("");
+    let xlsx = env.path("out.xlsx");
+    env.run(&["query", "--mask-pii", "--columns", "contact,phone", "--export-xlsx", &xlsx]).success();
+    let mut workbook = open_workbook_auto(&xlsx).unwrap();
+    let range = workbook.worksheet_range_at(0).unwrap().unwrap();
+    let row: Vec<Data> = range.rows().nth(1).unwrap().to_vec();
+    assert_eq!(row, [Data::String("张三".to_string()), Data::String("138****8000".to_string())]);
+}
+
+#[test]
+fn test_mask_pii_by_default_from_config() {
+    let env = pii_env("");
+    env.run(&["config", "set", "privacy.mask_pii", "true"]).success();
+    env.run(&["query", "--format", "csv"]).success().stdout(predicate::str::contains("13812348000").not());
+    env.run(&["audit"]).success().stdout(predicate::str::contains("暂无审计日志"));
+}
+
+#[test]
+fn test_reveal_pii_is_audited() {
+    let env = pii_env("[privacy]\nmask_pii = true\n");
+    env.command().env("USER", "审计员").args(["query", "--reveal-pii", "--format", "csv"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("13812348000"));
+    env.run(&["audit"])
+        .success()
+        .stdout(predicate::str::contains("审计员\treveal_pii\t"))
+        .stdout(predicate::str::contains("（1 条记录）"));
+}
+
+#[test]
+fn test_reveal_pii_conflicts_with_mask_pii() {
+    let env = pii_env("");
+    env.run(&["query", "--reveal-pii", "--mask-pii"]).code(2);
+}
+
+const ADMIN: [(&str, &str); 2] = [("WEI_ASSISTANT_USER", "boss"), ("WEI_ASSISTANT_PASSWORD", "admin-pass")];
+const CLERK: [(&str, &str); 2] = [("WEI_ASSISTANT_USER", "clerk"), ("WEI_ASSISTANT_PASSWORD", "clerk-pass")];
+
+/// 以 `vars` 中的登录环境变量执行命令，不继承外部的登录变量
+fn run_as(env: &TestEnv, vars: &[(&str, &str)], args: &[&str]) -> assert_cmd::assert::Assert {
+    let mut cmd = env.command();
+    for name in ["WEI_ASSISTANT_USER", "WEI_ASSISTANT_PASSWORD", "WEI_ASSISTANT_TOKEN", "WEI_ASSISTANT_NEW_PASSWORD"] {
+        cmd.env_remove(name);
+    }
+    cmd.envs(vars.iter().copied()).args(args).assert()
+}
+
+/// 一条带电话的记录，管理员 boss 和录入员 clerk
+fn users_env() -> TestEnv {
+    let env = TestEnv::new("");
+    run_as(&env, &[], &["add", "--contact", "张三", "--phone", "13812348000"]).success();
+    run_as(&env, &[("WEI_ASSISTANT_NEW_PASSWORD", "admin-pass")], &["user", "add", "boss", "--role", "admin"]).success();
+    let mut vars = ADMIN.to_vec();
+    vars.push(("WEI_ASSISTANT_NEW_PASSWORD", "clerk-pass"));
+    run_as(&env, &vars, &["user", "add", "clerk", "--role", "editor"]).success();
+    env
+}
+
+#[test]
+fn test_user_add_requires_admin_once_users_exist() {
+    // 没有用户时不做权限检查；第一个用户无需登录，之后添加用户需要 admin
+    let env = TestEnv::new("");
+    run_as(&env, &[], &["add", "--contact", "张三"]).success();
+    run_as(&env, &[("WEI_ASSISTANT_NEW_PASSWORD", "admin-pass")], &["user", "add", "boss", "--role", "admin"]).success();
+    run_as(&env, &[("WEI_ASSISTANT_NEW_PASSWORD", "clerk-pass")], &["user", "add", "clerk", "--role", "editor"]).code(8);
+}
+
+#[test]
+fn test_anonymous_is_read_only_and_masked() {
+    let env = users_env();
+    run_as(&env, &[], &["add", "--contact", "李四"]).code(8).stderr(predicate::str::contains("editor"));
+    run_as(&env, &[], &["query", "--format", "csv"]).success().stdout(predicate::str::contains("138****8000"));
+    run_as(&env, &[], &["query", "--reveal-pii"]).code(8);
+}
+
+#[test]
+fn test_wrong_password_is_rejected() {
+    let env = users_env();
+    run_as(&env, &[("WEI_ASSISTANT_USER", "clerk"), ("WEI_ASSISTANT_PASSWORD", "wrong-pass")], &["query"]).code(8);
+}
+
+#[test]
+fn test_editor_permissions() {
+    // editor 可以修改数据，但不能管理用户和查看审计日志
+    let env = users_env();
+    run_as(&env, &CLERK, &["add", "--contact", "李四"]).success();
+    run_as(&env, &CLERK, &["query", "--format", "csv"]).success().stdout(predicate::str::contains("13812348000"));
+    run_as(&env, &CLERK, &["user", "list"]).code(8);
+    run_as(&env, &CLERK, &["audit"]).code(8);
+    run_as(&env, &CLERK, &["user", "whoami"]).success().stdout(predicate::str::contains("clerk（editor）"));
+}
+
+#[test]
+fn test_all_profiles_authenticate_in_each_database() {
+    // 同名用户在各 profile 的库中分别认证：口令不符的库跳过，只是 viewer 的库脱敏
+    let env = users_env();
+    let other = TestEnv::new("");
+    run_as(&other, &[], &["add", "--contact", "李四", "--phone", "13900001111"]).success();
+    run_as(&other, &[("WEI_ASSISTANT_NEW_PASSWORD", "other-pass")], &["user", "add", "boss", "--role", "admin"]).success();
+    let viewer = TestEnv::new("");
+    run_as(&viewer, &[], &["add", "--contact", "王五", "--phone", "13700002222"]).success();
+    run_as(&viewer, &[("WEI_ASSISTANT_NEW_PASSWORD", "root-pass")], &["user", "add", "root", "--role", "admin"]).success();
+    let vars = [("WEI_ASSISTANT_USER", "root"), ("WEI_ASSISTANT_PASSWORD", "root-pass"), ("WEI_ASSISTANT_NEW_PASSWORD", "admin-pass")];
+    run_as(&viewer, &vars, &["user", "add", "boss", "--role", "viewer"]).success();
+    for (name, profile) in [("main", &env), ("other", &other), ("viewer", &viewer)] {
+        env.run(&["profile", "add", name, &profile.db]).success();
+    }
+
+    run_as(&env, &ADMIN, &["query", "--all-profiles", "--columns", "contact,phone", "--format", "csv"])
+        .success()
+        .stdout("Profile,联系人,手机\nmain,张三,13812348000\nviewer,王五,137****2222\n")
+        .stderr(predicate::str::contains("已跳过 profile other"));
+    run_as(&env, &ADMIN, &["query", "--all-profiles", "--reveal-pii"]).code(8).stdout("");
+}
+
+#[test]
+fn test_token_login_as_viewer() {
+    let env = users_env();
+    run_as(&env, &ADMIN, &["user", "role", "clerk", "viewer"]).success();
+    let output = run_as(&env, &ADMIN, &["user", "token", "clerk"]).success().get_output().stdout.clone();
+    let token_file = env.path("clerk.token");
+    std::fs::write(&token_file, &output).unwrap();
+    run_as(&env, &[], &["--token-file", &token_file, "delete", "--id", "1"]).code(8);
+    run_as(&env, &[], &["--token-file", &token_file, "user", "whoami"]).success().stdout(predicate::str::contains("clerk（viewer）"));
+    run_as(&env, &ADMIN, &["user", "list"]).success().stdout(predicate::str::contains("clerk\tviewer\t有令牌"));
+    run_as(&env, &ADMIN, &["audit"]).success().stdout(predicate::str::contains("boss\tuser_role\tclerk -> viewer"));
+}
+
+/// 一条带联系方式的北京资源，已发布目录，返回其报价编号
+fn catalog_env() -> (TestEnv, String) {
+    let env = TestEnv::new("");
+    env.run(&["add", "--contact", "张三", "--phone", "13812348000", "--wechat", "wxid_abc", "--location", "北京", "--price", "1000"]).success();
+    let output = env.run(&["catalog", "publish"]).success().get_output().stdout.clone();
+    let entries: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let code = entries[0]["code"].as_str().unwrap().to_string();
+    (env, code)
+}
+
+#[test]
+fn test_catalog_publish_with_markup() {
+    let (env, code) = catalog_env();
+    let output = env.run(&["catalog", "publish", "--markup", "20"]).success().get_output().stdout.clone();
+    let entries: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(entries[0]["code"], code.as_str(), "再次发布时编号不变");
+    assert_eq!(entries[0]["price"], 1200.0);
+    assert!(entries[0].get("id").is_none() && entries[0].get("contact").is_none());
+}
+
+#[test]
+fn test_catalog_publish_hides_supplier_identity() {
+    let (env, code) = catalog_env();
+    for format in ["json", "csv", "html"] {
+        env.run(&["catalog", "publish", "--format", format])
+            .success()
+            .stdout(predicate::str::contains(code.as_str()))
+            .stdout(predicate::str::contains("张三").not())
+            .stdout(predicate::str::contains("13812348000").not())
+            .stdout(predicate::str::contains("wxid_abc").not());
+    }
+}
+
+#[test]
+fn test_catalog_publish_to_file() {
+    let (env, code) = catalog_env();
+    let html = env.path("catalog.html");
+    env.run(&["catalog", "publish", "--format", "html", "--output", &html]).success();
+    assert!(std::fs::read_to_string(&html).unwrap().contains(&code));
+}
+
+#[test]
+fn test_catalog_lookup_is_audited() {
+    let (env, code) = catalog_env();
+    env.run(&["catalog", "lookup", &code]).success().stdout(predicate::str::contains("张三"));
+    env.run(&["audit"]).success().stdout(predicate::str::contains("offer_lookup"));
+    env.run(&["catalog", "lookup", "OF-NOTEXIST"]).code(5);
+}
+
+#[test]
+fn test_catalog_publish_requires_editor() {
+    // 首次发布会保存报价编号，viewer 和未登录用户不能发布
+    let env = users_env();
+    run_as(&env, &ADMIN, &["user", "role", "clerk", "viewer"]).success();
+    run_as(&env, &[], &["catalog", "publish"]).code(8);
+    run_as(&env, &CLERK, &["catalog", "publish"]).code(8);
+    run_as(&env, &ADMIN, &["catalog", "publish"]).success().stdout(predicate::str::contains("OF-"));
+}
+
+/// 一条北京资源：价格 1000，存储 20，8 台
+fn pricing_env() -> TestEnv {
+    let env = TestEnv::new("");
+    env.run(&["add", "--contact", "张三", "--location", "北京", "--price", "1000", "--storage-price", "20", "--quantity", "8"]).success();
+    env
+}
+
+#[test]
+fn test_quote_price_without_rules() {
+    let env = pricing_env();
+    env.run(&["quote", "price", "--offer", "1", "--qty", "2"]).success().stdout(predicate::str::contains("未匹配定价规则"));
+    env.run(&["quote", "price", "--offer", "42"]).code(5);
+}
+
+#[test]
+fn test_pricing_add_and_list() {
+    let env = pricing_env();
+    env.run(&["pricing", "add", "--percent", "10", "--fixed", "100"]).code(2);
+    env.run(&["pricing", "add", "--location", "北京", "--percent", "10"]).success();
+    env.run(&["pricing", "list"]).success().stdout(predicate::str::contains("北京/*/*: +10%"));
+}
+
+#[test]
+fn test_quote_price_with_rule() {
+    let env = pricing_env();
+    env.run(&["pricing", "add", "--location", "北京", "--percent", "10"]).success();
+    env.run(&["quote", "price", "--offer", "1", "--qty", "2"])
+        .success()
+        .stdout(predicate::str::contains("服务器\t1000.00\t1100.00\t2000.00\t2200.00\t200.00"))
+        .stdout(predicate::str::contains("合计\t\t\t2040.00\t2244.00\t204.00"));
+    let output = env.run(&["quote", "price", "--offer", "1", "--qty", "2", "--json"]).success().get_output().stdout.clone();
+    let quote: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(quote["total_price"], 2244.0);
+}
+
+#[test]
+fn test_pricing_remove() {
+    let env = pricing_env();
+    env.run(&["pricing", "add", "--percent", "10"]).success();
+    env.run(&["pricing", "remove", "1"]).success();
+    env.run(&["pricing", "remove", "1"]).code(5);
+    env.run(&["quote", "price", "--offer", "1", "--qty", "2"]).success().stdout(predicate::str::contains("未匹配定价规则"));
+}
+
+/// 一条季付资源，加价 10%
+fn quote_env() -> TestEnv {
+    let env = TestEnv::new("");
+    env.run(&["add", "--contact", "张三", "--price", "1000", "--server-config", "8×A100 | NVLink", "--payment-terms", "季付"]).success();
+    env.run(&["pricing", "add", "--percent", "10"]).success();
+    env
+}
+
+#[test]
+fn test_quote_generate() {
+    let env = quote_env();
+    env.run(&["quote", "generate", "--offer", "1", "--qty", "8", "--months", "6", "--customer", "某客户"])
+        .success()
+        .stdout(predicate::str::contains("| 服务器 | 1100.00 | 8 | 8800.00 | 52800.00 |"))
+        .stdout(predicate::str::contains("| 配置 | 8×A100 \\| NVLink |"))
+        .stdout(predicate::str::contains("张三").not())
+        .stderr(predicate::str::contains("-001"));
+    env.run(&["quote", "generate", "--offer", "1", "--customer", "某客户", "--months", "0"]).code(3);
+}
+
+#[test]
+fn test_quote_show_with_configured_template() {
+    let env = quote_env();
+    env.run(&["quote", "generate", "--offer", "1", "--qty", "8", "--months", "6", "--customer", "某客户"]).success();
+    let template = env.path("quote.md");
+    std::fs::write(&template, "{{quote_no}} {{customer}} {{total}} {{payment_terms}}\n").unwrap();
+    env.run(&["config", "set", "quotes.markdown_template", &template]).success();
+    let output = env.run(&["quote", "list", "--json"]).success().get_output().stdout.clone();
+    let quotes: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let quote_no = quotes[0]["quote_no"].as_str().unwrap().to_string();
+    env.run(&["quote", "show", &quote_no]).success().stdout(format!("{} 某客户 52800.00 季付\n", quote_no));
+    env.run(&["quote", "show", &quote_no, "--format", "html"]).success().stdout(predicate::str::contains("<!DOCTYPE html>"));
+}
+
+#[test]
+fn test_quote_generate_bad_template_saves_nothing() {
+    let env = quote_env();
+    let template = env.path("bad.md");
+    std::fs::write(&template, "{{quote_no}} {{custmer}}\n").unwrap();
+    env.run(&["quote", "generate", "--offer", "1", "--customer", "某客户", "--template", &template])
+        .code(3)
+        .stderr(predicate::str::contains("{{custmer}}"));
+    env.run(&["quote", "list"]).success().stdout("暂无报价单\n");
+
+    // 模板有误时不占用编号，下一份报价单仍是 -001
+    env.run(&["quote", "generate", "--offer", "1", "--customer", "某客户"]).success().stderr(predicate::str::contains("-001"));
+}
+
+/// 一条最短合同期 6 个月、违约金 2 个月租金的资源；`with_contract` 时再签一份 4 台的合同
+fn contract_env(with_contract: bool) -> TestEnv {
+    let env = TestEnv::new("");
+    env.run(&["add", "--contact", "张三", "--price", "1000", "--min-contract-period", "6个月", "--breach-penalties", "扣2个月租金"]).success();
+    if with_contract {
+        env.run(&["contract", "create", "--offer", "1", "--qty", "4", "--start", "2024-01-31"]).success();
+    }
+    env
+}
+
+#[test]
+fn test_contract_create() {
+    let env = contract_env(false);
+    env.run(&["contract", "create", "--offer", "1", "--qty", "4", "--start", "2024-01-31"])
+        .success()
+        .stdout(predicate::str::contains("2024-01-31 至 2024-07-31"));
+    env.run(&["contract", "create", "--offer", "1", "--qty", "1", "--months", "3", "--start", "2024-01-01"])
+        .success()
+        .stderr(predicate::str::contains("短于供应商的最短合同期"));
+    env.run(&["contract", "create", "--offer", "1", "--qty", "1", "--start", "2024-02-30"]).code(2);
+}
+
+#[test]
+fn test_contract_list() {
+    let env = contract_env(true);
+    env.run(&["contract", "list", "--status", "active"])
+        .success()
+        .stdout(predicate::str::contains("约定单价"))
+        .stdout(predicate::str::contains("4000.00"));
+    env.run(&["contract", "list", "--layout", "vertical"]).success().stdout(predicate::str::contains("状态     : active"));
+    env.run(&["contract", "show", "9"]).code(5);
+}
+
+#[test]
+fn test_contract_renew() {
+    let env = contract_env(true);
+    env.run(&["contract", "renew", "1"]).success().stdout(predicate::str::contains("2025-01-31"));
+}
+
+#[test]
+fn test_contract_terminate() {
+    let env = contract_env(true);
+    env.run(&["contract", "terminate", "1", "--on", "2024-03-10", "--dry-run"])
+        .success()
+        .stdout(predicate::str::contains("预计违约金 8000.00"));
+    env.run(&["contract", "list", "--status", "active"]).success().stdout(predicate::str::contains("4000.00"));
+    env.run(&["contract", "terminate", "1", "--on", "2024-03-10"]).success();
+    env.run(&["contract", "show", "1"]).success().stdout(predicate::str::contains("终止\t2024-03-10，违约金 8000.00"));
+    env.run(&["contract", "terminate", "1"]).code(3);
+}
+
+/// 两份从 2020 年开始的合同：合同 1 月付 2 台 3 个月，合同 2 的付款方式无法识别
+fn payments_env() -> TestEnv {
+    let env = TestEnv::new("");
+    env.run(&["add", "--contact", "张三", "--price", "1000", "--payment-terms", "月付"]).success();
+    env.run(&["add", "--contact", "李四", "--price", "500", "--payment-terms", "面议"]).success();
+    env.run(&["contract", "create", "--offer", "1", "--qty", "2", "--start", "2020-01-01", "--months", "3"]).success();
+    env.run(&["contract", "create", "--offer", "2", "--qty", "1", "--start", "2020-01-01", "--months", "2"]).success();
+    env
+}
+
+/// [`payments_env`] 并为两份合同都生成付款计划，前几期已逾期
+fn scheduled_payments_env() -> TestEnv {
+    let env = payments_env();
+    env.run(&["payments", "schedule"]).success();
+    env.run(&["payments", "schedule", "--contract", "2", "--cadence", "upfront"]).success();
+    env
+}
+
+#[test]
+fn test_payments_schedule() {
+    let env = payments_env();
+    env.run(&["payments", "schedule"])
+        .success()
+        .stdout(predicate::str::contains("已生成 3 期付款"))
+        .stderr(predicate::str::contains("无法识别合同 2 的付款方式"));
+    env.run(&["payments", "schedule", "--cadence", "upfront"]).code(2);
+    env.run(&["payments", "schedule", "--contract", "2", "--cadence", "upfront"]).success().stdout(predicate::str::contains("已生成 1 期付款"));
+}
+
+#[test]
+fn test_payments_due() {
+    let env = scheduled_payments_env();
+    env.run(&["payments", "due", "--within", "14d"])
+        .success()
+        .stdout(predicate::str::contains("到期 4 笔，合计 7000.00"))
+        .stdout(predicate::str::contains("逾期"));
+    env.run(&["payments", "due", "--within", "fortnight"]).code(2);
+}
+
+#[test]
+fn test_payments_pay() {
+    let env = scheduled_payments_env();
+    env.run(&["payments", "pay", "1", "--on", "2020-01-01"]).success();
+    env.run(&["payments", "pay", "1"]).code(3);
+    env.run(&["payments", "pay", "9"]).code(5);
+    env.run(&["payments", "list", "--contract", "1"]).success().stdout(predicate::str::contains("已付 2020-01-01"));
+}
+
+#[test]
+fn test_payments_balance() {
+    let env = scheduled_payments_env();
+    env.run(&["payments", "pay", "1", "--on", "2020-01-01"]).success();
+    env.run(&["payments", "balance"])
+        .success()
+        .stdout(predicate::str::contains("其中逾期"))
+        .stdout(predicate::str::contains("合计：已到期 5000.00"));
+}
+
+#[test]
+fn test_payments_list_vertical_layout() {
+    let env = scheduled_payments_env();
+    env.run(&["payments", "list", "--contract", "1", "--width", "30"])
+        .success()
+        .stdout(predicate::str::contains("-[ 记录 1 ]"))
+        .stdout(predicate::str::contains("账期结束 : 2020-02-01"));
+}
+
+/// 配置文件通道 `file:notify.jsonl`；供应商 1 仅剩 1 台，供应商 2 的合同已于 2020-04-01 到期
+fn notify_env() -> TestEnv {
+    let env = TestEnv::new("[notify]\nsinks = [\"file:notify.jsonl\"]\n");
+    env.run(&["add", "--contact", "张三", "--price", "1000", "--quantity", "1", "--location", "北京"]).success();
+    env.run(&["add", "--contact", "李四", "--price", "900", "--quantity", "20"]).success();
+    env.run(&["contract", "create", "--offer", "2", "--qty", "2", "--start", "2020-01-01", "--months", "3"]).success();
+    env
+}
+
+/// 在临时目录中运行，命令通道的相对路径写在其中
+fn run_in_dir(env: &TestEnv, args: &[&str]) -> assert_cmd::assert::Assert {
+    env.command().current_dir(env.dir.path()).args(args).assert()
+}
+
+#[test]
+fn test_notify_check_dry_run() {
+    let env = notify_env();
+    env.run(&["notify", "check", "--dry-run"])
+        .success()
+        .stdout(predicate::str::contains("[合同到期] 合同 1 已于 2020-04-01 到期"))
+        .stdout(predicate::str::contains("[库存偏低] 供应商 1 仅剩 1 台可提供"));
+    env.run(&["notify", "check", "--dry-run", "--low-quantity", "0"]).success().stdout(predicate::str::contains("库存偏低").not());
+    assert!(!std::path::Path::new(&env.path("notify.jsonl")).exists());
+}
+
+#[test]
+fn test_notify_check_configured_file_sink() {
+    let env = notify_env();
+    // 配置的文件通道，路径相对于配置文件；发送后不再重复
+    env.run(&["notify", "check"]).success().stderr(predicate::str::contains("已发送 2 条提醒"));
+    let log = std::fs::read_to_string(env.path("notify.jsonl")).unwrap();
+    assert_eq!(log.lines().count(), 2);
+    assert!(log.contains("\"kind\":\"contract_expiring\""));
+    env.run(&["notify", "check", "--dry-run"]).success().stdout(predicate::str::is_empty());
+}
+
+#[test]
+fn test_notify_check_maildir_and_command_sinks() {
+    let env = notify_env();
+    let maildir = env.path("mail");
+    let command = "command:cat >> hook.out; echo \"$WEI_NOTIFY_KIND\" >> kinds.out";
+    run_in_dir(&env, &["notify", "check", "--all", "--sink", &format!("maildir:{}", maildir), "--sink", command]).success();
+    assert_eq!(std::fs::read_dir(std::path::Path::new(&maildir).join("new")).unwrap().count(), 2);
+    assert_eq!(std::fs::read_to_string(env.path("kinds.out")).unwrap(), "contract_expiring\nlow_availability\n");
+    assert_eq!(std::fs::read_to_string(env.path("hook.out")).unwrap().lines().count(), 2);
+}
+
+#[test]
+fn test_notify_check_sink_errors() {
+    let env = notify_env();
+    run_in_dir(&env, &["notify", "check", "--all", "--sink", "command:exit 3"]).code(6);
+    env.run(&["notify", "check", "--sink", "email:ops"]).code(2);
+
+    // webhook 不可用时以退出码6结束
+    let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let closed_url = format!("webhook:http://{}/hook", closed.local_addr().unwrap());
+    drop(closed);
+    env.run(&["notify", "check", "--all", "--sink", &closed_url]).code(6).stderr(predicate::str::contains("发送失败"));
+}
+
+#[test]
+fn test_notify_check_webhook_sink() {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    let env = notify_env();
+    // 本地代替的 webhook 服务，接收两次 POST
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("webhook:http://{}/hook", listener.local_addr().unwrap());
+    let server = std::thread::spawn(move || {
+        let mut bodies = Vec::new();
+        for _ in 0..2 {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut length = 0;
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some(v) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    length = v.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            reader.get_mut().write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n").unwrap();
+            bodies.push((request_line, String::from_utf8(body).unwrap()));
+        }
+        bodies
+    });
+    env.run(&["notify", "check", "--all", "--sink", &url]).success();
+    let bodies = server.join().unwrap();
+    assert!(bodies[0].0.starts_with("POST /hook"));
+    let first: serde_json::Value = serde_json::from_str(&bodies[0].1).unwrap();
+    assert_eq!(first["kind"], "contract_expiring");
+    assert!(first["text"].as_str().unwrap().starts_with("[合同到期]"));
+}
+
+/// 三条资源；`rated` 时供应商 1 综合 4.50、记一起严重事故后为 4.00，供应商 2 为 3.00，供应商 3 未打分
+fn rating_env(rated: bool) -> TestEnv {
+    let env = TestEnv::new("");
+    env.run(&["add", "--contact", "张三", "--price", "1000"]).success();
+    env.run(&["add", "--contact", "李四", "--price", "900"]).success();
+    env.run(&["add", "--contact", "王五", "--price", "800"]).success();
+    if rated {
+        env.run(&["rating", "set", "1", "--delivery", "5", "--stability", "4", "--support", "5", "--price-honesty", "4"]).success();
+        env.run(&["rating", "set", "2", "--delivery", "3"]).success();
+        env.run(&["incident", "add", "1", "断网4小时", "--severity", "major"]).success();
+    }
+    env
+}
+
+#[test]
+fn test_rating_set() {
+    let env = rating_env(false);
+    // 没有评分时 query 不显示评分列
+    env.run(&["query"]).success().stdout(predicate::str::contains("评分").not());
+    env.run(&["rating", "set", "1", "--delivery", "5", "--stability", "4", "--support", "5", "--price-honesty", "4"])
+        .success()
+        .stdout(predicate::str::contains("综合评分 4.50"));
+    env.run(&["rating", "set", "1", "--support", "0"]).code(3);
+    env.run(&["rating", "set", "9", "--support", "4"]).code(5);
+    env.run(&["rating", "list"]).success().stdout(predicate::str::starts_with("供应商 1\t交付 5"));
+}
+
+#[test]
+fn test_incident_add() {
+    let env = rating_env(false);
+    env.run(&["rating", "set", "1", "--delivery", "5", "--stability", "4", "--support", "5", "--price-honesty", "4"]).success();
+    env.run(&["incident", "add", "1", "断网4小时", "--severity", "major"])
+        .success()
+        .stdout(predicate::str::contains("综合评分 4.00"));
+    env.run(&["incident", "add", "1", "未来", "--on", "2999-01-01"]).code(3);
+    env.run(&["incident", "add", "1", "x", "--severity", "fatal"]).code(2);
+    env.run(&["incident", "add", "9", "断网"]).code(5);
+    env.run(&["incident", "list", "--supplier", "1"]).success().stdout(predicate::str::contains("严重\t断网4小时"));
+}
+
+#[test]
+fn test_rating_show_and_incident_remove() {
+    let env = rating_env(true);
+    env.run(&["rating", "show", "1"])
+        .success()
+        .stdout(predicate::str::contains("事故扣分\t0.50"))
+        .stdout(predicate::str::contains("综合评分\t4.00"))
+        .stdout(predicate::str::contains("断网4小时"));
+    env.run(&["rating", "show", "9"]).code(5);
+    env.run(&["incident", "remove", "1"]).success();
+    env.run(&["incident", "remove", "1"]).code(5);
+    env.run(&["rating", "show", "1", "--json"]).success().stdout(predicate::str::contains("\"score\": 4.5"));
+}
+
+#[test]
+fn test_query_score_column() {
+    let env = rating_env(true);
+    env.run(&["query", "--format", "csv"]).success().stdout(predicate::str::contains("组网,评分").and(predicate::str::contains(",4.00\n")));
+    env.run(&["query", "--columns", "id,contact", "--format", "csv"]).success().stdout(predicate::str::contains("评分").not());
+    env.run(&["query", "--filter", "score is null", "--json"]).success().stdout(predicate::str::contains("王五").and(predicate::str::contains("score").not()));
+}
+
+#[test]
+fn test_query_sort_and_filter_by_score() {
+    let env = rating_env(true);
+    env.run(&["query", "--sort", "score:desc", "--columns", "id", "--format", "csv"]).success().stdout("ID\n1\n2\n3\n");
+    env.run(&["query", "--score-min", "3.5", "--columns", "id,score", "--format", "csv"]).success().stdout("ID,评分\n1,4.00\n");
+    env.run(&["query", "--score-min", "4", "--score-max", "3"]).code(3);
+}
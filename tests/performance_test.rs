@@ -8,8 +8,7 @@ use std::path::Path;
 // 获取正确的二进制名称
 #[allow(dead_code)]
 fn get_bin_name() -> String {
-    let manifest = env::var("CARGO_PKG_NAME").unwrap_or_else(|_| "wei-assistant-gpu".to_string());
-    manifest
+    env::var("CARGO_PKG_NAME").unwrap_or_else(|_| "wei-assistant-gpu".to_string())
 }
 
 // 添加互斥锁确保性能测试不会与其他测试冲突
@@ -162,7 +161,7 @@ fn batch_insert_suppliers(db_path: &str, count: usize) -> RusqliteResult<Duratio
             _ => "按量付费",
         };
         
-        stmt.execute(&[
+        stmt.execute([
             &format!("供应商{}", i) as &dyn rusqlite::ToSql,
             &format!("wx{}", i) as &dyn rusqlite::ToSql,
             &format!("1380013{:04}", i) as &dyn rusqlite::ToSql,
@@ -199,7 +198,7 @@ fn test_query_performance(db_path: &str) -> RusqliteResult<(Duration, usize)> {
     
     // 执行查询
     let mut stmt = conn.prepare("SELECT * FROM suppliers WHERE location = ?")?;
-    let mut rows = stmt.query(&[&"北京" as &dyn rusqlite::ToSql])?;
+    let mut rows = stmt.query([&"北京" as &dyn rusqlite::ToSql])?;
     
     // 收集结果
     let mut results = Vec::new();
@@ -249,7 +248,7 @@ fn test_complex_query_performance(db_path: &str) -> RusqliteResult<(Duration, us
     let price_param = 500.0;
     let model_param = "包年";
     
-    let mut rows = stmt.query(&[&price_param as &dyn rusqlite::ToSql, 
+    let mut rows = stmt.query([&price_param as &dyn rusqlite::ToSql, 
                                &model_param as &dyn rusqlite::ToSql])?;
     
     // 收集结果
@@ -0,0 +1,872 @@
+use wei_assistant_gpu::catalog;
+use wei_assistant_gpu::contract::{ContractStatus, NewContract};
+use wei_assistant_gpu::dates::Date;
+use wei_assistant_gpu::notify::{self, NotifyRules};
+use wei_assistant_gpu::payment::{Payment, PaymentCadence};
+use wei_assistant_gpu::pricing::{self, MarkupKind, PriceQuote, PricingRule};
+use wei_assistant_gpu::quotation::{QuoteFormat, QuoteRequest};
+use wei_assistant_gpu::rating::{self, IncidentSeverity, NewIncident, RatingUpdate};
+use wei_assistant_gpu::store::list_snapshots;
+use wei_assistant_gpu::auth::Role;
+use wei_assistant_gpu::{parse_filter, ComparisonOp, Condition, Error, FilterCriteria, MatchMode, QueryBuilder, Supplier, SupplierField, SupplierRow, SupplierStore, SCHEMA_VERSION};
+
+fn sample(contact: &str, location: &str, price: f64) -> Supplier {
+    Supplier {
+        contact: Some(contact.to_string()),
+        location: Some(location.to_string()),
+        price: Some(price),
+        quantity: Some(8),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_store_crud() {
+    let store = SupplierStore::open_in_memory().unwrap();
+    let id = store.add(&sample("张三", "北京", 1200.0)).unwrap();
+    store.add(&sample("李四", "上海", 1500.0)).unwrap();
+
+    let row = store.get(id).unwrap();
+    assert_eq!(row.contact.as_deref(), Some("张三"));
+    assert_eq!(row.price, Some(1200.0));
+
+    let changes = Supplier { price: Some(1100.0), ..Default::default() };
+    store.update(id, &changes).unwrap();
+    let row = store.get(id).unwrap();
+    assert_eq!(row.price, Some(1100.0));
+    assert_eq!(row.location.as_deref(), Some("北京"), "未提供的字段不应被修改");
+
+    store.delete(id).unwrap();
+    assert!(matches!(store.get(id), Err(Error::NotFound(_))));
+    assert!(matches!(store.delete(id), Err(Error::NotFound(_))));
+    assert!(matches!(store.add(&Supplier::default()), Err(Error::Validation(_))));
+}
+
+#[test]
+fn test_store_query_and_export() {
+    let store = SupplierStore::open_in_memory().unwrap();
+    store.add(&sample("张三", "北京", 1200.0)).unwrap();
+    store.add(&sample("李四", "上海", 1500.0)).unwrap();
+    store.add(&sample("王五", "北京", 1800.0)).unwrap();
+
+    let query = || {
+        QueryBuilder::new()
+            .filter(FilterCriteria::from_str_tuple("location", "=", Some("北京")).unwrap())
+            .filter(FilterCriteria::from_str_tuple("price", "<", Some("1500")).unwrap())
+    };
+    let rows = store.query(query()).unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].contact.as_deref(), Some("张三"));
+
+    // 字段与操作符不兼容时返回校验错误
+    let invalid = QueryBuilder::new().filter(FilterCriteria {
+        field: SupplierField::Price,
+        op: ComparisonOp::Like,
+        value: Some("1%".to_string()),
+        mode: MatchMode::Exact,
+    });
+    assert!(matches!(store.query(invalid), Err(Error::Validation(_))));
+
+    let dir = tempfile::tempdir().unwrap();
+    let csv_path = dir.path().join("out.csv");
+    let count = store
+        .export_csv(query(), csv_path.to_str().unwrap(), &Default::default())
+        .unwrap();
+    assert_eq!(count, 1);
+    assert!(std::fs::read_to_string(&csv_path).unwrap().contains("张三"));
+
+    let xlsx_path = dir.path().join("out.xlsx");
+    let count = store.export_xlsx(QueryBuilder::new(), &[], xlsx_path.to_str().unwrap(), false).unwrap();
+    assert_eq!(count, 3);
+}
+
+/// 按地点和匹配方式查询，返回联系人
+fn contacts_at(store: &SupplierStore, location: &str, mode: MatchMode) -> Vec<String> {
+    let criteria = FilterCriteria::from_str_tuple("location", "=", Some(location)).unwrap().with_mode(mode);
+    store.query(QueryBuilder::new().filter(criteria)).unwrap().into_iter().filter_map(|r| r.contact).collect()
+}
+
+#[test]
+fn test_store_synonyms_match_whole_values_only() {
+    let store = SupplierStore::open_in_memory().unwrap();
+    for (contact, location) in [("张三", "北京市朝阳区"), ("李四", "Beijing"), ("王五", "南京"), ("赵六", "Shenzhen"), ("钱七", "上海")] {
+        store.add(&sample(contact, location, 1000.0)).unwrap();
+    }
+    // 关键字本身按子串匹配，同义词只按整值匹配；“京”“SH”等短别名不做子串匹配
+    assert_eq!(contacts_at(&store, "北京", MatchMode::Contains), ["张三", "李四"]);
+    assert_eq!(contacts_at(&store, "上海", MatchMode::Contains), ["钱七"]);
+    assert_eq!(contacts_at(&store, "BJ", MatchMode::Prefix), ["李四"]);
+    assert_eq!(contacts_at(&store, "北京", MatchMode::Fuzzy), ["李四"]);
+    assert_eq!(contacts_at(&store, "sh", MatchMode::Icase), ["钱七"]);
+}
+
+#[test]
+fn test_store_saved_view_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("views.db");
+    let store = SupplierStore::open(&path).unwrap();
+    for (contact, location, price) in [("张三", "北京", 1200.0), ("李四", "北京", 900.0), ("王五", "上海", 800.0)] {
+        store.add(&sample(contact, location, price)).unwrap();
+    }
+    let criteria = FilterCriteria::from_str_tuple("location", "=", Some("北京")).unwrap();
+    let view = QueryBuilder::new().filter(criteria).order_by("price:desc".parse().unwrap()).into_view(vec!["contact".parse().unwrap()]);
+    store.save_view("bj", &view).unwrap();
+    let loaded = store.get_view("bj").unwrap();
+    assert_eq!(loaded.to_string(), "--filter \"location = 北京\" --sort price:desc --columns contact");
+
+    // 执行时追加的条件与视图叠加，未指定排序时用视图的排序
+    let extra = FilterCriteria::from_str_tuple("price", "<", Some("1500")).unwrap();
+    let rows = store.query(loaded.apply(QueryBuilder::new().filter(extra))).unwrap();
+    let contacts: Vec<_> = rows.into_iter().filter_map(|r| r.contact).collect();
+    assert_eq!(contacts, ["张三", "李四"]);
+
+    // 旧版本保存的命令行参数数组需要重新保存
+    drop(store);
+    rusqlite::Connection::open(&path).unwrap()
+        .execute("INSERT INTO views (name, args) VALUES ('old', '[\"--location\",\"北京\"]')", [])
+        .unwrap();
+    let store = SupplierStore::open(&path).unwrap();
+    assert!(matches!(store.get_view("old"), Err(Error::Validation(msg)) if msg.contains("重新保存")));
+}
+
+#[test]
+fn test_store_export_xlsx_columns() {
+    use calamine::{open_workbook_auto, Data, Reader};
+
+    let store = SupplierStore::open_in_memory().unwrap();
+    let id = store.add(&sample("张三", "北京", 1000.0)).unwrap();
+    store.set_rating(id, &RatingUpdate { delivery: Some(4), ..Default::default() }).unwrap();
+
+    // 只导出所选列，评分等计算列也可选
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("columns.xlsx");
+    let columns = ["contact".parse().unwrap(), "score".parse().unwrap()];
+    store.export_xlsx(QueryBuilder::new(), &columns, path.to_str().unwrap(), false).unwrap();
+    let mut workbook = open_workbook_auto(&path).unwrap();
+    let range = workbook.worksheet_range_at(0).unwrap().unwrap();
+    let rows: Vec<Vec<Data>> = range.rows().map(|r| r.to_vec()).collect();
+    assert_eq!(rows[0], vec![Data::String("联系人".to_string()), Data::String("评分".to_string())]);
+    assert_eq!(rows[1], vec![Data::String("张三".to_string()), Data::Float(4.0)]);
+}
+
+#[test]
+fn test_store_add_many_is_atomic() {
+    let store = SupplierStore::open_in_memory().unwrap();
+    let ids = store.add_many(&[sample("张三", "北京", 1200.0), sample("李四", "上海", 1500.0)]).unwrap();
+    assert_eq!(ids.len(), 2);
+
+    // 中间一条缺少联系人，整批回滚
+    let batch = [sample("王五", "北京", 1800.0), Supplier::default(), sample("赵六", "广州", 900.0)];
+    assert!(matches!(store.add_many(&batch), Err(Error::Validation(_))));
+    assert_eq!(store.query(QueryBuilder::new()).unwrap().len(), 2);
+}
+
+#[test]
+fn test_store_concurrent_writers() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("shared.db");
+    SupplierStore::open(&path).unwrap();
+
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    let mode: String = conn.query_row("PRAGMA journal_mode", [], |row| row.get(0)).unwrap();
+    assert_eq!(mode.to_lowercase(), "wal");
+
+    // 多个进程/线程各自打开连接同时写入，依靠忙等待而不是报 database is locked
+    let handles: Vec<_> = (0..4)
+        .map(|t| {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                let store = SupplierStore::open(&path).unwrap();
+                let batch: Vec<_> = (0..50).map(|i| sample(&format!("并发{}-{}", t, i), "北京", 1000.0)).collect();
+                store.add_many(&batch).unwrap();
+            })
+        })
+        .collect();
+    for h in handles {
+        h.join().unwrap();
+    }
+    let store = SupplierStore::open(&path).unwrap();
+    assert_eq!(store.query(QueryBuilder::new()).unwrap().len(), 200);
+}
+
+#[test]
+fn test_store_backup_and_restore() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut store = SupplierStore::open(dir.path().join("main.db")).unwrap();
+    assert_eq!(store.schema_version().unwrap(), SCHEMA_VERSION);
+    store.add(&sample("张三", "北京", 1200.0)).unwrap();
+
+    let backup = dir.path().join("backup.db");
+    store.backup(&backup).unwrap();
+    store.add(&sample("李四", "上海", 1500.0)).unwrap();
+    store.restore(&backup).unwrap();
+    let rows = store.query(QueryBuilder::new()).unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].contact.as_deref(), Some("张三"));
+    assert!(store.integrity_check().unwrap().is_empty());
+}
+
+#[test]
+fn test_store_restore_rejects_newer_schema() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut store = SupplierStore::open(dir.path().join("main.db")).unwrap();
+    let newer = dir.path().join("newer.db");
+    store.backup(&newer).unwrap();
+    rusqlite::Connection::open(&newer).unwrap().pragma_update(None, "user_version", SCHEMA_VERSION + 1).unwrap();
+    assert!(matches!(store.restore(&newer), Err(Error::Validation(_))));
+}
+
+#[test]
+fn test_store_restore_rejects_foreign_database() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut store = SupplierStore::open(dir.path().join("main.db")).unwrap();
+    let other = dir.path().join("other.db");
+    rusqlite::Connection::open(&other).unwrap().execute_batch("CREATE TABLE t (x)").unwrap();
+    assert!(matches!(store.restore(&other), Err(Error::Validation(_))));
+    assert!(matches!(store.restore(dir.path().join("missing.db")), Err(Error::NotFound(_))));
+}
+
+#[test]
+fn test_store_snapshot_retention() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = SupplierStore::open(dir.path().join("main.db")).unwrap();
+    let snapshot_dir = dir.path().join("snapshots");
+    for _ in 0..3 {
+        store.snapshot(&snapshot_dir, "delete", 2).unwrap();
+    }
+    let snapshots = list_snapshots(&snapshot_dir).unwrap();
+    assert_eq!(snapshots.len(), 2);
+    assert!(snapshots[0] < snapshots[1]);
+    assert!(snapshots[1].to_str().unwrap().ends_with("-delete.db"));
+}
+
+/// 在 `dir` 中建立两条记录并以“口令一”加密的数据库，返回路径
+fn encrypted_db(dir: &tempfile::TempDir) -> std::path::PathBuf {
+    let path = dir.path().join("secret.db");
+    let mut store = SupplierStore::open(&path).unwrap();
+    let mut supplier = sample("张三", "北京", 1200.0);
+    supplier.phone = Some("13800000000".to_string());
+    store.add(&supplier).unwrap();
+    store.encrypt("口令一").unwrap();
+    assert!(store.is_encrypted());
+    store.add(&sample("李四", "上海", 1500.0)).unwrap();
+    path
+}
+
+#[test]
+fn test_store_encryption_stores_ciphertext() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = encrypted_db(&dir);
+    let raw: Vec<String> = rusqlite::Connection::open(&path).unwrap()
+        .prepare("SELECT contact FROM suppliers").unwrap()
+        .query_map([], |row| row.get(0)).unwrap()
+        .collect::<rusqlite::Result<_>>().unwrap();
+    assert_eq!(raw.len(), 2);
+    assert!(raw.iter().all(|c| c.starts_with("enc:")));
+}
+
+#[test]
+fn test_store_encrypt_twice_is_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut store = SupplierStore::open(encrypted_db(&dir)).unwrap();
+    store.unlock("口令一").unwrap();
+    assert!(matches!(store.encrypt("口令一"), Err(Error::Validation(_))));
+}
+
+#[test]
+fn test_store_locked_rejects_reads_and_writes() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut locked = SupplierStore::open(encrypted_db(&dir)).unwrap();
+    assert!(matches!(locked.query(QueryBuilder::new()), Err(Error::Validation(_))));
+    assert!(matches!(locked.add(&sample("王五", "广州", 1.0)), Err(Error::Validation(_))));
+    assert!(matches!(locked.unlock("错误口令"), Err(Error::Validation(_))));
+}
+
+#[test]
+fn test_store_unlocked_filters_on_plaintext() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut store = SupplierStore::open(encrypted_db(&dir)).unwrap();
+    store.unlock("口令一").unwrap();
+    let criteria = FilterCriteria::from_str_tuple("contact", "=", Some("李四")).unwrap();
+    let rows = store.query(QueryBuilder::new().filter(criteria)).unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].id, 2);
+    assert_eq!(store.get(1).unwrap().phone.as_deref(), Some("13800000000"));
+}
+
+#[test]
+fn test_store_rekey() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = encrypted_db(&dir);
+    let mut store = SupplierStore::open(&path).unwrap();
+    store.unlock("口令一").unwrap();
+    store.rekey("口令二").unwrap();
+    let mut reopened = SupplierStore::open(&path).unwrap();
+    assert!(reopened.unlock("口令一").is_err());
+    reopened.unlock("口令二").unwrap();
+    assert_eq!(reopened.get(1).unwrap().contact.as_deref(), Some("张三"));
+}
+
+#[test]
+fn test_store_decrypt_restores_plaintext() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = encrypted_db(&dir);
+    let mut store = SupplierStore::open(&path).unwrap();
+    store.unlock("口令一").unwrap();
+    store.decrypt().unwrap();
+    assert!(!store.is_encrypted());
+    let contact: String = rusqlite::Connection::open(&path).unwrap()
+        .query_row("SELECT contact FROM suppliers WHERE id = 1", [], |row| row.get(0)).unwrap();
+    assert_eq!(contact, "张三");
+}
+
+#[test]
+fn test_store_plaintext_with_ciphertext_prefix() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("plain.db");
+    let mut store = SupplierStore::open(&path).unwrap();
+    // 未加密的数据库中，以 enc: 开头的值也是明文，不会使整个数据库无法读取
+    let id = store.add(&Supplier { wechat: Some("enc:wx123".to_string()), ..sample("张三", "北京", 1200.0) }).unwrap();
+    assert_eq!(store.query(QueryBuilder::new()).unwrap()[0].wechat.as_deref(), Some("enc:wx123"));
+    drop(store);
+    store = SupplierStore::open(&path).unwrap();
+    assert_eq!(store.get(id).unwrap().wechat.as_deref(), Some("enc:wx123"));
+
+    // 加密、解密前后取值不变
+    store.encrypt("口令一").unwrap();
+    let mut reopened = SupplierStore::open(&path).unwrap();
+    reopened.unlock("口令一").unwrap();
+    assert_eq!(reopened.get(id).unwrap().wechat.as_deref(), Some("enc:wx123"));
+    reopened.decrypt().unwrap();
+    assert_eq!(SupplierStore::open(&path).unwrap().get(id).unwrap().wechat.as_deref(), Some("enc:wx123"));
+}
+
+/// 管理员 boss 和录入员 clerk
+fn store_with_users() -> SupplierStore {
+    let store = SupplierStore::open_in_memory().unwrap();
+    store.add_user("boss", Role::Admin, "password1").unwrap();
+    store.add_user("clerk", Role::Editor, "password2").unwrap();
+    store
+}
+
+#[test]
+fn test_store_first_user_must_be_admin() {
+    let store = SupplierStore::open_in_memory().unwrap();
+    assert!(!store.has_users().unwrap());
+    assert!(matches!(store.add_user("clerk", Role::Editor, "password1"), Err(Error::Validation(_))));
+    store.add_user("boss", Role::Admin, "password1").unwrap();
+    assert!(store.has_users().unwrap());
+}
+
+#[test]
+fn test_store_add_user_validation() {
+    let store = store_with_users();
+    assert!(matches!(store.add_user("newbie", Role::Editor, "short"), Err(Error::Validation(_))), "口令过短");
+    assert!(matches!(store.add_user("clerk", Role::Viewer, "password3"), Err(Error::Validation(_))), "用户名重复");
+}
+
+#[test]
+fn test_store_password_authentication() {
+    let store = store_with_users();
+    assert_eq!(store.authenticate_password("clerk", "password2").unwrap().role, Role::Editor);
+    assert!(matches!(store.authenticate_password("clerk", "password1"), Err(Error::PermissionDenied(_))));
+    assert!(matches!(store.authenticate_password("nobody", "password1"), Err(Error::PermissionDenied(_))));
+}
+
+#[test]
+fn test_store_token_issue_and_revoke() {
+    let store = store_with_users();
+    let token = store.issue_token("clerk").unwrap();
+    assert_eq!(store.authenticate_token(&token).unwrap().name, "clerk");
+    store.revoke_token("clerk").unwrap();
+    assert!(matches!(store.authenticate_token(&token), Err(Error::PermissionDenied(_))));
+}
+
+#[test]
+fn test_store_last_admin_is_protected() {
+    // 不能去掉最后一个 admin，但可以删除最后一个用户
+    let store = store_with_users();
+    assert!(matches!(store.set_user_role("boss", Role::Viewer), Err(Error::Validation(_))));
+    assert!(matches!(store.remove_user("boss"), Err(Error::Validation(_))));
+    store.remove_user("clerk").unwrap();
+    store.remove_user("boss").unwrap();
+    assert!(!store.has_users().unwrap());
+}
+
+#[test]
+fn test_store_offer_codes_are_stable() {
+    let store = SupplierStore::open_in_memory().unwrap();
+    let a = store.add(&sample("张三", "北京", 1000.0)).unwrap();
+    let b = store.add(&sample("李四", "上海", 1200.5)).unwrap();
+
+    let codes = store.offer_codes(&[a, b]).unwrap();
+    assert!(codes[&a].starts_with(catalog::OFFER_CODE_PREFIX));
+    assert_ne!(codes[&a], codes[&b]);
+    assert_eq!(store.offer_codes(&[a]).unwrap()[&a], codes[&a], "再次发布时编号不变");
+}
+
+#[test]
+fn test_store_lookup_offer() {
+    let store = SupplierStore::open_in_memory().unwrap();
+    let id = store.add(&sample("李四", "上海", 1200.5)).unwrap();
+    let code = store.offer_codes(&[id]).unwrap().remove(&id).unwrap();
+    assert_eq!(store.lookup_offer(&code.to_lowercase()).unwrap().contact.as_deref(), Some("李四"));
+    assert!(matches!(store.lookup_offer("OF-NOTEXIST"), Err(Error::NotFound(_))));
+}
+
+#[test]
+fn test_store_lookup_offer_of_deleted_supplier() {
+    let store = SupplierStore::open_in_memory().unwrap();
+    let id = store.add(&sample("李四", "上海", 1200.5)).unwrap();
+    let code = store.offer_codes(&[id]).unwrap().remove(&id).unwrap();
+    store.delete(id).unwrap();
+    assert!(matches!(store.lookup_offer(&code), Err(Error::NotFound(_))));
+}
+
+fn pricing_rule(location: Option<&str>, kind: MarkupKind, value: f64, min_margin: Option<f64>) -> PricingRule {
+    PricingRule {
+        id: 0,
+        location: location.map(str::to_string),
+        networking_category: None,
+        rental_model: None,
+        kind,
+        value,
+        min_margin,
+    }
+}
+
+#[test]
+fn test_store_add_pricing_rule_validates() {
+    let store = SupplierStore::open_in_memory().unwrap();
+    assert!(matches!(store.add_pricing_rule(&pricing_rule(None, MarkupKind::Percent, -1.0, None)), Err(Error::Validation(_))));
+    let id = store.add_pricing_rule(&pricing_rule(None, MarkupKind::Percent, 10.0, None)).unwrap();
+    assert_eq!(store.pricing_rules().unwrap().iter().map(|r| r.id).collect::<Vec<_>>(), [id]);
+}
+
+#[test]
+fn test_store_pricing_rule_for_supplier() {
+    // 地点按同义词匹配，条件更多的规则优先
+    let store = SupplierStore::open_in_memory().unwrap();
+    let beijing = store.add(&sample("张三", "北京", 1000.0)).unwrap();
+    let shanghai = store.add(&sample("李四", "上海", 1200.5)).unwrap();
+    let general = store.add_pricing_rule(&pricing_rule(None, MarkupKind::Percent, 10.0, None)).unwrap();
+    let specific = store.add_pricing_rule(&pricing_rule(Some("Beijing"), MarkupKind::Fixed, 200.0, Some(300.0))).unwrap();
+
+    assert_eq!(store.pricing_rule_for(&store.get(beijing).unwrap()).unwrap().map(|r| r.id), Some(specific));
+    let row = store.get(shanghai).unwrap();
+    let rule = store.pricing_rule_for(&row).unwrap();
+    assert_eq!(rule.as_ref().map(|r| r.id), Some(general));
+    let quote = pricing::quote(&row, 2, rule.as_ref()).unwrap();
+    assert_eq!((quote.total_cost, quote.total_price), (2401.0, 2641.1));
+}
+
+#[test]
+fn test_store_remove_pricing_rule() {
+    let store = SupplierStore::open_in_memory().unwrap();
+    let id = store.add_pricing_rule(&pricing_rule(None, MarkupKind::Percent, 10.0, None)).unwrap();
+    store.remove_pricing_rule(id).unwrap();
+    assert!(matches!(store.remove_pricing_rule(id), Err(Error::NotFound(_))));
+    assert_eq!(store.pricing_rule_for(&sample("张三", "北京", 1.0).to_row(1)).unwrap(), None);
+}
+
+#[test]
+fn test_store_find_offer() {
+    let store = SupplierStore::open_in_memory().unwrap();
+    let id = store.add(&sample("李四", "上海", 1200.5)).unwrap();
+    let code = store.offer_codes(&[id]).unwrap().remove(&id).unwrap();
+    assert_eq!(store.find_offer(&id.to_string()).unwrap().id, id);
+    assert_eq!(store.find_offer(&code).unwrap().id, id);
+    assert!(matches!(store.find_offer("abc"), Err(Error::Validation(_))));
+    assert!(matches!(store.find_offer("42"), Err(Error::NotFound(_))));
+}
+
+/// 一条月付的北京资源，返回记录和 2 台的成本价报价
+fn quote_fixture(store: &SupplierStore) -> (SupplierRow, PriceQuote) {
+    let id = store
+        .add(&Supplier { payment_terms: Some("月付".to_string()), phone: Some("13812348000".to_string()), ..sample("张三", "北京", 1000.0) })
+        .unwrap();
+    let row = store.get(id).unwrap();
+    let price = pricing::quote(&row, 2, None).unwrap();
+    (row, price)
+}
+
+const QUOTE_REQUEST: QuoteRequest<'static> = QuoteRequest { customer: "某客户", months: 3, valid_days: 7, notes: None };
+
+#[test]
+fn test_store_quote_numbers_increase_per_day() {
+    let store = SupplierStore::open_in_memory().unwrap();
+    let (row, price) = quote_fixture(&store);
+    let first = store.create_quote(&row, &price, &QUOTE_REQUEST).unwrap();
+    let second = store.create_quote(&row, &price, &QUOTE_REQUEST).unwrap();
+    assert!(first.quote_no.starts_with('Q') && first.quote_no.ends_with("-001"));
+    assert!(second.quote_no.ends_with("-002"));
+    assert_eq!(store.list_quotes().unwrap().len(), 2);
+}
+
+#[test]
+fn test_store_create_quote_uses_offer_code() {
+    let store = SupplierStore::open_in_memory().unwrap();
+    let (row, price) = quote_fixture(&store);
+    let quote = store.create_quote(&row, &price, &QUOTE_REQUEST).unwrap();
+    assert_eq!((quote.monthly_total, quote.total, quote.total_cost), (2000.0, 6000.0, 6000.0));
+    assert_eq!(quote.offer_code, store.offer_codes(&[row.id]).unwrap()[&row.id]);
+    let markdown = quote.render(QuoteFormat::Markdown.builtin_template(), QuoteFormat::Markdown, Some("CNY")).unwrap();
+    assert!(markdown.contains("| 服务器 | 1000.00 | 2 | 2000.00 | 6000.00 |"));
+}
+
+#[test]
+fn test_store_create_quote_validation_saves_nothing() {
+    let store = SupplierStore::open_in_memory().unwrap();
+    let (row, price) = quote_fixture(&store);
+    assert!(matches!(store.create_quote(&row, &price, &QuoteRequest { months: 0, ..QUOTE_REQUEST }), Err(Error::Validation(_))));
+    assert!(store.list_quotes().unwrap().is_empty());
+}
+
+#[test]
+fn test_store_get_quote() {
+    let store = SupplierStore::open_in_memory().unwrap();
+    let (row, price) = quote_fixture(&store);
+    let quote = store.create_quote(&row, &price, &QUOTE_REQUEST).unwrap();
+    assert_eq!(store.get_quote(&quote.quote_no.to_lowercase()).unwrap(), quote);
+    assert!(matches!(store.get_quote("Q19700101-001"), Err(Error::NotFound(_))));
+}
+
+fn date(s: &str) -> Date {
+    s.parse().unwrap()
+}
+
+/// 2024-01-01 至 2024-07-01，4 台 × 1000，违约金为剩余租金的一半
+fn new_contract() -> NewContract {
+    NewContract {
+        supplier_id: 1,
+        quote_no: None,
+        quantity: 4,
+        agreed_price: 1000.0,
+        start_date: date("2024-01-01"),
+        end_date: date("2024-07-01"),
+        min_contract_period: Some("6个月".to_string()),
+        payment_terms: Some("月付".to_string()),
+        breach_penalties: Some("剩余租金的50%".to_string()),
+    }
+}
+
+#[test]
+fn test_store_create_contract() {
+    let store = SupplierStore::open_in_memory().unwrap();
+    assert!(matches!(store.create_contract(&NewContract { end_date: date("2024-01-01"), ..new_contract() }), Err(Error::Validation(_))));
+    assert_eq!(store.list_contracts(None).unwrap().len(), 0);
+
+    let id = store.create_contract(&new_contract()).unwrap();
+    let contract = store.get_contract(id).unwrap();
+    assert_eq!((contract.status, contract.renewals, contract.breach_penalties.as_deref()), (ContractStatus::Active, 0, Some("剩余租金的50%")));
+    assert!(matches!(store.get_contract(id + 1), Err(Error::NotFound(_))));
+}
+
+#[test]
+fn test_store_contract_termination_estimate() {
+    let store = SupplierStore::open_in_memory().unwrap();
+    let id = store.create_contract(&new_contract()).unwrap();
+    // 3月10日终止：剩余不足4个月按4个月计，违约金为剩余租金的一半
+    let estimate = store.get_contract(id).unwrap().termination_estimate(date("2024-03-10"));
+    assert_eq!((estimate.remaining_months, estimate.remaining_rent, estimate.penalty), (4, 16000.0, Some(8000.0)));
+}
+
+#[test]
+fn test_store_renew_contract() {
+    let store = SupplierStore::open_in_memory().unwrap();
+    let id = store.create_contract(&new_contract()).unwrap();
+    let renewed = store.renew_contract(id, 3, Some(900.0)).unwrap();
+    assert_eq!((renewed.end_date, renewed.agreed_price, renewed.renewals), (date("2024-10-01"), 900.0, 1));
+    let renewed = store.renew_contract(id, 1, None).unwrap();
+    assert_eq!((renewed.end_date, renewed.agreed_price, renewed.renewals), (date("2024-11-01"), 900.0, 2));
+}
+
+#[test]
+fn test_store_terminate_contract() {
+    let store = SupplierStore::open_in_memory().unwrap();
+    let id = store.create_contract(&new_contract()).unwrap();
+    assert!(matches!(store.terminate_contract(id, date("2023-12-31")), Err(Error::Validation(_))));
+    let terminated = store.terminate_contract(id, date("2024-05-10")).unwrap();
+    assert_eq!(terminated.status, ContractStatus::Terminated);
+    assert_eq!(terminated.terminated_on, Some(date("2024-05-10")));
+    assert_eq!(terminated.termination_cost, Some(4000.0));
+
+    // 已终止的合同不能再续约或终止
+    assert!(matches!(store.renew_contract(id, 1, None), Err(Error::Validation(_))));
+    assert!(matches!(store.terminate_contract(id, date("2024-06-01")), Err(Error::Validation(_))));
+    assert_eq!(store.list_contracts(Some(ContractStatus::Active)).unwrap().len(), 0);
+    assert_eq!(store.list_contracts(Some(ContractStatus::Terminated)).unwrap().len(), 1);
+}
+
+/// 供应商 7 的季付合同，2024-01-31 至 2024-08-31，月租 2000
+fn payment_contract() -> NewContract {
+    NewContract {
+        supplier_id: 7,
+        quote_no: None,
+        quantity: 2,
+        agreed_price: 1000.0,
+        start_date: date("2024-01-31"),
+        end_date: date("2024-08-31"),
+        min_contract_period: None,
+        payment_terms: Some("季付".to_string()),
+        breach_penalties: None,
+    }
+}
+
+/// 签订 [`payment_contract`] 并生成付款计划，返回合同 ID 和各期付款
+fn store_with_payments() -> (SupplierStore, i64, Vec<Payment>) {
+    let store = SupplierStore::open_in_memory().unwrap();
+    let id = store.create_contract(&payment_contract()).unwrap();
+    store.generate_payments(id, None).unwrap();
+    let payments = store.list_payments(Some(id)).unwrap();
+    (store, id, payments)
+}
+
+#[test]
+fn test_store_generate_payments() {
+    let store = SupplierStore::open_in_memory().unwrap();
+    let id = store.create_contract(&payment_contract()).unwrap();
+    // 分界按开始日期加整月计算，最后一期只有1个月
+    assert_eq!(store.generate_payments(id, None).unwrap(), 3);
+    let payments = store.list_payments(Some(id)).unwrap();
+    let periods: Vec<(String, f64)> = payments.iter().map(|p| (p.due_date.to_string(), p.amount)).collect();
+    assert_eq!(periods, [("2024-01-31".to_string(), 6000.0), ("2024-04-30".to_string(), 6000.0), ("2024-07-31".to_string(), 2000.0)]);
+    assert!(payments.iter().all(|p| p.supplier_id == 7 && p.cadence == PaymentCadence::Quarterly && p.paid_on.is_none()));
+    // 已生成的各期不重复生成
+    assert_eq!(store.generate_payments(id, None).unwrap(), 0);
+}
+
+#[test]
+fn test_store_generate_payments_unknown_terms() {
+    let store = SupplierStore::open_in_memory().unwrap();
+    let unknown = store.create_contract(&NewContract { payment_terms: Some("面议".to_string()), ..payment_contract() }).unwrap();
+    assert!(matches!(store.generate_payments(unknown, None), Err(Error::Validation(_))));
+    assert_eq!(store.generate_payments(unknown, Some(PaymentCadence::Upfront)).unwrap(), 1);
+    assert_eq!(store.list_payments(Some(unknown)).unwrap()[0].amount, 14000.0);
+}
+
+#[test]
+fn test_store_mark_payment_paid() {
+    let (store, id, payments) = store_with_payments();
+    store.mark_payment_paid(payments[0].id, date("2024-01-30")).unwrap();
+    assert_eq!(store.list_payments(Some(id)).unwrap()[0].paid_on, Some(date("2024-01-30")));
+    assert!(matches!(store.mark_payment_paid(payments[0].id, date("2024-01-31")), Err(Error::Validation(_))));
+    assert!(matches!(store.mark_payment_paid(99, date("2024-01-31")), Err(Error::NotFound(_))));
+}
+
+#[test]
+fn test_store_payments_due_and_balances() {
+    let (store, _, payments) = store_with_payments();
+    store.mark_payment_paid(payments[0].id, date("2024-01-30")).unwrap();
+    let due = store.payments_due(date("2024-05-10")).unwrap();
+    assert_eq!(due.iter().map(|p| p.id).collect::<Vec<_>>(), [payments[1].id]);
+    let balances = store.outstanding_balances(date("2024-05-10")).unwrap();
+    assert_eq!(balances.len(), 1);
+    assert_eq!((balances[0].supplier_id, balances[0].due, balances[0].overdue, balances[0].upcoming), (7, 6000.0, 6000.0, 2000.0));
+    assert_eq!(balances[0].next_due_date, Some(date("2024-04-30")));
+}
+
+#[test]
+fn test_store_payments_follow_renewal_and_termination() {
+    let (store, id, _) = store_with_payments();
+    // 续约后接着最后一期继续；终止日之后开始的未付各期被删除，已开始的一期保留
+    store.renew_contract(id, 6, None).unwrap();
+    assert_eq!(store.generate_payments(id, None).unwrap(), 2);
+    store.terminate_contract(id, date("2024-09-15")).unwrap();
+    let remaining: Vec<String> = store.list_payments(Some(id)).unwrap().iter().map(|p| p.due_date.to_string()).collect();
+    assert_eq!(remaining, ["2024-01-31", "2024-04-30", "2024-07-31", "2024-08-31"]);
+    assert!(matches!(store.generate_payments(id, None), Err(Error::Validation(_))));
+}
+
+/// 供应商 1 仅剩 1 台；供应商 2 有一份 10 天后到期的合同和一份 7 天有效的报价单
+fn notify_store() -> (SupplierStore, Date) {
+    let store = SupplierStore::open_in_memory().unwrap();
+    store.add(&Supplier { quantity: Some(1), ..sample("张三", "北京", 1000.0) }).unwrap();
+    let plenty = store.add(&Supplier { quantity: Some(20), ..sample("李四", "上海", 900.0) }).unwrap();
+    let today = store.today().unwrap();
+    let contract = NewContract { supplier_id: plenty, start_date: today.add_months(-6), end_date: today.add_days(10), ..new_contract() };
+    store.create_contract(&contract).unwrap();
+    let row = store.get(plenty).unwrap();
+    let price = pricing::quote(&row, 2, None).unwrap();
+    store.create_quote(&row, &price, &QuoteRequest { customer: "某客户", months: 3, valid_days: 7, notes: None }).unwrap();
+    (store, today)
+}
+
+#[test]
+fn test_store_notify_collect() {
+    let (store, today) = notify_store();
+    let rules = NotifyRules { contract_days: 14, low_quantity: 2 };
+    let kinds = |n: &[notify::Notification]| n.iter().map(|n| n.kind.name()).collect::<Vec<_>>();
+    assert_eq!(kinds(&notify::collect(&store, today, &rules).unwrap()), ["contract_expiring", "low_availability"]);
+    // 报价单过期后一并提醒，按类别排列
+    assert_eq!(kinds(&notify::collect(&store, today.add_days(8), &rules).unwrap()), ["contract_expiring", "quote_stale", "low_availability"]);
+    let strict = NotifyRules { contract_days: 7, low_quantity: 0 };
+    assert!(notify::collect(&store, today, &strict).unwrap().is_empty());
+}
+
+#[test]
+fn test_store_notifications_recorded_once() {
+    let (store, today) = notify_store();
+    let rules = NotifyRules::default();
+    let pending = store.unsent_notifications(notify::collect(&store, today, &rules).unwrap()).unwrap();
+    assert_eq!(pending.len(), 2);
+    store.record_notifications(&pending).unwrap();
+    store.record_notifications(&pending).unwrap();
+    assert!(store.unsent_notifications(notify::collect(&store, today, &rules).unwrap()).unwrap().is_empty());
+}
+
+#[test]
+fn test_store_notifications_resent_when_key_changes() {
+    let (store, today) = notify_store();
+    let rules = NotifyRules::default();
+    store.record_notifications(&notify::collect(&store, today, &rules).unwrap()).unwrap();
+    // 数量变化、续约后到期日改变时重新提醒
+    store.update(1, &Supplier { quantity: Some(0), ..Supplier::default() }).unwrap();
+    store.renew_contract(1, 1, None).unwrap();
+    let quote_no = store.list_quotes().unwrap()[0].quote_no.clone();
+    let pending = store.unsent_notifications(notify::collect(&store, today.add_days(30), &rules).unwrap()).unwrap();
+    let keys: Vec<String> = pending.iter().map(|n| n.key.clone()).collect();
+    assert_eq!(keys, [format!("contract_expiring:1:{}", today.add_days(10).add_months(1)), format!("quote_stale:{}", quote_no), "low_availability:1:0".to_string()]);
+}
+
+/// 三个供应商：1 打了交付 5、稳定性 4、支持 3，2 只打了价格诚信 3，3 未打分
+fn rated_store() -> SupplierStore {
+    let store = SupplierStore::open_in_memory().unwrap();
+    for (contact, location, price) in [("张三", "北京", 1000.0), ("李四", "上海", 900.0), ("王五", "北京", 800.0)] {
+        store.add(&sample(contact, location, price)).unwrap();
+    }
+    store.set_rating(1, &RatingUpdate { delivery: Some(5), stability: Some(4), support: Some(3), ..Default::default() }).unwrap();
+    store.set_rating(2, &RatingUpdate { price_honesty: Some(3), ..Default::default() }).unwrap();
+    store
+}
+
+fn incident(occurred_on: Date, severity: IncidentSeverity) -> NewIncident {
+    NewIncident { supplier_id: 1, occurred_on, severity, description: "断网".to_string() }
+}
+
+#[test]
+fn test_store_set_rating() {
+    let store = SupplierStore::open_in_memory().unwrap();
+    let id = store.add(&sample("张三", "北京", 1000.0)).unwrap();
+    assert!(matches!(store.set_rating(id, &RatingUpdate::default()), Err(Error::Validation(_))));
+    assert!(matches!(store.set_rating(id, &RatingUpdate { delivery: Some(6), ..Default::default() }), Err(Error::Validation(_))));
+    assert_eq!(store.get_rating(id).unwrap(), None);
+
+    store.set_rating(id, &RatingUpdate { delivery: Some(5), stability: Some(4), ..Default::default() }).unwrap();
+    // 再次打分只修改指定的项
+    let rating = store.set_rating(id, &RatingUpdate { support: Some(3), note: Some("响应慢".to_string()), ..Default::default() }).unwrap();
+    assert_eq!((rating.delivery, rating.stability, rating.support, rating.price_honesty), (Some(5), Some(4), Some(3), None));
+    assert_eq!(rating.note.as_deref(), Some("响应慢"));
+    assert_eq!(store.get_rating(id).unwrap(), Some(rating));
+}
+
+#[test]
+fn test_store_score_column() {
+    let store = rated_store();
+    assert_eq!(store.get(1).unwrap().score, Some(4.0));
+    assert_eq!(store.get(2).unwrap().score, Some(3.0));
+    assert_eq!(store.get(3).unwrap().score, None);
+    assert_eq!(store.list_ratings().unwrap().len(), 2);
+}
+
+#[test]
+fn test_store_add_incident() {
+    let store = rated_store();
+    let today = store.today().unwrap();
+    assert!(matches!(store.add_incident(&incident(today.add_days(1), IncidentSeverity::Minor)), Err(Error::Validation(_))));
+    assert!(matches!(store.add_incident(&NewIncident { description: " ".to_string(), ..incident(today, IncidentSeverity::Minor) }), Err(Error::Validation(_))));
+    let older = store.add_incident(&incident(today.add_days(-400), IncidentSeverity::Critical)).unwrap();
+    let recent = store.add_incident(&incident(today.add_days(-30), IncidentSeverity::Major)).unwrap();
+    // 最近的事故在前
+    let incidents = store.list_incidents(Some(1)).unwrap();
+    assert_eq!(incidents.iter().map(|i| i.id).collect::<Vec<_>>(), [recent, older]);
+    assert!(store.list_incidents(Some(2)).unwrap().is_empty());
+}
+
+#[test]
+fn test_store_incidents_lower_score() {
+    let store = rated_store();
+    let today = store.today().unwrap();
+    // 一年内的事故扣分，更早的不计
+    let recent = store.add_incident(&incident(today.add_days(-30), IncidentSeverity::Major)).unwrap();
+    store.add_incident(&incident(today.add_days(-400), IncidentSeverity::Critical)).unwrap();
+    let incidents = store.list_incidents(Some(1)).unwrap();
+    let score = rating::score(&store.get_rating(1).unwrap().unwrap(), &incidents, today);
+    assert_eq!(score, Some(3.5));
+    assert_eq!(store.get(1).unwrap().score, score);
+
+    store.remove_incident(recent).unwrap();
+    assert!(matches!(store.remove_incident(recent), Err(Error::NotFound(_))));
+    assert_eq!(store.get(1).unwrap().score, Some(4.0));
+}
+
+#[test]
+fn test_store_query_by_score() {
+    let store = rated_store();
+    // 按评分排序时未打分的在最后（降序），按评分筛选时未打分的不匹配
+    let by_score = store.query(QueryBuilder::new().order_by("score:desc".parse().unwrap())).unwrap();
+    assert_eq!(by_score.iter().map(|r| r.id).collect::<Vec<_>>(), [1, 2, 3]);
+    let above = store.query(QueryBuilder::new().condition(Condition::Score { op: ComparisonOp::Gte, value: Some(3.5) })).unwrap();
+    assert_eq!(above.iter().map(|r| r.id).collect::<Vec<_>>(), [1]);
+    let below = store.query(QueryBuilder::new().condition(parse_filter("评分 < 3.5 or score is null").unwrap())).unwrap();
+    assert_eq!(below.iter().map(|r| r.id).collect::<Vec<_>>(), [2, 3]);
+}
+
+#[test]
+fn test_store_rating_unknown_supplier() {
+    let store = SupplierStore::open_in_memory().unwrap();
+    let update = RatingUpdate { delivery: Some(4), ..Default::default() };
+    assert!(matches!(store.set_rating(42, &update), Err(Error::NotFound(_))));
+    let incident = NewIncident { supplier_id: 42, occurred_on: store.today().unwrap(), severity: IncidentSeverity::Minor, description: "断网".to_string() };
+    assert!(matches!(store.add_incident(&incident), Err(Error::NotFound(_))));
+    assert!(store.list_ratings().unwrap().is_empty());
+    assert!(store.list_incidents(None).unwrap().is_empty());
+}
+
+#[test]
+fn test_store_delete_removes_ratings_and_incidents() {
+    let store = SupplierStore::open_in_memory().unwrap();
+    let id = store.add(&sample("张三", "北京", 1000.0)).unwrap();
+    let other = store.add(&sample("李四", "上海", 900.0)).unwrap();
+    let today = store.today().unwrap();
+    for supplier_id in [id, other] {
+        store.set_rating(supplier_id, &RatingUpdate { delivery: Some(4), ..Default::default() }).unwrap();
+        store.add_incident(&NewIncident { supplier_id, occurred_on: today, severity: IncidentSeverity::Minor, description: "断网".to_string() }).unwrap();
+    }
+
+    store.delete(id).unwrap();
+    assert_eq!(store.get_rating(id).unwrap(), None);
+    assert!(store.list_incidents(Some(id)).unwrap().is_empty());
+    assert!(store.get_rating(other).unwrap().is_some(), "其他供应商的评分不应被删除");
+    assert_eq!(store.list_incidents(Some(other)).unwrap().len(), 1);
+    assert!(matches!(store.delete(id), Err(Error::NotFound(_))));
+}
+
+#[test]
+fn test_store_delete_rejects_active_contract() {
+    let date = |s: &str| s.parse::<Date>().unwrap();
+    let store = SupplierStore::open_in_memory().unwrap();
+    let id = store.add(&sample("张三", "北京", 1000.0)).unwrap();
+    store.set_rating(id, &RatingUpdate { delivery: Some(4), ..Default::default() }).unwrap();
+    let contract_id = store
+        .create_contract(&NewContract {
+            supplier_id: id,
+            quote_no: None,
+            quantity: 1,
+            agreed_price: 1000.0,
+            start_date: date("2024-01-01"),
+            end_date: date("2024-07-01"),
+            min_contract_period: None,
+            payment_terms: None,
+            breach_penalties: None,
+        })
+        .unwrap();
+
+    assert!(matches!(store.delete(id), Err(Error::Validation(_))));
+    assert!(store.get(id).is_ok());
+    assert!(store.get_rating(id).unwrap().is_some(), "拒绝删除时不应删除评分");
+
+    store.terminate_contract(contract_id, date("2024-03-01")).unwrap();
+    store.delete(id).unwrap();
+    assert!(matches!(store.get(id), Err(Error::NotFound(_))));
+}